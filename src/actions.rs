@@ -0,0 +1,204 @@
+use egui::{Key, KeyboardShortcut, Modifiers};
+
+/// Named actions of the GUI. Keyboard shortcuts and the command palette are
+/// both driven from this list, so a new action only needs adding here and to
+/// `FileManagerApp::run_action`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Action {
+    Refresh,
+    OpenFile,
+    RevealInFileManager,
+    ContextMenu,
+    Copy,
+    Cut,
+    CopyTo,
+    MoveTo,
+    Duplicate,
+    Paste,
+    PasteIntoFolder,
+    Delete,
+    Rename,
+    Touch,
+    Permissions,
+    NewFolder,
+    NewFolderWithSelection,
+    CompressToZip,
+    ExtractArchive,
+    ToggleHiddenFiles,
+    TogglePreview,
+    ToggleSidebar,
+    ToggleLogPanel,
+    ToggleMirrorNavigation,
+    ToggleReadOnlyMode,
+    SwitchPane,
+    SwapPanes,
+    OtherPaneToThisFolder,
+    ThisPaneToOtherFolder,
+    NavigateBack,
+    NavigateForward,
+    Filter,
+    AdvancedSearch,
+    CompareFiles,
+    ThreeWayCompare,
+    CompareWithClipboard,
+    DiffAgainstHead,
+    FolderStatistics,
+    SyncLeftToRight,
+    SyncRightToLeft,
+    SelectAll,
+    InvertSelection,
+    DeselectAll,
+    MountPoints,
+    AddBookmark,
+    Themes,
+    Settings,
+    About,
+    CommandPalette,
+    Exit,
+}
+
+impl Action {
+    pub fn all() -> &'static [Action] {
+        &[
+            Action::Refresh,
+            Action::OpenFile,
+            Action::RevealInFileManager,
+            Action::ContextMenu,
+            Action::Copy,
+            Action::Cut,
+            Action::CopyTo,
+            Action::MoveTo,
+            Action::Duplicate,
+            Action::Paste,
+            Action::PasteIntoFolder,
+            Action::Delete,
+            Action::Rename,
+            Action::Touch,
+            Action::Permissions,
+            Action::NewFolder,
+            Action::NewFolderWithSelection,
+            Action::CompressToZip,
+            Action::ExtractArchive,
+            Action::ToggleHiddenFiles,
+            Action::TogglePreview,
+            Action::ToggleSidebar,
+            Action::ToggleLogPanel,
+            Action::ToggleMirrorNavigation,
+            Action::ToggleReadOnlyMode,
+            Action::SwitchPane,
+            Action::SwapPanes,
+            Action::OtherPaneToThisFolder,
+            Action::ThisPaneToOtherFolder,
+            Action::NavigateBack,
+            Action::NavigateForward,
+            Action::Filter,
+            Action::AdvancedSearch,
+            Action::CompareFiles,
+            Action::ThreeWayCompare,
+            Action::CompareWithClipboard,
+            Action::DiffAgainstHead,
+            Action::FolderStatistics,
+            Action::SyncLeftToRight,
+            Action::SyncRightToLeft,
+            Action::SelectAll,
+            Action::InvertSelection,
+            Action::DeselectAll,
+            Action::MountPoints,
+            Action::AddBookmark,
+            Action::Themes,
+            Action::Settings,
+            Action::About,
+            Action::CommandPalette,
+            Action::Exit,
+        ]
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Action::Refresh => "Refresh",
+            Action::OpenFile => "Open file",
+            Action::RevealInFileManager => "Reveal in system file manager",
+            Action::ContextMenu => "Open context menu",
+            Action::Copy => "Copy to clipboard",
+            Action::Cut => "Cut to clipboard",
+            Action::CopyTo => "Copy to…",
+            Action::MoveTo => "Move to…",
+            Action::Duplicate => "Duplicate",
+            Action::Paste => "Paste",
+            Action::PasteIntoFolder => "Paste into highlighted folder",
+            Action::Delete => "Delete",
+            Action::Rename => "Rename",
+            Action::Touch => "Touch (set modification time)…",
+            Action::Permissions => "Permissions (chmod)…",
+            Action::NewFolder => "New folder",
+            Action::NewFolderWithSelection => "New folder with selection…",
+            Action::CompressToZip => "Compress to ZIP",
+            Action::ExtractArchive => "Extract archive…",
+            Action::ToggleHiddenFiles => "Toggle hidden files",
+            Action::TogglePreview => "Toggle preview panel",
+            Action::ToggleSidebar => "Toggle sidebar",
+            Action::ToggleLogPanel => "Toggle operation log",
+            Action::ToggleMirrorNavigation => "Toggle mirror navigation",
+            Action::ToggleReadOnlyMode => "Toggle read-only mode",
+            Action::SwitchPane => "Switch pane",
+            Action::SwapPanes => "Swap panes",
+            Action::OtherPaneToThisFolder => "Other pane to this folder",
+            Action::ThisPaneToOtherFolder => "This pane to other folder",
+            Action::NavigateBack => "Back",
+            Action::NavigateForward => "Forward",
+            Action::Filter => "Filter files",
+            Action::AdvancedSearch => "Advanced search",
+            Action::CompareFiles => "Compare files",
+            Action::ThreeWayCompare => "3-way compare…",
+            Action::CompareWithClipboard => "Compare with clipboard",
+            Action::DiffAgainstHead => "Diff against HEAD",
+            Action::FolderStatistics => "Folder statistics",
+            Action::SyncLeftToRight => "Sync left → right…",
+            Action::SyncRightToLeft => "Sync right → left…",
+            Action::SelectAll => "Select all",
+            Action::InvertSelection => "Invert selection",
+            Action::DeselectAll => "Deselect all",
+            Action::MountPoints => "Mount points",
+            Action::AddBookmark => "Bookmark current folder",
+            Action::Themes => "Themes…",
+            Action::Settings => "Settings…",
+            Action::About => "About",
+            Action::CommandPalette => "Command palette",
+            Action::Exit => "Exit",
+        }
+    }
+
+    /// Default keyboard shortcut, if the action has one.
+    pub fn shortcut(&self) -> Option<KeyboardShortcut> {
+        let (modifiers, key) = match self {
+            Action::Refresh => (Modifiers::NONE, Key::F2),
+            Action::OpenFile => (Modifiers::NONE, Key::F3),
+            Action::Copy => (Modifiers::NONE, Key::F5),
+            Action::Cut => (Modifiers::NONE, Key::F6),
+            Action::CopyTo => (Modifiers::SHIFT, Key::F5),
+            Action::MoveTo => (Modifiers::SHIFT, Key::F6),
+            Action::Paste => (Modifiers::NONE, Key::F7),
+            Action::PasteIntoFolder => (Modifiers::SHIFT, Key::F7),
+            Action::Delete => (Modifiers::NONE, Key::F8),
+            Action::ContextMenu => (Modifiers::SHIFT, Key::F10),
+            Action::NewFolder => (Modifiers::CTRL, Key::N),
+            Action::NewFolderWithSelection => (Modifiers::CTRL | Modifiers::SHIFT, Key::N),
+            Action::ToggleHiddenFiles => (Modifiers::CTRL, Key::H),
+            Action::TogglePreview => (Modifiers::NONE, Key::Space),
+            Action::ToggleSidebar => (Modifiers::CTRL, Key::B),
+            Action::SwitchPane => (Modifiers::NONE, Key::Tab),
+            Action::SwapPanes => (Modifiers::CTRL, Key::U),
+            Action::NavigateBack => (Modifiers::ALT, Key::ArrowLeft),
+            Action::NavigateForward => (Modifiers::ALT, Key::ArrowRight),
+            Action::Filter => (Modifiers::CTRL, Key::F),
+            Action::AdvancedSearch => (Modifiers::CTRL | Modifiers::SHIFT, Key::F),
+            Action::CompareFiles => (Modifiers::CTRL, Key::D),
+            Action::Duplicate => (Modifiers::CTRL | Modifiers::SHIFT, Key::D),
+            Action::SelectAll => (Modifiers::CTRL, Key::A),
+            Action::DeselectAll => (Modifiers::CTRL | Modifiers::SHIFT, Key::A),
+            Action::CommandPalette => (Modifiers::CTRL | Modifiers::SHIFT, Key::P),
+            _ => return None,
+        };
+        Some(KeyboardShortcut::new(modifiers, key))
+    }
+}
@@ -1,34 +1,43 @@
+use crate::filesystem::SharedGitStatusCache;
 use crate::pane::Pane;
 use anyhow::Result;
-use std::env;
 use std::fs;
 
 pub struct App {
     pub left_pane: Pane,
     pub right_pane: Pane,
+    pub git_cache: SharedGitStatusCache,
+    pub settings: crate::settings::Settings,
     pub active_pane: usize,
     pub should_quit: bool,
     pub show_mounts: bool,
     pub show_preview: bool,
+    /// Rows visible in a pane, updated on every draw for PageUp/PageDown.
+    pub viewport_height: usize,
 }
 
 impl App {
     pub fn new() -> Result<Self> {
-        // Try to get home directory first, fallback to current dir
-        let start_dir = dirs::home_dir()
-            .or_else(|| env::current_dir().ok())
-            .unwrap_or_else(|| std::path::PathBuf::from("."));
-
-        let left_pane = Pane::new(start_dir.clone())?;
-        let right_pane = Pane::new(start_dir)?;
+        let git_cache = SharedGitStatusCache::default();
+        let (mut left_pane, _) = Pane::open_start_pane(git_cache.clone());
+        let mut right_pane =
+            Pane::with_git_cache(left_pane.current_path.clone(), git_cache.clone())?;
+        let settings = crate::settings::Settings::load().unwrap_or_default();
+        left_pane.set_group_hidden(settings.group_hidden_files);
+        right_pane.set_group_hidden(settings.group_hidden_files);
+        left_pane.set_type_sorts(settings.active_type_sorts());
+        right_pane.set_type_sorts(settings.active_type_sorts());
 
         Ok(App {
             left_pane,
             right_pane,
+            git_cache,
+            settings,
             active_pane: 0,
             should_quit: false,
             show_mounts: false,
             show_preview: false,
+            viewport_height: 20,
         })
     }
 
@@ -64,6 +73,10 @@ impl App {
         self.get_active_pane_mut().move_down();
     }
 
+    pub fn move_by(&mut self, delta: isize, extend_selection: bool) {
+        self.get_active_pane_mut().move_by(delta, extend_selection);
+    }
+
     pub fn move_up_with_selection(&mut self) {
         self.get_active_pane_mut().move_up_with_selection();
     }
@@ -81,12 +94,28 @@ impl App {
     }
 
     pub fn refresh(&mut self) -> Result<()> {
+        self.git_cache.borrow_mut().clear();
         self.left_pane.refresh()?;
         self.right_pane.refresh()?;
         Ok(())
     }
 
+    /// Every operation that changes files starts here, so read-only mode
+    /// can't be bypassed.
+    fn ensure_writable(&self) -> Result<()> {
+        if self.settings.read_only_mode {
+            anyhow::bail!("Read-only mode is on");
+        }
+        Ok(())
+    }
+
+    pub fn toggle_read_only_mode(&mut self) -> Result<()> {
+        self.settings.read_only_mode = !self.settings.read_only_mode;
+        self.settings.save()
+    }
+
     pub fn copy_file(&mut self) -> Result<()> {
+        self.ensure_writable()?;
         let source_pane = self.get_active_pane();
         let target_pane = if self.active_pane == 0 {
             &self.right_pane
@@ -96,17 +125,24 @@ impl App {
 
         if let Some(item) = source_pane.get_selected_item() {
             if !item.is_dir && item.name != ".." {
-                let dest_path = target_pane.current_path.join(&item.name);
+                crate::filesystem::check_free_space(
+                    std::slice::from_ref(&item.path),
+                    &target_pane.current_path,
+                    false,
+                    self.settings.size_units,
+                )?;
+                let dest_path =
+                    crate::filesystem::copy_destination(&item.path, &target_pane.current_path)?;
                 fs::copy(&item.path, &dest_path)?;
                 // Refresh both panes
-                self.left_pane.refresh()?;
-                self.right_pane.refresh()?;
+                self.refresh()?;
             }
         }
         Ok(())
     }
 
     pub fn delete_file(&mut self) -> Result<()> {
+        self.ensure_writable()?;
         let items_to_delete: Vec<_> = self.get_active_pane()
             .get_selected_items()
             .iter()
@@ -122,18 +158,18 @@ impl App {
             }
         }
 
-        let active_pane = self.get_active_pane_mut();
-        active_pane.clear_selection();
-        active_pane.refresh()?;
-        Ok(())
+        // Refresh both panes, the other one may show the same folder
+        self.get_active_pane_mut().clear_selection();
+        self.refresh()
     }
 
     pub fn move_files(&mut self) -> Result<()> {
+        self.ensure_writable()?;
         let source_items: Vec<_> = self.get_active_pane()
             .get_selected_items()
             .iter()
             .filter(|item| item.name != "..")
-            .map(|item| (item.path.clone(), item.name.clone()))
+            .map(|item| item.path.clone())
             .collect();
 
         let target_path = if self.active_pane == 0 {
@@ -142,15 +178,14 @@ impl App {
             self.left_pane.current_path.clone()
         };
 
-        for (source_path, name) in source_items {
-            let dest_path = target_path.join(&name);
-            fs::rename(&source_path, &dest_path)?;
+        // A move that has to copy checks for space before it starts
+        for source_path in source_items {
+            let dest_path = crate::filesystem::transfer_destination(&source_path, &target_path)?;
+            crate::filesystem::move_path(&source_path, &dest_path)?;
         }
 
         self.get_active_pane_mut().clear_selection();
-        self.left_pane.refresh()?;
-        self.right_pane.refresh()?;
-        Ok(())
+        self.refresh()
     }
 
     pub fn update_scroll(&mut self, viewport_height: usize) {
@@ -1,49 +1,19 @@
+use crate::actions::Action;
+use crate::filesystem::SharedGitStatusCache;
 use crate::pane::Pane;
+use crate::progress::BackgroundTask;
+pub use crate::settings::Theme;
 use anyhow::Result;
 use std::env;
 use std::fs;
 use std::path::PathBuf;
 
 #[derive(Clone, Debug)]
-enum ClipboardOperation {
+pub enum ClipboardOperation {
     Copy,
     Cut,
 }
 
-#[derive(Clone, Copy, Debug, PartialEq)]
-pub enum Theme {
-    Dark,
-    Light,
-    Dracula,
-    Nord,
-    Monokai,
-    SolarizedDark,
-}
-
-impl Theme {
-    pub fn name(&self) -> &str {
-        match self {
-            Theme::Dark => "Dark (Default)",
-            Theme::Light => "Light",
-            Theme::Dracula => "Dracula",
-            Theme::Nord => "Nord",
-            Theme::Monokai => "Monokai",
-            Theme::SolarizedDark => "Solarized Dark",
-        }
-    }
-
-    pub fn all() -> Vec<Theme> {
-        vec![
-            Theme::Dark,
-            Theme::Light,
-            Theme::Dracula,
-            Theme::Nord,
-            Theme::Monokai,
-            Theme::SolarizedDark,
-        ]
-    }
-}
-
 pub struct ImageViewerState {
     pub image_path: PathBuf,
     pub image_name: String,
@@ -68,45 +38,606 @@ impl ImageViewerState {
     }
 }
 
+/// Label and value of one line in the file row tooltip.
+type DetailRow = (&'static str, String);
+
+/// Images already uploaded to the GPU, keyed by file path and modification
+/// time so an edited file is decoded again. The least recently used entry is
+/// dropped once `TEXTURE_CACHE_SIZE` is exceeded. Failed decodes are cached
+/// too, so a broken file isn't retried every frame.
+#[derive(Default)]
+pub struct TextureCache {
+    /// Most recently used last.
+    entries: Vec<TextureCacheEntry>,
+}
+
+struct TextureCacheEntry {
+    path: PathBuf,
+    modified: Option<std::time::SystemTime>,
+    texture: Result<egui::TextureHandle, String>,
+}
+
+const TEXTURE_CACHE_SIZE: usize = 16;
+
+impl TextureCache {
+    /// The texture for `path`, calling `decode` only on a cache miss.
+    pub fn get_or_load(
+        &mut self,
+        ctx: &egui::Context,
+        path: &std::path::Path,
+        decode: impl FnOnce() -> Result<egui::ColorImage, String>,
+    ) -> Result<egui::TextureHandle, String> {
+        let modified = fs::metadata(path).and_then(|m| m.modified()).ok();
+        if let Some(index) = self.entries.iter().position(|e| e.path == path) {
+            let entry = self.entries.remove(index);
+            if entry.modified == modified {
+                let texture = entry.texture.clone();
+                self.entries.push(entry);
+                return texture;
+            }
+        }
+
+        let texture = decode().map(|image| {
+            ctx.load_texture(
+                format!("image_{}", path.display()),
+                image,
+                egui::TextureOptions::LINEAR,
+            )
+        });
+        self.entries.push(TextureCacheEntry {
+            path: path.to_path_buf(),
+            modified,
+            texture: texture.clone(),
+        });
+        if self.entries.len() > TEXTURE_CACHE_SIZE {
+            self.entries.remove(0);
+        }
+        texture
+    }
+}
+
+/// Lay out a search result row, drawing the part of `name` that matched the
+/// filename pattern in `accent`. `name` is expected at the end of `text`.
+fn highlight_match(
+    text: &str,
+    name: &str,
+    name_match: Option<std::ops::Range<usize>>,
+    color: egui::Color32,
+    accent: egui::Color32,
+) -> egui::text::LayoutJob {
+    let format = |color| egui::TextFormat {
+        font_id: egui::FontId::proportional(11.0),
+        color,
+        ..Default::default()
+    };
+    let mut job = egui::text::LayoutJob::default();
+    match name_match {
+        Some(range) if text.ends_with(name) && name.get(range.clone()).is_some() => {
+            let name_start = text.len() - name.len();
+            let start = name_start + range.start;
+            let end = name_start + range.end;
+            job.append(&text[..start], 0.0, format(color));
+            job.append(&text[start..end], 0.0, format(accent));
+            job.append(&text[end..], 0.0, format(color));
+        }
+        _ => job.append(text, 0.0, format(color)),
+    }
+    job
+}
+
+/// `text` with the characters starting at the byte offsets in `positions`
+/// drawn in `accent`, as returned by `fuzzy_match`.
+fn highlight_chars(
+    text: &str,
+    positions: &[usize],
+    size: f32,
+    color: egui::Color32,
+    accent: egui::Color32,
+) -> egui::text::LayoutJob {
+    let mut job = egui::text::LayoutJob::default();
+    for (offset, c) in text.char_indices() {
+        let format = egui::TextFormat {
+            font_id: egui::FontId::proportional(size),
+            color: if positions.contains(&offset) { accent } else { color },
+            ..Default::default()
+        };
+        job.append(&text[offset..offset + c.len_utf8()], 0.0, format);
+    }
+    job
+}
+
+/// Green, yellow or red depending on how full a disk is, as in the mount
+/// list: over 70% in use is a warning, over 90% nearly full.
+fn usage_color(usage: f32) -> egui::Color32 {
+    if usage > 90.0 {
+        egui::Color32::from_rgb(242, 139, 130)
+    } else if usage > 70.0 {
+        egui::Color32::from_rgb(255, 193, 7)
+    } else {
+        egui::Color32::from_rgb(129, 201, 149)
+    }
+}
+
+/// How many entries get their own bar in the disk usage view; the rest are
+/// summed up in one row.
+const USAGE_BARS_SHOWN: usize = 12;
+
+/// Draw one bar per entry, as wide as its share of `total`, biggest first.
+/// Folders can be clicked; returns the index of the one that was.
+fn usage_bars(
+    ui: &mut egui::Ui,
+    entries: &[crate::filesystem::FileItem],
+    total: u64,
+    size_units: crate::filesystem::SizeUnits,
+) -> Option<usize> {
+    let share = |size: u64| {
+        if total > 0 {
+            (size as f64 / total as f64).min(1.0)
+        } else {
+            0.0
+        }
+    };
+    let text_color = ui.visuals().text_color();
+    let mut clicked = None;
+    let bar = |ui: &mut egui::Ui, label: String, size: u64, fill: egui::Color32, sense| {
+        let (rect, response) =
+            ui.allocate_exact_size(egui::vec2(ui.available_width(), 22.0), sense);
+        let painter = ui.painter();
+        painter.rect_filled(rect, 4.0, egui::Color32::from_rgb(55, 58, 64));
+        let mut filled = rect;
+        filled.set_width(rect.width() * share(size) as f32);
+        let fill = if response.hovered() && sense == egui::Sense::click() {
+            fill.gamma_multiply(1.3)
+        } else {
+            fill
+        };
+        painter.rect_filled(filled, 4.0, fill);
+        let font = egui::FontId::proportional(12.0);
+        painter.text(
+            rect.left_center() + egui::vec2(8.0, 0.0),
+            egui::Align2::LEFT_CENTER,
+            label,
+            font.clone(),
+            text_color,
+        );
+        painter.text(
+            rect.right_center() - egui::vec2(8.0, 0.0),
+            egui::Align2::RIGHT_CENTER,
+            format!(
+                "{}  {:.1}%",
+                crate::filesystem::format_size_with(size, size_units),
+                share(size) * 100.0
+            ),
+            font,
+            text_color,
+        );
+        response
+    };
+
+    for (index, item) in entries.iter().take(USAGE_BARS_SHOWN).enumerate() {
+        let response = if item.is_dir {
+            bar(
+                ui,
+                format!("📁 {}", item.name),
+                item.size,
+                egui::Color32::from_rgb(66, 133, 244),
+                egui::Sense::click(),
+            )
+            .on_hover_cursor(egui::CursorIcon::PointingHand)
+            .on_hover_text("Show what's inside")
+        } else {
+            bar(
+                ui,
+                format!("📄 {}", item.name),
+                item.size,
+                egui::Color32::from_rgb(95, 99, 104),
+                egui::Sense::hover(),
+            )
+        };
+        if response.clicked() {
+            clicked = Some(index);
+        }
+    }
+    let rest = &entries[entries.len().min(USAGE_BARS_SHOWN)..];
+    if !rest.is_empty() {
+        bar(
+            ui,
+            format!("… {} more", rest.len()),
+            rest.iter().map(|item| item.size).sum(),
+            egui::Color32::from_rgb(75, 78, 84),
+            egui::Sense::hover(),
+        );
+    }
+    clicked
+}
+
+/// Last non-empty line a finished program printed, preferring stderr when it
+/// failed, shortened to fit in a toast.
+fn command_output_summary(output: &std::process::Output) -> Option<String> {
+    let (first, second) = if output.status.success() {
+        (&output.stdout, &output.stderr)
+    } else {
+        (&output.stderr, &output.stdout)
+    };
+    let last_line = |bytes: &Vec<u8>| {
+        String::from_utf8_lossy(bytes)
+            .lines()
+            .map(str::trim)
+            .rfind(|line| !line.is_empty())
+            .map(str::to_string)
+    };
+    let line = last_line(first).or_else(|| last_line(second))?;
+    Some(if line.chars().count() > 120 {
+        format!("{}…", line.chars().take(120).collect::<String>())
+    } else {
+        line
+    })
+}
+
+/// Run a custom command's program in `dir` and wait for it to finish.
+/// Returns the last line it printed, or an error if it failed.
+fn run_command(arguments: &[String], dir: &std::path::Path) -> Result<Option<String>> {
+    let output = std::process::Command::new(&arguments[0])
+        .args(&arguments[1..])
+        .current_dir(dir)
+        .output()
+        .map_err(|e| anyhow::anyhow!("cannot start {}: {}", arguments[0], e))?;
+    let summary = command_output_summary(&output);
+    if !output.status.success() {
+        return Err(match summary {
+            Some(line) => anyhow::anyhow!("{} ({})", line, output.status),
+            None => anyhow::anyhow!("{}", output.status),
+        });
+    }
+    Ok(summary)
+}
+
+/// Path and modification time of a file, or `None` if it can't be read.
+fn file_stamp(path: &std::path::Path) -> Option<(PathBuf, std::time::SystemTime)> {
+    let modified = fs::metadata(path).and_then(|m| m.modified()).ok()?;
+    Some((path.to_path_buf(), modified))
+}
+
+/// Measure the total size of each of `items` on its own thread, for dialogs
+/// that show it.
+fn spawn_size_task(items: &[crate::filesystem::FileItem]) -> BackgroundTask<Vec<u64>> {
+    let paths: Vec<PathBuf> = items.iter().map(|item| item.path.clone()).collect();
+    BackgroundTask::spawn("Calculating size", move |_| {
+        Ok(paths
+            .iter()
+            .map(|path| crate::filesystem::calculate_total_size(path))
+            .collect())
+    })
+}
+
+fn to_color_image(image: &image::DynamicImage) -> egui::ColorImage {
+    let size = [image.width() as usize, image.height() as usize];
+    let rgba = image.to_rgba8();
+    egui::ColorImage::from_rgba_unmultiplied(size, rgba.as_flat_samples().as_slice())
+}
+
+fn decode_image_file(path: &std::path::Path) -> Result<egui::ColorImage, String> {
+    image::open(path)
+        .map(|image| to_color_image(&image))
+        .map_err(|e| e.to_string())
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum TransferMode {
+    Copy,
+    Move,
+    Extract,
+}
+
+impl TransferMode {
+    fn title(&self) -> &'static str {
+        match self {
+            TransferMode::Copy => "📋 Copy to…",
+            TransferMode::Move => "✂ Move to…",
+            TransferMode::Extract => "📦 Extract to…",
+        }
+    }
+
+    fn confirm_text(&self) -> &'static str {
+        match self {
+            TransferMode::Copy => "📋 Copy",
+            TransferMode::Move => "✂ Move",
+            TransferMode::Extract => "📦 Extract",
+        }
+    }
+}
+
+/// State of the "Touch" dialog, which sets the modification time of the
+/// selected entries.
+pub struct TouchDialog {
+    pub paths: Vec<PathBuf>,
+    /// Use the current time instead of `date` and `time`.
+    pub use_now: bool,
+    pub date: chrono::NaiveDate,
+    /// Time of day as "HH:MM" or "HH:MM:SS".
+    pub time: String,
+    /// Set the access time as well.
+    pub access: bool,
+}
+
+impl TouchDialog {
+    /// The chosen time, or `None` for now. Errors if the time of day can't
+    /// be parsed or doesn't exist on that day in the local time zone.
+    fn chosen_time(&self) -> Result<Option<std::time::SystemTime>> {
+        if self.use_now {
+            return Ok(None);
+        }
+        let time = chrono::NaiveTime::parse_from_str(self.time.trim(), "%H:%M:%S")
+            .or_else(|_| chrono::NaiveTime::parse_from_str(self.time.trim(), "%H:%M"))
+            .map_err(|_| anyhow::anyhow!("\"{}\" is not a time like 14:30", self.time.trim()))?;
+        let local = self
+            .date
+            .and_time(time)
+            .and_local_timezone(chrono::Local)
+            .earliest()
+            .ok_or_else(|| anyhow::anyhow!("{} {} does not exist in this time zone", self.date, self.time.trim()))?;
+        Ok(Some(local.into()))
+    }
+}
+
+/// State of the "Permissions" dialog, which applies one mode to every
+/// selected entry.
+pub struct PermissionsDialog {
+    pub paths: Vec<PathBuf>,
+    pub mode: u32,
+    /// `mode` as typed in octal; kept in sync with the checkboxes.
+    pub octal: String,
+    /// Also change everything inside the selected folders.
+    pub recursive: bool,
+    pub has_folders: bool,
+}
+
+/// State of the "Copy to…" / "Move to…" / "Extract to…" dialog.
+pub struct TransferDialog {
+    pub sources: Vec<PathBuf>,
+    pub mode: TransferMode,
+    /// Destination folder, typed or picked in the dialog's folder browser.
+    pub destination: String,
+    /// Entries and extracted size of the archives, when extracting.
+    pub archive_summary: Option<crate::filesystem::ZipSummary>,
+    /// Password typed for an encrypted zip archive; `None` when the archive
+    /// isn't encrypted. Only kept while the dialog is open.
+    pub password: Option<String>,
+    /// Set when the password typed last didn't open the archive.
+    pub wrong_password: bool,
+}
+
+/// How to run the failed items of a batch again.
+#[derive(Clone, Debug)]
+pub enum BatchRetry {
+    Transfer {
+        dest_dir: PathBuf,
+        is_move: bool,
+        merge: Option<crate::filesystem::ConflictPolicy>,
+    },
+    Delete,
+}
+
+/// A finished batch that skipped or failed some items, listed in one
+/// summary dialog at its end.
+#[derive(Clone, Debug)]
+pub struct BatchSummary {
+    /// What the batch did, e.g. "Copy", as in the operation log.
+    pub action: String,
+    pub report: crate::oplog::BatchReport,
+    pub retry: BatchRetry,
+}
+
+/// State of the sync preview dialog: the planned changes, shown before any
+/// of them are made.
+pub struct SyncPreview {
+    pub plan: crate::filesystem::SyncPlan,
+    /// Pane whose folder receives the changes.
+    pub dest_pane: usize,
+    /// Also delete what exists only in the destination.
+    pub delete_extras: bool,
+    /// Bytes the copies and updates will write.
+    pub bytes_to_copy: u64,
+}
+
+/// State of the "Folder statistics" dialog: a finished scan and the folder
+/// its usage bars show.
+pub struct FolderStatsView {
+    pub stats: crate::filesystem::TreeStats,
+    pub tab: StatsTab,
+    /// Folder within `stats.root` whose entries the usage bars show.
+    pub usage_dir: PathBuf,
+    /// Entries of `usage_dir`, biggest first.
+    pub usage: Vec<crate::filesystem::FileItem>,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum StatsTab {
+    Usage,
+    LargestFiles,
+    Extensions,
+}
+
+impl FolderStatsView {
+    fn new(stats: crate::filesystem::TreeStats) -> Result<Self> {
+        let usage = stats.children_by_size(&stats.root)?;
+        Ok(Self {
+            usage_dir: stats.root.clone(),
+            stats,
+            tab: StatsTab::Usage,
+            usage,
+        })
+    }
+
+    /// Show the entries of `dir` in the usage bars, sized from the scan.
+    fn show_usage_of(&mut self, dir: PathBuf) -> Result<()> {
+        self.usage = self.stats.children_by_size(&dir)?;
+        self.usage_dir = dir;
+        Ok(())
+    }
+}
+
+/// State of the "3-way compare" dialog: the two files being compared, the
+/// base they are compared against, and the comparison once it has run.
+pub struct ThreeWayDialog {
+    pub left: PathBuf,
+    pub right: PathBuf,
+    /// Path of the common ancestor, as typed.
+    pub base: String,
+    pub result: Option<crate::filesystem::ThreeWayComparison>,
+}
+
+/// A search walking the disk on its own thread. Hits arrive on `hits` in
+/// batches while it runs and are shown before the walk is done.
+struct SearchRun {
+    task: BackgroundTask<crate::filesystem::SearchResults>,
+    hits: std::sync::mpsc::Receiver<Vec<crate::filesystem::SearchHit>>,
+    started: std::time::Instant,
+}
+
+/// What picking an entry in the command palette does.
+#[derive(Clone)]
+enum PaletteTarget {
+    Action(Action),
+    Theme(Theme),
+    /// Bookmarks and typed paths: open the folder in the active pane.
+    Folder(PathBuf),
+    CustomCommand(usize),
+}
+
+/// State of the command palette (Ctrl+Shift+P).
+#[derive(Default)]
+pub struct CommandPalette {
+    pub query: String,
+    /// Index of the highlighted entry among the matches.
+    pub selected: usize,
+}
+
 pub struct FileManagerApp {
     pub left_pane: Pane,
     pub right_pane: Pane,
+    pub git_cache: SharedGitStatusCache,
     pub active_pane: usize,
     pub status_message: String,
     pub show_delete_confirm: bool,
-    pub item_to_delete: Option<String>,
+    /// Items listed in the delete confirmation, with their total size once
+    /// `delete_size_task` has added it up.
+    pub items_to_delete: Vec<crate::filesystem::FileItem>,
+    pub delete_total_size: Option<u64>,
     pub show_hidden_files: bool,
     pub clipboard: Option<(std::path::PathBuf, ClipboardOperation)>,
     pub show_context_menu: bool,
     pub context_menu_pos: egui::Pos2,
     pub context_menu_item_index: usize,
     pub context_menu_just_opened: bool,
+    /// The context menu is driven from the keyboard: opened with Shift+F10
+    /// or navigated with the arrow keys since it opened.
+    context_menu_keyboard: bool,
+    /// Entry of the context menu the arrow keys picked, by position.
+    context_menu_focus: usize,
+    /// Whether each entry drawn in the context menu is enabled, in order,
+    /// gathered while drawing it.
+    context_menu_entries: Vec<bool>,
+    /// Where each pane's cursor row was last drawn, to open the context
+    /// menu there from the keyboard.
+    cursor_row_rects: [Option<egui::Rect>; 2],
+    /// Pane and item index of the file row under the pointer.
     pub hovered_item: Option<(usize, usize)>,
+    /// Details shown in the hover tooltip, computed once per path and
+    /// modification time.
+    hover_details: Option<(PathBuf, std::time::SystemTime, Vec<DetailRow>)>,
     pub filter_mode: bool,
     pub show_new_folder_dialog: bool,
     pub new_folder_name: String,
+    /// Entries the new folder dialog moves into the folder it creates
+    pub new_folder_contents: Vec<PathBuf>,
     pub show_rename_dialog: bool,
     pub rename_new_name: String,
+    /// Extension of the file being renamed, if it has one
+    pub rename_extension: Option<String>,
+    /// Apply the typed name to the stem only and keep `rename_extension`
+    pub rename_keep_extension: bool,
     pub show_about_dialog: bool,
     pub show_properties_dialog: bool,
     pub properties_item: Option<crate::filesystem::FileItem>,
     pub current_theme: Theme,
     pub show_theme_selector: bool,
+    /// Theme and palette to restore if the custom theme editor is cancelled.
+    pub theme_editor_backup: Option<(Theme, crate::settings::CustomTheme)>,
+    /// File used by the theme editor's import and export buttons.
+    pub theme_file_path: String,
+    pub show_settings_dialog: bool,
+    pub mirror_navigation: bool,
+    /// Active pane index and path last seen by mirror navigation.
+    mirror_anchor: Option<(usize, PathBuf)>,
+    pub settings: crate::settings::Settings,
     pub image_viewer: Option<ImageViewerState>,
+    pub transfer_dialog: Option<TransferDialog>,
+    /// Summary of the last batch with skipped or failed items, shown until
+    /// closed or retried.
+    pub batch_summary: Option<BatchSummary>,
+    /// Where a batch running in the background leaves its summary when it
+    /// had problems; moved to `batch_summary` once the task ends.
+    finished_batch: std::sync::Arc<std::sync::Mutex<Option<BatchSummary>>>,
+    pub touch_dialog: Option<TouchDialog>,
+    pub permissions_dialog: Option<PermissionsDialog>,
+    /// Folders either pane visited this session, most recent first
+    pub recent_folders: Vec<PathBuf>,
+    /// Folder each pane showed when `recent_folders` was last updated
+    recent_seen: [Option<PathBuf>; 2],
+    /// Files waiting for "Open all" after a large multi-selection
+    pub pending_open: Option<Vec<PathBuf>>,
+    /// Entries to select once the running background task is done: the
+    /// pane, the folder it must still show, and the paths.
+    select_after_task: Option<(usize, PathBuf, Vec<PathBuf>)>,
+    pub command_palette: Option<CommandPalette>,
+    pub sync_preview: Option<SyncPreview>,
+    /// Last folder copied or moved to, or created, in this session. Offered
+    /// first by the next "Copy to…" / "Move to…".
+    pub last_transfer_dir: Option<PathBuf>,
+    /// Last folder extracted to in this session.
+    pub last_extract_dir: Option<PathBuf>,
+    pub texture_cache: TextureCache,
     pub show_preview_panel: bool,
     pub preview_content_left: Option<PreviewContent>,
     pub preview_content_right: Option<PreviewContent>,
+    /// Path and modification time of the file behind each pane's preview,
+    /// so the preview can be re-read when the file changes.
+    preview_stamps: [Option<(PathBuf, std::time::SystemTime)>; 2],
+    last_change_check: std::time::Instant,
+    /// Modified and Size column widths of each pane in the last frame.
+    column_widths: [[f32; 2]; 2],
+    /// A file list column edge is being dragged; widths are saved on release.
+    column_resize_pending: [bool; 2],
     pub show_search_dialog: bool,
     pub search_criteria: crate::filesystem::SearchCriteria,
-    pub search_results: Vec<crate::filesystem::FileItem>,
-    pub search_in_progress: bool,
+    pub search_results: Vec<crate::filesystem::SearchHit>,
+    pub search_skipped_dirs: usize,
     pub search_min_size_text: String,
     pub search_max_size_text: String,
     pub search_days_ago: String,
+    /// Extensions typed next to the extension groups, e.g. "rs, toml".
+    pub search_extensions_text: String,
     pub show_compare_dialog: bool,
     pub comparison_result: Option<crate::filesystem::FileComparison>,
+    pub three_way: Option<ThreeWayDialog>,
+    /// Whitespace and case handling of the compare dialog, kept between
+    /// comparisons
+    pub diff_options: crate::filesystem::DiffOptions,
     pub compare_scroll_offset: f32,
     pub show_mounts_dialog: bool,
+    /// Mount list shared by the dialog and the sidebar, scanned on first use
+    /// and again only when refreshed or when the dialog is opened.
+    mount_points: Option<Vec<crate::filesystem::MountPoint>>,
+    /// Devices with a filesystem that isn't mounted, listed in the dialog
+    /// below the mount points, or why they couldn't be listed. Scanned by
+    /// `device_scan` since `lsblk` runs as a separate program.
+    unmounted_devices: Option<Result<Vec<crate::filesystem::UnmountedDevice>, String>>,
+    device_scan: Option<BackgroundTask<Vec<crate::filesystem::UnmountedDevice>>>,
+    /// Device being mounted; udisks may wait indefinitely on a password
+    /// prompt, so this runs on its own thread.
+    mount_task: Option<(crate::filesystem::UnmountedDevice, BackgroundTask<PathBuf>)>,
+    pub mount_filter: String,
     pub show_sidebar: bool,
     pub bookmark_manager: crate::bookmarks::BookmarkManager,
     pub show_add_bookmark_dialog: bool,
@@ -119,21 +650,170 @@ pub struct FileManagerApp {
     pub filter_pdf: bool,
     pub filter_doc: bool,
     pub filter_xls: bool,
+    pub filter_combine: FilterCombine,
+    /// Matching used by the quick name filter, picked in the filter bar.
+    pub filter_matching: NameMatch,
+    /// Show only folders or only files, whatever the other filters say.
+    pub filter_entries: crate::filesystem::SearchFileType,
+    pub operation_in_progress: Option<String>,
+    operation_started: Option<std::time::Instant>,
+    /// Label and duration of the last finished operation, for the status bar.
+    pub last_operation: Option<(String, std::time::Duration)>,
+    /// Items shown in the compress dialog. Each one is compressed into its
+    /// own archive.
+    pub pending_compress: Option<Vec<crate::filesystem::FileItem>>,
+    /// Total size of each item in `pending_compress`, once
+    /// `compress_size_task` has measured them.
+    compress_sizes: Option<Vec<u64>>,
+    /// Compression settings being edited in the compress dialog.
+    pub compress_options: crate::filesystem::ZipOptions,
+    /// Encrypt the archives with the password typed (twice) in the compress
+    /// dialog. The password is never saved and is cleared when the dialog
+    /// closes.
+    pub compress_encrypt: bool,
+    pub compress_password: String,
+    pub compress_password_confirm: String,
+    /// Long operation running off the UI thread. Its result is the message
+    /// shown when it completes successfully.
+    pub background_task: Option<BackgroundTask<String>>,
+    /// Optional snapshot used to answer searches without walking the disk.
+    pub search_index: Option<crate::search_index::SearchIndex>,
+    /// Builds `search_index` on its own thread; unlike `background_task` it
+    /// doesn't block other operations.
+    index_task: Option<BackgroundTask<crate::search_index::SearchIndex>>,
+    /// Result of the last "Folder statistics" scan, shown until closed.
+    pub folder_stats: Option<FolderStatsView>,
+    /// Scans the tree for `folder_stats`; read-only, so like `index_task` it
+    /// runs beside other operations.
+    stats_task: Option<BackgroundTask<crate::filesystem::TreeStats>>,
+    /// Add up the sizes shown in the delete and compress dialogs, so the
+    /// dialogs open without waiting for the walk.
+    delete_size_task: Option<BackgroundTask<Vec<u64>>>,
+    compress_size_task: Option<BackgroundTask<Vec<u64>>>,
+    /// Compares the folders for `sync_preview`, with the pane that receives
+    /// the changes; the dialog shows "calculating…" until it is done.
+    sync_task: Option<(usize, BackgroundTask<(crate::filesystem::SyncPlan, u64)>)>,
+    /// Search from the search dialog that walks the disk; read-only, so it
+    /// runs beside other operations too.
+    search_task: Option<SearchRun>,
+    /// Repository being fetched from the status bar, with the fetch running
+    /// on its own thread since it waits on the network.
+    git_fetch: Option<(PathBuf, BackgroundTask<String>)>,
+    pub toasts: Vec<Toast>,
+    /// Every file operation of this session, shown in the log panel. Shared
+    /// with background tasks, which log each item of a batch.
+    pub op_log: crate::oplog::OperationLog,
+    pub show_log_panel: bool,
+    /// The log panel lists only failed operations.
+    log_failures_only: bool,
+    /// Rows that fit in each pane's list, measured while rendering.
+    pub pane_visible_rows: [usize; 2],
+    /// Scroll the active pane to its cursor on the next frame.
+    pub scroll_to_selection: bool,
+}
+
+/// How the type toggles in the filter bar combine with the quick name
+/// filter (Ctrl+F). The type toggles themselves always match any of the
+/// checked types.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum FilterCombine {
+    /// Show items matching both the name filter and a checked type.
+    #[default]
+    All,
+    /// Show items matching either the name filter or a checked type.
+    Any,
+}
+
+/// How the quick name filter (Ctrl+F) matches names.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum NameMatch {
+    /// Names containing the typed text.
+    #[default]
+    Substring,
+    /// Names containing the typed characters in order, like editors' file
+    /// pickers: "amr" finds "app_manager.rs". Best matches are listed first.
+    Fuzzy,
+}
+
+impl NameMatch {
+    pub fn name(&self) -> &'static str {
+        match self {
+            NameMatch::Substring => "Substring",
+            NameMatch::Fuzzy => "Fuzzy",
+        }
+    }
 }
 
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ToastKind {
+    Success,
+    Error,
+}
+
+/// A transient notification shown in the top-right corner.
+pub struct Toast {
+    pub message: String,
+    pub kind: ToastKind,
+    pub created: std::time::Instant,
+}
+
+const TOAST_DURATION: std::time::Duration = std::time::Duration::from_secs(5);
+const ERROR_TOAST_DURATION: std::time::Duration = std::time::Duration::from_secs(10);
+const MAX_TOASTS: usize = 5;
+
+/// How many folders the address bar's recent list keeps.
+const RECENT_FOLDERS_KEPT: usize = 20;
+
+/// Opening more files than this at once asks for confirmation first.
+const OPEN_MANY_THRESHOLD: usize = 10;
+
+/// Sources larger than this ask for confirmation before being compressed.
+const LARGE_COMPRESS_THRESHOLD: u64 = 500 * 1024 * 1024;
+
+/// Narrowest the resizable Modified and Size columns can get.
+const MIN_COLUMN_WIDTH: f32 = 60.0;
+
+/// Bounds of the preview panel's height; the panes above it always keep at
+/// least `MIN_PANES_HEIGHT`.
+const MIN_PREVIEW_HEIGHT: f32 = 80.0;
+const MIN_PANES_HEIGHT: f32 = 200.0;
+const DEFAULT_PREVIEW_HEIGHT: f32 = 200.0;
+
+/// How often the shown folders and previewed files are checked for changes
+/// made by other programs.
+const CHANGE_CHECK_INTERVAL: std::time::Duration = std::time::Duration::from_secs(2);
+
+const READ_ONLY_HINT: &str = "This folder is on a read-only filesystem";
+const READ_ONLY_MODE_HINT: &str = "Read-only mode is on; turn it off to make changes";
+
 #[derive(Clone)]
 pub enum PreviewContent {
-    Text(String),
+    /// Text content and, when it wasn't UTF-8, a note on how it was decoded.
+    Text(String, Option<String>),
     Image(PathBuf),
     Pdf {
         name: String,
+        path: PathBuf,
         size: String,
         modified: String,
         pages: usize,
         image: Option<image::DynamicImage>,
     },
+    Audio {
+        name: String,
+        path: PathBuf,
+        size: String,
+        title: Option<String>,
+        artist: Option<String>,
+        album: Option<String>,
+        duration: String,
+        /// Bitrate and sample rate, e.g. "320 kbps • 44.1 kHz".
+        quality: Option<String>,
+        cover: Option<image::DynamicImage>,
+    },
     FileInfo {
         name: String,
+        path: String,
         size: String,
         modified: String,
         permissions: String,
@@ -141,62 +821,173 @@ pub enum PreviewContent {
     },
 }
 
+/// Folders to open the panes in, as given on the command line.
+#[derive(clap::Args, Clone, Debug, Default)]
+pub struct StartDirs {
+    /// Folder to open in the left pane (a file opens its folder with the
+    /// cursor on it)
+    pub path: Option<PathBuf>,
+    /// Folder to open in the right pane
+    #[arg(long, value_name = "PATH")]
+    pub right: Option<PathBuf>,
+}
+
+/// Open the Filane window and run it until it is closed.
+pub fn run(start_dirs: StartDirs) -> eframe::Result<()> {
+    // Load icon from embedded bytes
+    let icon_bytes = include_bytes!("../assets/icon.png");
+    let icon = eframe::icon_data::from_png_bytes(icon_bytes).expect("Failed to load icon");
+
+    let options = eframe::NativeOptions {
+        viewport: egui::ViewportBuilder::default()
+            .with_inner_size([1200.0, 700.0])
+            .with_min_inner_size([800.0, 500.0])
+            .with_icon(icon),
+        ..Default::default()
+    };
+
+    eframe::run_native(
+        "Filane - Dual Pane FM",
+        options,
+        Box::new(|cc| {
+            egui_extras::install_image_loaders(&cc.egui_ctx);
+            cc.egui_ctx.set_zoom_factor(1.1);
+
+            Ok(Box::new(FileManagerApp::with_start_dirs(cc, start_dirs)))
+        }),
+    )
+}
+
 impl FileManagerApp {
-    pub fn new(_cc: &eframe::CreationContext<'_>) -> Self {
-        // Try to get home directory first, fallback to current dir
-        let start_dir = dirs::home_dir()
-            .or_else(|| env::current_dir().ok())
-            .unwrap_or_else(|| std::path::PathBuf::from("."));
-
-        let left_pane = Pane::new(start_dir.clone()).unwrap_or_else(|e| {
-            eprintln!("Error creating left pane: {}", e);
-            Pane::new(std::path::PathBuf::from("/")).expect("Failed to create pane")
-        });
+    pub fn new(cc: &eframe::CreationContext<'_>) -> Self {
+        Self::with_start_dirs(cc, StartDirs::default())
+    }
 
-        let right_pane = Pane::new(start_dir.clone()).unwrap_or_else(|e| {
-            eprintln!("Error creating right pane: {}", e);
-            Pane::new(std::path::PathBuf::from("/")).expect("Failed to create pane")
-        });
+    /// Open the panes in `start_dirs`. A path that can't be opened falls
+    /// back to the usual start folder, with the reason in the status bar.
+    pub fn with_start_dirs(_cc: &eframe::CreationContext<'_>, start_dirs: StartDirs) -> Self {
+        let git_cache = SharedGitStatusCache::default();
+        let mut notes = Vec::new();
+        let mut open_requested = |path: Option<PathBuf>| {
+            let path = path?;
+            Pane::open_at(&path, git_cache.clone())
+                .map_err(|e| notes.push(e.to_string()))
+                .ok()
+        };
+        let requested_left = open_requested(start_dirs.path);
+        let requested_right = open_requested(start_dirs.right);
+
+        // Home, then the current dir, then the first readable mount point
+        let (mut left_pane, fallback_note) = match requested_left {
+            Some(pane) => (pane, None),
+            None => Pane::open_start_pane(git_cache.clone()),
+        };
+        let start_dir = left_pane.current_path.clone();
+        let mut right_pane = match requested_right {
+            Some(pane) => pane,
+            None => Pane::with_git_cache(start_dir.clone(), git_cache.clone())
+                .unwrap_or_else(|_| Pane::open_start_pane(git_cache.clone()).0),
+        };
+        notes.extend(fallback_note);
+
+        let status_message = if notes.is_empty() {
+            format!("Ready - Starting directory: {}", start_dir.display())
+        } else {
+            format!("⚠ {}", notes.join("; "))
+        };
+
+        let settings = crate::settings::Settings::load().unwrap_or_default();
+        left_pane.set_group_hidden(settings.group_hidden_files);
+        right_pane.set_group_hidden(settings.group_hidden_files);
+        left_pane.set_type_sorts(settings.active_type_sorts());
+        right_pane.set_type_sorts(settings.active_type_sorts());
+        let column_widths = [settings.date_column_width, settings.size_column_width];
 
         Self {
             left_pane,
             right_pane,
+            git_cache,
             active_pane: 0,
-            status_message: format!("Ready - Starting directory: {}", start_dir.display()),
+            status_message,
             show_delete_confirm: false,
-            item_to_delete: None,
+            items_to_delete: Vec::new(),
+            delete_total_size: None,
             show_hidden_files: false,
             clipboard: None,
             show_context_menu: false,
             context_menu_pos: egui::Pos2::ZERO,
             context_menu_item_index: 0,
             context_menu_just_opened: false,
+            context_menu_keyboard: false,
+            context_menu_focus: 0,
+            context_menu_entries: Vec::new(),
+            cursor_row_rects: [None; 2],
             hovered_item: None,
+            hover_details: None,
             filter_mode: false,
             show_new_folder_dialog: false,
             new_folder_name: String::new(),
+            new_folder_contents: Vec::new(),
             show_rename_dialog: false,
             rename_new_name: String::new(),
+            rename_extension: None,
+            rename_keep_extension: false,
             show_about_dialog: false,
             show_properties_dialog: false,
             properties_item: None,
-            current_theme: Theme::Dark,
+            current_theme: settings.theme,
             show_theme_selector: false,
+            theme_editor_backup: None,
+            theme_file_path: dirs::home_dir()
+                .unwrap_or_default()
+                .join("filane-theme.json")
+                .display()
+                .to_string(),
+            show_settings_dialog: false,
+            mirror_navigation: false,
+            mirror_anchor: None,
+            settings,
             image_viewer: None,
+            transfer_dialog: None,
+            batch_summary: None,
+            finished_batch: Default::default(),
+            touch_dialog: None,
+            permissions_dialog: None,
+            recent_folders: Vec::new(),
+            recent_seen: [None, None],
+            pending_open: None,
+            select_after_task: None,
+            command_palette: None,
+            sync_preview: None,
+            last_transfer_dir: None,
+            last_extract_dir: None,
+            texture_cache: TextureCache::default(),
             show_preview_panel: true,
             preview_content_left: None,
             preview_content_right: None,
+            preview_stamps: [None, None],
+            last_change_check: std::time::Instant::now(),
+            column_widths: [column_widths; 2],
+            column_resize_pending: [false; 2],
             show_search_dialog: false,
             search_criteria: crate::filesystem::SearchCriteria::default(),
             search_results: Vec::new(),
-            search_in_progress: false,
+            search_skipped_dirs: 0,
             search_min_size_text: String::new(),
             search_max_size_text: String::new(),
             search_days_ago: String::new(),
+            search_extensions_text: String::new(),
             show_compare_dialog: false,
             comparison_result: None,
+            three_way: None,
+            diff_options: crate::filesystem::DiffOptions::default(),
             compare_scroll_offset: 0.0,
             show_mounts_dialog: false,
+            mount_points: None,
+            unmounted_devices: None,
+            device_scan: None,
+            mount_task: None,
+            mount_filter: String::new(),
             show_sidebar: true,
             bookmark_manager: crate::bookmarks::BookmarkManager::load().unwrap_or_default(),
             show_add_bookmark_dialog: false,
@@ -209,6 +1000,34 @@ impl FileManagerApp {
             filter_pdf: false,
             filter_doc: false,
             filter_xls: false,
+            filter_combine: FilterCombine::default(),
+            filter_matching: NameMatch::default(),
+            filter_entries: crate::filesystem::SearchFileType::All,
+            operation_in_progress: None,
+            operation_started: None,
+            last_operation: None,
+            pending_compress: None,
+            compress_sizes: None,
+            compress_options: crate::filesystem::ZipOptions::default(),
+            compress_encrypt: false,
+            compress_password: String::new(),
+            compress_password_confirm: String::new(),
+            background_task: None,
+            search_index: None,
+            index_task: None,
+            folder_stats: None,
+            stats_task: None,
+            delete_size_task: None,
+            compress_size_task: None,
+            sync_task: None,
+            search_task: None,
+            git_fetch: None,
+            toasts: Vec::new(),
+            op_log: crate::oplog::OperationLog::default(),
+            show_log_panel: false,
+            log_failures_only: false,
+            pane_visible_rows: [20; 2],
+            scroll_to_selection: false,
         }
     }
 
@@ -220,6 +1039,14 @@ impl FileManagerApp {
         }
     }
 
+    pub fn get_pane(&self, pane_index: usize) -> &Pane {
+        if pane_index == 0 {
+            &self.left_pane
+        } else {
+            &self.right_pane
+        }
+    }
+
     pub fn get_active_pane_mut(&mut self) -> &mut Pane {
         if self.active_pane == 0 {
             &mut self.left_pane
@@ -228,160 +1055,2875 @@ impl FileManagerApp {
         }
     }
 
+    /// Reload a pane after its contents changed on disk, and the other pane
+    /// too when it shows the same folder. Cached git statuses are dropped
+    /// first so the panes pick up the change.
     pub fn refresh_pane(&mut self, pane_index: usize) -> Result<()> {
-        if pane_index == 0 {
-            self.left_pane.refresh()?;
+        self.git_cache.borrow_mut().clear();
+        let path = self.get_pane(pane_index).current_path.clone();
+        self.invalidate_search_index(&path);
+        let (pane, other) = if pane_index == 0 {
+            (&mut self.left_pane, &mut self.right_pane)
         } else {
-            self.right_pane.refresh()?;
+            (&mut self.right_pane, &mut self.left_pane)
+        };
+        pane.refresh()?;
+        if other.current_path == pane.current_path {
+            other.refresh()?;
         }
         Ok(())
     }
 
-    fn is_image_file(path: &std::path::Path) -> bool {
-        if let Some(ext) = path.extension() {
-            let ext = ext.to_string_lossy().to_lowercase();
-            matches!(
-                ext.as_str(),
-                "png" | "jpg" | "jpeg" | "gif" | "bmp" | "ico" | "webp"
-            )
-        } else {
-            false
-        }
-    }
-
-    pub fn open_image_viewer(&mut self, path: PathBuf, name: String) {
-        self.image_viewer = Some(ImageViewerState::new(path, name));
+    pub fn refresh_active_pane(&mut self) -> Result<()> {
+        self.refresh_pane(self.active_pane)
     }
 
-    pub fn close_image_viewer(&mut self) {
-        self.image_viewer = None;
+    pub fn refresh_both_panes(&mut self) -> Result<()> {
+        self.git_cache.borrow_mut().clear();
+        for path in [self.left_pane.current_path.clone(), self.right_pane.current_path.clone()] {
+            self.invalidate_search_index(&path);
+        }
+        self.left_pane.refresh()?;
+        self.right_pane.refresh()
     }
 
-    pub fn copy_to_clipboard(&mut self) {
-        let item_data = self
-            .get_active_pane()
-            .get_selected_item()
-            .map(|item| (item.path.clone(), item.name.clone()));
-
-        if let Some((path, name)) = item_data {
-            if name != ".." {
-                self.clipboard = Some((path, ClipboardOperation::Copy));
-                self.status_message = format!("Copied to clipboard: {}", name);
-            }
+    /// Drop the search index if it covers `path`, which changed on disk.
+    fn invalidate_search_index(&mut self, path: &std::path::Path) {
+        if self.search_index.as_ref().is_some_and(|index| index.covers(path)) {
+            self.search_index = None;
         }
     }
 
-    pub fn cut_to_clipboard(&mut self) {
-        let item_data = self
-            .get_active_pane()
-            .get_selected_item()
-            .map(|item| (item.path.clone(), item.name.clone()));
-
-        if let Some((path, name)) = item_data {
-            if name != ".." {
-                self.clipboard = Some((path, ClipboardOperation::Cut));
-                self.status_message = format!("Cut to clipboard: {}", name);
-            }
+    /// Index `root` on a background thread for instant searches.
+    fn start_indexing(&mut self, root: PathBuf) {
+        if self.index_task.is_some() {
+            return;
         }
+        self.search_index = None;
+        self.index_task = Some(BackgroundTask::spawn("Indexing", move |progress| {
+            crate::search_index::SearchIndex::build(&root, progress)
+        }));
     }
 
-    pub fn paste_from_clipboard(&mut self) -> Result<()> {
-        // Clone clipboard data to avoid borrow issues
-        let clipboard_data = self.clipboard.clone();
-
-        if let Some((source_path, operation)) = clipboard_data {
-            let target_path = if self.active_pane == 0 {
-                self.right_pane.current_path.clone()
+    /// Index status in the search dialog, with buttons to build or drop it.
+    fn render_search_index_row(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal(|ui| {
+            ui.label(egui::RichText::new("Index:").strong().size(13.0));
+            if let Some(task) = &self.index_task {
+                ui.spinner();
+                ui.label(
+                    egui::RichText::new(format!(
+                        "Indexing… {} entries",
+                        task.progress.done
+                    ))
+                    .size(12.0),
+                )
+                .on_hover_text(task.progress.current_path.display().to_string());
+            } else if let Some(index) = &self.search_index {
+                let root = index.root.clone();
+                ui.label(
+                    egui::RichText::new(format!(
+                        "📇 {} entries in {}, built {} ago",
+                        index.len(),
+                        root.display(),
+                        crate::filesystem::format_duration(index.built_at.elapsed())
+                    ))
+                    .size(12.0),
+                )
+                .on_hover_text(
+                    "Searches by name, size or date inside this folder use the index. \
+                     Content searches always read the files.",
+                );
+                if ui.small_button("Rebuild").clicked() {
+                    self.start_indexing(root);
+                }
+                if ui.small_button("Drop").clicked() {
+                    self.search_index = None;
+                }
             } else {
-                self.left_pane.current_path.clone()
-            };
-
-            let file_name = source_path
-                .file_name()
-                .and_then(|n| n.to_str())
-                .unwrap_or("unknown");
-            let dest_path = target_path.join(file_name);
-
-            match operation {
-                ClipboardOperation::Copy => {
-                    if source_path.is_dir() {
-                        self.copy_dir_recursive(&source_path, &dest_path)?;
-                        self.status_message =
-                            format!("Copied directory: {} → {}", file_name, dest_path.display());
-                    } else {
-                        fs::copy(&source_path, &dest_path)?;
-                        self.status_message =
-                            format!("Copied: {} → {}", file_name, dest_path.display());
+                let root = self.search_criteria.search_paths.first().cloned();
+                if ui
+                    .add_enabled(root.is_some(), egui::Button::new("📇 Build index"))
+                    .on_hover_text(
+                        "Index the first search folder in the background so repeated \
+                         searches by name, size or date are instant",
+                    )
+                    .clicked()
+                {
+                    if let Some(root) = root {
+                        self.start_indexing(root);
                     }
                 }
-                ClipboardOperation::Cut => {
-                    fs::rename(&source_path, &dest_path)?;
-                    self.status_message = format!("Moved: {} → {}", file_name, dest_path.display());
-                    self.clipboard = None; // Clear clipboard after cut
-                }
+                ui.label(
+                    egui::RichText::new("Not built")
+                        .size(12.0)
+                        .color(egui::Color32::from_rgb(154, 160, 166)),
+                );
             }
+        });
+    }
 
-            // Refresh both panes
-            self.left_pane.refresh()?;
-            self.right_pane.refresh()?;
-        } else {
-            self.status_message = "Clipboard is empty".to_string();
+    fn poll_index_task(&mut self, ctx: &egui::Context) {
+        let Some(task) = &mut self.index_task else {
+            return;
+        };
+        match task.poll() {
+            Some(Ok(index)) => {
+                self.index_task = None;
+                self.notify_success(format!(
+                    "Indexed {} entries in {}",
+                    index.len(),
+                    index.root.display()
+                ));
+                self.search_index = Some(index);
+            }
+            Some(Err(e)) => {
+                self.index_task = None;
+                self.notify_error(format!("Indexing failed: {}", e));
+            }
+            None => ctx.request_repaint_after(std::time::Duration::from_millis(100)),
         }
-        Ok(())
     }
 
-    fn copy_dir_recursive(&self, src: &std::path::Path, dst: &std::path::Path) -> Result<()> {
-        fs::create_dir_all(dst)?;
-        for entry in fs::read_dir(src)? {
-            let entry = entry?;
-            let file_type = entry.file_type()?;
-            let src_path = entry.path();
-            let dst_path = dst.join(entry.file_name());
+    /// Search with the search dialog's criteria. Searches the index can
+    /// answer are instant; anything else walks the disk on a worker thread
+    /// whose hits fill `search_results` as they are found.
+    fn run_search(&mut self) {
+        self.stop_search();
+        self.search_results.clear();
+        self.search_skipped_dirs = 0;
+        let started = std::time::Instant::now();
+        // Folders changed behind the app's back make the index stale: search
+        // the disk this time and rebuild the index meanwhile
+        let stale_root = self
+            .search_index
+            .as_ref()
+            .filter(|index| index.changed_below(&self.search_criteria.search_paths))
+            .map(|index| index.root.clone());
+        if let Some(root) = stale_root {
+            self.start_indexing(root);
+        }
+        // Name, size and date searches inside the index skip the walk
+        let indexed = self
+            .search_index
+            .as_ref()
+            .and_then(|index| index.search(&self.search_criteria));
+        if let Some(results) = indexed {
+            self.show_search_results(results, started.elapsed(), "Search complete", " (from index)");
+            return;
+        }
 
-            if file_type.is_dir() {
-                self.copy_dir_recursive(&src_path, &dst_path)?;
-            } else {
-                fs::copy(&src_path, &dst_path)?;
-            }
+        let criteria = self.search_criteria.clone();
+        let (sender, hits) = std::sync::mpsc::channel();
+        let task = BackgroundTask::spawn("Searching", move |progress| {
+            let mut found = 0;
+            crate::filesystem::search_files_with_progress(
+                &criteria,
+                &mut |batch, path| {
+                    found += batch.len() as u64;
+                    progress.report(found, 0, path);
+                    if !batch.is_empty() {
+                        let _ = sender.send(batch.to_vec());
+                    }
+                },
+                &|| progress.is_cancelled(),
+            )
+        })
+        .cancellable();
+        self.search_task = Some(SearchRun { task, hits, started });
+    }
+
+    /// Abandon a running search, e.g. because its dialog was closed.
+    fn stop_search(&mut self) {
+        if let Some(run) = self.search_task.take() {
+            run.task.cancel();
         }
-        Ok(())
     }
 
-    pub fn open_file_with_default_app(&mut self) -> Result<()> {
-        let item_data = self
+    fn show_search_results(
+        &mut self,
+        results: crate::filesystem::SearchResults,
+        elapsed: std::time::Duration,
+        heading: &str,
+        note: &str,
+    ) {
+        self.search_results = results.items;
+        self.search_skipped_dirs = results.skipped_dirs;
+        self.status_message = format!(
+            "{}: {} results in {}{}",
+            heading,
+            self.search_results.len(),
+            crate::filesystem::format_duration(elapsed),
+            note
+        );
+        if self.search_skipped_dirs > 0 {
+            self.status_message.push_str(&format!(
+                " ({} unreadable folders skipped)",
+                self.search_skipped_dirs
+            ));
+        }
+    }
+
+    fn poll_search_task(&mut self, ctx: &egui::Context) {
+        let Some(run) = &mut self.search_task else {
+            return;
+        };
+        while let Ok(batch) = run.hits.try_recv() {
+            self.search_results.extend(batch);
+        }
+        match run.task.poll() {
+            Some(result) => {
+                let cancelled = run.task.is_cancelled();
+                let elapsed = run.started.elapsed();
+                self.search_task = None;
+                match result {
+                    // The final list replaces the streamed one: it has duplicates removed
+                    Ok(results) => {
+                        let heading = if cancelled { "Search cancelled" } else { "Search complete" };
+                        self.show_search_results(results, elapsed, heading, "");
+                    }
+                    Err(e) => self.status_message = format!("Search error: {}", e),
+                }
+            }
+            None => ctx.request_repaint_after(std::time::Duration::from_millis(100)),
+        }
+    }
+
+    /// Mark the app as busy with a long-running operation. Destructive actions
+    /// stay disabled until `end_operation` is called. Returns false if another
+    /// operation is already running.
+    pub fn begin_operation(&mut self, label: &str) -> bool {
+        if self.operation_in_progress.is_some() {
+            return false;
+        }
+        self.operation_in_progress = Some(label.to_string());
+        self.operation_started = Some(std::time::Instant::now());
+        true
+    }
+
+    /// Mark the running operation as finished and return how long it took.
+    pub fn end_operation(&mut self) -> std::time::Duration {
+        let elapsed = self
+            .operation_started
+            .take()
+            .map(|started| started.elapsed())
+            .unwrap_or_default();
+        if let Some(label) = self.operation_in_progress.take() {
+            self.last_operation = Some((label, elapsed));
+        }
+        elapsed
+    }
+
+    pub fn is_busy(&self) -> bool {
+        self.operation_in_progress.is_some()
+    }
+
+    /// Returns true if no operation is running, otherwise reports why the
+    /// requested action was refused.
+    fn ensure_idle(&mut self) -> bool {
+        if let Some(operation) = &self.operation_in_progress {
+            self.status_message = format!("⏳ Please wait: {} in progress", operation);
+            false
+        } else {
+            true
+        }
+    }
+
+    fn save_settings(&mut self) {
+        if let Err(e) = self.settings.save() {
+            self.notify_error(format!("Failed to save settings: {}", e));
+        }
+    }
+
+    fn is_pane_read_only(&self, pane_index: usize) -> bool {
+        let pane = if pane_index == 0 {
+            &self.left_pane
+        } else {
+            &self.right_pane
+        };
+        self.settings.read_only_mode || pane.fs_status.is_some_and(|status| status.read_only)
+    }
+
+    /// Why changes are refused, for the hover text of disabled commands.
+    fn read_only_hint(&self) -> &'static str {
+        if self.settings.read_only_mode {
+            READ_ONLY_MODE_HINT
+        } else {
+            READ_ONLY_HINT
+        }
+    }
+
+    /// Refuse any change to the disk while read-only mode is on. Every
+    /// operation that modifies files passes through here, most of them via
+    /// `ensure_writable`.
+    fn ensure_read_write_mode(&mut self) -> bool {
+        if self.settings.read_only_mode {
+            self.status_message = format!("🔒 {}", READ_ONLY_MODE_HINT);
+            false
+        } else {
+            true
+        }
+    }
+
+    /// Refuse to modify a pane while read-only mode is on or its directory
+    /// is on a read-only filesystem, explaining why in the status bar.
+    fn ensure_writable(&mut self, pane_index: usize) -> bool {
+        if !self.ensure_read_write_mode() {
+            return false;
+        }
+        if self.is_pane_read_only(pane_index) {
+            self.status_message = format!("🔒 {}", READ_ONLY_HINT);
+            false
+        } else {
+            true
+        }
+    }
+
+    fn toggle_read_only_mode(&mut self) {
+        self.settings.read_only_mode = !self.settings.read_only_mode;
+        self.save_settings();
+        self.status_message = if self.settings.read_only_mode {
+            "🔒 Read-only mode on: files can't be changed".to_string()
+        } else {
+            "Read-only mode off".to_string()
+        };
+    }
+
+    /// Inline feedback under the name field of the create/rename dialogs.
+    fn show_name_check(ui: &mut egui::Ui, check: &crate::filesystem::NameCheck) {
+        let (text, color) = match check {
+            crate::filesystem::NameCheck::Valid => return,
+            crate::filesystem::NameCheck::Warning(message) => {
+                (format!("⚠ {}", message), egui::Color32::from_rgb(220, 170, 90))
+            }
+            crate::filesystem::NameCheck::Invalid(message) => {
+                (format!("✗ {}", message), egui::Color32::from_rgb(242, 139, 130))
+            }
+        };
+        ui.add_space(6.0);
+        ui.label(egui::RichText::new(text).size(12.0).color(color));
+    }
+
+    fn open_new_folder_dialog(&mut self) {
+        if self.ensure_writable(self.active_pane) {
+            self.new_folder_contents.clear();
+            self.show_new_folder_dialog = true;
+        }
+    }
+
+    /// Open the new folder dialog so that the active pane's selection is
+    /// moved into the folder once it is created.
+    fn open_new_folder_with_selection(&mut self) {
+        if !self.ensure_writable(self.active_pane) {
+            return;
+        }
+        let pane = self.get_active_pane();
+        let paths: Vec<PathBuf> = pane
+            .get_selected_items()
+            .into_iter()
+            .filter(|item| item.name != "..")
+            .map(|item| item.path.clone())
+            .collect();
+        if paths.is_empty() {
+            self.status_message = "Select the items to put into the new folder".to_string();
+            return;
+        }
+        // Suggest the first free "New Folder", "New Folder 2", ...
+        let dir = &pane.current_path;
+        self.new_folder_name = (1..)
+            .map(|n| if n == 1 { "New Folder".to_string() } else { format!("New Folder {}", n) })
+            .find(|name| fs::symlink_metadata(dir.join(name)).is_err())
+            .unwrap_or_default();
+        self.new_folder_contents = paths;
+        self.show_new_folder_dialog = true;
+    }
+
+    /// Create `name` in the active pane's folder and move
+    /// `new_folder_contents` into it in the background, then select the new
+    /// folder. Entries that fail to move stay where they are and are
+    /// reported together.
+    fn create_folder_with_contents(&mut self, name: &str) -> Result<PathBuf> {
+        if !self.ensure_idle() {
+            anyhow::bail!("another operation is running");
+        }
+        let dir = self.get_active_pane().current_path.clone();
+        let new_path = crate::filesystem::child_path(&dir, name)?;
+        let created = fs::create_dir(&new_path);
+        self.op_log.record("New folder", None, Some(&new_path), &created);
+        created?;
+
+        let contents = std::mem::take(&mut self.new_folder_contents);
+        let name = name.to_string();
+        let target = new_path.clone();
+        let log = self.op_log.clone();
+        let started = self.start_background_task("Moving", move |progress| {
+            let mut failures = Vec::new();
+            for source in &contents {
+                let result = crate::filesystem::check_source_exists(source)
+                    .and_then(|_| crate::filesystem::transfer_destination(source, &target))
+                    .and_then(|dest| {
+                        crate::filesystem::move_path_with_progress(
+                            source,
+                            &dest,
+                            &mut |done, total, current| progress.report(done, total, current),
+                        )
+                    });
+                log.record("Move", Some(source), Some(&target), &result);
+                if let Err(e) = result {
+                    let name = source.file_name().unwrap_or_default().to_string_lossy();
+                    failures.push(format!("{} ({})", name, e));
+                }
+            }
+            if !failures.is_empty() {
+                anyhow::bail!("Created {} but could not move: {}", name, failures.join(", "));
+            }
+            Ok(format!("Moved {} item(s) into {}", contents.len(), name))
+        });
+        if started {
+            self.get_active_pane_mut().clear_selection();
+            self.select_after_task = Some((self.active_pane, dir, vec![new_path.clone()]));
+        }
+        Ok(new_path)
+    }
+
+    fn open_rename_dialog(&mut self) {
+        if !self.ensure_writable(self.active_pane) {
+            return;
+        }
+        if let Some(item) = self.get_active_pane().get_selected_item() {
+            if item.name != ".." {
+                let path = std::path::Path::new(&item.name);
+                let extension = if item.is_dir {
+                    None
+                } else {
+                    path.extension().map(|ext| ext.to_string_lossy().to_string())
+                };
+                // Files with an extension start out editing just the stem
+                self.rename_new_name = match &extension {
+                    Some(_) => path.file_stem().unwrap_or_default().to_string_lossy().to_string(),
+                    None => item.name.clone(),
+                };
+                self.rename_keep_extension = extension.is_some();
+                self.rename_extension = extension;
+                self.show_rename_dialog = true;
+            }
+        }
+    }
+
+    /// Copy the active pane's selection next to itself as "name (copy)",
+    /// "name (copy 2)" and so on, then select the copies.
+    fn duplicate_selection(&mut self) {
+        if !self.ensure_idle() || !self.ensure_writable(self.active_pane) {
+            return;
+        }
+        let pane = self.get_active_pane();
+        let dir = pane.current_path.clone();
+        let sources: Vec<PathBuf> = pane
+            .get_selected_items()
+            .into_iter()
+            .filter(|item| item.name != "..")
+            .map(|item| item.path.clone())
+            .collect();
+        if sources.is_empty() {
+            self.status_message = "Nothing selected".to_string();
+            return;
+        }
+
+        let size_units = self.settings.size_units;
+        let planned: Result<Vec<(PathBuf, PathBuf)>> = sources
+            .iter()
+            .map(|source| {
+                let dest = crate::filesystem::copy_destination(source, &dir)?;
+                Ok((source.clone(), dest))
+            })
+            .collect();
+        let planned = match planned {
+            Ok(planned) => planned,
+            Err(e) => {
+                self.notify_error(format!("Cannot duplicate: {}", e));
+                return;
+            }
+        };
+
+        let copies: Vec<PathBuf> = planned.iter().map(|(_, dest)| dest.clone()).collect();
+        let log = self.op_log.clone();
+        let target = dir.clone();
+        let started = self.start_background_task("Duplicating", move |progress| {
+            // One walk sizes the copies for both the space check and progress
+            let total: u64 = sources
+                .iter()
+                .map(|source| crate::filesystem::calculate_total_size(source))
+                .sum();
+            crate::filesystem::check_space(total, &target, size_units)?;
+            let mut copied = 0;
+            for (source, dest) in &planned {
+                let result = crate::filesystem::copy_recursive_with_progress(
+                    source,
+                    dest,
+                    &mut |done, current| progress.report(copied + done, total, current),
+                );
+                log.record("Duplicate", Some(source), Some(dest), &result);
+                copied += result?;
+            }
+            let names: Vec<String> = planned
+                .iter()
+                .map(|(_, dest)| dest.file_name().unwrap_or_default().to_string_lossy().to_string())
+                .collect();
+            Ok(format!(
+                "Duplicated as {} ({})",
+                names.join(", "),
+                crate::filesystem::format_size_with(copied, size_units)
+            ))
+        });
+        if started {
+            self.select_after_task = Some((self.active_pane, dir, copies));
+        }
+    }
+
+    /// Open the "Touch" dialog for the active pane's selection, starting
+    /// from the first entry's current modification time.
+    fn open_touch_dialog(&mut self) {
+        if !self.ensure_writable(self.active_pane) {
+            return;
+        }
+        let items: Vec<&crate::filesystem::FileItem> = self
+            .get_active_pane()
+            .get_selected_items()
+            .into_iter()
+            .filter(|item| item.name != "..")
+            .collect();
+        let Some(first) = items.first() else {
+            self.status_message = "Nothing selected".to_string();
+            return;
+        };
+        let modified: chrono::DateTime<chrono::Local> = first.modified.into();
+        self.touch_dialog = Some(TouchDialog {
+            paths: items.iter().map(|item| item.path.clone()).collect(),
+            use_now: true,
+            date: modified.date_naive(),
+            time: modified.format("%H:%M:%S").to_string(),
+            access: false,
+        });
+    }
+
+    /// Open the "Permissions" dialog for the active pane's selection,
+    /// starting from the first entry's current mode.
+    fn open_permissions_dialog(&mut self) {
+        if !self.ensure_writable(self.active_pane) {
+            return;
+        }
+        let items: Vec<&crate::filesystem::FileItem> = self
+            .get_active_pane()
+            .get_selected_items()
+            .into_iter()
+            .filter(|item| item.name != "..")
+            .collect();
+        let Some(first) = items.first() else {
+            self.status_message = "Nothing selected".to_string();
+            return;
+        };
+        let mode = match crate::filesystem::permission_mode(&first.path) {
+            Ok(mode) => mode,
+            Err(e) => {
+                self.notify_error(format!("Cannot read permissions: {}", e));
+                return;
+            }
+        };
+        self.permissions_dialog = Some(PermissionsDialog {
+            paths: items.iter().map(|item| item.path.clone()).collect(),
+            mode,
+            octal: format!("{:03o}", mode),
+            recursive: false,
+            has_folders: items.iter().any(|item| item.is_dir),
+        });
+    }
+
+    /// The "Permissions" dialog: read/write/execute per class or an octal
+    /// mode, applied on the background worker to every selected entry and,
+    /// optionally, to the contents of selected folders.
+    fn render_permissions_dialog(&mut self, ctx: &egui::Context) {
+        let Some(dialog) = &mut self.permissions_dialog else {
+            return;
+        };
+        let mut apply = false;
+        let mut close = false;
+        egui::Window::new("🔐 Permissions")
+            .collapsible(false)
+            .resizable(false)
+            .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
+            .frame(
+                egui::Frame::window(&ctx.style())
+                    .fill(egui::Color32::from_rgb(45, 47, 50))
+                    .rounding(12.0)
+                    .inner_margin(20.0),
+            )
+            .show(ctx, |ui| {
+                ui.set_width(380.0);
+                let target = match dialog.paths.as_slice() {
+                    [path] => path
+                        .file_name()
+                        .map(|name| name.to_string_lossy().to_string())
+                        .unwrap_or_else(|| path.display().to_string()),
+                    paths => format!("{} items", paths.len()),
+                };
+                ui.label(egui::RichText::new(format!("Set the permissions of {}", target)).size(13.0));
+                ui.add_space(10.0);
+
+                let mut bits_changed = false;
+                egui::Grid::new("permission_bits")
+                    .num_columns(4)
+                    .spacing([18.0, 6.0])
+                    .show(ui, |ui| {
+                        ui.label("");
+                        for heading in ["Read", "Write", "Execute"] {
+                            ui.label(egui::RichText::new(heading).size(12.0).strong());
+                        }
+                        ui.end_row();
+                        for (class, shift) in [("Owner", 6), ("Group", 3), ("Others", 0)] {
+                            ui.label(class);
+                            for bit in [4, 2, 1] {
+                                let mask = bit << shift;
+                                let mut on = dialog.mode & mask != 0;
+                                if ui.checkbox(&mut on, "").changed() {
+                                    dialog.mode ^= mask;
+                                    bits_changed = true;
+                                }
+                            }
+                            ui.end_row();
+                        }
+                    });
+                if bits_changed {
+                    dialog.octal = format!("{:03o}", dialog.mode);
+                }
+
+                ui.add_space(8.0);
+                ui.horizontal(|ui| {
+                    ui.label("Octal:");
+                    let response = ui.add(
+                        egui::TextEdit::singleline(&mut dialog.octal)
+                            .desired_width(60.0)
+                            .hint_text("755"),
+                    );
+                    if response.changed() {
+                        if let Ok(mode) = crate::filesystem::parse_mode(&dialog.octal) {
+                            dialog.mode = mode;
+                        }
+                    }
+                });
+                let parsed = crate::filesystem::parse_mode(&dialog.octal);
+                if let Err(e) = &parsed {
+                    ui.label(
+                        egui::RichText::new(format!("⚠ {}", e))
+                            .size(12.0)
+                            .color(egui::Color32::from_rgb(242, 139, 130)),
+                    );
+                }
+                if dialog.has_folders {
+                    ui.add_space(6.0);
+                    ui.checkbox(&mut dialog.recursive, "Apply to everything inside the folders");
+                    ui.label(
+                        egui::RichText::new(
+                            "Folders also get execute wherever read is allowed, so they can still be opened.",
+                        )
+                        .size(11.0)
+                        .color(egui::Color32::from_rgb(154, 160, 166)),
+                    );
+                }
+
+                ui.add_space(15.0);
+                ui.horizontal(|ui| {
+                    apply = ui
+                        .add_enabled_ui(parsed.is_ok(), |ui| {
+                            ui.add_sized(
+                                [140.0, 36.0],
+                                egui::Button::new(egui::RichText::new("🔐 Apply").size(14.0))
+                                    .fill(egui::Color32::from_rgb(40, 167, 69))
+                                    .rounding(6.0),
+                            )
+                        })
+                        .inner
+                        .clicked();
+                    if ui
+                        .add_sized(
+                            [140.0, 36.0],
+                            egui::Button::new(egui::RichText::new("✗ Cancel").size(14.0))
+                                .fill(egui::Color32::from_rgb(66, 70, 77))
+                                .rounding(6.0),
+                        )
+                        .clicked()
+                    {
+                        close = true;
+                    }
+                });
+            });
+
+        if apply {
+            let Some(dialog) = self.permissions_dialog.take() else {
+                return;
+            };
+            if !self.ensure_idle() {
+                return;
+            }
+            let PermissionsDialog { paths, mode, recursive, .. } = dialog;
+            let log = self.op_log.clone();
+            self.start_cancellable_task("Setting permissions", move |progress| {
+                let total: u64 = if recursive {
+                    paths.iter().map(|path| crate::filesystem::count_entries(path)).sum()
+                } else {
+                    paths.len() as u64
+                };
+                let mut changed = 0;
+                let mut failures = Vec::new();
+                let mut cancelled = false;
+                let action = format!("Set mode {:03o}", mode);
+                for path in &paths {
+                    let result = crate::filesystem::set_permissions_with_progress(
+                        path,
+                        mode,
+                        recursive,
+                        &mut changed,
+                        &mut |done, current| progress.report(done, total, current),
+                        &|| progress.is_cancelled(),
+                    );
+                    // A cancelled item was left partly done; the task's own result says so
+                    if !matches!(result, Ok(false)) {
+                        log.record(&action, Some(path), None, &result);
+                    }
+                    match result {
+                        Ok(true) => {}
+                        Ok(false) => {
+                            cancelled = true;
+                            break;
+                        }
+                        Err(e) => {
+                            let name = path.file_name().unwrap_or_default().to_string_lossy();
+                            failures.push(format!("{} ({})", name, e));
+                        }
+                    }
+                }
+                if !failures.is_empty() {
+                    anyhow::bail!(
+                        "Set mode {:03o} on {} entries. Failed: {}",
+                        mode,
+                        changed,
+                        failures.join(", ")
+                    );
+                }
+                Ok(if cancelled {
+                    format!("Cancelled: set mode {:03o} on {} of {} entries", mode, changed, total)
+                } else {
+                    format!("Set mode {:03o} on {} entries", mode, changed)
+                })
+            });
+        } else if close {
+            self.permissions_dialog = None;
+        }
+    }
+
+    /// The "Touch" dialog: now or a picked date and time, optionally for the
+    /// access time too, applied to every selected entry.
+    fn render_touch_dialog(&mut self, ctx: &egui::Context) {
+        let Some(dialog) = &mut self.touch_dialog else {
+            return;
+        };
+        let mut apply = false;
+        let mut close = false;
+        egui::Window::new("🕒 Touch")
+            .collapsible(false)
+            .resizable(false)
+            .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
+            .frame(
+                egui::Frame::window(&ctx.style())
+                    .fill(egui::Color32::from_rgb(45, 47, 50))
+                    .rounding(12.0)
+                    .inner_margin(20.0),
+            )
+            .show(ctx, |ui| {
+                ui.set_width(380.0);
+                let target = match dialog.paths.as_slice() {
+                    [path] => path
+                        .file_name()
+                        .map(|name| name.to_string_lossy().to_string())
+                        .unwrap_or_else(|| path.display().to_string()),
+                    paths => format!("{} items", paths.len()),
+                };
+                ui.label(
+                    egui::RichText::new(format!("Set the modification time of {}", target))
+                        .size(13.0),
+                );
+                ui.add_space(10.0);
+                ui.radio_value(&mut dialog.use_now, true, "Now");
+                ui.radio_value(&mut dialog.use_now, false, "At a specific date and time");
+                ui.add_enabled_ui(!dialog.use_now, |ui| {
+                    ui.horizontal(|ui| {
+                        ui.add_space(20.0);
+                        ui.add(egui_extras::DatePickerButton::new(&mut dialog.date).id_salt("touch_date"));
+                        ui.add(
+                            egui::TextEdit::singleline(&mut dialog.time)
+                                .desired_width(80.0)
+                                .hint_text("HH:MM:SS"),
+                        );
+                    });
+                });
+                let chosen = dialog.chosen_time();
+                if let Err(e) = &chosen {
+                    ui.label(
+                        egui::RichText::new(format!("⚠ {}", e))
+                            .size(12.0)
+                            .color(egui::Color32::from_rgb(242, 139, 130)),
+                    );
+                }
+                ui.add_space(6.0);
+                ui.checkbox(&mut dialog.access, "Also set the access time");
+
+                ui.add_space(15.0);
+                ui.horizontal(|ui| {
+                    apply = ui
+                        .add_enabled_ui(chosen.is_ok(), |ui| {
+                            ui.add_sized(
+                                [140.0, 36.0],
+                                egui::Button::new(egui::RichText::new("🕒 Touch").size(14.0))
+                                    .fill(egui::Color32::from_rgb(40, 167, 69))
+                                    .rounding(6.0),
+                            )
+                        })
+                        .inner
+                        .clicked();
+                    if ui
+                        .add_sized(
+                            [140.0, 36.0],
+                            egui::Button::new(egui::RichText::new("✗ Cancel").size(14.0))
+                                .fill(egui::Color32::from_rgb(66, 70, 77))
+                                .rounding(6.0),
+                        )
+                        .clicked()
+                    {
+                        close = true;
+                    }
+                });
+            });
+
+        if apply && self.ensure_read_write_mode() {
+            let Some(dialog) = self.touch_dialog.take() else {
+                return;
+            };
+            let time = match dialog.chosen_time() {
+                Ok(time) => time,
+                Err(e) => {
+                    self.notify_error(format!("Touch failed: {}", e));
+                    return;
+                }
+            };
+            let mut failures = Vec::new();
+            for path in &dialog.paths {
+                let result = if dialog.access {
+                    crate::filesystem::touch_with_access(path, time)
+                } else {
+                    crate::filesystem::touch(path, time)
+                };
+                self.op_log.record("Touch", Some(path), None, &result);
+                if let Err(e) = result {
+                    let name = path.file_name().unwrap_or_default().to_string_lossy();
+                    failures.push(format!("{}: {}", name, e));
+                }
+            }
+            let _ = self.refresh_both_panes();
+            let touched = dialog.paths.len() - failures.len();
+            let when = time.map_or_else(|| "now".to_string(), crate::filesystem::format_date);
+            if failures.is_empty() {
+                self.notify_success(format!("Touched {} item(s), modified {}", touched, when));
+            } else {
+                self.notify_error(format!(
+                    "Touched {} of {} item(s); failed: {}",
+                    touched,
+                    dialog.paths.len(),
+                    failures.join("; ")
+                ));
+            }
+        } else if close {
+            self.touch_dialog = None;
+        }
+    }
+
+    fn render_three_way_dialog(&mut self, ctx: &egui::Context) {
+        let Some(dialog) = &mut self.three_way else {
+            return;
+        };
+        let diff_options = self.diff_options;
+        let mut compare = false;
+        let mut back = false;
+        let mut close = false;
+        egui::Window::new("⚖ 3-way Compare")
+            .collapsible(false)
+            .resizable(true)
+            .default_size([1100.0, 600.0])
+            .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
+            .frame(
+                egui::Frame::window(&ctx.style())
+                    .fill(egui::Color32::from_rgb(45, 47, 50))
+                    .rounding(12.0)
+                    .inner_margin(15.0),
+            )
+            .show(ctx, |ui| {
+                for (label, path) in [("Left:", &dialog.left), ("Right:", &dialog.right)] {
+                    ui.horizontal(|ui| {
+                        ui.label(egui::RichText::new(label).strong().size(12.0));
+                        ui.label(
+                            egui::RichText::new(path.display().to_string())
+                                .size(11.0)
+                                .color(egui::Color32::from_rgb(138, 180, 248)),
+                        );
+                    });
+                }
+
+                let Some(result) = &dialog.result else {
+                    ui.add_space(10.0);
+                    ui.label(
+                        egui::RichText::new("Base file both sides started from:").size(13.0),
+                    );
+                    let response = ui.add(
+                        egui::TextEdit::singleline(&mut dialog.base)
+                            .desired_width(f32::INFINITY)
+                            .hint_text("/path/to/base"),
+                    );
+                    let base = PathBuf::from(dialog.base.trim());
+                    let problem = if dialog.base.trim().is_empty() {
+                        Some("Enter the path of the base file")
+                    } else if !base.is_file() {
+                        Some("Base must be an existing file")
+                    } else {
+                        None
+                    };
+                    if let Some(problem) = problem {
+                        ui.label(
+                            egui::RichText::new(format!("⚠ {}", problem))
+                                .size(12.0)
+                                .color(egui::Color32::from_rgb(242, 139, 130)),
+                        );
+                    }
+                    let submitted =
+                        response.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter));
+
+                    ui.add_space(15.0);
+                    ui.horizontal(|ui| {
+                        let clicked = ui
+                            .add_enabled_ui(problem.is_none(), |ui| {
+                                ui.add_sized(
+                                    [140.0, 36.0],
+                                    egui::Button::new(egui::RichText::new("⚖ Compare").size(14.0))
+                                        .fill(egui::Color32::from_rgb(40, 167, 69))
+                                        .rounding(6.0),
+                                )
+                            })
+                            .inner
+                            .clicked();
+                        compare = problem.is_none() && (clicked || submitted);
+                        if ui
+                            .add_sized(
+                                [140.0, 36.0],
+                                egui::Button::new(egui::RichText::new("✗ Cancel").size(14.0))
+                                    .fill(egui::Color32::from_rgb(66, 70, 77))
+                                    .rounding(6.0),
+                            )
+                            .clicked()
+                        {
+                            close = true;
+                        }
+                    });
+                    return;
+                };
+
+                ui.horizontal(|ui| {
+                    ui.label(egui::RichText::new("Base:").strong().size(12.0));
+                    ui.label(
+                        egui::RichText::new(result.base_path.display().to_string())
+                            .size(11.0)
+                            .color(egui::Color32::from_rgb(138, 180, 248)),
+                    );
+                });
+                ui.add_space(6.0);
+                ui.horizontal(|ui| {
+                    ui.label(egui::RichText::new("Changed from base:").strong().size(12.0));
+                    ui.label(
+                        egui::RichText::new(format!(
+                            "Left: {} | Right: {} | Both: {} | Conflicts: {}",
+                            result.left_changes,
+                            result.right_changes,
+                            result.both_changes,
+                            result.conflicts
+                        ))
+                        .size(11.0),
+                    );
+                });
+                ui.add_space(5.0);
+                ui.separator();
+                ui.add_space(5.0);
+
+                ui.horizontal(|ui| {
+                    ui.label(egui::RichText::new("Legend:").strong().size(11.0));
+                    ui.label(egui::RichText::new("⬜ Unchanged").size(10.0));
+                    ui.label(
+                        egui::RichText::new("🟦 Left")
+                            .size(10.0)
+                            .color(egui::Color32::from_rgb(138, 180, 248)),
+                    );
+                    ui.label(
+                        egui::RichText::new("🟩 Right")
+                            .size(10.0)
+                            .color(egui::Color32::from_rgb(129, 201, 149)),
+                    );
+                    ui.label(
+                        egui::RichText::new("⬛ Both alike")
+                            .size(10.0)
+                            .color(egui::Color32::from_rgb(154, 160, 166)),
+                    );
+                    ui.label(
+                        egui::RichText::new("🟥 Conflict")
+                            .size(10.0)
+                            .color(egui::Color32::from_rgb(242, 139, 130)),
+                    );
+                });
+                ui.add_space(10.0);
+
+                let column_width = ((ui.available_width() - 60.0) / 3.0).max(100.0);
+                egui::ScrollArea::vertical().max_height(400.0).show(ui, |ui| {
+                    ui.style_mut().spacing.item_spacing.y = 1.0;
+                    for line in &result.lines {
+                        let fill = match line.line_type {
+                            crate::filesystem::MergeLineType::Unchanged => {
+                                egui::Color32::from_rgb(50, 52, 55)
+                            }
+                            crate::filesystem::MergeLineType::LeftChanged => {
+                                egui::Color32::from_rgb(35, 55, 85)
+                            }
+                            crate::filesystem::MergeLineType::RightChanged => {
+                                egui::Color32::from_rgb(40, 80, 40)
+                            }
+                            crate::filesystem::MergeLineType::BothChanged => {
+                                egui::Color32::from_rgb(65, 67, 71)
+                            }
+                            crate::filesystem::MergeLineType::Conflict => {
+                                egui::Color32::from_rgb(80, 40, 40)
+                            }
+                        };
+                        // A side without a line removed it, unless the line
+                        // is one the other side inserted.
+                        let side_text = |side: &Option<String>| match side {
+                            Some(text) => egui::RichText::new(text.as_str()),
+                            None if line.base.is_some() => egui::RichText::new("(removed)")
+                                .italics()
+                                .color(egui::Color32::from_rgb(154, 160, 166)),
+                            None => egui::RichText::new(""),
+                        };
+                        egui::Frame::default()
+                            .fill(fill)
+                            .inner_margin(egui::Margin::symmetric(8.0, 4.0))
+                            .show(ui, |ui| {
+                                ui.horizontal(|ui| {
+                                    let number = line
+                                        .base_line_num
+                                        .map(|n| format!("{:4}", n))
+                                        .unwrap_or_else(|| "    ".to_string());
+                                    ui.label(
+                                        egui::RichText::new(number)
+                                            .size(10.0)
+                                            .family(egui::FontFamily::Monospace)
+                                            .color(egui::Color32::from_rgb(154, 160, 166)),
+                                    );
+                                    for text in [
+                                        side_text(&line.base),
+                                        side_text(&line.left),
+                                        side_text(&line.right),
+                                    ] {
+                                        ui.vertical(|ui| {
+                                            ui.set_width(column_width);
+                                            ui.add(
+                                                egui::Label::new(
+                                                    text.size(10.0).family(egui::FontFamily::Monospace),
+                                                )
+                                                .wrap(),
+                                            );
+                                        });
+                                    }
+                                });
+                            });
+                    }
+                });
+
+                ui.add_space(15.0);
+                ui.separator();
+                ui.add_space(10.0);
+                ui.horizontal(|ui| {
+                    if ui
+                        .add_sized(
+                            [140.0, 36.0],
+                            egui::Button::new(egui::RichText::new("⬅ Change Base").size(14.0))
+                                .fill(egui::Color32::from_rgb(66, 70, 77))
+                                .rounding(6.0),
+                        )
+                        .clicked()
+                    {
+                        back = true;
+                    }
+                    if ui
+                        .add_sized(
+                            [140.0, 36.0],
+                            egui::Button::new(egui::RichText::new("Close (ESC)").size(14.0))
+                                .fill(egui::Color32::from_rgb(66, 70, 77))
+                                .rounding(6.0),
+                        )
+                        .clicked()
+                    {
+                        close = true;
+                    }
+                });
+            });
+
+        if compare {
+            let base = PathBuf::from(dialog.base.trim());
+            match crate::filesystem::compare_three_way(
+                &base,
+                &dialog.left,
+                &dialog.right,
+                diff_options,
+            ) {
+                Ok(result) => {
+                    self.status_message = format!(
+                        "3-way compare: {} left, {} right, {} both, {} conflict(s)",
+                        result.left_changes,
+                        result.right_changes,
+                        result.both_changes,
+                        result.conflicts
+                    );
+                    dialog.result = Some(result);
+                }
+                Err(e) => {
+                    self.status_message = format!("❌ Comparison failed: {}", e);
+                }
+            }
+        } else if back {
+            dialog.result = None;
+        }
+        if close {
+            self.three_way = None;
+        }
+    }
+
+    fn is_image_file(path: &std::path::Path) -> bool {
+        if let Some(ext) = path.extension() {
+            let ext = ext.to_string_lossy().to_lowercase();
+            matches!(
+                ext.as_str(),
+                "png" | "jpg" | "jpeg" | "gif" | "bmp" | "ico" | "webp"
+            )
+        } else {
+            false
+        }
+    }
+
+    pub fn open_image_viewer(&mut self, path: PathBuf, name: String) {
+        self.image_viewer = Some(ImageViewerState::new(path, name));
+    }
+
+    pub fn close_image_viewer(&mut self) {
+        self.image_viewer = None;
+    }
+
+    pub fn copy_to_clipboard(&mut self) {
+        let item_data = self
+            .get_active_pane()
+            .get_selected_item()
+            .map(|item| (item.path.clone(), item.name.clone()));
+
+        if let Some((path, name)) = item_data {
+            if name != ".." {
+                self.clipboard = Some((path, ClipboardOperation::Copy));
+                self.status_message = format!("Copied to clipboard: {}", name);
+            }
+        }
+    }
+
+    pub fn cut_to_clipboard(&mut self) {
+        let item_data = self
+            .get_active_pane()
+            .get_selected_item()
+            .map(|item| (item.path.clone(), item.name.clone()));
+
+        if let Some((path, name)) = item_data {
+            if name != ".." {
+                self.clipboard = Some((path, ClipboardOperation::Cut));
+                self.status_message = format!("Cut to clipboard: {}", name);
+            }
+        }
+    }
+
+    pub fn notify_success(&mut self, message: String) {
+        self.push_toast(ToastKind::Success, message);
+    }
+
+    pub fn notify_error(&mut self, message: String) {
+        self.push_toast(ToastKind::Error, message);
+    }
+
+    fn push_toast(&mut self, kind: ToastKind, message: String) {
+        if self.toasts.len() >= MAX_TOASTS {
+            self.toasts.remove(0);
+        }
+        self.toasts.push(Toast {
+            message,
+            kind,
+            created: std::time::Instant::now(),
+        });
+    }
+
+    /// Draw the toast stack in the top-right corner. Toasts expire on their
+    /// own and can be dismissed early by clicking them.
+    fn render_toasts(&mut self, ctx: &egui::Context) {
+        self.toasts.retain(|toast| {
+            let duration = match toast.kind {
+                ToastKind::Success => TOAST_DURATION,
+                ToastKind::Error => ERROR_TOAST_DURATION,
+            };
+            toast.created.elapsed() < duration
+        });
+        if self.toasts.is_empty() {
+            return;
+        }
+
+        let mut dismissed = None;
+        egui::Area::new(egui::Id::new("toasts"))
+            .anchor(egui::Align2::RIGHT_TOP, [-12.0, 44.0])
+            .order(egui::Order::Foreground)
+            .show(ctx, |ui| {
+                ui.set_max_width(360.0);
+                for (index, toast) in self.toasts.iter().enumerate() {
+                    let (icon, accent) = match toast.kind {
+                        ToastKind::Success => ("✅", egui::Color32::from_rgb(129, 201, 149)),
+                        ToastKind::Error => ("❌", egui::Color32::from_rgb(242, 139, 130)),
+                    };
+                    let response = egui::Frame::default()
+                        .fill(egui::Color32::from_rgb(45, 47, 50))
+                        .stroke(egui::Stroke::new(1.0, accent))
+                        .rounding(8.0)
+                        .inner_margin(egui::Margin::symmetric(12.0, 8.0))
+                        .show(ui, |ui| {
+                            ui.horizontal_wrapped(|ui| {
+                                ui.label(egui::RichText::new(icon).color(accent));
+                                ui.label(
+                                    egui::RichText::new(&toast.message)
+                                        .size(13.0)
+                                        .color(egui::Color32::from_rgb(232, 234, 237)),
+                                );
+                            });
+                        })
+                        .response
+                        .interact(egui::Sense::click())
+                        .on_hover_text("Click to dismiss");
+                    if response.clicked() {
+                        dismissed = Some(index);
+                    }
+                    ui.add_space(6.0);
+                }
+            });
+
+        if let Some(index) = dismissed {
+            self.toasts.remove(index);
+        }
+        ctx.request_repaint_after(std::time::Duration::from_millis(250));
+    }
+
+    pub fn paste_from_clipboard(&mut self) -> Result<()> {
+        let target_pane = 1 - self.active_pane;
+        let target_path = self.get_pane(target_pane).current_path.clone();
+        self.paste_into(target_pane, target_path)
+    }
+
+    /// Paste into the folder under the cursor in the active pane instead of
+    /// the other pane's folder.
+    pub fn paste_into_selected_folder(&mut self) -> Result<()> {
+        let folder = self
+            .get_pane(self.active_pane)
+            .get_selected_item()
+            .filter(|item| item.is_dir && item.name != "..")
+            .map(|item| item.path.clone());
+        match folder {
+            Some(folder) => self.paste_into(self.active_pane, folder),
+            None => {
+                self.status_message = "Highlight a folder to paste into".to_string();
+                Ok(())
+            }
+        }
+    }
+
+    /// Copy or move the clipboard entry into `target_path`, a folder shown
+    /// in (or below) `target_pane`.
+    fn paste_into(&mut self, target_pane: usize, target_path: PathBuf) -> Result<()> {
+        if !self.ensure_idle() || !self.ensure_writable(target_pane) {
+            return Ok(());
+        }
+
+        // Clone clipboard data to avoid borrow issues
+        let clipboard_data = self.clipboard.clone();
+
+        if let Some((source_path, operation)) = clipboard_data {
+            // The clipboard may hold a path that was moved or deleted since
+            if let Err(e) = crate::filesystem::check_source_exists(&source_path) {
+                self.clipboard = None;
+                self.notify_error(format!("Cannot paste: {}", e));
+                let _ = self.refresh_both_panes();
+                return Ok(());
+            }
+
+            let Some(file_name) = source_path.file_name() else {
+                self.notify_error(format!(
+                    "Cannot paste {}: it is a filesystem root",
+                    source_path.display()
+                ));
+                return Ok(());
+            };
+            let file_name = file_name.to_string_lossy().to_string();
+            // Pasting into the source's own folder copies under a new name
+            // and refuses to move
+            let dest_path = match operation {
+                ClipboardOperation::Copy => {
+                    crate::filesystem::copy_destination(&source_path, &target_path)
+                }
+                ClipboardOperation::Cut => {
+                    crate::filesystem::transfer_destination(&source_path, &target_path)
+                }
+            };
+            let is_move = matches!(operation, ClipboardOperation::Cut);
+            let dest_path = match dest_path {
+                Ok(dest_path) => dest_path,
+                Err(e) => {
+                    let action = if is_move { "Move" } else { "Copy" };
+                    self.op_log.record(action, Some(&source_path), Some(&target_path), &Err::<(), _>(&e));
+                    self.notify_error(format!("Cannot paste {}: {}", file_name, e));
+                    return Ok(());
+                }
+            };
+
+            match operation {
+                ClipboardOperation::Copy => {
+                    // Copies run in the background; the panes refresh when
+                    // the task finishes.
+                    let size_units = self.settings.size_units;
+                    let log = self.op_log.clone();
+                    self.start_background_task("Copying", move |progress| {
+                        let total = crate::filesystem::calculate_total_size(&source_path);
+                        let result = crate::filesystem::check_space(total, &target_path, size_units)
+                            .and_then(|_| {
+                                crate::filesystem::copy_recursive_with_progress(
+                                    &source_path,
+                                    &dest_path,
+                                    &mut |done, current| progress.report(done, total, current),
+                                )
+                            });
+                        log.record("Copy", Some(&source_path), Some(&dest_path), &result);
+                        let copied = result?;
+                        Ok(format!(
+                            "Copied {} ({}) → {}",
+                            file_name,
+                            crate::filesystem::format_size_with(copied, size_units),
+                            dest_path.display()
+                        ))
+                    });
+                }
+                ClipboardOperation::Cut => {
+                    // A rename is instant, but moving to another drive
+                    // copies, so this runs in the background like a copy
+                    let size_units = self.settings.size_units;
+                    let log = self.op_log.clone();
+                    let started = self.start_background_task("Moving", move |progress| {
+                        let result = crate::filesystem::move_path_with_progress(
+                            &source_path,
+                            &dest_path,
+                            &mut |done, total, current| progress.report(done, total, current),
+                        );
+                        log.record("Move", Some(&source_path), Some(&dest_path), &result);
+                        Ok(match result? {
+                            Some(copied) => format!(
+                                "Moved {} ({}) → {}",
+                                file_name,
+                                crate::filesystem::format_size_with(copied, size_units),
+                                dest_path.display()
+                            ),
+                            None => format!("Moved {} → {}", file_name, dest_path.display()),
+                        })
+                    });
+                    if started {
+                        self.clipboard = None; // Clear clipboard after cut
+                    }
+                }
+            }
+        } else {
+            self.status_message = "Clipboard is empty".to_string();
+        }
+        Ok(())
+    }
+
+    /// Run one of the user's custom commands on the active pane's selection
+    /// in the background. The pane folder is the working directory, and the
+    /// last line the program prints ends up in the toast.
+    pub fn run_custom_command(&mut self, index: usize) {
+        if !self.ensure_idle() {
+            return;
+        }
+        let Some(command) = self.settings.custom_commands.get(index).cloned() else {
+            return;
+        };
+        let pane = self.get_active_pane();
+        let dir = pane.current_path.clone();
+        let file = pane
+            .get_selected_item()
+            .filter(|item| item.name != "..")
+            .map(|item| item.path.clone())
+            .unwrap_or_else(|| dir.clone());
+        let selection: Vec<PathBuf> = pane
+            .get_selected_items()
+            .into_iter()
+            .filter(|item| item.name != "..")
+            .map(|item| item.path.clone())
+            .collect();
+        let arguments = match command.arguments(&file, &dir, &selection) {
+            Ok(arguments) => arguments,
+            Err(e) => {
+                self.notify_error(format!("Cannot run {}: {}", command.name, e));
+                return;
+            }
+        };
+
+        let label = command.name.clone();
+        let log = self.op_log.clone();
+        self.start_background_task(&label, move |_| {
+            let result = run_command(&arguments, &dir);
+            log.record(&command.name, Some(&file), None, &result);
+            Ok(match result? {
+                Some(line) => format!("{}: {}", command.name, line),
+                None => format!("{} finished", command.name),
+            })
+        });
+    }
+
+    /// Recreate the subfolders of the folder under the cursor in the other
+    /// pane, leaving out all files.
+    pub fn copy_folder_structure(&mut self) {
+        if !self.ensure_idle() || !self.ensure_writable(1 - self.active_pane) {
+            return;
+        }
+        let Some(item) = self.get_active_pane().get_selected_item().cloned() else {
+            return;
+        };
+        if !item.is_dir || item.name == ".." {
+            self.status_message = "Select a folder to copy its structure".to_string();
+            return;
+        }
+        let dest_dir = if self.active_pane == 0 {
+            self.right_pane.current_path.clone()
+        } else {
+            self.left_pane.current_path.clone()
+        };
+
+        let log = self.op_log.clone();
+        self.start_background_task("Copying folder structure", move |_| {
+            let result =
+                crate::filesystem::copy_destination(&item.path, &dest_dir).and_then(|dest| {
+                    crate::filesystem::copy_dir_structure(&item.path, &dest)?;
+                    Ok(dest)
+                });
+            let destination = result.as_ref().map_or(dest_dir.as_path(), |dest| dest.as_path());
+            log.record("Copy folder structure", Some(&item.path), Some(destination), &result);
+            let dest = result?;
+            Ok(format!("Copied folder structure: {} → {}", item.name, dest.display()))
+        });
+    }
+
+    /// Open the "Copy to…" / "Move to…" dialog for the active pane's selection.
+    pub fn open_transfer_dialog(&mut self, mode: TransferMode) {
+        let is_move = mode == TransferMode::Move;
+        if !self.ensure_idle() || (is_move && !self.ensure_writable(self.active_pane)) {
+            return;
+        }
+        let sources: Vec<PathBuf> = self
+            .get_active_pane()
+            .get_selected_items()
+            .into_iter()
+            .filter(|item| item.name != "..")
+            .map(|item| item.path.clone())
+            .collect();
+        if sources.is_empty() {
+            self.status_message = "Nothing selected".to_string();
+            return;
+        }
+        let other_pane = if self.active_pane == 0 {
+            &self.right_pane
+        } else {
+            &self.left_pane
+        };
+        let destination = self
+            .last_transfer_dir
+            .clone()
+            .filter(|dir| dir.is_dir())
+            .unwrap_or_else(|| other_pane.current_path.clone());
+        self.transfer_dialog = Some(TransferDialog {
+            sources,
+            mode,
+            destination: destination.display().to_string(),
+            archive_summary: None,
+            password: None,
+            wrong_password: false,
+        });
+    }
+
+    /// Run the failed items of the summarized batch again. Skipped items are
+    /// left out: they were skipped for a reason that still holds.
+    fn retry_batch_failures(&mut self) {
+        if !self.ensure_idle() {
+            return;
+        }
+        let Some(summary) = self.batch_summary.take() else {
+            return;
+        };
+        let failed = summary.report.failed_paths();
+        match summary.retry {
+            BatchRetry::Transfer { dest_dir, is_move, merge } => {
+                self.start_transfer(failed, dest_dir, is_move, merge)
+            }
+            BatchRetry::Delete => self.start_delete(failed),
+        }
+    }
+
+    /// One dialog at the end of a batch copy, move or delete that skipped
+    /// or failed items: what succeeded, what was skipped and why, and what
+    /// failed, with a button to retry the failures.
+    fn render_batch_summary(&mut self, ctx: &egui::Context) {
+        let Some(summary) = &self.batch_summary else {
+            return;
+        };
+        let report = &summary.report;
+        let grey = egui::Color32::from_rgb(154, 160, 166);
+        let mut retry = false;
+        let mut close = false;
+        egui::Window::new(format!("📋 {} Summary", summary.action))
+            .collapsible(false)
+            .resizable(true)
+            .default_size([640.0, 420.0])
+            .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
+            .frame(
+                egui::Frame::window(&ctx.style())
+                    .fill(egui::Color32::from_rgb(45, 47, 50))
+                    .rounding(12.0)
+                    .inner_margin(20.0),
+            )
+            .show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    for (text, count, color) in [
+                        ("✔ succeeded", report.succeeded.len(), egui::Color32::from_rgb(129, 201, 149)),
+                        ("⏭ skipped", report.skipped.len(), egui::Color32::from_rgb(220, 170, 90)),
+                        ("✗ failed", report.failed.len(), egui::Color32::from_rgb(242, 139, 130)),
+                    ] {
+                        ui.label(
+                            egui::RichText::new(format!("{} {}", count, text))
+                                .size(14.0)
+                                .strong()
+                                .color(if count > 0 { color } else { grey }),
+                        );
+                        ui.add_space(12.0);
+                    }
+                });
+                ui.add_space(10.0);
+                ui.separator();
+
+                let row = |ui: &mut egui::Ui, path: &std::path::Path, reason: Option<&str>, color| {
+                    let name = path.file_name().unwrap_or(path.as_os_str()).to_string_lossy();
+                    ui.horizontal_wrapped(|ui| {
+                        ui.label(egui::RichText::new(name).size(12.0).color(color))
+                            .on_hover_text(path.display().to_string());
+                        if let Some(reason) = reason {
+                            ui.label(egui::RichText::new(reason).size(11.0).color(grey));
+                        }
+                    });
+                };
+                egui::ScrollArea::vertical()
+                    .max_height(300.0)
+                    .auto_shrink([false, true])
+                    .show(ui, |ui| {
+                        for (title, entries, color) in [
+                            ("Failed", &report.failed, egui::Color32::from_rgb(242, 139, 130)),
+                            ("Skipped", &report.skipped, egui::Color32::from_rgb(220, 170, 90)),
+                        ] {
+                            if entries.is_empty() {
+                                continue;
+                            }
+                            ui.add_space(6.0);
+                            ui.label(egui::RichText::new(title).size(13.0).strong());
+                            for (path, reason) in entries {
+                                row(ui, path, Some(reason), color);
+                            }
+                        }
+                        if !report.succeeded.is_empty() {
+                            ui.add_space(6.0);
+                            egui::CollapsingHeader::new(
+                                egui::RichText::new(format!("Succeeded ({})", report.succeeded.len()))
+                                    .size(13.0)
+                                    .strong(),
+                            )
+                            .default_open(false)
+                            .show(ui, |ui| {
+                                for path in &report.succeeded {
+                                    row(ui, path, None, ui.visuals().text_color());
+                                }
+                            });
+                        }
+                    });
+
+                ui.add_space(15.0);
+                ui.horizontal(|ui| {
+                    if !report.failed.is_empty()
+                        && ui
+                            .add_sized(
+                                [140.0, 36.0],
+                                egui::Button::new(
+                                    egui::RichText::new(format!("🔁 Retry {} failed", report.failed.len()))
+                                        .size(14.0),
+                                )
+                                .fill(egui::Color32::from_rgb(40, 167, 69))
+                                .rounding(6.0),
+                            )
+                            .clicked()
+                    {
+                        retry = true;
+                    }
+                    if ui
+                        .add_sized(
+                            [140.0, 36.0],
+                            egui::Button::new(egui::RichText::new("Close").size(14.0))
+                                .fill(egui::Color32::from_rgb(66, 70, 77))
+                                .rounding(6.0),
+                        )
+                        .clicked()
+                    {
+                        close = true;
+                    }
+                });
+            });
+
+        if retry {
+            self.retry_batch_failures();
+        } else if close {
+            self.batch_summary = None;
+        }
+    }
+
+    /// Copy or move `sources` into `dest_dir` in the background. Items whose
+    /// destination already exists are skipped, unless `merge` is given: then
+    /// copies merge into existing folders and resolve files present on both
+    /// sides by that policy, without asking. A failed item doesn't stop the
+    /// rest; skipped and failed items end up in the batch summary.
+    fn start_transfer(
+        &mut self,
+        sources: Vec<PathBuf>,
+        dest_dir: PathBuf,
+        is_move: bool,
+        merge: Option<crate::filesystem::ConflictPolicy>,
+    ) {
+        let size_units = self.settings.size_units;
+        let label = if is_move { "Moving" } else { "Copying" };
+        let action = if is_move { "Move" } else { "Copy" };
+        let log = self.op_log.clone();
+        let finished_batch = self.finished_batch.clone();
+        self.start_background_task(label, move |progress| {
+            // Moves within a drive are renames and need neither a total nor
+            // space; the ones that have to copy check both item by item. For
+            // copies one walk serves the space check and the progress total.
+            let total: u64 = if is_move {
+                0
+            } else {
+                sources
+                    .iter()
+                    .map(|source| crate::filesystem::calculate_total_size(source))
+                    .sum()
+            };
+            if !is_move {
+                crate::filesystem::check_space(total, &dest_dir, size_units)?;
+            }
+            let mut report = crate::oplog::BatchReport::default();
+            let mut bytes = 0;
+            let mut kept = 0;
+            for source in &sources {
+                // Sources picked before the task started may have vanished since
+                let dest = crate::filesystem::check_source_exists(source).and_then(|_| {
+                    if is_move {
+                        crate::filesystem::transfer_destination(source, &dest_dir)
+                    } else if merge.is_some() {
+                        crate::filesystem::merge_destination(source, &dest_dir)
+                    } else {
+                        crate::filesystem::copy_destination(source, &dest_dir)
+                    }
+                });
+                let dest = match dest {
+                    Ok(dest) => dest,
+                    Err(e) => {
+                        log.record(action, Some(source), Some(&dest_dir), &Err::<(), _>(&e));
+                        report.add_skip(source, e);
+                        continue;
+                    }
+                };
+                let base = bytes;
+                let mut on_progress =
+                    |done, current: &std::path::Path| progress.report(base + done, total, current);
+                let result = if is_move {
+                    crate::filesystem::move_path_with_progress(
+                        source,
+                        &dest,
+                        &mut |done, item_total, current| progress.report(done, item_total, current),
+                    )
+                    .map(|copied| copied.unwrap_or(0))
+                } else if let Some(policy) = merge {
+                    crate::filesystem::merge_copy_with_progress(source, &dest, policy, &mut on_progress)
+                        .map(|summary| {
+                            kept += summary.kept.len();
+                            for path in &summary.mismatched {
+                                log.record(
+                                    action,
+                                    Some(path),
+                                    None,
+                                    &Err::<(), _>("file/folder mismatch, skipped"),
+                                );
+                                report.add_skip(path, "file/folder mismatch");
+                            }
+                            summary.bytes
+                        })
+                } else {
+                    crate::filesystem::copy_recursive_with_progress(source, &dest, &mut on_progress)
+                };
+                log.record(action, Some(source), Some(&dest), &result);
+                // A failed item doesn't stop the rest; the summary lists it
+                match result {
+                    Ok(copied) => {
+                        bytes += copied;
+                        report.add_success(source);
+                    }
+                    Err(e) => report.add_failure(source, e),
+                }
+            }
+
+            // Renamed items copy nothing, so an all-rename move has no size to show
+            let size = if is_move && bytes == 0 {
+                String::new()
+            } else {
+                format!(" ({})", crate::filesystem::format_size_with(bytes, size_units))
+            };
+            let mut message = format!(
+                "{} {} item(s){} → {}",
+                if is_move { "Moved" } else { "Copied" },
+                report.succeeded.len(),
+                size,
+                dest_dir.display()
+            );
+            if kept > 0 {
+                message.push_str(&format!("; kept {} existing file(s)", kept));
+            }
+            if !report.skipped.is_empty() {
+                message.push_str(&format!("; skipped {}", report.skipped.len()));
+            }
+            let failed = report.failed.len();
+            if report.has_problems() {
+                *finished_batch.lock().unwrap_or_else(|e| e.into_inner()) = Some(BatchSummary {
+                    action: action.to_string(),
+                    report,
+                    retry: BatchRetry::Transfer { dest_dir, is_move, merge },
+                });
+            }
+            if failed > 0 {
+                anyhow::bail!("{}; {} failed", message, failed);
+            }
+            Ok(message)
+        });
+    }
+
+    /// The "Copy to…" / "Move to…" / "Extract to…" dialog: a destination field, shortcuts
+    /// to the panes and bookmarks, and a browser for the destination's
+    /// subfolders.
+    fn render_transfer_dialog(&mut self, ctx: &egui::Context) {
+        let Some(dialog) = &self.transfer_dialog else {
+            return;
+        };
+        let mode = dialog.mode;
+        let sources = dialog.sources.clone();
+        let archive_summary = dialog.archive_summary;
+        let mut destination = dialog.destination.clone();
+        let mut password = dialog.password.clone();
+        let wrong_password = dialog.wrong_password;
+        let dest_dir = PathBuf::from(&destination);
+
+        let fs_status = crate::filesystem::filesystem_status(&dest_dir);
+        let problem = if self.settings.read_only_mode {
+            Some(READ_ONLY_MODE_HINT.to_string())
+        } else if !dest_dir.is_dir() {
+            Some("Not an existing folder".to_string())
+        } else if fs_status.as_ref().is_some_and(|s| s.read_only) {
+            Some(READ_ONLY_HINT.to_string())
+        } else if password.as_ref().is_some_and(|password| password.is_empty()) {
+            Some("Enter the archive's password".to_string())
+        } else {
+            None
+        };
+        let size_units = self.settings.size_units;
+        // Extraction can still be started, overwritten files may free space
+        let space_warning = archive_summary
+            .zip(fs_status.filter(|_| dest_dir.is_dir()))
+            .filter(|(summary, status)| summary.uncompressed_size > status.available_space)
+            .map(|(summary, status)| {
+                format!(
+                    "Extracting needs {} but only {} is free here",
+                    crate::filesystem::format_size_with(summary.uncompressed_size, size_units),
+                    crate::filesystem::format_size_with(status.available_space, size_units)
+                )
+            });
+        let mut subfolders: Vec<PathBuf> = fs::read_dir(&dest_dir)
+            .map(|entries| {
+                entries
+                    .flatten()
+                    .map(|entry| entry.path())
+                    .filter(|path| path.is_dir())
+                    .filter(|path| {
+                        self.show_hidden_files
+                            || !path
+                                .file_name()
+                                .is_some_and(|name| name.to_string_lossy().starts_with('.'))
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+        subfolders.sort_by_key(|path| path.file_name().map(|n| n.to_ascii_lowercase()));
+
+        let mut merge_copies = self.settings.merge_copies;
+        let mut merge_policy = self.settings.merge_conflict_policy;
+        let mut merge_changed = false;
+        let mut confirmed = false;
+        let mut close = false;
+        egui::Window::new(mode.title())
+            .collapsible(false)
+            .resizable(false)
+            .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
+            .frame(
+                egui::Frame::window(&ctx.style())
+                    .fill(egui::Color32::from_rgb(45, 47, 50))
+                    .rounding(12.0)
+                    .inner_margin(20.0),
+            )
+            .show(ctx, |ui| {
+                ui.set_width(460.0);
+                let names: Vec<String> = sources
+                    .iter()
+                    .filter_map(|path| path.file_name())
+                    .map(|name| name.to_string_lossy().to_string())
+                    .collect();
+                let summary = if names.len() == 1 {
+                    names[0].clone()
+                } else {
+                    format!("{} items", names.len())
+                };
+                ui.label(egui::RichText::new(summary).strong().size(14.0))
+                    .on_hover_text(names.join("\n"));
+                if let Some(archive) = archive_summary {
+                    ui.label(
+                        egui::RichText::new(format!(
+                            "{} entries, {} when extracted",
+                            archive.entries,
+                            crate::filesystem::format_size_with(
+                                archive.uncompressed_size,
+                                size_units
+                            )
+                        ))
+                        .size(12.0)
+                        .color(egui::Color32::from_rgb(154, 160, 166)),
+                    );
+                }
+                ui.add_space(10.0);
+
+                ui.label(egui::RichText::new("Destination folder:").size(13.0));
+                let response = ui.add_sized(
+                    [ui.available_width(), 24.0],
+                    egui::TextEdit::singleline(&mut destination),
+                );
+                ui.horizontal(|ui| {
+                    if mode == TransferMode::Extract {
+                        if let Some(parent) = sources.first().and_then(|path| path.parent()) {
+                            if ui.small_button("Next to archive").clicked() {
+                                destination = parent.display().to_string();
+                            }
+                        }
+                    }
+                    if ui.small_button("Left pane").clicked() {
+                        destination = self.left_pane.current_path.display().to_string();
+                    }
+                    if ui.small_button("Right pane").clicked() {
+                        destination = self.right_pane.current_path.display().to_string();
+                    }
+                    let bookmarks = self.bookmark_manager.get_bookmarks().to_vec();
+                    ui.add_enabled_ui(!bookmarks.is_empty(), |ui| {
+                        ui.menu_button("⭐ Bookmark", |ui| {
+                            for bookmark in &bookmarks {
+                                if ui
+                                    .button(format!("{} {}", bookmark.icon, bookmark.name))
+                                    .clicked()
+                                {
+                                    destination = bookmark.path.display().to_string();
+                                    ui.close_menu();
+                                }
+                            }
+                        });
+                    });
+                    ui.menu_button("📌 Quick Access", |ui| {
+                        for item in self.settings.quick_access_items() {
+                            if ui.button(format!("{} {}", item.icon, item.name)).clicked() {
+                                destination = item.path.display().to_string();
+                                ui.close_menu();
+                            }
+                        }
+                    });
+                });
+
+                ui.add_space(8.0);
+                egui::Frame::default()
+                    .fill(egui::Color32::from_rgb(35, 37, 40))
+                    .rounding(6.0)
+                    .inner_margin(6.0)
+                    .show(ui, |ui| {
+                        egui::ScrollArea::vertical()
+                            .max_height(200.0)
+                            .auto_shrink([false, true])
+                            .show(ui, |ui| {
+                                if let Some(parent) = dest_dir.parent() {
+                                    if ui.selectable_label(false, "⬆ ..").clicked() {
+                                        destination = parent.display().to_string();
+                                    }
+                                }
+                                for folder in &subfolders {
+                                    let name = folder
+                                        .file_name()
+                                        .map(|n| n.to_string_lossy().to_string())
+                                        .unwrap_or_default();
+                                    if ui.selectable_label(false, format!("📁 {}", name)).clicked() {
+                                        destination = folder.display().to_string();
+                                    }
+                                }
+                            });
+                    });
+
+                if let Some(password) = &mut password {
+                    ui.add_space(8.0);
+                    ui.horizontal(|ui| {
+                        ui.label(egui::RichText::new("🔒 Password:").size(13.0));
+                        ui.add(
+                            egui::TextEdit::singleline(password)
+                                .password(true)
+                                .desired_width(f32::INFINITY),
+                        );
+                    });
+                    if wrong_password {
+                        ui.label(
+                            egui::RichText::new("✗ Wrong password, try again")
+                                .size(12.0)
+                                .color(egui::Color32::from_rgb(242, 139, 130)),
+                        );
+                    }
+                }
+
+                if mode == TransferMode::Copy {
+                    ui.add_space(8.0);
+                    ui.horizontal(|ui| {
+                        merge_changed |= ui
+                            .checkbox(&mut merge_copies, "Merge into existing folders")
+                            .on_hover_text(
+                                "Copy into folders that already exist instead of skipping them, \
+                                 without asking about each file",
+                            )
+                            .changed();
+                        ui.add_enabled_ui(merge_copies, |ui| {
+                            egui::ComboBox::from_id_salt("merge_conflict_policy")
+                                .selected_text(merge_policy.name())
+                                .show_ui(ui, |ui| {
+                                    for policy in crate::filesystem::ConflictPolicy::all() {
+                                        merge_changed |= ui
+                                            .selectable_value(&mut merge_policy, policy, policy.name())
+                                            .changed();
+                                    }
+                                });
+                        });
+                    });
+                }
+
+                if let Some(problem) = &problem {
+                    ui.add_space(6.0);
+                    ui.label(
+                        egui::RichText::new(format!("✗ {}", problem))
+                            .size(12.0)
+                            .color(egui::Color32::from_rgb(242, 139, 130)),
+                    );
+                } else if let Some(warning) = &space_warning {
+                    ui.add_space(6.0);
+                    ui.label(
+                        egui::RichText::new(format!("⚠ {}", warning))
+                            .size(12.0)
+                            .color(egui::Color32::from_rgb(220, 170, 90)),
+                    );
+                }
+
+                ui.add_space(15.0);
+                ui.horizontal(|ui| {
+                    confirmed = ui
+                        .add_enabled_ui(problem.is_none(), |ui| {
+                            ui.add_sized(
+                                [140.0, 36.0],
+                                egui::Button::new(
+                                    egui::RichText::new(mode.confirm_text()).size(14.0),
+                                )
+                                    .fill(egui::Color32::from_rgb(40, 167, 69))
+                                    .rounding(6.0),
+                            )
+                        })
+                        .inner
+                        .clicked()
+                        || (problem.is_none()
+                            && response.lost_focus()
+                            && ui.input(|i| i.key_pressed(egui::Key::Enter)));
+                    if ui
+                        .add_sized(
+                            [140.0, 36.0],
+                            egui::Button::new(egui::RichText::new("✗ Cancel").size(14.0))
+                                .fill(egui::Color32::from_rgb(66, 70, 77))
+                                .rounding(6.0),
+                        )
+                        .clicked()
+                    {
+                        close = true;
+                    }
+                });
+            });
+
+        if merge_changed {
+            self.settings.merge_copies = merge_copies;
+            self.settings.merge_conflict_policy = merge_policy;
+            self.save_settings();
+        }
+        // Check the password up front so a wrong one can be retyped
+        let password_rejected = confirmed
+            && password.as_deref().is_some_and(|password| {
+                sources.iter().any(|archive| {
+                    !crate::filesystem::zip_password_matches(archive, password).unwrap_or(true)
+                })
+            });
+        if password_rejected {
+            if let Some(dialog) = &mut self.transfer_dialog {
+                dialog.destination = destination;
+                dialog.password = Some(String::new());
+                dialog.wrong_password = true;
+            }
+        } else if confirmed {
+            self.transfer_dialog = None;
+            if mode == TransferMode::Extract {
+                self.last_extract_dir = Some(dest_dir.clone());
+                self.extract_archives_to(sources, dest_dir, password);
+            } else {
+                self.last_transfer_dir = Some(dest_dir.clone());
+                let merge = (mode == TransferMode::Copy && self.settings.merge_copies)
+                    .then_some(self.settings.merge_conflict_policy);
+                self.start_transfer(sources, dest_dir, mode == TransferMode::Move, merge);
+            }
+        } else if close {
+            self.transfer_dialog = None;
+        } else if let Some(dialog) = &mut self.transfer_dialog {
+            dialog.destination = destination;
+            dialog.password = password;
+        }
+    }
+
+    /// Plan a one-way sync from the folder of pane `source_pane` to the other
+    /// pane's folder and show it for confirmation.
+    fn open_sync_dialog(&mut self, source_pane: usize) {
+        let dest_pane = 1 - source_pane;
+        if !self.ensure_idle() || !self.ensure_writable(dest_pane) {
+            return;
+        }
+        let source = self.get_pane(source_pane).current_path.clone();
+        let dest = self.get_pane(dest_pane).current_path.clone();
+        let canonical = |path: &PathBuf| fs::canonicalize(path).unwrap_or_else(|_| path.clone());
+        let (source_dir, dest_dir) = (canonical(&source), canonical(&dest));
+        if source_dir.starts_with(&dest_dir) || dest_dir.starts_with(&source_dir) {
+            self.notify_error("Cannot sync a folder with itself or a folder inside it".to_string());
+            return;
+        }
+
+        self.sync_preview = None;
+        let task = BackgroundTask::spawn("Comparing folders", move |_| {
+            let plan = crate::filesystem::plan_sync(&source, &dest)?;
+            let bytes_to_copy = plan.bytes_to_copy();
+            Ok((plan, bytes_to_copy))
+        });
+        self.sync_task = Some((dest_pane, task));
+    }
+
+    fn poll_sync_task(&mut self, ctx: &egui::Context) {
+        let Some((dest_pane, task)) = &mut self.sync_task else {
+            return;
+        };
+        let dest_pane = *dest_pane;
+        match task.poll() {
+            Some(Ok((plan, bytes_to_copy))) => {
+                self.sync_task = None;
+                self.sync_preview = Some(SyncPreview {
+                    plan,
+                    dest_pane,
+                    delete_extras: false,
+                    bytes_to_copy,
+                });
+            }
+            Some(Err(e)) => {
+                self.sync_task = None;
+                self.notify_error(format!("Sync failed: {}", e));
+            }
+            None => ctx.request_repaint_after(std::time::Duration::from_millis(100)),
+        }
+    }
+
+    /// Shown while the folders for a sync are being compared.
+    fn render_sync_pending_dialog(&mut self, ctx: &egui::Context) {
+        let Some((dest_pane, _)) = &self.sync_task else {
+            return;
+        };
+        let title = if *dest_pane == 1 {
+            "🔁 Sync Left → Right"
+        } else {
+            "🔁 Sync Right → Left"
+        };
+        let mut close = false;
+        egui::Window::new(title)
+            .collapsible(false)
+            .resizable(false)
+            .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
+            .frame(
+                egui::Frame::window(&ctx.style())
+                    .fill(egui::Color32::from_rgb(45, 47, 50))
+                    .rounding(12.0)
+                    .inner_margin(20.0),
+            )
+            .show(ctx, |ui| {
+                ui.set_width(520.0);
+                ui.horizontal(|ui| {
+                    ui.spinner();
+                    ui.label(egui::RichText::new("Comparing folders… calculating…").size(13.0));
+                });
+                ui.add_space(15.0);
+                if ui
+                    .add_sized(
+                        [140.0, 36.0],
+                        egui::Button::new(egui::RichText::new("✗ Cancel").size(14.0))
+                            .fill(egui::Color32::from_rgb(66, 70, 77))
+                            .rounding(6.0),
+                    )
+                    .clicked()
+                {
+                    close = true;
+                }
+            });
+        if close {
+            // The comparison only reads, so it is simply abandoned
+            self.sync_task = None;
+        }
+    }
+
+    /// The sync preview: every planned copy, update and deletion, with an
+    /// option to delete extras, applied in the background once confirmed.
+    fn render_sync_dialog(&mut self, ctx: &egui::Context) {
+        let Some(preview) = &self.sync_preview else {
+            self.render_sync_pending_dialog(ctx);
+            return;
+        };
+        let mut delete_extras = preview.delete_extras;
+        let plan = if delete_extras {
+            preview.plan.clone()
+        } else {
+            preview.plan.without_deletions()
+        };
+        let title = if preview.dest_pane == 1 {
+            "🔁 Sync Left → Right"
+        } else {
+            "🔁 Sync Right → Left"
+        };
+        let size_units = self.settings.size_units;
+        let bytes_to_copy = preview.bytes_to_copy;
+        let extras = preview
+            .plan
+            .actions
+            .iter()
+            .filter(|action| matches!(action, crate::filesystem::SyncAction::Delete(_)))
+            .count();
+
+        let mut confirmed = false;
+        let mut close = false;
+        egui::Window::new(title)
+            .collapsible(false)
+            .resizable(false)
+            .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
+            .frame(
+                egui::Frame::window(&ctx.style())
+                    .fill(egui::Color32::from_rgb(45, 47, 50))
+                    .rounding(12.0)
+                    .inner_margin(20.0),
+            )
+            .show(ctx, |ui| {
+                ui.set_width(520.0);
+                let grey = egui::Color32::from_rgb(154, 160, 166);
+                ui.label(
+                    egui::RichText::new(format!("From: {}", plan.source.display()))
+                        .size(12.0)
+                        .color(grey),
+                );
+                ui.label(
+                    egui::RichText::new(format!("To:   {}", plan.dest.display()))
+                        .size(12.0)
+                        .color(grey),
+                );
+                ui.add_space(8.0);
+                ui.checkbox(
+                    &mut delete_extras,
+                    format!("Delete what exists only in the destination ({})", extras),
+                );
+                ui.add_space(8.0);
+
+                let count = |wanted: fn(&crate::filesystem::SyncAction) -> bool| {
+                    plan.actions.iter().filter(|action| wanted(action)).count()
+                };
+                use crate::filesystem::SyncAction;
+                ui.label(
+                    egui::RichText::new(format!(
+                        "{} to copy, {} to update, {} to delete ({} to write)",
+                        count(|action| matches!(action, SyncAction::Copy(_))),
+                        count(|action| matches!(action, SyncAction::Update(_))),
+                        count(|action| matches!(action, SyncAction::Delete(_))),
+                        crate::filesystem::format_size_with(bytes_to_copy, size_units)
+                    ))
+                    .strong()
+                    .size(13.0),
+                );
+                ui.add_space(6.0);
+
+                egui::Frame::default()
+                    .fill(egui::Color32::from_rgb(35, 37, 40))
+                    .rounding(6.0)
+                    .inner_margin(6.0)
+                    .show(ui, |ui| {
+                        egui::ScrollArea::vertical()
+                            .max_height(280.0)
+                            .auto_shrink([false, true])
+                            .show(ui, |ui| {
+                                if plan.actions.is_empty() {
+                                    ui.label(
+                                        egui::RichText::new("✓ Already in sync")
+                                            .size(12.0)
+                                            .color(grey),
+                                    );
+                                }
+                                for action in &plan.actions {
+                                    let (sign, relative, color) = match action {
+                                        SyncAction::Copy(relative) => {
+                                            ("+", relative, egui::Color32::from_rgb(129, 201, 149))
+                                        }
+                                        SyncAction::Update(relative) => {
+                                            ("~", relative, egui::Color32::from_rgb(220, 170, 90))
+                                        }
+                                        SyncAction::Delete(relative) => {
+                                            ("−", relative, egui::Color32::from_rgb(242, 139, 130))
+                                        }
+                                    };
+                                    ui.label(
+                                        egui::RichText::new(format!(
+                                            "{} {}",
+                                            sign,
+                                            relative.display()
+                                        ))
+                                        .monospace()
+                                        .size(12.0)
+                                        .color(color),
+                                    );
+                                }
+                            });
+                    });
+
+                if !plan.conflicts.is_empty() {
+                    ui.add_space(6.0);
+                    let names: Vec<String> = plan
+                        .conflicts
+                        .iter()
+                        .map(|relative| relative.display().to_string())
+                        .collect();
+                    ui.label(
+                        egui::RichText::new(format!(
+                            "⚠ {} left alone: a file on one side, a folder on the other",
+                            plan.conflicts.len()
+                        ))
+                        .size(12.0)
+                        .color(egui::Color32::from_rgb(220, 170, 90)),
+                    )
+                    .on_hover_text(names.join("\n"));
+                }
+
+                ui.add_space(15.0);
+                ui.horizontal(|ui| {
+                    confirmed = ui
+                        .add_enabled_ui(!plan.actions.is_empty(), |ui| {
+                            ui.add_sized(
+                                [140.0, 36.0],
+                                egui::Button::new(egui::RichText::new("🔁 Sync").size(14.0))
+                                    .fill(egui::Color32::from_rgb(40, 167, 69))
+                                    .rounding(6.0),
+                            )
+                        })
+                        .inner
+                        .clicked();
+                    if ui
+                        .add_sized(
+                            [140.0, 36.0],
+                            egui::Button::new(egui::RichText::new("✗ Cancel").size(14.0))
+                                .fill(egui::Color32::from_rgb(66, 70, 77))
+                                .rounding(6.0),
+                        )
+                        .clicked()
+                    {
+                        close = true;
+                    }
+                });
+            });
+
+        if confirmed {
+            self.sync_preview = None;
+            let log = self.op_log.clone();
+            self.start_background_task("Syncing", move |progress| {
+                let result = crate::filesystem::apply_sync(&plan, &mut |done, path| {
+                    progress.report(done, bytes_to_copy, path)
+                });
+                log.record("Sync", Some(&plan.source), Some(&plan.dest), &result);
+                let summary = result?;
+                Ok(format!(
+                    "Synced {} → {}: {} copied, {} updated, {} deleted ({})",
+                    plan.source.display(),
+                    plan.dest.display(),
+                    summary.copied,
+                    summary.updated,
+                    summary.deleted,
+                    crate::filesystem::format_size_with(summary.bytes, size_units)
+                ))
+            });
+        } else if close {
+            self.sync_preview = None;
+        } else if let Some(preview) = &mut self.sync_preview {
+            preview.delete_extras = delete_extras;
+        }
+    }
+
+    /// Scan the highlighted folder, or the pane's folder when a file is
+    /// highlighted, for the "Folder statistics" dialog.
+    fn open_folder_stats(&mut self) {
+        let pane = self.get_pane(self.active_pane);
+        let root = pane
+            .get_selected_item()
+            .filter(|item| item.is_dir && item.name != "..")
+            .map(|item| item.path.clone())
+            .unwrap_or_else(|| pane.current_path.clone());
+        self.folder_stats = None;
+        self.stats_task = Some(BackgroundTask::spawn("Folder statistics", move |progress| {
+            crate::filesystem::tree_stats(&root, &mut |files, path| {
+                progress.report(files, 0, path)
+            })
+        }));
+    }
+
+    /// Open the folder holding `path` in the active pane with `path`
+    /// highlighted.
+    fn reveal_in_active_pane(&mut self, path: &std::path::Path) {
+        let Some(parent) = path.parent() else {
+            return;
+        };
+        let pane = self.get_active_pane_mut();
+        if let Err(e) = pane.navigate_to(parent.to_path_buf()) {
+            self.notify_error(format!("Cannot open {}: {}", parent.display(), e));
+            return;
+        }
+        if let Some(index) = pane.items.iter().position(|item| item.path == path) {
+            pane.selected_index = index;
+        }
+    }
+
+    /// Fetch the remote the current branch of `repo_path` tracks, to bring
+    /// its ahead/behind counts up to date.
+    fn start_git_fetch(&mut self, repo_path: PathBuf) {
+        if self.git_fetch.is_some() {
+            return;
+        }
+        let task = BackgroundTask::spawn("Fetching", {
+            let repo_path = repo_path.clone();
+            move |_| crate::filesystem::fetch_upstream(&repo_path)
+        });
+        self.git_fetch = Some((repo_path, task));
+    }
+
+    fn poll_git_fetch(&mut self, ctx: &egui::Context) {
+        let Some((repo_path, task)) = &mut self.git_fetch else {
+            return;
+        };
+        let Some(result) = task.poll() else {
+            ctx.request_repaint_after(std::time::Duration::from_millis(100));
+            return;
+        };
+        let repo_path = repo_path.clone();
+        self.git_fetch = None;
+        match result {
+            Ok(remote) => {
+                let _ = self.refresh_both_panes();
+                let info = crate::filesystem::get_git_repo_info(&repo_path).ok();
+                let counts = info.map_or_else(String::new, |info| match (info.ahead, info.behind) {
+                    (0, 0) => ": up to date".to_string(),
+                    (ahead, behind) => format!(": ↑{} ahead, ↓{} behind", ahead, behind),
+                });
+                self.notify_success(format!("Fetched {}{}", remote, counts));
+            }
+            Err(e) => self.notify_error(format!("Fetch failed: {}", e)),
+        }
+    }
+
+    /// Close the statistics dialog; a scan still running is abandoned.
+    fn close_folder_stats(&mut self) {
+        self.folder_stats = None;
+        self.stats_task = None;
+    }
+
+    fn poll_stats_task(&mut self, ctx: &egui::Context) {
+        let Some(task) = &mut self.stats_task else {
+            return;
+        };
+        match task.poll() {
+            Some(Ok(stats)) => {
+                self.stats_task = None;
+                match FolderStatsView::new(stats) {
+                    Ok(view) => self.folder_stats = Some(view),
+                    Err(e) => self.notify_error(format!("Folder statistics failed: {}", e)),
+                }
+            }
+            Some(Err(e)) => {
+                self.stats_task = None;
+                self.notify_error(format!("Folder statistics failed: {}", e));
+            }
+            None => ctx.request_repaint_after(std::time::Duration::from_millis(100)),
+        }
+    }
+
+    /// Recursive totals of a folder: disk usage bars to drill into, the
+    /// largest files and a breakdown by extension. Shows the scan's progress
+    /// until it is done.
+    fn render_folder_stats_dialog(&mut self, ctx: &egui::Context) {
+        if self.folder_stats.is_none() && self.stats_task.is_none() {
+            return;
+        }
+        let size_units = self.settings.size_units;
+        let format_size = |bytes| crate::filesystem::format_size_with(bytes, size_units);
+        let grey = egui::Color32::from_rgb(154, 160, 166);
+        let mut close = false;
+        let mut reveal = None;
+        let mut drill_into = None;
+        let mut open_in_pane = None;
+
+        egui::Window::new("📊 Folder Statistics")
+            .collapsible(false)
+            .resizable(false)
+            .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
+            .frame(
+                egui::Frame::window(&ctx.style())
+                    .fill(egui::Color32::from_rgb(45, 47, 50))
+                    .rounding(12.0)
+                    .inner_margin(20.0),
+            )
+            .show(ctx, |ui| {
+                ui.set_width(520.0);
+                if let Some(task) = &self.stats_task {
+                    ui.horizontal(|ui| {
+                        ui.spinner();
+                        ui.label(
+                            egui::RichText::new(format!("Scanning… {} files", task.progress.done))
+                                .size(13.0),
+                        );
+                    });
+                    ui.label(
+                        egui::RichText::new(task.progress.current_path.display().to_string())
+                            .size(12.0)
+                            .color(grey),
+                    );
+                } else if let Some(view) = &mut self.folder_stats {
+                    let stats = &view.stats;
+                    ui.label(
+                        egui::RichText::new(stats.root.display().to_string())
+                            .size(12.0)
+                            .color(grey),
+                    );
+                    ui.add_space(8.0);
+                    ui.label(
+                        egui::RichText::new(format!(
+                            "{} files in {} folders, {}",
+                            stats.file_count,
+                            stats.folder_count,
+                            format_size(stats.total_size)
+                        ))
+                        .strong()
+                        .size(14.0),
+                    );
+                    if stats.skipped_dirs > 0 {
+                        ui.label(
+                            egui::RichText::new(format!(
+                                "⚠ {} folder(s) could not be read",
+                                stats.skipped_dirs
+                            ))
+                            .size(12.0)
+                            .color(egui::Color32::from_rgb(220, 170, 90)),
+                        );
+                    }
+
+                    ui.add_space(10.0);
+                    ui.horizontal(|ui| {
+                        ui.selectable_value(&mut view.tab, StatsTab::Usage, "Disk usage");
+                        ui.selectable_value(&mut view.tab, StatsTab::LargestFiles, "Largest files");
+                        ui.selectable_value(&mut view.tab, StatsTab::Extensions, "By extension");
+                    });
+                    ui.add_space(6.0);
+                    let stats = &view.stats;
+                    egui::Frame::default()
+                        .fill(egui::Color32::from_rgb(35, 37, 40))
+                        .rounding(6.0)
+                        .inner_margin(6.0)
+                        .show(ui, |ui| {
+                            ui.set_width(ui.available_width());
+                            match view.tab {
+                                StatsTab::Usage => {
+                                    ui.horizontal(|ui| {
+                                        if ui
+                                            .add_enabled(
+                                                view.usage_dir != stats.root,
+                                                egui::Button::new("⬆ Up"),
+                                            )
+                                            .clicked()
+                                        {
+                                            drill_into = view.usage_dir.parent().map(PathBuf::from);
+                                        }
+                                        let relative = view
+                                            .usage_dir
+                                            .strip_prefix(&stats.root)
+                                            .unwrap_or(&view.usage_dir);
+                                        let name = stats
+                                            .root
+                                            .file_name()
+                                            .map(|name| name.to_string_lossy().to_string())
+                                            .unwrap_or_else(|| stats.root.display().to_string());
+                                        ui.label(
+                                            egui::RichText::new(
+                                                std::path::Path::new(&name).join(relative).display().to_string(),
+                                            )
+                                            .strong()
+                                            .size(12.0),
+                                        );
+                                        ui.with_layout(
+                                            egui::Layout::right_to_left(egui::Align::Center),
+                                            |ui| {
+                                                if ui
+                                                    .small_button("📂 Open in pane")
+                                                    .on_hover_text("Show this folder in the active pane")
+                                                    .clicked()
+                                                {
+                                                    open_in_pane = Some(view.usage_dir.clone());
+                                                }
+                                            },
+                                        );
+                                    });
+                                    ui.add_space(4.0);
+                                    let total = stats
+                                        .folder_sizes
+                                        .get(&view.usage_dir)
+                                        .copied()
+                                        .unwrap_or(0);
+                                    if view.usage.is_empty() {
+                                        ui.label(egui::RichText::new("Empty folder").size(12.0).color(grey));
+                                    } else if let Some(index) =
+                                        usage_bars(ui, &view.usage, total, size_units)
+                                    {
+                                        drill_into = Some(view.usage[index].path.clone());
+                                    }
+                                }
+                                StatsTab::LargestFiles => {
+                                    if stats.largest_files.is_empty() {
+                                        ui.label(egui::RichText::new("No files").size(12.0).color(grey));
+                                    }
+                                    egui::Grid::new("folder_stats_largest")
+                                        .num_columns(2)
+                                        .striped(true)
+                                        .show(ui, |ui| {
+                                            for (path, size) in &stats.largest_files {
+                                                let relative =
+                                                    path.strip_prefix(&stats.root).unwrap_or(path);
+                                                if ui
+                                                    .link(
+                                                        egui::RichText::new(
+                                                            relative.display().to_string(),
+                                                        )
+                                                        .size(12.0),
+                                                    )
+                                                    .on_hover_text("Show in this pane")
+                                                    .clicked()
+                                                {
+                                                    reveal = Some(path.clone());
+                                                }
+                                                ui.label(
+                                                    egui::RichText::new(format_size(*size)).size(12.0),
+                                                );
+                                                ui.end_row();
+                                            }
+                                        });
+                                }
+                                StatsTab::Extensions => {
+                                    egui::ScrollArea::vertical()
+                                        .max_height(280.0)
+                                        .auto_shrink([false, true])
+                                        .show(ui, |ui| {
+                                            egui::Grid::new("folder_stats_extensions")
+                                                .num_columns(4)
+                                                .striped(true)
+                                                .show(ui, |ui| {
+                                                    for group in &stats.by_extension {
+                                                        let name = if group.extension.is_empty() {
+                                                            "(no extension)".to_string()
+                                                        } else {
+                                                            format!(".{}", group.extension)
+                                                        };
+                                                        let share = if stats.total_size > 0 {
+                                                            group.total_size as f64 * 100.0
+                                                                / stats.total_size as f64
+                                                        } else {
+                                                            0.0
+                                                        };
+                                                        ui.label(
+                                                            egui::RichText::new(name)
+                                                                .monospace()
+                                                                .size(12.0),
+                                                        );
+                                                        ui.label(
+                                                            egui::RichText::new(format!(
+                                                                "{} files",
+                                                                group.file_count
+                                                            ))
+                                                            .size(12.0),
+                                                        );
+                                                        ui.label(
+                                                            egui::RichText::new(format_size(
+                                                                group.total_size,
+                                                            ))
+                                                            .size(12.0),
+                                                        );
+                                                        ui.label(
+                                                            egui::RichText::new(format!("{:.1}%", share))
+                                                                .size(12.0)
+                                                                .color(grey),
+                                                        );
+                                                        ui.end_row();
+                                                    }
+                                                });
+                                        });
+                                }
+                            }
+                        });
+                }
+
+                ui.add_space(15.0);
+                if ui
+                    .add_sized(
+                        [140.0, 36.0],
+                        egui::Button::new(egui::RichText::new("✗ Close").size(14.0))
+                            .fill(egui::Color32::from_rgb(66, 70, 77))
+                            .rounding(6.0),
+                    )
+                    .clicked()
+                {
+                    close = true;
+                }
+            });
+
+        if let Some(dir) = drill_into {
+            if let Some(view) = &mut self.folder_stats {
+                if let Err(e) = view.show_usage_of(dir) {
+                    self.notify_error(format!("Cannot read folder: {}", e));
+                }
+            }
+        } else if let Some(dir) = open_in_pane {
+            self.close_folder_stats();
+            if let Err(e) = self.get_active_pane_mut().navigate_to(dir) {
+                self.notify_error(format!("Cannot open folder: {}", e));
+            }
+        } else if let Some(path) = reveal {
+            self.close_folder_stats();
+            self.reveal_in_active_pane(&path);
+        } else if close {
+            self.close_folder_stats();
+        }
+    }
+
+    /// Hand a file, folder or URL to the system's default application.
+    fn spawn_default_app(target: &std::ffi::OsStr) -> Result<()> {
+        #[cfg(target_os = "linux")]
+        {
+            std::process::Command::new("xdg-open").arg(target).spawn()?;
+        }
+
+        #[cfg(target_os = "macos")]
+        {
+            std::process::Command::new("open").arg(target).spawn()?;
+        }
+
+        #[cfg(target_os = "windows")]
+        {
+            std::process::Command::new("cmd")
+                .args(["/C", "start", ""])
+                .arg(target)
+                .spawn()?;
+        }
+
+        Ok(())
+    }
+
+    /// Open the system's file manager at the active pane's folder, with the
+    /// item under the cursor highlighted where the platform supports it
+    /// (Finder and Explorer; on Linux the folder is opened with xdg-open).
+    fn reveal_in_file_manager(&mut self) {
+        let pane = self.get_active_pane();
+        let folder = pane.current_path.clone();
+        let item = pane
+            .get_selected_item()
+            .filter(|item| item.name != "..")
+            .map(|item| item.path.clone());
+        match Self::spawn_file_manager(&folder, item.as_deref()) {
+            Ok(()) => {
+                self.status_message = format!("Opened the file manager at {}", folder.display());
+            }
+            Err(e) => {
+                self.status_message = format!("❌ Cannot open the file manager: {}", e);
+            }
+        }
+    }
+
+    fn spawn_file_manager(folder: &std::path::Path, item: Option<&std::path::Path>) -> Result<()> {
+        #[cfg(target_os = "linux")]
+        {
+            let _ = item;
+            std::process::Command::new("xdg-open").arg(folder).spawn()?;
+        }
+
+        #[cfg(target_os = "macos")]
+        {
+            match item {
+                Some(item) => std::process::Command::new("open").arg("-R").arg(item).spawn()?,
+                None => std::process::Command::new("open").arg(folder).spawn()?,
+            };
+        }
+
+        #[cfg(target_os = "windows")]
+        {
+            use std::os::windows::process::CommandExt;
+            let mut command = std::process::Command::new("explorer");
+            match item {
+                // Explorer wants the quotes around the path only, not the switch
+                Some(item) => command.raw_arg(format!("/select,\"{}\"", item.display())),
+                None => command.arg(folder),
+            };
+            command.spawn()?;
+        }
+
+        Ok(())
+    }
+
+    fn open_shortcut_target(
+        &mut self,
+        name: &str,
+        target: crate::filesystem::ShortcutTarget,
+    ) -> Result<()> {
+        match target {
+            crate::filesystem::ShortcutTarget::Command(args) => {
+                std::process::Command::new(&args[0])
+                    .args(&args[1..])
+                    .spawn()
+                    .map_err(|e| anyhow::anyhow!("Failed to launch {}: {}", args[0], e))?;
+                self.status_message = format!("Launching: {}", name);
+            }
+            crate::filesystem::ShortcutTarget::Location(location) => {
+                let target_path = std::path::PathBuf::from(&location);
+                if target_path.is_dir() {
+                    self.get_active_pane_mut().navigate_to(target_path)?;
+                    self.status_message = format!("Navigated to {}", location);
+                } else {
+                    Self::spawn_default_app(std::ffi::OsStr::new(&location))?;
+                    self.status_message = format!("Opening: {}", location);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    pub fn open_file_with_default_app(&mut self) -> Result<()> {
+        // With several items marked (and the cursor among them), open every
+        // marked file instead of just the one under the cursor
+        let pane = self.get_active_pane();
+        if pane.selected_items.len() > 1 && pane.is_item_selected(pane.selected_index) {
+            let files: Vec<PathBuf> = pane
+                .get_selected_items()
+                .into_iter()
+                .filter(|item| !item.is_dir && item.name != "..")
+                .map(|item| item.path.clone())
+                .collect();
+            if files.is_empty() {
+                self.status_message = "No files among the selection to open".to_string();
+            } else if files.len() > OPEN_MANY_THRESHOLD {
+                self.pending_open = Some(files);
+            } else {
+                self.open_files(files);
+            }
+            return Ok(());
+        }
+
+        let item_data = self
             .get_active_pane()
             .get_selected_item()
             .map(|item| (item.path.clone(), item.name.clone(), item.is_dir));
 
         if let Some((path, name, is_dir)) = item_data {
             if !is_dir && name != ".." {
-                // Check if it's an image file - open in built-in viewer
-                if Self::is_image_file(&path) {
+                use crate::settings::OpenAction;
+
+                // Images and archives can be handled in-app, depending on settings
+                if Self::is_image_file(&path)
+                    && self.settings.image_open_action == OpenAction::BuiltIn
+                {
                     self.open_image_viewer(path, name.clone());
                     self.status_message = format!("Opening image: {}", name);
                     return Ok(());
                 }
-
-                // Use system default app to open file
-                #[cfg(target_os = "linux")]
-                {
-                    std::process::Command::new("xdg-open").arg(&path).spawn()?;
-                    self.status_message = format!("Opening: {}", name);
+                let is_archive = crate::filesystem::ArchiveFormat::of(&path).is_some();
+                if is_archive && self.settings.archive_open_action == OpenAction::BuiltIn {
+                    return self.extract_archive();
                 }
 
-                #[cfg(target_os = "macos")]
-                {
-                    std::process::Command::new("open").arg(&path).spawn()?;
-                    self.status_message = format!("Opening: {}", name);
+                // Launchers and shortcuts open what they point at
+                if let Some(target) = crate::filesystem::resolve_shortcut(&path) {
+                    return self.open_shortcut_target(&name, target);
                 }
 
-                #[cfg(target_os = "windows")]
-                {
-                    std::process::Command::new("cmd")
-                        .args(["/C", "start", "", &path.to_string_lossy()])
-                        .spawn()?;
-                    self.status_message = format!("Opening: {}", name);
-                }
+                Self::spawn_default_app(path.as_os_str())?;
+                self.status_message = format!("Opening: {}", name);
             } else if is_dir && name != ".." {
                 self.status_message = "Use Enter or double-click to open directories".to_string();
             }
@@ -389,90 +3931,876 @@ impl FileManagerApp {
         Ok(())
     }
 
-    pub fn delete_selected_file(&mut self) -> Result<()> {
-        let items_to_delete: Vec<_> = self.get_active_pane()
-            .get_selected_items()
-            .iter()
-            .filter(|item| item.name != "..")
-            .map(|item| (item.path.clone(), item.is_dir, item.name.clone()))
-            .collect();
+    /// Move the folder each pane shows to the front of `recent_folders`
+    /// whenever it changed since the last frame.
+    fn track_recent_folders(&mut self) {
+        for pane_index in 0..2 {
+            let path = &self.get_pane(pane_index).current_path;
+            if self.recent_seen[pane_index].as_ref() == Some(path) {
+                continue;
+            }
+            let path = path.clone();
+            self.recent_folders.retain(|recent| *recent != path);
+            self.recent_folders.insert(0, path.clone());
+            self.recent_folders.truncate(RECENT_FOLDERS_KEPT);
+            self.recent_seen[pane_index] = Some(path);
+        }
+    }
+
+    /// Open each file with its default app. The first image goes to the
+    /// built-in viewer when that is the configured action; since the viewer
+    /// shows one image at a time, any further images use the default app.
+    /// Launch failures are collected into one toast.
+    fn open_files(&mut self, paths: Vec<PathBuf>) {
+        use crate::settings::OpenAction;
+
+        let mut viewer_used = false;
+        let mut failures = Vec::new();
+        for path in &paths {
+            let name = path
+                .file_name()
+                .map(|name| name.to_string_lossy().to_string())
+                .unwrap_or_else(|| path.display().to_string());
+            if !viewer_used
+                && Self::is_image_file(path)
+                && self.settings.image_open_action == OpenAction::BuiltIn
+            {
+                self.open_image_viewer(path.clone(), name);
+                viewer_used = true;
+                continue;
+            }
+            if let Err(e) = Self::spawn_default_app(path.as_os_str()) {
+                failures.push(format!("{} ({})", name, e));
+            }
+        }
+
+        let opened = paths.len() - failures.len();
+        if failures.is_empty() {
+            self.notify_success(format!("Opened {} files", opened));
+        } else {
+            const MAX_LISTED: usize = 3;
+            let mut summary = failures[..failures.len().min(MAX_LISTED)].join(", ");
+            if failures.len() > MAX_LISTED {
+                summary.push_str(&format!(" and {} more", failures.len() - MAX_LISTED));
+            }
+            self.notify_error(format!(
+                "Opened {} of {} files. Failed: {}",
+                opened,
+                paths.len(),
+                summary
+            ));
+        }
+    }
+
+    /// Confirmation shown before opening more than `OPEN_MANY_THRESHOLD`
+    /// files at once.
+    fn render_open_many_confirm(&mut self, ctx: &egui::Context) {
+        let Some(paths) = &self.pending_open else {
+            return;
+        };
+        let count = paths.len();
+        let mut open = false;
+        let mut close = false;
+        egui::Window::new("📄 Open Files")
+            .collapsible(false)
+            .resizable(false)
+            .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
+            .frame(
+                egui::Frame::window(&ctx.style())
+                    .fill(egui::Color32::from_rgb(45, 47, 50))
+                    .rounding(12.0)
+                    .inner_margin(20.0),
+            )
+            .show(ctx, |ui| {
+                ui.set_width(380.0);
+                ui.label(
+                    egui::RichText::new(format!("Open {} files at once?", count))
+                        .size(16.0)
+                        .strong(),
+                );
+                ui.add_space(4.0);
+                ui.label(
+                    egui::RichText::new("Each one starts its own application window.")
+                        .size(12.0)
+                        .color(egui::Color32::from_rgb(154, 160, 166)),
+                );
+                ui.add_space(8.0);
+                egui::ScrollArea::vertical().max_height(200.0).show(ui, |ui| {
+                    for path in paths {
+                        let name = path.file_name().unwrap_or_default().to_string_lossy();
+                        ui.label(
+                            egui::RichText::new(format!("📄 {}", name))
+                                .size(13.0)
+                                .color(egui::Color32::from_rgb(138, 180, 248)),
+                        );
+                    }
+                });
+
+                ui.add_space(15.0);
+                ui.horizontal(|ui| {
+                    open = ui
+                        .add_sized(
+                            [140.0, 36.0],
+                            egui::Button::new(egui::RichText::new("✓ Open All").size(14.0))
+                                .fill(egui::Color32::from_rgb(40, 167, 69))
+                                .rounding(6.0),
+                        )
+                        .clicked();
+                    if ui
+                        .add_sized(
+                            [140.0, 36.0],
+                            egui::Button::new(egui::RichText::new("✗ Cancel").size(14.0))
+                                .fill(egui::Color32::from_rgb(66, 70, 77))
+                                .rounding(6.0),
+                        )
+                        .clicked()
+                    {
+                        close = true;
+                    }
+                });
+            });
+
+        if open {
+            if let Some(paths) = self.pending_open.take() {
+                self.open_files(paths);
+            }
+        } else if close {
+            self.pending_open = None;
+        }
+    }
+
+    /// Queue the marked items (or the item under the cursor when it isn't
+    /// part of the marked set) and open the delete confirmation.
+    pub fn request_delete_selection(&mut self) {
+        if !self.ensure_writable(self.active_pane) {
+            return;
+        }
+        let pane = self.get_active_pane();
+        let items: Vec<crate::filesystem::FileItem> =
+            if pane.selected_items.is_empty() || pane.is_item_selected(pane.selected_index) {
+                pane.get_selected_items().into_iter().cloned().collect()
+            } else {
+                pane.get_selected_item().into_iter().cloned().collect()
+            };
+        let items: Vec<_> = items.into_iter().filter(|item| item.name != "..").collect();
+
+        if items.is_empty() {
+            return;
+        }
+
+        self.delete_total_size = None;
+        self.delete_size_task = Some(spawn_size_task(&items));
+        self.items_to_delete = items;
+        self.show_delete_confirm = true;
+    }
+
+    fn cancel_delete(&mut self) {
+        self.show_delete_confirm = false;
+        self.items_to_delete.clear();
+        self.delete_total_size = None;
+        self.delete_size_task = None;
+    }
+
+    /// Pick up the sizes for the delete and compress dialogs once they are
+    /// added up.
+    fn poll_size_tasks(&mut self, ctx: &egui::Context) {
+        if let Some(task) = &mut self.delete_size_task {
+            match task.poll() {
+                Some(result) => {
+                    self.delete_total_size = result.ok().map(|sizes| sizes.iter().sum());
+                    self.delete_size_task = None;
+                }
+                None => ctx.request_repaint_after(std::time::Duration::from_millis(100)),
+            }
+        }
+        if let Some(task) = &mut self.compress_size_task {
+            match task.poll() {
+                Some(result) => {
+                    self.compress_sizes = result.ok();
+                    self.compress_size_task = None;
+                }
+                None => ctx.request_repaint_after(std::time::Duration::from_millis(100)),
+            }
+        }
+    }
+
+    /// Delete every queued item on the background worker, reporting entries
+    /// removed out of the total. Failures don't stop the rest; they are
+    /// listed in the batch summary. Cancel stops before the next entry.
+    pub fn delete_queued_items(&mut self) -> Result<()> {
+        if !self.ensure_idle() {
+            return Ok(());
+        }
+
+        let items = std::mem::take(&mut self.items_to_delete);
+        // Marks refer to row indices, which the delete invalidates
+        self.get_active_pane_mut().clear_selection();
+        self.start_delete(items.into_iter().map(|item| item.path).collect());
+        Ok(())
+    }
+
+    /// Delete `paths` on the background worker; see
+    /// [`Self::delete_queued_items`].
+    fn start_delete(&mut self, paths: Vec<PathBuf>) {
+        let log = self.op_log.clone();
+        let finished_batch = self.finished_batch.clone();
+        self.start_cancellable_task("Deleting", move |progress| {
+            let total: u64 = paths
+                .iter()
+                .map(|path| crate::filesystem::count_entries(path))
+                .sum();
+            let mut removed = 0;
+            let mut report = crate::oplog::BatchReport::default();
+            let mut cancelled = false;
+            for path in &paths {
+                let result = crate::filesystem::remove_tree_with_progress(
+                    path,
+                    &mut removed,
+                    &mut |done, path| progress.report(done, total, path),
+                    &|| progress.is_cancelled(),
+                );
+                // A cancelled item was left partly deleted; the task's own result says so
+                if !matches!(result, Ok(false)) {
+                    log.record("Delete", Some(path), None, &result);
+                }
+                match result {
+                    Ok(true) => report.add_success(path),
+                    Ok(false) => {
+                        cancelled = true;
+                        break;
+                    }
+                    Err(e) => report.add_failure(path, e),
+                }
+            }
 
-        let mut deleted_count = 0;
-        for (path, is_dir, name) in items_to_delete {
-            if is_dir {
-                fs::remove_dir_all(&path)?;
+            let deleted_count = report.succeeded.len();
+            if !report.failed.is_empty() {
+                let failed = report.failed.len();
+                *finished_batch.lock().unwrap_or_else(|e| e.into_inner()) = Some(BatchSummary {
+                    action: "Delete".to_string(),
+                    report,
+                    retry: BatchRetry::Delete,
+                });
+                anyhow::bail!(
+                    "Deleted {} of {} items; {} failed",
+                    deleted_count,
+                    paths.len(),
+                    failed
+                );
+            }
+            Ok(if cancelled {
+                format!(
+                    "Cancelled: deleted {} of {} items ({} of {} entries)",
+                    deleted_count,
+                    paths.len(),
+                    removed,
+                    total
+                )
+            } else if deleted_count == 1 {
+                "Deleted 1 item".to_string()
             } else {
-                fs::remove_file(&path)?;
+                format!("Deleted {} items", deleted_count)
+            })
+        });
+    }
+
+    /// Open the context menu of the item under the cursor from the keyboard
+    /// (Shift+F10), at that item's row, with its first entry focused.
+    fn open_context_menu_from_keyboard(&mut self, ctx: &egui::Context) {
+        let pane = self.get_active_pane();
+        if pane.get_selected_item().is_none() {
+            return;
+        }
+        self.context_menu_item_index = pane.selected_index;
+        self.context_menu_pos = match self.cursor_row_rects[self.active_pane] {
+            Some(row) => row.left_bottom() + egui::vec2(24.0, 0.0),
+            None => ctx.screen_rect().center(),
+        };
+        self.show_context_menu = true;
+        self.context_menu_keyboard = true;
+        self.context_menu_focus = 0;
+        self.context_menu_entries.clear();
+        self.scroll_to_selection = true;
+    }
+
+    /// Move the context menu's keyboard focus to the next (or previous)
+    /// enabled entry, wrapping around. The first arrow key press on a menu
+    /// opened with the mouse focuses the first (or last) entry.
+    fn move_context_menu_focus(&mut self, down: bool) {
+        let enabled: Vec<usize> = (0..self.context_menu_entries.len())
+            .filter(|&index| self.context_menu_entries[index])
+            .collect();
+        let (Some(&first), Some(&last)) = (enabled.first(), enabled.last()) else {
+            return;
+        };
+        let focus = self.context_menu_focus;
+        self.context_menu_focus = if !self.context_menu_keyboard {
+            if down { first } else { last }
+        } else if down {
+            enabled.iter().copied().find(|&index| index > focus).unwrap_or(first)
+        } else {
+            enabled.iter().rev().copied().find(|&index| index < focus).unwrap_or(last)
+        };
+        self.context_menu_keyboard = true;
+    }
+
+    /// Count `response` as the next entry of the context menu. When the menu
+    /// is driven from the keyboard and the arrow keys picked this entry, it
+    /// gets the focus, so Enter clicks it.
+    fn context_menu_entry(&mut self, response: egui::Response) -> egui::Response {
+        let index = self.context_menu_entries.len();
+        self.context_menu_entries.push(response.enabled());
+        if self.context_menu_keyboard && index == self.context_menu_focus {
+            response.request_focus();
+            // The arrow keys pick entries here instead of moving egui's focus
+            response.ctx.memory_mut(|memory| {
+                memory.set_focus_lock_filter(
+                    response.id,
+                    egui::EventFilter {
+                        tab: true,
+                        horizontal_arrows: true,
+                        vertical_arrows: true,
+                        escape: false,
+                    },
+                )
+            });
+            response.ctx.layer_painter(response.layer_id).rect_stroke(
+                response.rect,
+                4.0,
+                egui::Stroke::new(1.0, egui::Color32::from_rgb(138, 180, 248)),
+            );
+        }
+        response
+    }
+
+    /// Cached mount points, scanning the disks if nothing is cached yet.
+    fn mount_points(&mut self) -> Vec<crate::filesystem::MountPoint> {
+        self.mount_points
+            .get_or_insert_with(crate::filesystem::get_mount_points)
+            .clone()
+    }
+
+    /// Cached unmounted devices, or the error from scanning them. Starts a
+    /// scan in the background if nothing is cached yet; empty until it's done.
+    fn unmounted_devices(&mut self) -> Result<Vec<crate::filesystem::UnmountedDevice>, String> {
+        if self.unmounted_devices.is_none() && self.device_scan.is_none() {
+            self.device_scan = Some(BackgroundTask::spawn("Listing devices", |_| {
+                crate::filesystem::get_unmounted_devices()
+            }));
+        }
+        self.unmounted_devices.clone().unwrap_or_else(|| Ok(Vec::new()))
+    }
+
+    /// Forget the scanned mount points and devices so they're listed again.
+    fn rescan_devices(&mut self) {
+        self.mount_points = None;
+        self.unmounted_devices = None;
+        self.device_scan = None;
+    }
+
+    fn poll_device_tasks(&mut self, ctx: &egui::Context) {
+        if let Some(task) = &mut self.device_scan {
+            match task.poll() {
+                Some(result) => {
+                    self.unmounted_devices = Some(result);
+                    self.device_scan = None;
+                }
+                None => ctx.request_repaint_after(std::time::Duration::from_millis(100)),
+            }
+        }
+        let Some((device, task)) = &mut self.mount_task else {
+            return;
+        };
+        let Some(result) = task.poll() else {
+            ctx.request_repaint_after(std::time::Duration::from_millis(100));
+            return;
+        };
+        let device = device.clone();
+        self.mount_task = None;
+        match result {
+            Ok(mount_point) => {
+                self.rescan_devices();
+                self.show_mounts_dialog = false;
+                let message = format!("Mounted {} at {}", device.name(), mount_point.display());
+                match self.get_active_pane_mut().navigate_to(mount_point) {
+                    Ok(()) => self.status_message = message.clone(),
+                    Err(e) => self.status_message = format!("{}, but cannot open it: {}", message, e),
+                }
+                self.notify_success(message);
+            }
+            Err(e) => self.notify_error(format!("❌ {}", e)),
+        }
+    }
+
+    /// Show the mount points dialog with a freshly scanned list.
+    fn open_mounts_dialog(&mut self) {
+        self.rescan_devices();
+        self.mount_filter.clear();
+        self.show_mounts_dialog = true;
+    }
+
+    /// Mount `device` in the background; once mounted, the dialog closes
+    /// and the device opens in the active pane.
+    fn mount_device(&mut self, device: crate::filesystem::UnmountedDevice) {
+        if self.mount_task.is_some() {
+            return;
+        }
+        let path = device.device.clone();
+        let task = BackgroundTask::spawn("Mounting", move |_| {
+            crate::filesystem::mount_device(&path)
+        });
+        self.mount_task = Some((device, task));
+    }
+
+    /// Open the compress dialog for the current item, or with `each` for
+    /// every selected item, each going into its own archive.
+    pub fn compress_item(&mut self, each: bool) -> Result<()> {
+        if !self.ensure_idle() || !self.ensure_writable(self.active_pane) {
+            return Ok(());
+        }
+
+        let items: Vec<crate::filesystem::FileItem> = if each {
+            self.get_active_pane()
+                .get_selected_items()
+                .into_iter()
+                .cloned()
+                .collect()
+        } else {
+            self.get_active_pane().get_selected_item().cloned().into_iter().collect()
+        };
+        let items: Vec<_> = items.into_iter().filter(|item| item.name != "..").collect();
+
+        if !items.is_empty() {
+            self.compress_options = self.settings.zip_options;
+            self.compress_sizes = None;
+            self.compress_size_task = Some(spawn_size_task(&items));
+            self.pending_compress = Some(items);
+        }
+        Ok(())
+    }
+
+    /// Close the compress dialog, forgetting any password typed into it.
+    fn close_compress_dialog(&mut self) {
+        self.pending_compress = None;
+        self.compress_sizes = None;
+        self.compress_size_task = None;
+        self.compress_encrypt = false;
+        self.compress_password.clear();
+        self.compress_password_confirm.clear();
+    }
+
+    /// Compress each item into `<name>.zip` next to itself on a background
+    /// thread, using the options chosen in the compress dialog. Failures
+    /// don't stop the rest; they are reported together at the end.
+    fn start_compression(&mut self, items: Vec<crate::filesystem::FileItem>) {
+        let zip_options = self.compress_options;
+        let password = self
+            .compress_encrypt
+            .then(|| std::mem::take(&mut self.compress_password));
+        // Sizes the dialog didn't get to are measured by the worker
+        let sizes = self.compress_sizes.take();
+        self.close_compress_dialog();
+
+        let log = self.op_log.clone();
+        let started = self.start_background_task("Compressing", move |progress| {
+            let sizes = sizes.unwrap_or_else(|| {
+                items
+                    .iter()
+                    .map(|item| crate::filesystem::calculate_total_size(&item.path))
+                    .collect()
+            });
+            let size: u64 = sizes.iter().sum();
+            let mut base = 0;
+            let mut archives = Vec::new();
+            let mut skipped = 0;
+            let mut failures = Vec::new();
+            for (item, item_size) in items.iter().zip(&sizes) {
+                let zip_name = format!("{}.zip", item.name);
+                let result = crate::filesystem::sibling_path(&item.path, &zip_name).and_then(
+                    |zip_path| {
+                        crate::filesystem::compress_to_zip_with_progress(
+                            &item.path,
+                            &zip_path,
+                            &zip_options,
+                            password.as_deref(),
+                            &mut |done, current| progress.report(base + done, size, current),
+                        )
+                    },
+                );
+                base += item_size;
+                let zip_path = item.path.with_file_name(&zip_name);
+                log.record("Compress", Some(&item.path), Some(&zip_path), &result);
+                match result {
+                    Ok(special_files) => {
+                        skipped += special_files.len();
+                        archives.push((item.name.clone(), zip_name));
+                    }
+                    Err(e) => failures.push(format!("{} ({})", item.name, e)),
+                }
+            }
+
+            if !failures.is_empty() {
+                anyhow::bail!(
+                    "{} of {} compressed; failed: {}",
+                    archives.len(),
+                    items.len(),
+                    failures.join(", ")
+                );
+            }
+            let mut message = match archives.as_slice() {
+                [(name, zip_name)] => format!("Compressed: {} → {}", name, zip_name),
+                _ => format!("Compressed {} items into their own archives", archives.len()),
+            };
+            if skipped > 0 {
+                message.push_str(&format!(" ({} special files skipped)", skipped));
+            }
+            Ok(message)
+        });
+        if started {
+            self.status_message = "📦 Compressing...".to_string();
+        }
+    }
+
+    /// Run `work` on a background thread while the app is marked busy.
+    /// Progress shows in the shared progress dialog; the returned message is
+    /// shown as a toast when the work succeeds. Background work changes
+    /// files, so it is refused in read-only mode.
+    pub fn start_background_task<F>(&mut self, label: &str, work: F) -> bool
+    where
+        F: FnOnce(&crate::progress::ProgressSender<String>) -> Result<String> + Send + 'static,
+    {
+        if !self.ensure_read_write_mode() || !self.begin_operation(label) {
+            return false;
+        }
+        self.background_task = Some(BackgroundTask::spawn(label, work));
+        true
+    }
+
+    /// [`Self::start_background_task`] for work that checks
+    /// `ProgressSender::is_cancelled`; the progress dialog offers Cancel.
+    pub fn start_cancellable_task<F>(&mut self, label: &str, work: F) -> bool
+    where
+        F: FnOnce(&crate::progress::ProgressSender<String>) -> Result<String> + Send + 'static,
+    {
+        if !self.ensure_read_write_mode() || !self.begin_operation(label) {
+            return false;
+        }
+        self.background_task = Some(BackgroundTask::spawn(label, work).cancellable());
+        true
+    }
+
+    fn poll_background_task(&mut self, ctx: &egui::Context) {
+        let Some(task) = &mut self.background_task else {
+            return;
+        };
+
+        match task.poll() {
+            Some(result) => {
+                let label = task.label.clone();
+                self.background_task = None;
+                let elapsed = self.end_operation();
+                match result {
+                    Ok(message) => self.notify_success(format!(
+                        "{} in {}",
+                        message,
+                        crate::filesystem::format_duration(elapsed)
+                    )),
+                    Err(e) => self.notify_error(format!("{} failed: {}", label, e)),
+                }
+                let finished_batch = self.finished_batch.lock().unwrap_or_else(|e| e.into_inner()).take();
+                if finished_batch.is_some() {
+                    self.batch_summary = finished_batch;
+                }
+                let _ = self.refresh_both_panes();
+                if let Some((pane_index, dir, paths)) = self.select_after_task.take() {
+                    let pane = if pane_index == 0 { &mut self.left_pane } else { &mut self.right_pane };
+                    if pane.current_path == dir {
+                        pane.select_paths(&paths);
+                        self.scroll_to_selection = true;
+                    }
+                }
             }
-            deleted_count += 1;
+            None => ctx.request_repaint_after(std::time::Duration::from_millis(100)),
+        }
+    }
+
+    fn toggle_log_panel(&mut self) {
+        self.show_log_panel = !self.show_log_panel;
+    }
+
+    /// Collapsible panel above the status bar listing every file operation
+    /// of the session, newest at the bottom, with the error of each one
+    /// that failed.
+    fn render_log_panel(&mut self, ctx: &egui::Context) {
+        if !self.show_log_panel {
+            return;
         }
+        let all = self.op_log.entries();
+        let failed = all.iter().filter(|entry| !entry.succeeded()).count();
+        let total = all.len();
+        let entries: Vec<crate::oplog::LogEntry> = all
+            .into_iter()
+            .filter(|entry| !self.log_failures_only || !entry.succeeded())
+            .collect();
+        let visuals = ctx.style().visuals.clone();
+
+        egui::TopBottomPanel::bottom("log_panel")
+            .resizable(true)
+            .default_height(160.0)
+            .min_height(80.0)
+            .frame(
+                egui::Frame::default()
+                    .fill(visuals.panel_fill)
+                    .inner_margin(egui::Margin::symmetric(10.0, 6.0)),
+            )
+            .show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    ui.label(egui::RichText::new("📜 Operation Log").strong().size(13.0));
+                    ui.add_space(8.0);
+                    ui.label(
+                        egui::RichText::new(format!("{} operations, {} failed", total, failed))
+                            .size(12.0)
+                            .color(egui::Color32::from_rgb(154, 160, 166)),
+                    );
+                    ui.add_space(8.0);
+                    ui.checkbox(&mut self.log_failures_only, "Failures only");
+                    ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                        if ui.small_button("✗").on_hover_text("Hide the log").clicked() {
+                            self.show_log_panel = false;
+                        }
+                        if ui
+                            .add_enabled(total > 0, egui::Button::new("Clear").small())
+                            .clicked()
+                        {
+                            self.op_log.clear();
+                        }
+                    });
+                });
+                ui.separator();
+
+                if entries.is_empty() {
+                    let text = if self.log_failures_only {
+                        "No failed operations"
+                    } else {
+                        "No operations yet this session"
+                    };
+                    ui.label(
+                        egui::RichText::new(text)
+                            .size(12.0)
+                            .color(egui::Color32::from_rgb(154, 160, 166)),
+                    );
+                    return;
+                }
 
-        self.status_message = if deleted_count == 1 {
-            format!("Deleted 1 item")
-        } else {
-            format!("Deleted {} items", deleted_count)
-        };
-        
-        self.get_active_pane_mut().clear_selection();
-        self.get_active_pane_mut().refresh()?;
-        Ok(())
+                let row_height = ui.text_style_height(&egui::TextStyle::Body) + 4.0;
+                egui::ScrollArea::vertical()
+                    .auto_shrink([false; 2])
+                    .stick_to_bottom(true)
+                    .show_rows(ui, row_height, entries.len(), |ui, range| {
+                        for entry in &entries[range] {
+                            let paths = match (&entry.source, &entry.destination) {
+                                (Some(source), Some(dest)) => {
+                                    format!("{} → {}", source.display(), dest.display())
+                                }
+                                (Some(path), None) | (None, Some(path)) => path.display().to_string(),
+                                (None, None) => String::new(),
+                            };
+                            let (icon, color, text) = match &entry.error {
+                                None => ("✓", egui::Color32::from_rgb(129, 201, 149), paths),
+                                Some(error) => (
+                                    "✗",
+                                    egui::Color32::from_rgb(242, 139, 130),
+                                    format!("{}: {}", paths, error),
+                                ),
+                            };
+                            ui.horizontal(|ui| {
+                                ui.label(
+                                    egui::RichText::new(entry.time.format("%H:%M:%S").to_string())
+                                        .size(12.0)
+                                        .color(egui::Color32::from_rgb(154, 160, 166)),
+                                );
+                                ui.label(egui::RichText::new(icon).size(12.0).color(color));
+                                ui.label(egui::RichText::new(&entry.action).size(12.0).strong());
+                                let text_color = if entry.succeeded() {
+                                    visuals.text_color()
+                                } else {
+                                    color
+                                };
+                                ui.add(
+                                    egui::Label::new(
+                                        egui::RichText::new(&text).size(12.0).color(text_color),
+                                    )
+                                    .truncate(),
+                                )
+                                .on_hover_text(&text);
+                            });
+                        }
+                    });
+            });
     }
 
-    pub fn compress_item(&mut self) -> Result<()> {
-        let item = self.get_active_pane().get_selected_item().cloned();
+    /// Progress window shared by every background task.
+    fn render_progress_dialog(&mut self, ctx: &egui::Context) {
+        let Some(task) = &self.background_task else {
+            return;
+        };
+        let progress = &task.progress;
 
-        if let Some(item) = item {
-            if item.name != ".." {
-                let zip_name = format!("{}.zip", item.name);
-                let zip_path = item
-                    .path
-                    .parent()
-                    .unwrap_or(item.path.as_path())
-                    .join(&zip_name);
-
-                match crate::filesystem::compress_to_zip(&item.path, &zip_path) {
-                    Ok(_) => {
-                        self.status_message =
-                            format!("✅ Compressed: {} → {}", item.name, zip_name);
-                        self.get_active_pane_mut().refresh()?;
+        egui::Window::new(format!("⏳ {}", task.label))
+            .id(egui::Id::new("background_task_progress"))
+            .collapsible(false)
+            .resizable(false)
+            .anchor(egui::Align2::RIGHT_BOTTOM, [-16.0, -48.0])
+            .frame(
+                egui::Frame::window(&ctx.style())
+                    .fill(egui::Color32::from_rgb(45, 47, 50))
+                    .rounding(12.0)
+                    .inner_margin(16.0),
+            )
+            .show(ctx, |ui| {
+                ui.set_width(320.0);
+                match progress.fraction() {
+                    Some(fraction) => {
+                        ui.add(egui::ProgressBar::new(fraction).show_percentage());
                     }
-                    Err(e) => {
-                        self.status_message = format!("❌ Compression failed: {}", e);
+                    None => {
+                        ui.horizontal(|ui| {
+                            ui.spinner();
+                            ui.label(format!("{} done", progress.done));
+                        });
                     }
                 }
-            }
-        }
-        Ok(())
+                if !progress.current_path.as_os_str().is_empty() {
+                    ui.add_space(6.0);
+                    let name = progress
+                        .current_path
+                        .file_name()
+                        .map(|n| n.to_string_lossy().to_string())
+                        .unwrap_or_default();
+                    ui.add(
+                        egui::Label::new(
+                            egui::RichText::new(name)
+                                .size(11.0)
+                                .color(egui::Color32::from_rgb(189, 193, 198)),
+                        )
+                        .truncate(),
+                    )
+                    .on_hover_text(progress.current_path.display().to_string());
+                }
+                if task.cancellable {
+                    ui.add_space(8.0);
+                    let cancelling = task.is_cancelled();
+                    let label = if cancelling { "Cancelling…" } else { "✗ Cancel" };
+                    if ui
+                        .add_enabled(
+                            !cancelling,
+                            egui::Button::new(egui::RichText::new(label).size(13.0))
+                                .fill(egui::Color32::from_rgb(66, 70, 77))
+                                .rounding(6.0),
+                        )
+                        .clicked()
+                    {
+                        task.cancel();
+                    }
+                }
+            });
     }
 
     pub fn extract_archive(&mut self) -> Result<()> {
+        if !self.ensure_idle() || !self.ensure_read_write_mode() {
+            return Ok(());
+        }
+
         let item = self.get_active_pane().get_selected_item().cloned();
 
         if let Some(item) = item {
             if item.name != ".." && !item.is_dir {
-                let extract_dir = item.path.parent().unwrap_or(item.path.as_path());
-
-                if item.name.ends_with(".zip") {
-                    match crate::filesystem::extract_zip(&item.path, extract_dir) {
-                        Ok(_) => {
-                            self.status_message = format!("✅ Extracted: {}", item.name);
-                            self.get_active_pane_mut().refresh()?;
-                        }
-                        Err(e) => {
-                            self.status_message = format!("❌ Extraction failed: {}", e);
+                let format = crate::filesystem::ArchiveFormat::of(&item.path);
+                if let Some(format) = format {
+                    // Say up front when the program for .7z or .rar is missing
+                    if format.is_external() {
+                        if let Err(e) = crate::filesystem::find_external_archiver(&item.path) {
+                            self.notify_error(e.to_string());
+                            return Ok(());
                         }
                     }
+                    // Repeated extractions default to wherever the last one went
+                    let destination = match self.last_extract_dir.clone().filter(|d| d.is_dir()) {
+                        Some(dir) => dir,
+                        None => crate::filesystem::parent_dir(&item.path)?.to_path_buf(),
+                    };
+                    self.transfer_dialog = Some(TransferDialog {
+                        sources: vec![item.path.clone()],
+                        mode: TransferMode::Extract,
+                        destination: destination.display().to_string(),
+                        archive_summary: crate::filesystem::zip_summary(&item.path).ok(),
+                        password: crate::filesystem::zip_is_encrypted(&item.path)
+                            .unwrap_or(false)
+                            .then(String::new),
+                        wrong_password: false,
+                    });
                 } else {
-                    self.status_message = "Only .zip files are supported".to_string();
+                    self.status_message =
+                        "Only .zip, .tar, .tar.gz, .tar.bz2, .tar.xz, .7z and .rar archives are supported"
+                            .to_string();
                 }
             }
         }
         Ok(())
     }
 
+    /// Extract `archives` into `dest_dir` on the background worker; .7z and
+    /// .rar archives wait for an external program, which can take a while.
+    /// Failures don't stop the rest; they are reported together at the end.
+    fn extract_archives_to(
+        &mut self,
+        archives: Vec<PathBuf>,
+        dest_dir: PathBuf,
+        password: Option<String>,
+    ) {
+        let log = self.op_log.clone();
+        self.start_background_task("Extracting", move |progress| {
+            let mut extracted = Vec::new();
+            let mut failures = Vec::new();
+            for (i, archive) in archives.iter().enumerate() {
+                progress.report(i as u64, archives.len() as u64, archive);
+                let name = archive.file_name().unwrap_or_default().to_string_lossy().to_string();
+                let result = crate::filesystem::extract_any_with_password(
+                    archive,
+                    &dest_dir,
+                    password.as_deref(),
+                );
+                log.record("Extract", Some(archive), Some(&dest_dir), &result);
+                match result {
+                    Ok(()) => extracted.push(name),
+                    Err(e) => failures.push(format!("{} ({})", name, e)),
+                }
+            }
+            if !failures.is_empty() {
+                anyhow::bail!(
+                    "{} of {} extracted; failed: {}",
+                    extracted.len(),
+                    archives.len(),
+                    failures.join(", ")
+                );
+            }
+            Ok(format!("Extracted: {} → {}", extracted.join(", "), dest_dir.display()))
+        });
+    }
+
+    /// Compare two files: either exactly two files marked in the active pane,
+    /// or the current file of each pane.
     pub fn compare_selected_files(&mut self) -> Result<()> {
-        let left_item = self.left_pane.get_selected_item().cloned();
-        let right_item = self.right_pane.get_selected_item().cloned();
+        let active_pane = self.get_active_pane();
+        let (left_item, right_item) = if active_pane.selected_items.len() == 2 {
+            let marked = active_pane.get_selected_items();
+            (Some(marked[0].clone()), Some(marked[1].clone()))
+        } else {
+            (
+                self.left_pane.get_selected_item().cloned(),
+                self.right_pane.get_selected_item().cloned(),
+            )
+        };
 
         if let (Some(left), Some(right)) = (left_item, right_item) {
             if left.name == ".." || right.name == ".." {
@@ -485,7 +4813,7 @@ impl FileManagerApp {
                 return Ok(());
             }
 
-            match crate::filesystem::compare_files(&left.path, &right.path) {
+            match crate::filesystem::compare_files(&left.path, &right.path, self.diff_options) {
                 Ok(comparison) => {
                     self.comparison_result = Some(comparison.clone());
                     self.show_compare_dialog = true;
@@ -507,14 +4835,347 @@ impl FileManagerApp {
                 }
             }
         } else {
-            self.status_message = "Select files in both panes to compare".to_string();
+            self.status_message =
+                "Select two files in one pane, or a file in each pane, to compare".to_string();
+        }
+
+        Ok(())
+    }
+
+    pub fn compare_with_head(&mut self) -> Result<()> {
+        let item = self.get_active_pane().get_selected_item().cloned();
+
+        if let Some(item) = item {
+            if item.name == ".." || item.is_dir {
+                self.status_message = "Select a file to diff against HEAD".to_string();
+                return Ok(());
+            }
+
+            match crate::filesystem::compare_with_git_head(&item.path, self.diff_options) {
+                Ok(comparison) => {
+                    self.status_message = if comparison.are_identical {
+                        format!("✅ {} is unchanged since HEAD", item.name)
+                    } else {
+                        format!(
+                            "{} vs HEAD: {} added, {} removed, {} modified",
+                            item.name,
+                            comparison.right_only_lines,
+                            comparison.left_only_lines,
+                            comparison.modified_lines
+                        )
+                    };
+                    self.comparison_result = Some(comparison);
+                    self.show_compare_dialog = true;
+                    self.compare_scroll_offset = 0.0;
+                }
+                Err(e) => {
+                    self.status_message = format!("❌ Diff against HEAD failed: {}", e);
+                }
+            }
         }
 
         Ok(())
     }
 
+    /// Diff the selected file against the text on the system clipboard.
+    /// Open the "3-way compare" dialog for the two files Compare Files would
+    /// compare, asking for the base file they both started from.
+    fn open_three_way_compare(&mut self) {
+        let active_pane = self.get_active_pane();
+        let (left, right) = if active_pane.selected_items.len() == 2 {
+            let marked = active_pane.get_selected_items();
+            (Some(marked[0].clone()), Some(marked[1].clone()))
+        } else {
+            (
+                self.left_pane.get_selected_item().cloned(),
+                self.right_pane.get_selected_item().cloned(),
+            )
+        };
+        let (Some(left), Some(right)) = (left, right) else {
+            self.status_message = "Select a file in each pane to compare".to_string();
+            return;
+        };
+        if left.name == ".." || right.name == ".." || left.is_dir || right.is_dir {
+            self.status_message = "Cannot compare directories (files only)".to_string();
+            return;
+        }
+        let mut base = left
+            .path
+            .parent()
+            .map(|dir| dir.display().to_string())
+            .unwrap_or_default();
+        if !base.ends_with(std::path::MAIN_SEPARATOR) {
+            base.push(std::path::MAIN_SEPARATOR);
+        }
+        self.three_way = Some(ThreeWayDialog {
+            left: left.path,
+            right: right.path,
+            base,
+            result: None,
+        });
+    }
+
+    pub fn compare_with_clipboard(&mut self) {
+        let item = self.get_active_pane().get_selected_item().cloned();
+        let Some(item) = item.filter(|item| item.name != ".." && !item.is_dir) else {
+            self.status_message = "Select a file to compare with the clipboard".to_string();
+            return;
+        };
+
+        let text = match arboard::Clipboard::new().and_then(|mut clipboard| clipboard.get_text()) {
+            Ok(text) => text,
+            Err(e) => {
+                self.status_message = format!("❌ Cannot read clipboard text: {}", e);
+                return;
+            }
+        };
+
+        match crate::filesystem::compare_with_text(
+            &item.path,
+            &text,
+            "Clipboard",
+            self.diff_options,
+        ) {
+            Ok(comparison) => {
+                self.status_message = if comparison.are_identical {
+                    format!("✅ {} matches the clipboard", item.name)
+                } else {
+                    format!(
+                        "{} vs clipboard: {} added, {} removed, {} modified",
+                        item.name,
+                        comparison.right_only_lines,
+                        comparison.left_only_lines,
+                        comparison.modified_lines
+                    )
+                };
+                self.comparison_result = Some(comparison);
+                self.show_compare_dialog = true;
+                self.compare_scroll_offset = 0.0;
+            }
+            Err(e) => {
+                self.status_message = format!("❌ Compare with clipboard failed: {}", e);
+            }
+        }
+    }
+
+    fn pane_filter_text(&self, pane_index: usize) -> String {
+        if pane_index == 0 {
+            self.left_pane.filter_text.to_lowercase()
+        } else {
+            self.right_pane.filter_text.to_lowercase()
+        }
+    }
+
+    fn has_type_filter(&self) -> bool {
+        self.filter_txt || self.filter_image || self.filter_pdf || self.filter_doc || self.filter_xls
+    }
+
+    /// Whether `item` passes the quick filter (`filter_text`, lowercased)
+    /// and the type toggles, combined according to `filter_combine`, and
+    /// the hidden-file and files/folders toggles.
+    fn is_item_visible(&self, item: &crate::filesystem::FileItem, filter_text: &str) -> bool {
+        if item.name == ".." {
+            return true;
+        }
+        if !self.show_hidden_files && item.name.starts_with('.') {
+            return false;
+        }
+        if !self.matches_entry_filter(item) {
+            return false;
+        }
+
+        let name_match = || self.name_matches(&item.name, filter_text);
+        match (!filter_text.is_empty(), self.has_type_filter()) {
+            (false, false) => true,
+            (true, false) => name_match(),
+            (false, true) => self.should_show_file(item),
+            (true, true) => match self.filter_combine {
+                FilterCombine::All => name_match() && self.should_show_file(item),
+                FilterCombine::Any => name_match() || self.should_show_file(item),
+            },
+        }
+    }
+
+    /// Whether `name` passes the quick filter `filter_text` (lowercased).
+    fn name_matches(&self, name: &str, filter_text: &str) -> bool {
+        match self.filter_matching {
+            NameMatch::Substring => name.to_lowercase().contains(filter_text),
+            NameMatch::Fuzzy => crate::filesystem::fuzzy_match(name, filter_text).is_some(),
+        }
+    }
+
+    /// Positions of the characters the fuzzy filter matched in `name`, for
+    /// highlighting. `None` unless a fuzzy filter is typed in.
+    fn fuzzy_positions(&self, name: &str, filter_text: &str) -> Option<Vec<usize>> {
+        if self.filter_matching != NameMatch::Fuzzy || filter_text.is_empty() {
+            return None;
+        }
+        crate::filesystem::fuzzy_match(name, filter_text).map(|(_, positions)| positions)
+    }
+
+    /// Indices of a pane's items that are currently shown, in the order
+    /// they're listed: best matches first while a fuzzy filter is typed in.
+    fn visible_indices(&self, pane_index: usize) -> Vec<usize> {
+        let pane = self.get_pane(pane_index);
+        let filter_text = self.pane_filter_text(pane_index);
+        let mut indices: Vec<usize> = pane
+            .items
+            .iter()
+            .enumerate()
+            .filter(|(_, item)| self.is_item_visible(item, &filter_text))
+            .map(|(index, _)| index)
+            .collect();
+        if self.filter_matching == NameMatch::Fuzzy && !filter_text.is_empty() {
+            // ".." stays on top; items shown only by a type toggle go last
+            indices.sort_by_cached_key(|&index| {
+                let name = &pane.items[index].name;
+                let score = if name == ".." {
+                    i64::MAX
+                } else {
+                    crate::filesystem::fuzzy_match(name, &filter_text)
+                        .map_or(i64::MIN, |(score, _)| score)
+                };
+                std::cmp::Reverse(score)
+            });
+        }
+        indices
+    }
+
+    /// Indices of the active pane's items that are currently shown.
+    fn visible_item_indices(&self) -> Vec<usize> {
+        self.visible_indices(self.active_pane)
+    }
+
+    /// How many of a pane's items are left out because they are hidden
+    /// files, and how many because of the name and type filters.
+    fn excluded_counts(&self, pane_index: usize) -> (usize, usize) {
+        let pane = if pane_index == 0 {
+            &self.left_pane
+        } else {
+            &self.right_pane
+        };
+        let filter_text = self.pane_filter_text(pane_index);
+        let mut hidden = 0;
+        let mut filtered = 0;
+        for item in &pane.items {
+            if !self.show_hidden_files && item.name != ".." && item.name.starts_with('.') {
+                hidden += 1;
+            } else if !self.is_item_visible(item, &filter_text) {
+                filtered += 1;
+            }
+        }
+        (hidden, filtered)
+    }
+
+    /// Drop the pane's name filter and all type toggles.
+    fn clear_filters(&mut self, pane_index: usize) {
+        if pane_index == 0 {
+            self.left_pane.filter_text.clear();
+        } else {
+            self.right_pane.filter_text.clear();
+        }
+        if self.active_pane == pane_index {
+            self.filter_mode = false;
+        }
+        self.filter_txt = false;
+        self.filter_image = false;
+        self.filter_pdf = false;
+        self.filter_doc = false;
+        self.filter_xls = false;
+        self.filter_entries = crate::filesystem::SearchFileType::All;
+        self.status_message = "Filters cleared".to_string();
+    }
+
+    pub fn select_all(&mut self) {
+        let visible = self.visible_item_indices();
+        let pane = self.get_active_pane_mut();
+        pane.select_all(&visible);
+        self.status_message = format!("Selected {} items", pane.selected_items.len());
+    }
+
+    pub fn invert_selection(&mut self) {
+        let visible = self.visible_item_indices();
+        let pane = self.get_active_pane_mut();
+        pane.invert_selection(&visible);
+        self.status_message = format!("Selected {} items", pane.selected_items.len());
+    }
+
+    pub fn deselect_all(&mut self) {
+        self.get_active_pane_mut().clear_selection();
+        self.status_message = "Selection cleared".to_string();
+    }
+
+    /// Rows for the hover tooltip of `item`, reusing the last result while the
+    /// same unchanged file stays hovered.
+    fn hover_details(&mut self, item: &crate::filesystem::FileItem) -> Vec<DetailRow> {
+        if let Some((path, modified, details)) = &self.hover_details {
+            if *path == item.path && *modified == item.modified {
+                return details.clone();
+            }
+        }
+
+        let mut details = vec![("Path", item.path.display().to_string())];
+        if crate::filesystem::is_link(&item.path) {
+            let target = fs::read_link(&item.path)
+                .map(|target| target.display().to_string())
+                .unwrap_or_else(|_| "reparse point".to_string());
+            details.push(("Link to", target));
+        }
+        if item.is_dir {
+            details.push(("Type", "Folder".to_string()));
+        } else {
+            details.push((
+                "Size",
+                crate::filesystem::format_size_with(item.size, self.settings.size_units),
+            ));
+        }
+        details.push(("Modified", crate::filesystem::format_date(item.modified)));
+        if let Ok((summary, _)) = crate::filesystem::get_permissions(&item.path) {
+            details.push(("Permissions", summary));
+        }
+        if !item.is_dir {
+            if Self::is_image_file(&item.path) {
+                if let Ok((width, height)) = image::image_dimensions(&item.path) {
+                    details.push(("Dimensions", format!("{} × {} px", width, height)));
+                }
+            }
+            let is_zip = item
+                .path
+                .extension()
+                .is_some_and(|ext| ext.eq_ignore_ascii_case("zip"));
+            if is_zip {
+                if let Ok(summary) = crate::filesystem::zip_summary(&item.path) {
+                    details.push(("Entries", summary.entries.to_string()));
+                    details.push((
+                        "Extracted size",
+                        crate::filesystem::format_size_with(
+                            summary.uncompressed_size,
+                            self.settings.size_units,
+                        ),
+                    ));
+                }
+            }
+        }
+
+        self.hover_details = Some((item.path.clone(), item.modified, details.clone()));
+        details
+    }
+
+    /// Whether `item` is the kind of entry (folder or file) the filter bar
+    /// asks for.
+    fn matches_entry_filter(&self, item: &crate::filesystem::FileItem) -> bool {
+        match self.filter_entries {
+            crate::filesystem::SearchFileType::All => true,
+            crate::filesystem::SearchFileType::Files => !item.is_dir,
+            crate::filesystem::SearchFileType::Directories => item.is_dir,
+        }
+    }
+
     pub fn should_show_file(&self, item: &crate::filesystem::FileItem) -> bool {
-        if !self.filter_txt && !self.filter_image && !self.filter_pdf && !self.filter_doc && !self.filter_xls {
+        if !self.matches_entry_filter(item) {
+            return false;
+        }
+        if !self.has_type_filter() {
             return true;
         }
 
@@ -569,9 +5230,7 @@ impl eframe::App for FileManagerApp {
                 egui::menu::bar(ui, |ui| {
                     ui.menu_button("Files", |ui| {
                         if ui.button("Refresh (F2)").clicked() {
-                            let _ = self.left_pane.refresh();
-                            let _ = self.right_pane.refresh();
-                            self.status_message = "Refreshed".to_string();
+                            self.run_action(ctx, Action::Refresh);
                             ui.close_menu();
                         }
 
@@ -584,14 +5243,7 @@ impl eframe::App for FileManagerApp {
                             "☐ Show Hidden Files (Ctrl+H)"
                         };
                         if ui.button(hidden_text).clicked() {
-                            self.show_hidden_files = !self.show_hidden_files;
-                            let _ = self.left_pane.refresh();
-                            let _ = self.right_pane.refresh();
-                            self.status_message = if self.show_hidden_files {
-                                "Showing hidden files".to_string()
-                            } else {
-                                "Hiding hidden files".to_string()
-                            };
+                            self.run_action(ctx, Action::ToggleHiddenFiles);
                             ui.close_menu();
                         }
 
@@ -602,7 +5254,42 @@ impl eframe::App for FileManagerApp {
                             "☐ Show Preview Panel (Space)"
                         };
                         if ui.button(preview_text).clicked() {
-                            self.show_preview_panel = !self.show_preview_panel;
+                            self.run_action(ctx, Action::TogglePreview);
+                            ui.close_menu();
+                        }
+
+                        let mirror_text = if self.mirror_navigation {
+                            "☑ Mirror Navigation"
+                        } else {
+                            "☐ Mirror Navigation"
+                        };
+                        if ui
+                            .button(mirror_text)
+                            .on_hover_text("Navigate the other pane to the same relative folder")
+                            .clicked()
+                        {
+                            self.run_action(ctx, Action::ToggleMirrorNavigation);
+                            ui.close_menu();
+                        }
+
+                        if ui.button("⇄ Swap Panes (Ctrl+U)").clicked() {
+                            self.run_action(ctx, Action::SwapPanes);
+                            ui.close_menu();
+                        }
+                        if ui
+                            .button("⇉ Other Pane to This Folder")
+                            .on_hover_text("Target = source: open the active pane's folder in the other pane")
+                            .clicked()
+                        {
+                            self.run_action(ctx, Action::OtherPaneToThisFolder);
+                            ui.close_menu();
+                        }
+                        if ui
+                            .button("⇇ This Pane to Other Folder")
+                            .on_hover_text("Source = target: open the other pane's folder in the active pane")
+                            .clicked()
+                        {
+                            self.run_action(ctx, Action::ThisPaneToOtherFolder);
                             ui.close_menu();
                         }
 
@@ -612,7 +5299,31 @@ impl eframe::App for FileManagerApp {
                             "☐ Show Sidebar (Ctrl+B)"
                         };
                         if ui.button(sidebar_text).clicked() {
-                            self.show_sidebar = !self.show_sidebar;
+                            self.run_action(ctx, Action::ToggleSidebar);
+                            ui.close_menu();
+                        }
+
+                        let log_text = if self.show_log_panel {
+                            "☑ Show Operation Log"
+                        } else {
+                            "☐ Show Operation Log"
+                        };
+                        if ui.button(log_text).clicked() {
+                            self.run_action(ctx, Action::ToggleLogPanel);
+                            ui.close_menu();
+                        }
+
+                        let read_only_text = if self.settings.read_only_mode {
+                            "☑ Read-only Mode"
+                        } else {
+                            "☐ Read-only Mode"
+                        };
+                        if ui
+                            .button(read_only_text)
+                            .on_hover_text("Disable every command that changes files")
+                            .clicked()
+                        {
+                            self.run_action(ctx, Action::ToggleReadOnlyMode);
                             ui.close_menu();
                         }
 
@@ -620,26 +5331,24 @@ impl eframe::App for FileManagerApp {
 
                         // Theme selector
                         if ui.button("🎨 Themes...").clicked() {
-                            self.show_theme_selector = true;
+                            self.run_action(ctx, Action::Themes);
+                            ui.close_menu();
+                        }
+
+                        if ui.button("⚙ Settings...").clicked() {
+                            self.run_action(ctx, Action::Settings);
                             ui.close_menu();
                         }
 
                         ui.separator();
                         if ui.button("Exit (Alt+F4)").clicked() {
-                            ctx.send_viewport_cmd(egui::ViewportCommand::Close);
+                            self.run_action(ctx, Action::Exit);
                         }
                     });
 
                     ui.menu_button("Bookmarks", |ui| {
                         if ui.button("⭐ Add Current Folder").clicked() {
-                            self.show_add_bookmark_dialog = true;
-                            self.new_bookmark_name = self
-                                .get_active_pane()
-                                .current_path
-                                .file_name()
-                                .and_then(|n| n.to_str())
-                                .unwrap_or("Bookmark")
-                                .to_string();
+                            self.run_action(ctx, Action::AddBookmark);
                             ui.close_menu();
                         }
 
@@ -649,7 +5358,7 @@ impl eframe::App for FileManagerApp {
                         if bookmarks.is_empty() {
                             ui.label("No bookmarks yet");
                         } else {
-                            for (idx, bookmark) in bookmarks.iter().enumerate() {
+                            for bookmark in bookmarks.iter() {
                                 if ui
                                     .button(format!("{} {}", bookmark.icon, bookmark.name))
                                     .clicked()
@@ -665,59 +5374,181 @@ impl eframe::App for FileManagerApp {
                     });
 
                     ui.menu_button("Commands", |ui| {
+                        if ui.button("⌨ Command Palette (Ctrl+Shift+P)").clicked() {
+                            self.run_action(ctx, Action::CommandPalette);
+                            ui.close_menu();
+                        }
                         if ui.button("💾 Mount Points").clicked() {
-                            self.show_mounts_dialog = true;
+                            self.run_action(ctx, Action::MountPoints);
                             ui.close_menu();
                         }
                         ui.separator();
-                        if ui.button("📁 New Folder (Ctrl+N)").clicked() {
-                            self.show_new_folder_dialog = true;
+                        let active_writable = !self.is_pane_read_only(self.active_pane);
+                        let paste_writable = !self.is_pane_read_only(1 - self.active_pane);
+                        if ui
+                            .add_enabled(
+                                active_writable,
+                                egui::Button::new("📁 New Folder (Ctrl+N)"),
+                            )
+                            .on_disabled_hover_text(self.read_only_hint())
+                            .clicked()
+                        {
+                            self.run_action(ctx, Action::NewFolder);
+                            ui.close_menu();
+                        }
+                        if ui
+                            .add_enabled(
+                                active_writable,
+                                egui::Button::new("📁 New Folder with Selection (Ctrl+Shift+N)"),
+                            )
+                            .on_disabled_hover_text(self.read_only_hint())
+                            .clicked()
+                        {
+                            self.run_action(ctx, Action::NewFolderWithSelection);
+                            ui.close_menu();
+                        }
+                        if ui
+                            .add_enabled(active_writable, egui::Button::new("✏ Rename (F2)"))
+                            .on_disabled_hover_text(self.read_only_hint())
+                            .clicked()
+                        {
+                            self.run_action(ctx, Action::Rename);
+                            ui.close_menu();
+                        }
+                        if ui
+                            .add_enabled(active_writable, egui::Button::new("🕒 Touch…"))
+                            .on_disabled_hover_text(self.read_only_hint())
+                            .clicked()
+                        {
+                            self.run_action(ctx, Action::Touch);
                             ui.close_menu();
                         }
-                        if ui.button("✏ Rename (F2)").clicked() {
-                            if let Some(item) = self.get_active_pane().get_selected_item() {
-                                if item.name != ".." {
-                                    self.rename_new_name = item.name.clone();
-                                    self.show_rename_dialog = true;
-                                }
-                            }
+                        if ui
+                            .add_enabled(active_writable, egui::Button::new("🔐 Permissions…"))
+                            .on_disabled_hover_text(self.read_only_hint())
+                            .clicked()
+                        {
+                            self.run_action(ctx, Action::Permissions);
                             ui.close_menu();
                         }
                         ui.separator();
                         if ui.button("📄 Open File (F3)").clicked() {
-                            let _ = self.open_file_with_default_app();
+                            self.run_action(ctx, Action::OpenFile);
+                            ui.close_menu();
+                        }
+                        if ui.button("🗔 Reveal in System File Manager").clicked() {
+                            self.run_action(ctx, Action::RevealInFileManager);
                             ui.close_menu();
                         }
                         ui.separator();
                         if ui.button("🔍 Advanced Search (Ctrl+Shift+F)").clicked() {
-                            self.search_criteria.search_path =
-                                self.get_active_pane().current_path.clone();
-                            self.show_search_dialog = true;
+                            self.run_action(ctx, Action::AdvancedSearch);
                             ui.close_menu();
                         }
                         if ui.button("⚖ Compare Files (Ctrl+D)").clicked() {
-                            let _ = self.compare_selected_files();
+                            self.run_action(ctx, Action::CompareFiles);
+                            ui.close_menu();
+                        }
+                        if ui.button("⚖ 3-way Compare…").clicked() {
+                            self.run_action(ctx, Action::ThreeWayCompare);
+                            ui.close_menu();
+                        }
+                        if ui.button("📋 Compare with Clipboard").clicked() {
+                            self.run_action(ctx, Action::CompareWithClipboard);
+                            ui.close_menu();
+                        }
+                        if ui.button("⎇ Diff against HEAD").clicked() {
+                            self.run_action(ctx, Action::DiffAgainstHead);
+                            ui.close_menu();
+                        }
+                        if ui.button("📊 Folder Statistics").clicked() {
+                            self.run_action(ctx, Action::FolderStatistics);
+                            ui.close_menu();
+                        }
+                        if ui
+                            .add_enabled(!self.is_pane_read_only(1), egui::Button::new("🔁 Sync Left → Right…"))
+                            .on_disabled_hover_text(self.read_only_hint())
+                            .clicked()
+                        {
+                            self.run_action(ctx, Action::SyncLeftToRight);
+                            ui.close_menu();
+                        }
+                        if ui
+                            .add_enabled(!self.is_pane_read_only(0), egui::Button::new("🔁 Sync Right → Left…"))
+                            .on_disabled_hover_text(self.read_only_hint())
+                            .clicked()
+                        {
+                            self.run_action(ctx, Action::SyncRightToLeft);
                             ui.close_menu();
                         }
                         ui.separator();
                         if ui.button("📋 Copy (F5)").clicked() {
-                            self.copy_to_clipboard();
+                            self.run_action(ctx, Action::Copy);
                             ui.close_menu();
                         }
                         if ui.button("✂ Cut/Move (F6)").clicked() {
-                            self.cut_to_clipboard();
+                            self.run_action(ctx, Action::Cut);
+                            ui.close_menu();
+                        }
+                        if ui.button("📋 Copy to… (Shift+F5)").clicked() {
+                            self.run_action(ctx, Action::CopyTo);
+                            ui.close_menu();
+                        }
+                        if ui
+                            .add_enabled(active_writable, egui::Button::new("✂ Move to… (Shift+F6)"))
+                            .on_disabled_hover_text(self.read_only_hint())
+                            .clicked()
+                        {
+                            self.run_action(ctx, Action::MoveTo);
+                            ui.close_menu();
+                        }
+                        if ui
+                            .add_enabled(active_writable, egui::Button::new("⧉ Duplicate (Ctrl+Shift+D)"))
+                            .on_disabled_hover_text(self.read_only_hint())
+                            .clicked()
+                        {
+                            self.run_action(ctx, Action::Duplicate);
+                            ui.close_menu();
+                        }
+                        if ui
+                            .add_enabled(paste_writable, egui::Button::new("📎 Paste (F7)"))
+                            .on_disabled_hover_text(self.read_only_hint())
+                            .clicked()
+                        {
+                            self.run_action(ctx, Action::Paste);
                             ui.close_menu();
                         }
-                        if ui.button("📎 Paste (F7)").clicked() {
-                            let _ = self.paste_from_clipboard();
+                        if ui
+                            .add_enabled(
+                                self.clipboard.is_some(),
+                                egui::Button::new("📎 Paste into Folder (Shift+F7)"),
+                            )
+                            .on_hover_text("Paste into the highlighted folder of this pane")
+                            .clicked()
+                        {
+                            self.run_action(ctx, Action::PasteIntoFolder);
                             ui.close_menu();
                         }
                         ui.separator();
-                        if ui.button("🗑 Delete (F8)").clicked() {
-                            if let Some(item) = self.get_active_pane().get_selected_item() {
-                                self.item_to_delete = Some(item.name.clone());
-                                self.show_delete_confirm = true;
-                            }
+                        if ui.button("☑ Select All (Ctrl+A)").clicked() {
+                            self.run_action(ctx, Action::SelectAll);
+                            ui.close_menu();
+                        }
+                        if ui.button("⇄ Invert Selection").clicked() {
+                            self.run_action(ctx, Action::InvertSelection);
+                            ui.close_menu();
+                        }
+                        if ui.button("☐ Deselect All (Ctrl+Shift+A)").clicked() {
+                            self.run_action(ctx, Action::DeselectAll);
+                            ui.close_menu();
+                        }
+                        ui.separator();
+                        if ui
+                            .add_enabled(active_writable, egui::Button::new("🗑 Delete (F8)"))
+                            .on_disabled_hover_text(self.read_only_hint())
+                            .clicked()
+                        {
+                            self.run_action(ctx, Action::Delete);
                             ui.close_menu();
                         }
                     });
@@ -737,17 +5568,15 @@ impl eframe::App for FileManagerApp {
                         }
                         ui.separator();
                         ui.label("Keyboard Shortcuts:");
-                        ui.label("• F2: Refresh");
-                        ui.label("• F3: Open file");
-                        ui.label("• F5: Copy");
-                        ui.label("• F6: Cut/Move");
-                        ui.label("• F7: Paste");
-                        ui.label("• F8: Delete");
-                        ui.label("• Space: Toggle preview");
-                        ui.label("• Ctrl+H: Toggle hidden");
-                        ui.label("• Ctrl+F: Filter");
-                        ui.label("• Tab: Switch pane");
-                        ui.label("• Alt+←/→: Back/Forward");
+                        for action in Action::all() {
+                            if let Some(shortcut) = action.shortcut() {
+                                ui.label(format!(
+                                    "• {}: {}",
+                                    ctx.format_shortcut(&shortcut),
+                                    action.name()
+                                ));
+                            }
+                        }
                     });
                 });
             });
@@ -762,35 +5591,56 @@ impl eframe::App for FileManagerApp {
                 ui.horizontal(|ui| {
                     ui.label(egui::RichText::new("🔍 Filter:").size(13.0));
                     ui.add_space(8.0);
+
+                    let entries = [
+                        (crate::filesystem::SearchFileType::All, "All", "Show folders and files"),
+                        (crate::filesystem::SearchFileType::Directories, "📁 Folders", "Show only folders"),
+                        (crate::filesystem::SearchFileType::Files, "📄 Files", "Show only files"),
+                    ];
+                    for (kind, label, hint) in entries {
+                        if ui
+                            .selectable_value(&mut self.filter_entries, kind, label)
+                            .on_hover_text(hint)
+                            .changed()
+                        {
+                            let _ = self.refresh_both_panes();
+                        }
+                    }
+                    ui.add_space(4.0);
+                    ui.separator();
+                    ui.add_space(4.0);
                     
                     if ui.checkbox(&mut self.filter_txt, "📝 TXT").changed() {
-                        let _ = self.left_pane.refresh();
-                        let _ = self.right_pane.refresh();
+                        let _ = self.refresh_both_panes();
                     }
                     ui.add_space(4.0);
                     
                     if ui.checkbox(&mut self.filter_image, "🖼 Image").changed() {
-                        let _ = self.left_pane.refresh();
-                        let _ = self.right_pane.refresh();
+                        let _ = self.refresh_both_panes();
                     }
                     ui.add_space(4.0);
                     
                     if ui.checkbox(&mut self.filter_pdf, "📄 PDF").changed() {
-                        let _ = self.left_pane.refresh();
-                        let _ = self.right_pane.refresh();
+                        let _ = self.refresh_both_panes();
                     }
                     ui.add_space(4.0);
                     
                     if ui.checkbox(&mut self.filter_doc, "📃 DOC").changed() {
-                        let _ = self.left_pane.refresh();
-                        let _ = self.right_pane.refresh();
+                        let _ = self.refresh_both_panes();
                     }
                     ui.add_space(4.0);
                     
                     if ui.checkbox(&mut self.filter_xls, "📊 XLS").changed() {
-                        let _ = self.left_pane.refresh();
-                        let _ = self.right_pane.refresh();
+                        let _ = self.refresh_both_panes();
                     }
+
+                    ui.add_space(8.0);
+                    ui.separator();
+                    ui.label(egui::RichText::new("Name filter:").size(13.0));
+                    ui.selectable_value(&mut self.filter_combine, FilterCombine::All, "AND")
+                        .on_hover_text("Show items matching the name filter and a checked type");
+                    ui.selectable_value(&mut self.filter_combine, FilterCombine::Any, "OR")
+                        .on_hover_text("Show items matching the name filter or a checked type");
                     
                     ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
                         if ui.small_button("Clear All").clicked() {
@@ -799,8 +5649,8 @@ impl eframe::App for FileManagerApp {
                             self.filter_pdf = false;
                             self.filter_doc = false;
                             self.filter_xls = false;
-                            let _ = self.left_pane.refresh();
-                            let _ = self.right_pane.refresh();
+                            self.filter_entries = crate::filesystem::SearchFileType::All;
+                            let _ = self.refresh_both_panes();
                         }
                     });
                 });
@@ -832,6 +5682,24 @@ impl eframe::App for FileManagerApp {
                     .fg_stroke
                     .color = visuals.widgets.noninteractive.fg_stroke.color;
                 ui.horizontal(|ui| {
+                    if self.settings.read_only_mode {
+                        if ui
+                            .add(
+                                egui::Button::new(
+                                    egui::RichText::new("🔒 READ-ONLY")
+                                        .size(13.0)
+                                        .strong()
+                                        .color(egui::Color32::from_rgb(242, 139, 130)),
+                                )
+                                .frame(false),
+                            )
+                            .on_hover_text("Read-only mode is on: nothing can be changed. Click to turn it off.")
+                            .clicked()
+                        {
+                            self.toggle_read_only_mode();
+                        }
+                        ui.add_space(8.0);
+                    }
                     ui.label(egui::RichText::new("📊").size(16.0));
                     ui.add_space(8.0);
 
@@ -844,7 +5712,10 @@ impl eframe::App for FileManagerApp {
                             left_stats.total_items,
                             left_stats.folder_count,
                             left_stats.file_count,
-                            crate::filesystem::format_size(left_stats.total_size)
+                            crate::filesystem::format_size_with(
+                                left_stats.total_size,
+                                self.settings.size_units
+                            )
                         ))
                         .size(14.0),
                     );
@@ -866,42 +5737,126 @@ impl eframe::App for FileManagerApp {
                             right_stats.total_items,
                             right_stats.folder_count,
                             right_stats.file_count,
-                            crate::filesystem::format_size(right_stats.total_size)
+                            crate::filesystem::format_size_with(
+                                right_stats.total_size,
+                                self.settings.size_units
+                            )
                         ))
                         .size(14.0),
                     );
 
                     ui.add_space(8.0);
 
-                    // Git info for active pane
-                    let active_pane = self.get_active_pane();
-                    if let Some(repo_path) = &active_pane.git_repo_path {
-                        if let Ok(git_info) = crate::filesystem::get_git_repo_info(repo_path) {
-                            ui.label(
-                                egui::RichText::new("│")
-                                    .size(12.0)
-                                    .color(visuals.widgets.noninteractive.bg_fill),
-                            );
-                            ui.add_space(4.0);
+                    // Git info for each pane, using the info cached on refresh
+                    let mut fetch_repo = None;
+                    for (side, pane) in [("L", &self.left_pane), ("R", &self.right_pane)] {
+                        let (Some(repo_path), Some(git_info)) =
+                            (&pane.git_repo_path, &pane.git_info)
+                        else {
+                            continue;
+                        };
 
-                            let branch_icon = if git_info.has_changes { "🔶" } else { "🔷" };
-                            let mut branch_text =
-                                format!("{} {}", branch_icon, git_info.current_branch);
+                        ui.label(
+                            egui::RichText::new("│")
+                                .size(12.0)
+                                .color(visuals.widgets.noninteractive.bg_fill),
+                        );
+                        ui.add_space(4.0);
 
-                            if git_info.ahead > 0 || git_info.behind > 0 {
-                                branch_text.push_str(&format!(
-                                    " (↑{} ↓{})",
-                                    git_info.ahead, git_info.behind
-                                ));
-                            }
+                        let repo_name = repo_path
+                            .file_name()
+                            .map(|n| n.to_string_lossy().to_string())
+                            .unwrap_or_else(|| repo_path.display().to_string());
+                        let branch_icon = if git_info.has_changes { "🔶" } else { "🔷" };
+                        let branch_text = format!(
+                            "{}: {} {} {}",
+                            side, repo_name, branch_icon, git_info.current_branch
+                        );
 
-                            ui.label(
-                                egui::RichText::new(branch_text)
-                                    .size(12.0)
-                                    .color(visuals.widgets.active.bg_fill),
-                            );
-                            ui.add_space(8.0);
+                        ui.label(
+                            egui::RichText::new(branch_text)
+                                .size(12.0)
+                                .color(visuals.widgets.active.bg_fill),
+                        )
+                        .on_hover_text(repo_path.display().to_string());
+
+                        // Ahead/behind the upstream; clicking fetches it
+                        if git_info.has_upstream {
+                            let fetching = self
+                                .git_fetch
+                                .as_ref()
+                                .is_some_and(|(fetching, _)| fetching == repo_path);
+                            if fetching {
+                                ui.spinner();
+                            } else if ui
+                                .add(
+                                    egui::Label::new(
+                                        egui::RichText::new(format!(
+                                            "↑{} ↓{}",
+                                            git_info.ahead, git_info.behind
+                                        ))
+                                        .size(12.0)
+                                        .color(visuals.widgets.active.bg_fill),
+                                    )
+                                    .sense(egui::Sense::click()),
+                                )
+                                .on_hover_cursor(egui::CursorIcon::PointingHand)
+                                .on_hover_text("Commits ahead of / behind the upstream. Click to fetch")
+                                .clicked()
+                            {
+                                fetch_repo = Some(repo_path.clone());
+                            }
                         }
+                        ui.add_space(8.0);
+                    }
+                    if let Some(repo_path) = fetch_repo {
+                        self.start_git_fetch(repo_path);
+                    }
+
+                    // Mirror navigation indicator
+                    if self.mirror_navigation {
+                        ui.label(
+                            egui::RichText::new("│")
+                                .size(12.0)
+                                .color(visuals.widgets.noninteractive.bg_fill),
+                        );
+                        ui.add_space(4.0);
+                        ui.label(
+                            egui::RichText::new("🪞 Mirror")
+                                .size(12.0)
+                                .color(visuals.widgets.active.bg_fill),
+                        );
+                        ui.add_space(8.0);
+                    }
+
+                    // Selection count for the active pane
+                    let selected = &self.get_active_pane().selected_items;
+                    if !selected.is_empty() {
+                        let pane = self.get_active_pane();
+                        let selected_size: u64 = selected
+                            .iter()
+                            .filter_map(|&index| pane.items.get(index))
+                            .map(|item| item.size)
+                            .sum();
+                        ui.label(
+                            egui::RichText::new("│")
+                                .size(12.0)
+                                .color(visuals.widgets.noninteractive.bg_fill),
+                        );
+                        ui.add_space(4.0);
+                        ui.label(
+                            egui::RichText::new(format!(
+                                "☑ {} selected • {}",
+                                selected.len(),
+                                crate::filesystem::format_size_with(
+                                    selected_size,
+                                    self.settings.size_units
+                                )
+                            ))
+                            .size(12.0)
+                            .color(visuals.widgets.active.bg_fill),
+                        );
+                        ui.add_space(8.0);
                     }
 
                     // Clipboard indicator
@@ -936,15 +5891,69 @@ impl eframe::App for FileManagerApp {
                     }
 
                     ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                        // Clock, repainted when the minute changes
+                        let now = chrono::Local::now();
                         ui.label(
-                            egui::RichText::new("⚡ Ready")
+                            egui::RichText::new(now.format("%H:%M").to_string())
                                 .size(12.0)
-                                .color(visuals.widgets.active.bg_fill),
+                                .color(visuals.widgets.noninteractive.fg_stroke.color),
                         );
+                        let secs_left = 60 - u64::from(chrono::Timelike::second(&now));
+                        ui.ctx()
+                            .request_repaint_after(std::time::Duration::from_secs(secs_left));
+                        ui.add_space(8.0);
+
+                        let failed = self.op_log.failure_count();
+                        let (log_text, log_color) = if failed > 0 {
+                            (format!("📜 Log ({} ✗)", failed), egui::Color32::from_rgb(242, 139, 130))
+                        } else {
+                            ("📜 Log".to_string(), visuals.widgets.noninteractive.fg_stroke.color)
+                        };
+                        if ui
+                            .add(
+                                egui::Button::new(egui::RichText::new(log_text).size(12.0).color(log_color))
+                                    .frame(false)
+                                    .selected(self.show_log_panel),
+                            )
+                            .on_hover_text("Show or hide the log of this session's file operations")
+                            .clicked()
+                        {
+                            self.run_action(ctx, Action::ToggleLogPanel);
+                        }
+                        ui.add_space(8.0);
+
+                        if let Some(operation) = &self.operation_in_progress {
+                            ui.label(
+                                egui::RichText::new(operation)
+                                    .size(12.0)
+                                    .color(visuals.widgets.active.bg_fill),
+                            );
+                            ui.spinner();
+                        } else {
+                            ui.label(
+                                egui::RichText::new("⚡ Ready")
+                                    .size(12.0)
+                                    .color(visuals.widgets.active.bg_fill),
+                            );
+                            if let Some((label, elapsed)) = &self.last_operation {
+                                ui.add_space(8.0);
+                                ui.label(
+                                    egui::RichText::new(format!(
+                                        "⏱ {} took {}",
+                                        label,
+                                        crate::filesystem::format_duration(*elapsed)
+                                    ))
+                                    .size(12.0)
+                                    .color(egui::Color32::from_rgb(154, 160, 166)),
+                                );
+                            }
+                        }
                     });
                 });
             });
 
+        self.render_log_panel(ctx);
+
         // Sidebar (Left Panel)
         if self.show_sidebar {
             egui::SidePanel::left("sidebar")
@@ -1018,9 +6027,17 @@ impl eframe::App for FileManagerApp {
                     .inner_margin(2.0),
             )
             .show(ctx, |ui| {
-                let full_rect = ui.available_rect_before_wrap();
-                let preview_height = if self.show_preview_panel { 200.0 } else { 0.0 };
-                let spacing = if self.show_preview_panel { 4.0 } else { 0.0 };
+                let max_preview_height =
+                    (ui.available_height() - MIN_PANES_HEIGHT).max(MIN_PREVIEW_HEIGHT);
+                let preview_height = if self.show_preview_panel {
+                    self.settings
+                        .preview_height
+                        .clamp(MIN_PREVIEW_HEIGHT, max_preview_height)
+                } else {
+                    0.0
+                };
+                // Room for the drag handle above the preview
+                let spacing = if self.show_preview_panel { 6.0 } else { 0.0 };
                 
                 // Use StripBuilder for proper responsive layout with exact sizes
                 egui_extras::StripBuilder::new(ui)
@@ -1061,6 +6078,7 @@ impl eframe::App for FileManagerApp {
                         // Preview panel
                         if self.show_preview_panel {
                             strip.cell(|ui| {
+                                self.preview_resize_handle(ui, preview_height, max_preview_height);
                                 self.render_preview_panel(ui, ctx);
                             });
                         }
@@ -1070,6 +6088,30 @@ impl eframe::App for FileManagerApp {
         // Handle keyboard shortcuts
         self.handle_keyboard(ctx);
 
+        self.sync_mirror_navigation();
+        self.poll_background_task(ctx);
+        self.poll_index_task(ctx);
+        self.track_recent_folders();
+        self.poll_stats_task(ctx);
+        self.poll_size_tasks(ctx);
+        self.poll_sync_task(ctx);
+        self.poll_device_tasks(ctx);
+        self.poll_search_task(ctx);
+        self.poll_git_fetch(ctx);
+        self.check_external_changes(ctx);
+        self.render_progress_dialog(ctx);
+        self.render_transfer_dialog(ctx);
+        self.render_touch_dialog(ctx);
+        self.render_three_way_dialog(ctx);
+        self.render_batch_summary(ctx);
+        self.render_permissions_dialog(ctx);
+        self.render_open_many_confirm(ctx);
+        self.render_sync_dialog(ctx);
+        self.render_folder_stats_dialog(ctx);
+        self.render_command_palette(ctx);
+        self.render_theme_editor(ctx);
+        self.render_toasts(ctx);
+
         // Image Viewer Window
         if let Some(viewer) = &mut self.image_viewer {
             let mut close_viewer = false;
@@ -1082,22 +6124,13 @@ impl eframe::App for FileManagerApp {
                 .show(ctx, |ui| {
                     // Load image if not loaded
                     if viewer.texture.is_none() {
-                        match image::open(&viewer.image_path) {
-                            Ok(img) => {
-                                let size = [img.width() as usize, img.height() as usize];
-                                let rgba = img.to_rgba8();
-                                let pixels = rgba.as_flat_samples();
-
-                                let color_image = egui::ColorImage::from_rgba_unmultiplied(
-                                    size,
-                                    pixels.as_slice(),
-                                );
-
-                                viewer.texture = Some(ctx.load_texture(
-                                    &viewer.image_name,
-                                    color_image,
-                                    egui::TextureOptions::LINEAR,
-                                ));
+                        let path = viewer.image_path.clone();
+                        match self
+                            .texture_cache
+                            .get_or_load(ctx, &path, || decode_image_file(&path))
+                        {
+                            Ok(texture) => {
+                                viewer.texture = Some(texture);
                             }
                             Err(e) => {
                                 ui.label(
@@ -1141,7 +6174,6 @@ impl eframe::App for FileManagerApp {
 
                     // Image display area
                     if let Some(texture) = &viewer.texture {
-                        let available_size = ui.available_size();
                         let img_size = texture.size_vec2();
                         let scaled_size = img_size * viewer.zoom;
 
@@ -1204,10 +6236,17 @@ impl eframe::App for FileManagerApp {
             }
         }
 
-        // Context menu (right-click menu)
+        // Context menu (right-click menu, or Shift+F10)
         if self.show_context_menu {
             let item = self.get_active_pane().get_selected_item().cloned();
             let mut close_menu = false;
+            let (up, down) = ctx.input(|i| {
+                (i.key_pressed(egui::Key::ArrowUp), i.key_pressed(egui::Key::ArrowDown))
+            });
+            if up || down {
+                self.move_context_menu_focus(down);
+            }
+            self.context_menu_entries.clear();
 
             let menu_response = egui::Area::new(egui::Id::new("context_menu"))
                 .fixed_pos(self.context_menu_pos)
@@ -1236,31 +6275,88 @@ impl eframe::App for FileManagerApp {
                             if let Some(item) = &item {
                                 // Open file option (for non-directories)
                                 if !item.is_dir && item.name != ".." {
-                                    if ui
-                                        .add_sized(
-                                            [ui.available_width(), 28.0],
-                                            egui::Button::new(
-                                                egui::RichText::new("📄  Open").size(13.0),
-                                            )
-                                            .frame(false),
+                                    if self
+                                        .context_menu_entry(
+                                            ui.add_sized(
+                                                [ui.available_width(), 28.0],
+                                                egui::Button::new(
+                                                    egui::RichText::new("📄  Open").size(13.0),
+                                                )
+                                                .frame(false),
+                                            ),
                                         )
                                         .clicked()
                                     {
                                         let _ = self.open_file_with_default_app();
                                         close_menu = true;
                                     }
+
+                                    if self
+                                        .context_menu_entry(
+                                            ui.add_sized(
+                                                [ui.available_width(), 28.0],
+                                                egui::Button::new(
+                                                    egui::RichText::new("📋  Compare with Clipboard")
+                                                        .size(13.0),
+                                                )
+                                                .frame(false),
+                                            ),
+                                        )
+                                        .clicked()
+                                    {
+                                        self.compare_with_clipboard();
+                                        close_menu = true;
+                                    }
+
+                                    if item.git_status.is_some()
+                                        && self
+                                            .context_menu_entry(
+                                                ui.add_sized(
+                                                    [ui.available_width(), 28.0],
+                                                    egui::Button::new(
+                                                        egui::RichText::new("⎇  Diff against HEAD")
+                                                            .size(13.0),
+                                                    )
+                                                    .frame(false),
+                                                ),
+                                            )
+                                            .clicked()
+                                    {
+                                        let _ = self.compare_with_head();
+                                        close_menu = true;
+                                    }
                                     ui.separator();
                                 }
 
+                                if item.name != ".."
+                                    && self
+                                        .context_menu_entry(
+                                            ui.add_sized(
+                                                [ui.available_width(), 28.0],
+                                                egui::Button::new(
+                                                    egui::RichText::new("🗔  Reveal in File Manager")
+                                                        .size(13.0),
+                                                )
+                                                .frame(false),
+                                            ),
+                                        )
+                                        .clicked()
+                                {
+                                    self.reveal_in_file_manager();
+                                    close_menu = true;
+                                }
+
                                 // Properties
                                 if item.name != ".." {
-                                    if ui
-                                        .add_sized(
-                                            [ui.available_width(), 28.0],
-                                            egui::Button::new(
-                                                egui::RichText::new("ℹ  Properties").size(13.0),
-                                            )
-                                            .frame(false),
+                                    if self
+                                        .context_menu_entry(
+                                            ui.add_sized(
+                                                [ui.available_width(), 28.0],
+                                                egui::Button::new(
+                                                    egui::RichText::new("ℹ  Properties").size(13.0),
+                                                )
+                                                .frame(false),
+                                            ),
                                         )
                                         .clicked()
                                     {
@@ -1268,36 +6364,104 @@ impl eframe::App for FileManagerApp {
                                         self.show_properties_dialog = true;
                                         close_menu = true;
                                     }
+                                    if item.is_dir
+                                        && self
+                                            .context_menu_entry(
+                                                ui.add_sized(
+                                                    [ui.available_width(), 28.0],
+                                                    egui::Button::new(
+                                                        egui::RichText::new("📊  Folder Statistics")
+                                                            .size(13.0),
+                                                    )
+                                                    .frame(false),
+                                                )
+                                                .on_hover_text(
+                                                    "Totals for everything inside, largest files and sizes by extension",
+                                                ),
+                                            )
+                                            .clicked()
+                                    {
+                                        self.run_action(ctx, Action::FolderStatistics);
+                                        close_menu = true;
+                                    }
                                     ui.separator();
                                 }
 
                                 // Rename
                                 if item.name != ".." {
-                                    if ui
-                                        .add_sized(
-                                            [ui.available_width(), 28.0],
-                                            egui::Button::new(
-                                                egui::RichText::new("✏  Rename").size(13.0),
-                                            )
-                                            .frame(false),
+                                    let writable = !self.is_pane_read_only(self.active_pane);
+                                    if self
+                                        .context_menu_entry(
+                                            ui.add_enabled_ui(writable, |ui| {
+                                                ui.add_sized(
+                                                    [ui.available_width(), 28.0],
+                                                    egui::Button::new(
+                                                        egui::RichText::new("✏  Rename").size(13.0),
+                                                    )
+                                                    .frame(false),
+                                                )
+                                            })
+                                            .inner
+                                            .on_disabled_hover_text(self.read_only_hint()),
+                                        )
+                                        .clicked()
+                                    {
+                                        self.open_rename_dialog();
+                                        close_menu = true;
+                                    }
+                                    if self
+                                        .context_menu_entry(
+                                            ui.add_enabled_ui(writable, |ui| {
+                                                ui.add_sized(
+                                                    [ui.available_width(), 28.0],
+                                                    egui::Button::new(
+                                                        egui::RichText::new("🕒  Touch…").size(13.0),
+                                                    )
+                                                    .frame(false),
+                                                )
+                                            })
+                                            .inner
+                                            .on_hover_text("Set the modification time to now or a chosen date")
+                                            .on_disabled_hover_text(self.read_only_hint()),
+                                        )
+                                        .clicked()
+                                    {
+                                        self.run_action(ctx, Action::Touch);
+                                        close_menu = true;
+                                    }
+                                    if self
+                                        .context_menu_entry(
+                                            ui.add_enabled_ui(writable, |ui| {
+                                                ui.add_sized(
+                                                    [ui.available_width(), 28.0],
+                                                    egui::Button::new(
+                                                        egui::RichText::new("🔐  Permissions…").size(13.0),
+                                                    )
+                                                    .frame(false),
+                                                )
+                                            })
+                                            .inner
+                                            .on_hover_text("Set the permission mode, optionally for folder contents too")
+                                            .on_disabled_hover_text(self.read_only_hint()),
                                         )
                                         .clicked()
                                     {
-                                        self.rename_new_name = item.name.clone();
-                                        self.show_rename_dialog = true;
+                                        self.run_action(ctx, Action::Permissions);
                                         close_menu = true;
                                     }
 
                                     ui.separator();
 
                                     // Copy
-                                    if ui
-                                        .add_sized(
-                                            [ui.available_width(), 28.0],
-                                            egui::Button::new(
-                                                egui::RichText::new("📋  Copy").size(13.0),
-                                            )
-                                            .frame(false),
+                                    if self
+                                        .context_menu_entry(
+                                            ui.add_sized(
+                                                [ui.available_width(), 28.0],
+                                                egui::Button::new(
+                                                    egui::RichText::new("📋  Copy").size(13.0),
+                                                )
+                                                .frame(false),
+                                            ),
                                         )
                                         .clicked()
                                     {
@@ -1306,13 +6470,15 @@ impl eframe::App for FileManagerApp {
                                     }
 
                                     // Cut
-                                    if ui
-                                        .add_sized(
-                                            [ui.available_width(), 28.0],
-                                            egui::Button::new(
-                                                egui::RichText::new("✂  Cut").size(13.0),
-                                            )
-                                            .frame(false),
+                                    if self
+                                        .context_menu_entry(
+                                            ui.add_sized(
+                                                [ui.available_width(), 28.0],
+                                                egui::Button::new(
+                                                    egui::RichText::new("✂  Cut").size(13.0),
+                                                )
+                                                .frame(false),
+                                            ),
                                         )
                                         .clicked()
                                     {
@@ -1320,59 +6486,223 @@ impl eframe::App for FileManagerApp {
                                         close_menu = true;
                                     }
 
-                                    ui.separator();
+                                    if self
+                                        .context_menu_entry(
+                                            ui.add_sized(
+                                                [ui.available_width(), 28.0],
+                                                egui::Button::new(
+                                                    egui::RichText::new("📋  Copy to…").size(13.0),
+                                                )
+                                                .frame(false),
+                                            ),
+                                        )
+                                        .clicked()
+                                    {
+                                        self.open_transfer_dialog(TransferMode::Copy);
+                                        close_menu = true;
+                                    }
 
-                                    // Compress (for files and folders, not "..")
-                                    if ui
-                                        .add_sized(
-                                            [ui.available_width(), 28.0],
-                                            egui::Button::new(
-                                                egui::RichText::new("🗃️  Compress to ZIP")
-                                                    .size(13.0),
-                                            )
-                                            .frame(false),
+                                    if self
+                                        .context_menu_entry(
+                                            ui.add_enabled_ui(writable, |ui| {
+                                                ui.add_sized(
+                                                    [ui.available_width(), 28.0],
+                                                    egui::Button::new(
+                                                        egui::RichText::new("⧉  Duplicate").size(13.0),
+                                                    )
+                                                    .frame(false),
+                                                )
+                                            })
+                                            .inner
+                                            .on_hover_text("Copy into this folder as \"name (copy)\"")
+                                            .on_disabled_hover_text(self.read_only_hint()),
+                                        )
+                                        .clicked()
+                                    {
+                                        self.run_action(ctx, Action::Duplicate);
+                                        close_menu = true;
+                                    }
+                                    if self
+                                        .context_menu_entry(
+                                            ui.add_enabled_ui(writable, |ui| {
+                                                ui.add_sized(
+                                                    [ui.available_width(), 28.0],
+                                                    egui::Button::new(
+                                                        egui::RichText::new("📁  New Folder with Selection…")
+                                                            .size(13.0),
+                                                    )
+                                                    .frame(false),
+                                                )
+                                            })
+                                            .inner
+                                            .on_hover_text("Create a folder here and move the selection into it")
+                                            .on_disabled_hover_text(self.read_only_hint()),
                                         )
                                         .clicked()
                                     {
-                                        let _ = self.compress_item();
+                                        self.run_action(ctx, Action::NewFolderWithSelection);
                                         close_menu = true;
                                     }
 
-                                    // Extract (only for .zip files)
-                                    if !item.is_dir && item.name.ends_with(".zip") {
-                                        if ui
-                                            .add_sized(
-                                                [ui.available_width(), 28.0],
-                                                egui::Button::new(
-                                                    egui::RichText::new("📦  Extract ZIP")
-                                                        .size(13.0),
+                                    if self
+                                        .context_menu_entry(
+                                            ui.add_enabled_ui(writable, |ui| {
+                                                ui.add_sized(
+                                                    [ui.available_width(), 28.0],
+                                                    egui::Button::new(
+                                                        egui::RichText::new("✂  Move to…").size(13.0),
+                                                    )
+                                                    .frame(false),
                                                 )
-                                                .frame(false),
+                                            })
+                                            .inner
+                                            .on_disabled_hover_text(self.read_only_hint()),
+                                        )
+                                        .clicked()
+                                    {
+                                        self.open_transfer_dialog(TransferMode::Move);
+                                        close_menu = true;
+                                    }
+
+                                    // Clipboard into this folder rather than the other pane
+                                    if item.is_dir {
+                                        let can_paste = self.clipboard.is_some() && writable;
+                                        let hint = if writable {
+                                            "Clipboard is empty"
+                                        } else {
+                                            self.read_only_hint()
+                                        };
+                                        if self
+                                            .context_menu_entry(
+                                                ui.add_enabled_ui(can_paste, |ui| {
+                                                    ui.add_sized(
+                                                        [ui.available_width(), 28.0],
+                                                        egui::Button::new(
+                                                            egui::RichText::new("📎  Paste into Folder")
+                                                                .size(13.0),
+                                                        )
+                                                        .frame(false),
+                                                    )
+                                                })
+                                                .inner
+                                                .on_disabled_hover_text(hint),
                                             )
                                             .clicked()
                                         {
-                                            let _ = self.extract_archive();
+                                            self.run_action(ctx, Action::PasteIntoFolder);
                                             close_menu = true;
                                         }
                                     }
 
+                                    // Folder tree without the files (folders only)
+                                    if item.is_dir
+                                        && self
+                                            .context_menu_entry(
+                                                ui.add_sized(
+                                                    [ui.available_width(), 28.0],
+                                                    egui::Button::new(
+                                                        egui::RichText::new("🗂  Copy Folder Structure")
+                                                            .size(13.0),
+                                                    )
+                                                    .frame(false),
+                                                )
+                                                .on_hover_text(
+                                                    "Recreate the subfolders in the other pane, without files",
+                                                ),
+                                            )
+                                            .clicked()
+                                    {
+                                        self.copy_folder_structure();
+                                        close_menu = true;
+                                    }
+
                                     ui.separator();
 
-                                    // Delete
-                                    if ui
-                                        .add_sized(
-                                            [ui.available_width(), 28.0],
-                                            egui::Button::new(
-                                                egui::RichText::new("🗑  Delete")
-                                                    .size(13.0)
-                                                    .color(egui::Color32::from_rgb(242, 139, 130)),
+                                    // Compress (for files and folders, not "..")
+                                    ui.menu_button(
+                                        egui::RichText::new("🗃️  Compress to ZIP").size(13.0),
+                                        |ui| {
+                                            if ui.button("This item").clicked() {
+                                                let _ = self.compress_item(false);
+                                                close_menu = true;
+                                                ui.close_menu();
+                                            }
+                                            if ui
+                                                .button("Each selected item to its own ZIP")
+                                                .clicked()
+                                            {
+                                                let _ = self.compress_item(true);
+                                                close_menu = true;
+                                                ui.close_menu();
+                                            }
+                                        },
+                                    );
+
+                                    // Extract (zip and tar archives)
+                                    if !item.is_dir
+                                        && crate::filesystem::ArchiveFormat::of(&item.path).is_some()
+                                        && self
+                                            .context_menu_entry(
+                                                ui.add_sized(
+                                                    [ui.available_width(), 28.0],
+                                                    egui::Button::new(
+                                                        egui::RichText::new("📦  Extract…")
+                                                            .size(13.0),
+                                                    )
+                                                    .frame(false),
+                                                ),
                                             )
-                                            .frame(false),
+                                            .clicked()
+                                    {
+                                        let _ = self.extract_archive();
+                                        close_menu = true;
+                                    }
+
+                                    // User-defined external programs
+                                    let commands = self.settings.custom_commands.clone();
+                                    if !commands.is_empty() {
+                                        ui.menu_button(
+                                            egui::RichText::new("🧩  Custom Commands").size(13.0),
+                                            |ui| {
+                                                for (index, command) in commands.iter().enumerate() {
+                                                    if ui
+                                                        .button(&command.name)
+                                                        .on_hover_text(&command.command)
+                                                        .clicked()
+                                                    {
+                                                        self.run_custom_command(index);
+                                                        close_menu = true;
+                                                        ui.close_menu();
+                                                    }
+                                                }
+                                            },
+                                        );
+                                    }
+
+                                    ui.separator();
+
+                                    // Delete
+                                    if self
+                                        .context_menu_entry(
+                                            ui.add_enabled_ui(writable, |ui| {
+                                                ui.add_sized(
+                                                    [ui.available_width(), 28.0],
+                                                    egui::Button::new(
+                                                        egui::RichText::new("🗑  Delete")
+                                                            .size(13.0)
+                                                            .color(egui::Color32::from_rgb(
+                                                                242, 139, 130,
+                                                            )),
+                                                    )
+                                                    .frame(false),
+                                                )
+                                            })
+                                            .inner
+                                            .on_disabled_hover_text(self.read_only_hint()),
                                         )
                                         .clicked()
                                     {
-                                        self.item_to_delete = Some(item.name.clone());
-                                        self.show_delete_confirm = true;
+                                        self.request_delete_selection();
                                         close_menu = true;
                                     }
                                 }
@@ -1380,13 +6710,15 @@ impl eframe::App for FileManagerApp {
                                 ui.separator();
 
                                 // Cancel
-                                if ui
-                                    .add_sized(
-                                        [ui.available_width(), 28.0],
-                                        egui::Button::new(
-                                            egui::RichText::new("✗  Cancel").size(13.0),
-                                        )
-                                        .frame(false),
+                                if self
+                                    .context_menu_entry(
+                                        ui.add_sized(
+                                            [ui.available_width(), 28.0],
+                                            egui::Button::new(
+                                                egui::RichText::new("✗  Cancel").size(13.0),
+                                            )
+                                            .frame(false),
+                                        ),
                                     )
                                     .clicked()
                                 {
@@ -1422,6 +6754,9 @@ impl eframe::App for FileManagerApp {
             if ctx.input(|i| i.key_pressed(egui::Key::Escape)) {
                 self.show_context_menu = false;
             }
+            if !self.show_context_menu {
+                self.context_menu_keyboard = false;
+            }
         }
 
         // Properties Dialog
@@ -1466,7 +6801,17 @@ impl eframe::App for FileManagerApp {
                                     ui.end_row();
 
                                     ui.label(egui::RichText::new("Size:").strong());
-                                    ui.label(crate::filesystem::format_size(item.size));
+                                    let mut size_text = crate::filesystem::format_size_with(
+                                        item.size,
+                                        self.settings.size_units,
+                                    );
+                                    if self.settings.show_byte_counts {
+                                        size_text.push_str(&format!(
+                                            " ({} bytes)",
+                                            crate::filesystem::format_with_separators(item.size)
+                                        ));
+                                    }
+                                    ui.label(size_text);
                                     ui.end_row();
 
                                     ui.label(egui::RichText::new("Modified:").strong());
@@ -1505,6 +6850,34 @@ impl eframe::App for FileManagerApp {
                                     ui.label(egui::RichText::new("Path:").strong());
                                     ui.label(item.path.display().to_string());
                                     ui.end_row();
+
+                                    if let Some(info) = crate::filesystem::link_info(&item.path) {
+                                        ui.label(egui::RichText::new("Inode:").strong());
+                                        ui.label(info.inode.to_string());
+                                        ui.end_row();
+
+                                        if !item.is_dir {
+                                            ui.label(egui::RichText::new("Hard links:").strong());
+                                            let text = match info.links {
+                                                1 => "1".to_string(),
+                                                n => format!(
+                                                    "{} (the data is shared with {} other name(s))",
+                                                    n,
+                                                    n - 1
+                                                ),
+                                            };
+                                            ui.label(text);
+                                            ui.end_row();
+                                        }
+                                    }
+
+                                    if let Some(target) =
+                                        crate::filesystem::resolve_shortcut(&item.path)
+                                    {
+                                        ui.label(egui::RichText::new("Target:").strong());
+                                        ui.label(target.describe());
+                                        ui.end_row();
+                                    }
                                 });
 
                             ui.add_space(15.0);
@@ -1533,7 +6906,10 @@ impl eframe::App for FileManagerApp {
 
         // New Folder Dialog
         if self.show_new_folder_dialog {
-            egui::Window::new("📁 Create New Folder")
+            let moving = self.new_folder_contents.len();
+            let title = if moving > 0 { "📁 New Folder with Selection" } else { "📁 Create New Folder" };
+            egui::Window::new(title)
+                .id(egui::Id::new("new_folder_dialog"))
                 .collapsible(false)
                 .resizable(false)
                 .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
@@ -1554,39 +6930,76 @@ impl eframe::App for FileManagerApp {
                                 .hint_text("Enter folder name..."),
                         );
                         response.request_focus();
+                        if moving > 0 {
+                            ui.add_space(4.0);
+                            ui.label(
+                                egui::RichText::new(format!("{} selected item(s) will be moved into it", moving))
+                                    .size(12.0)
+                                    .color(egui::Color32::from_rgb(154, 160, 166)),
+                            );
+                        }
+
+                        let name_check = crate::filesystem::check_new_name(
+                            &self.get_active_pane().current_path,
+                            &self.new_folder_name,
+                            None,
+                        );
+                        // No complaint about the empty field before anything was typed
+                        if !self.new_folder_name.is_empty() {
+                            Self::show_name_check(ui, &name_check);
+                        }
+                        let name_valid = !name_check.is_invalid();
 
                         ui.add_space(15.0);
                         ui.horizontal(|ui| {
-                            if ui
-                                .add_sized(
-                                    [140.0, 36.0],
-                                    egui::Button::new(egui::RichText::new("✓ Create").size(14.0))
+                            let create_clicked = ui
+                                .add_enabled_ui(name_valid, |ui| {
+                                    ui.add_sized(
+                                        [140.0, 36.0],
+                                        egui::Button::new(
+                                            egui::RichText::new("✓ Create").size(14.0),
+                                        )
                                         .fill(egui::Color32::from_rgb(40, 167, 69))
                                         .rounding(6.0),
-                                )
+                                    )
+                                })
+                                .inner
                                 .clicked()
                                 || (response.lost_focus()
-                                    && ui.input(|i| i.key_pressed(egui::Key::Enter)))
-                            {
-                                if !self.new_folder_name.is_empty() {
-                                    let new_path = self
-                                        .get_active_pane()
-                                        .current_path
-                                        .join(&self.new_folder_name);
-                                    match fs::create_dir(&new_path) {
-                                        Ok(_) => {
-                                            self.status_message =
-                                                format!("Created folder: {}", self.new_folder_name);
-                                            let _ = self.get_active_pane_mut().refresh();
-                                        }
-                                        Err(e) => {
-                                            self.status_message =
-                                                format!("Error creating folder: {}", e);
-                                        }
+                                    && ui.input(|i| i.key_pressed(egui::Key::Enter)));
+                            if create_clicked && name_valid && moving > 0 && self.ensure_read_write_mode() {
+                                let name = self.new_folder_name.clone();
+                                match self.create_folder_with_contents(&name) {
+                                    Ok(new_path) => self.last_transfer_dir = Some(new_path),
+                                    Err(e) => {
+                                        self.notify_error(format!("Error creating folder: {}", e));
                                     }
-                                    self.new_folder_name.clear();
-                                    self.show_new_folder_dialog = false;
                                 }
+                                self.new_folder_contents.clear();
+                                self.new_folder_name.clear();
+                                self.show_new_folder_dialog = false;
+                            } else if create_clicked && name_valid && self.ensure_read_write_mode() {
+                                let dir = self.get_active_pane().current_path.clone();
+                                let result = crate::filesystem::child_path(&dir, &self.new_folder_name)
+                                    .and_then(|new_path| {
+                                        fs::create_dir(&new_path)?;
+                                        Ok(new_path)
+                                    });
+                                let created = result.as_ref().map_or(dir.join(&self.new_folder_name), Clone::clone);
+                                self.op_log.record("New folder", None, Some(&created), &result);
+                                match result {
+                                    Ok(new_path) => {
+                                        self.last_transfer_dir = Some(new_path);
+                                        self.status_message =
+                                            format!("Created folder: {}", self.new_folder_name);
+                                        let _ = self.refresh_active_pane();
+                                    }
+                                    Err(e) => {
+                                        self.notify_error(format!("Error creating folder: {}", e));
+                                    }
+                                }
+                                self.new_folder_name.clear();
+                                self.show_new_folder_dialog = false;
                             }
                             ui.add_space(10.0);
                             if ui
@@ -1599,6 +7012,7 @@ impl eframe::App for FileManagerApp {
                                 .clicked()
                             {
                                 self.new_folder_name.clear();
+                                self.new_folder_contents.clear();
                                 self.show_new_folder_dialog = false;
                             }
                         });
@@ -1623,50 +7037,114 @@ impl eframe::App for FileManagerApp {
                         ui.add_space(5.0);
                         ui.label(egui::RichText::new("New name:").size(14.0));
                         ui.add_space(5.0);
-                        let response = ui.add_sized(
-                            [300.0, 30.0],
-                            egui::TextEdit::singleline(&mut self.rename_new_name),
-                        );
+                        let extension = self.rename_extension.clone();
+                        let response = ui
+                            .horizontal(|ui| {
+                                let suffix = extension
+                                    .as_ref()
+                                    .filter(|_| self.rename_keep_extension)
+                                    .map(|ext| format!(".{}", ext));
+                                let width = if suffix.is_some() { 230.0 } else { 300.0 };
+                                let response = ui.add_sized(
+                                    [width, 30.0],
+                                    egui::TextEdit::singleline(&mut self.rename_new_name),
+                                );
+                                if let Some(suffix) = suffix {
+                                    ui.label(
+                                        egui::RichText::new(suffix)
+                                            .size(14.0)
+                                            .color(egui::Color32::from_rgb(154, 160, 166)),
+                                    );
+                                }
+                                response
+                            })
+                            .inner;
                         response.request_focus();
+                        if let Some(ext) = &extension {
+                            let was_kept = self.rename_keep_extension;
+                            ui.checkbox(&mut self.rename_keep_extension, "Keep extension");
+                            if self.rename_keep_extension != was_kept {
+                                let suffix = format!(".{}", ext);
+                                if self.rename_keep_extension {
+                                    if let Some(stem) = self.rename_new_name.strip_suffix(&suffix) {
+                                        self.rename_new_name = stem.to_string();
+                                    }
+                                } else {
+                                    self.rename_new_name.push_str(&suffix);
+                                }
+                            }
+                        }
+                        let new_name = match &extension {
+                            Some(ext) if self.rename_keep_extension => {
+                                format!("{}.{}", self.rename_new_name, ext)
+                            }
+                            _ => self.rename_new_name.clone(),
+                        };
+
+                        let current_name = self
+                            .get_active_pane()
+                            .get_selected_item()
+                            .map(|item| (item.path.clone(), item.name.clone()));
+                        let name_check = match &current_name {
+                            Some((path, name)) => match crate::filesystem::parent_dir(path) {
+                                Ok(dir) => crate::filesystem::check_new_name(
+                                    dir,
+                                    &new_name,
+                                    Some(name),
+                                ),
+                                Err(e) => crate::filesystem::NameCheck::Invalid(e.to_string()),
+                            },
+                            None => crate::filesystem::NameCheck::Invalid(
+                                "Nothing selected".to_string(),
+                            ),
+                        };
+                        let unchanged = current_name
+                            .as_ref()
+                            .is_some_and(|(_, name)| *name == new_name);
+                        if !unchanged {
+                            Self::show_name_check(ui, &name_check);
+                        }
+                        let name_valid = !name_check.is_invalid();
 
                         ui.add_space(15.0);
                         ui.horizontal(|ui| {
-                            if ui
-                                .add_sized(
-                                    [140.0, 36.0],
-                                    egui::Button::new(egui::RichText::new("✓ Rename").size(14.0))
+                            let rename_clicked = ui
+                                .add_enabled_ui(name_valid, |ui| {
+                                    ui.add_sized(
+                                        [140.0, 36.0],
+                                        egui::Button::new(
+                                            egui::RichText::new("✓ Rename").size(14.0),
+                                        )
                                         .fill(egui::Color32::from_rgb(40, 167, 69))
                                         .rounding(6.0),
-                                )
+                                    )
+                                })
+                                .inner
                                 .clicked()
                                 || (response.lost_focus()
-                                    && ui.input(|i| i.key_pressed(egui::Key::Enter)))
-                            {
-                                if !self.rename_new_name.is_empty() {
-                                    if let Some(item) = self.get_active_pane().get_selected_item() {
-                                        let old_path = item.path.clone();
-                                        let new_path = item
-                                            .path
-                                            .parent()
-                                            .unwrap_or(item.path.as_ref())
-                                            .join(&self.rename_new_name);
-                                        match fs::rename(&old_path, &new_path) {
-                                            Ok(_) => {
-                                                self.status_message = format!(
-                                                    "Renamed: {} → {}",
-                                                    item.name, self.rename_new_name
-                                                );
-                                                let _ = self.get_active_pane_mut().refresh();
-                                            }
-                                            Err(e) => {
-                                                self.status_message =
-                                                    format!("Error renaming: {}", e);
-                                            }
+                                    && ui.input(|i| i.key_pressed(egui::Key::Enter)));
+                            if rename_clicked && name_valid && self.ensure_read_write_mode() {
+                                if let Some(item) = self.get_active_pane().get_selected_item() {
+                                    let old_path = item.path.clone();
+                                    let new_path = old_path.with_file_name(&new_name);
+                                    let result = crate::filesystem::sibling_path(&old_path, &new_name)
+                                        .and_then(|new_path| Ok(fs::rename(&old_path, new_path)?));
+                                    self.op_log.record("Rename", Some(&old_path), Some(&new_path), &result);
+                                    match result {
+                                        Ok(_) => {
+                                            self.status_message = format!(
+                                                "Renamed: {} → {}",
+                                                item.name, new_name
+                                            );
+                                            let _ = self.refresh_active_pane();
+                                        }
+                                        Err(e) => {
+                                            self.notify_error(format!("Error renaming: {}", e));
                                         }
                                     }
-                                    self.rename_new_name.clear();
-                                    self.show_rename_dialog = false;
                                 }
+                                self.rename_new_name.clear();
+                                self.show_rename_dialog = false;
                             }
                             ui.add_space(10.0);
                             if ui
@@ -1737,27 +7215,267 @@ impl eframe::App for FileManagerApp {
                                     .fill(egui::Color32::from_rgb(0, 112, 186))
                                     .rounding(6.0),
                             )
-                            .clicked()
-                        {
-                            if let Err(e) = open::that("https://www.paypal.com/paypalme/AchmadFachrie") {
-                                self.status_message = format!("Failed to open PayPal: {}", e);
-                            } else {
-                                self.status_message = "Opening PayPal donation page...".to_string();
+                            .clicked()
+                        {
+                            if let Err(e) = open::that("https://www.paypal.com/paypalme/AchmadFachrie") {
+                                self.status_message = format!("Failed to open PayPal: {}", e);
+                            } else {
+                                self.status_message = "Opening PayPal donation page...".to_string();
+                            }
+                        }
+                        ui.add_space(20.0);
+                        if ui
+                            .add_sized(
+                                [200.0, 36.0],
+                                egui::Button::new(egui::RichText::new("Close").size(14.0))
+                                    .fill(egui::Color32::from_rgb(66, 133, 244))
+                                    .rounding(6.0),
+                            )
+                            .clicked()
+                        {
+                            self.show_about_dialog = false;
+                        }
+                        ui.add_space(10.0);
+                    });
+                });
+        }
+
+        // Settings Dialog
+        if self.show_settings_dialog {
+            let visuals = ctx.style().visuals.clone();
+
+            egui::Window::new("⚙ Settings")
+                .collapsible(false)
+                .resizable(false)
+                .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
+                .frame(
+                    egui::Frame::window(&ctx.style())
+                        .fill(visuals.panel_fill)
+                        .rounding(12.0)
+                        .inner_margin(20.0),
+                )
+                .show(ctx, |ui| {
+                    ui.vertical(|ui| {
+                        let mut changed = false;
+
+                        ui.add_space(5.0);
+                        ui.label(egui::RichText::new("Size units:").size(14.0).strong());
+                        ui.add_space(6.0);
+                        for units in crate::filesystem::SizeUnits::all() {
+                            changed |= ui
+                                .radio_value(&mut self.settings.size_units, units, units.name())
+                                .changed();
+                        }
+
+                        ui.add_space(10.0);
+                        ui.label(egui::RichText::new("Density:").size(14.0).strong());
+                        ui.add_space(6.0);
+                        ui.horizontal(|ui| {
+                            for density in crate::settings::Density::all() {
+                                changed |= ui
+                                    .radio_value(&mut self.settings.density, density, density.name())
+                                    .changed();
+                            }
+                        });
+
+                        ui.add_space(10.0);
+                        ui.label(egui::RichText::new("Double-click opens:").size(14.0).strong());
+                        ui.add_space(6.0);
+                        {
+                            use crate::settings::OpenAction;
+                            let choices: [(&str, &mut OpenAction, &str); 2] = [
+                                ("Images", &mut self.settings.image_open_action, "Built-in viewer"),
+                                ("Archives", &mut self.settings.archive_open_action, "Extract dialog"),
+                            ];
+                            egui::Grid::new("open_actions").show(ui, |ui| {
+                                for (label, action, built_in) in choices {
+                                    ui.label(label);
+                                    for choice in OpenAction::all() {
+                                        let text = match choice {
+                                            OpenAction::BuiltIn => built_in,
+                                            OpenAction::DefaultApp => "Default app",
+                                        };
+                                        changed |= ui.radio_value(action, choice, text).changed();
+                                    }
+                                    ui.end_row();
+                                }
+                            });
+                        }
+
+                        ui.add_space(10.0);
+                        changed |= ui
+                            .checkbox(
+                                &mut self.settings.show_byte_counts,
+                                "Show exact byte counts in Properties",
+                            )
+                            .changed();
+                        changed |= ui
+                            .checkbox(
+                                &mut self.settings.mark_hard_links,
+                                "Mark hard-linked files (🔗)",
+                            )
+                            .on_hover_text("Files that share their data with another file in the same folder")
+                            .changed();
+                        if ui
+                            .checkbox(
+                                &mut self.settings.group_hidden_files,
+                                "Group hidden files after the others",
+                            )
+                            .on_hover_text("Sort dotfiles after all visible entries instead of among them")
+                            .changed()
+                        {
+                            let group = self.settings.group_hidden_files;
+                            self.left_pane.set_group_hidden(group);
+                            self.right_pane.set_group_hidden(group);
+                            changed = true;
+                        }
+                        let mut type_sorts_changed = ui
+                            .checkbox(
+                                &mut self.settings.sort_by_folder_type,
+                                "Sort folders by their main file type",
+                            )
+                            .on_hover_text(
+                                "When more than half of a folder's files are of one type, switch to \
+                                 the sort chosen for it. A sort picked by hand in a folder wins",
+                            )
+                            .changed();
+                        if self.settings.sort_by_folder_type {
+                            use crate::filesystem::ExtensionGroup;
+                            use crate::pane::SortBy;
+                            egui::Grid::new("type_sorts").show(ui, |ui| {
+                                for &group in ExtensionGroup::all() {
+                                    let position = self
+                                        .settings
+                                        .type_sorts
+                                        .iter()
+                                        .position(|(g, _)| *g == group);
+                                    let mut choice =
+                                        position.map(|index| self.settings.type_sorts[index].1);
+                                    ui.label(group.name());
+                                    egui::ComboBox::from_id_salt(("type_sort", group.name()))
+                                        .selected_text(choice.map_or("Keep current", |sort_by| sort_by.name()))
+                                        .show_ui(ui, |ui| {
+                                            ui.selectable_value(&mut choice, None, "Keep current");
+                                            for sort_by in [SortBy::Name, SortBy::Size, SortBy::Date] {
+                                                ui.selectable_value(&mut choice, Some(sort_by), sort_by.name());
+                                            }
+                                        });
+                                    ui.end_row();
+                                    if choice != position.map(|index| self.settings.type_sorts[index].1) {
+                                        if let Some(index) = position {
+                                            self.settings.type_sorts.remove(index);
+                                        }
+                                        if let Some(sort_by) = choice {
+                                            self.settings.type_sorts.push((group, sort_by));
+                                        }
+                                        type_sorts_changed = true;
+                                    }
+                                }
+                            });
+                        }
+                        if type_sorts_changed {
+                            self.left_pane.set_type_sorts(self.settings.active_type_sorts());
+                            self.right_pane.set_type_sorts(self.settings.active_type_sorts());
+                            changed = true;
+                        }
+                        changed |= ui
+                            .checkbox(&mut self.settings.read_only_mode, "Read-only mode")
+                            .on_hover_text(
+                                "Disable delete, rename, move, paste, create, compress, extract \
+                                 and permission changes, e.g. while browsing a production server",
+                            )
+                            .changed();
+
+                        if !self.settings.hidden_quick_access.is_empty() {
+                            ui.add_space(10.0);
+                            ui.label(
+                                egui::RichText::new("Hidden Quick Access folders:")
+                                    .size(14.0)
+                                    .strong(),
+                            );
+                            ui.add_space(6.0);
+                            let mut restore = None;
+                            for (index, path) in
+                                self.settings.hidden_quick_access.iter().enumerate()
+                            {
+                                ui.horizontal(|ui| {
+                                    if ui.small_button("Show").clicked() {
+                                        restore = Some(index);
+                                    }
+                                    ui.label(path.display().to_string());
+                                });
+                            }
+                            if let Some(index) = restore {
+                                self.settings.hidden_quick_access.remove(index);
+                                changed = true;
                             }
                         }
-                        ui.add_space(20.0);
-                        if ui
-                            .add_sized(
-                                [200.0, 36.0],
-                                egui::Button::new(egui::RichText::new("Close").size(14.0))
-                                    .fill(egui::Color32::from_rgb(66, 133, 244))
-                                    .rounding(6.0),
+
+                        ui.add_space(10.0);
+                        ui.label(egui::RichText::new("Custom commands:").size(14.0).strong());
+                        ui.label(
+                            egui::RichText::new(
+                                "Shown in the context menu. $FILE, $DIR and $SELECTION are replaced by paths.",
                             )
-                            .clicked()
-                        {
-                            self.show_about_dialog = false;
+                            .size(11.0)
+                            .color(egui::Color32::from_rgb(154, 160, 166)),
+                        );
+                        ui.add_space(6.0);
+                        let mut remove = None;
+                        for (index, command) in self.settings.custom_commands.iter_mut().enumerate() {
+                            ui.horizontal(|ui| {
+                                changed |= ui
+                                    .add(
+                                        egui::TextEdit::singleline(&mut command.name)
+                                            .hint_text("Name")
+                                            .desired_width(120.0),
+                                    )
+                                    .changed();
+                                changed |= ui
+                                    .add(
+                                        egui::TextEdit::singleline(&mut command.command)
+                                            .hint_text("optipng $FILE")
+                                            .desired_width(260.0),
+                                    )
+                                    .changed();
+                                if ui.small_button("🗑").on_hover_text("Remove").clicked() {
+                                    remove = Some(index);
+                                }
+                            });
+                        }
+                        if let Some(index) = remove {
+                            self.settings.custom_commands.remove(index);
+                            changed = true;
                         }
+                        if ui.button("➕ Add command").clicked() {
+                            self.settings
+                                .custom_commands
+                                .push(crate::settings::CustomCommand::default());
+                            changed = true;
+                        }
+
+                        if changed {
+                            self.save_settings();
+                        }
+
                         ui.add_space(10.0);
+                        ui.separator();
+                        ui.add_space(10.0);
+
+                        ui.horizontal(|ui| {
+                            ui.add_space(ui.available_width() / 2.0 - 100.0);
+                            if ui
+                                .add_sized(
+                                    [200.0, 36.0],
+                                    egui::Button::new(egui::RichText::new("Close").size(14.0))
+                                        .fill(visuals.widgets.inactive.bg_fill)
+                                        .rounding(6.0),
+                                )
+                                .clicked()
+                            {
+                                self.show_settings_dialog = false;
+                            }
+                        });
                     });
                 });
         }
@@ -1803,12 +7521,31 @@ impl eframe::App for FileManagerApp {
 
                             if button.clicked() {
                                 self.current_theme = theme;
+                                self.settings.theme = theme;
+                                self.save_settings();
                                 self.status_message = format!("Theme changed to: {}", theme.name());
                             }
 
                             ui.add_space(6.0);
                         }
 
+                        if ui
+                            .add_sized(
+                                [280.0, 30.0],
+                                egui::Button::new(
+                                    egui::RichText::new("✏ Edit Custom Theme...").size(13.0),
+                                )
+                                .fill(visuals.widgets.inactive.bg_fill)
+                                .rounding(6.0),
+                            )
+                            .clicked()
+                        {
+                            self.theme_editor_backup =
+                                Some((self.current_theme, self.settings.custom_theme.clone()));
+                            self.current_theme = Theme::Custom;
+                            self.show_theme_selector = false;
+                        }
+
                         ui.add_space(10.0);
                         ui.separator();
                         ui.add_space(10.0);
@@ -1852,18 +7589,64 @@ impl eframe::App for FileManagerApp {
                             .spacing([15.0, 10.0])
                             .show(ui, |ui| {
                                 ui.label(egui::RichText::new("Search in:").strong().size(13.0));
-                                ui.horizontal(|ui| {
-                                    ui.label(
-                                        egui::RichText::new(
-                                            self.search_criteria.search_path.display().to_string(),
-                                        )
-                                        .size(12.0)
-                                        .color(visuals.widgets.active.bg_fill),
-                                    );
-                                    if ui.small_button("📁").clicked() {
-                                        self.search_criteria.search_path =
-                                            self.get_active_pane().current_path.clone();
+                                ui.vertical(|ui| {
+                                    let mut remove_root = None;
+                                    for (index, root) in
+                                        self.search_criteria.search_paths.iter().enumerate()
+                                    {
+                                        ui.horizontal(|ui| {
+                                            ui.label(
+                                                egui::RichText::new(root.display().to_string())
+                                                    .size(12.0)
+                                                    .color(visuals.widgets.active.bg_fill),
+                                            );
+                                            if ui
+                                                .small_button("✗")
+                                                .on_hover_text("Remove this folder")
+                                                .clicked()
+                                            {
+                                                remove_root = Some(index);
+                                            }
+                                        });
                                     }
+                                    if let Some(index) = remove_root {
+                                        self.search_criteria.search_paths.remove(index);
+                                    }
+
+                                    ui.horizontal(|ui| {
+                                        if ui
+                                            .small_button("➕ Left pane")
+                                            .clicked()
+                                        {
+                                            self.search_criteria
+                                                .add_search_path(self.left_pane.current_path.clone());
+                                        }
+                                        if ui
+                                            .small_button("➕ Right pane")
+                                            .clicked()
+                                        {
+                                            self.search_criteria
+                                                .add_search_path(self.right_pane.current_path.clone());
+                                        }
+                                        let bookmarks = self.bookmark_manager.get_bookmarks().to_vec();
+                                        ui.add_enabled_ui(!bookmarks.is_empty(), |ui| {
+                                            ui.menu_button("➕ Bookmark", |ui| {
+                                                for bookmark in &bookmarks {
+                                                    if ui
+                                                        .button(format!(
+                                                            "{} {}",
+                                                            bookmark.icon, bookmark.name
+                                                        ))
+                                                        .clicked()
+                                                    {
+                                                        self.search_criteria
+                                                            .add_search_path(bookmark.path.clone());
+                                                        ui.close_menu();
+                                                    }
+                                                }
+                                            });
+                                        });
+                                    });
                                 });
                                 ui.end_row();
 
@@ -1907,6 +7690,36 @@ impl eframe::App for FileManagerApp {
                                 });
                                 ui.end_row();
 
+                                ui.label(egui::RichText::new("Extensions:").size(13.0));
+                                ui.vertical(|ui| {
+                                    ui.horizontal_wrapped(|ui| {
+                                        for group in crate::filesystem::ExtensionGroup::all() {
+                                            let groups = &mut self.search_criteria.extension_groups;
+                                            let mut checked = groups.contains(group);
+                                            if ui
+                                                .checkbox(&mut checked, group.name())
+                                                .on_hover_text(group.extensions().join(", "))
+                                                .changed()
+                                            {
+                                                if checked {
+                                                    groups.push(*group);
+                                                } else {
+                                                    groups.retain(|other| other != group);
+                                                }
+                                            }
+                                        }
+                                    });
+                                    ui.add_sized(
+                                        [300.0, 24.0],
+                                        egui::TextEdit::singleline(&mut self.search_extensions_text)
+                                            .hint_text("Other extensions, e.g. rs, toml"),
+                                    )
+                                    .on_hover_text(
+                                        "Only files with a checked or listed extension match; leave all empty for any",
+                                    );
+                                });
+                                ui.end_row();
+
                                 ui.label(egui::RichText::new("Size (KB):").size(13.0));
                                 ui.horizontal(|ui| {
                                     ui.label("Min:");
@@ -1947,22 +7760,45 @@ impl eframe::App for FileManagerApp {
                                         &mut self.search_criteria.include_hidden,
                                         "Include hidden files",
                                     );
+                                    ui.checkbox(
+                                        &mut self.search_criteria.respect_gitignore,
+                                        "Skip gitignored files",
+                                    )
+                                    .on_hover_text(
+                                        "Inside git repositories, leave out paths such as target/ or node_modules/ that git ignores",
+                                    );
                                 });
                                 ui.end_row();
                             });
 
+                        ui.add_space(10.0);
+                        self.render_search_index_row(ui);
+
                         ui.add_space(15.0);
                         ui.separator();
                         ui.add_space(10.0);
 
-                        if self.search_in_progress {
+                        if let Some(operation) = &self.operation_in_progress {
                             ui.horizontal(|ui| {
                                 ui.spinner();
-                                ui.label(egui::RichText::new("Searching...").size(13.0));
+                                ui.label(egui::RichText::new(format!("{}...", operation)).size(13.0));
                             });
                             ui.add_space(10.0);
                         }
 
+                        if self.search_skipped_dirs > 0 {
+                            ui.label(
+                                egui::RichText::new(format!(
+                                    "🔒 {} folders could not be read and were skipped",
+                                    self.search_skipped_dirs
+                                ))
+                                .size(12.0)
+                                .color(egui::Color32::from_rgb(220, 170, 90)),
+                            )
+                            .on_hover_text("Results may be incomplete (permission denied)");
+                            ui.add_space(5.0);
+                        }
+
                         if !self.search_results.is_empty() {
                             ui.label(
                                 egui::RichText::new(format!(
@@ -1980,15 +7816,18 @@ impl eframe::App for FileManagerApp {
                             egui::ScrollArea::vertical()
                                 .max_height(200.0)
                                 .show(ui, |ui| {
-                                    for item in &results_clone {
+                                    for hit in &results_clone {
+                                        let item = &hit.item;
                                         let icon = if item.is_dir { "📁" } else { "📄" };
                                         let text = format!("{} {}", icon, item.path.display());
-                                        if ui
-                                            .selectable_label(
-                                                false,
-                                                egui::RichText::new(text).size(11.0),
-                                            )
-                                            .clicked()
+                                        let text = highlight_match(
+                                            &text,
+                                            &item.name,
+                                            hit.name_match.clone(),
+                                            ui.visuals().text_color(),
+                                            visuals.widgets.active.bg_fill,
+                                        );
+                                        if ui.selectable_label(false, text).clicked()
                                         {
                                             if let Some(parent) = item.path.parent() {
                                                 navigate_to = Some(parent.to_path_buf());
@@ -2000,19 +7839,64 @@ impl eframe::App for FileManagerApp {
                             if let Some(path) = navigate_to {
                                 let _ = self.get_active_pane_mut().navigate_to(path);
                                 self.show_search_dialog = false;
+                                self.stop_search();
                             }
 
                             ui.add_space(10.0);
                         }
 
+                        if let Some(run) = &self.search_task {
+                            let progress = &run.task.progress;
+                            ui.horizontal(|ui| {
+                                ui.spinner();
+                                let folder = if progress.current_path.as_os_str().is_empty() {
+                                    String::new()
+                                } else {
+                                    format!(" in {}", progress.current_path.display())
+                                };
+                                ui.add(
+                                    egui::Label::new(
+                                        egui::RichText::new(format!("Searching{}…", folder))
+                                            .size(12.0)
+                                            .color(egui::Color32::from_rgb(154, 160, 166)),
+                                    )
+                                    .truncate(),
+                                );
+                            });
+                            ui.add_space(5.0);
+                        }
+
                         ui.horizontal(|ui| {
-                            if ui
-                                .add_sized(
-                                    [140.0, 36.0],
-                                    egui::Button::new(egui::RichText::new("🔍 Search").size(14.0))
+                            let has_roots = !self.search_criteria.search_paths.is_empty();
+                            if let Some(run) = &self.search_task {
+                                let cancelling = run.task.is_cancelled();
+                                let label = if cancelling { "Cancelling…" } else { "✗ Cancel" };
+                                if ui
+                                    .add_enabled(
+                                        !cancelling,
+                                        egui::Button::new(egui::RichText::new(label).size(14.0))
+                                            .fill(egui::Color32::from_rgb(66, 70, 77))
+                                            .rounding(6.0)
+                                            .min_size(egui::vec2(140.0, 36.0)),
+                                    )
+                                    .on_hover_text("Stop searching and keep the results found so far")
+                                    .clicked()
+                                {
+                                    run.task.cancel();
+                                }
+                            } else if ui
+                                .add_enabled_ui(has_roots, |ui| {
+                                    ui.add_sized(
+                                        [140.0, 36.0],
+                                        egui::Button::new(
+                                            egui::RichText::new("🔍 Search").size(14.0),
+                                        )
                                         .fill(egui::Color32::from_rgb(66, 133, 244))
                                         .rounding(6.0),
-                                )
+                                    )
+                                })
+                                .inner
+                                .on_disabled_hover_text("Add at least one folder to search in")
                                 .clicked()
                             {
                                 if !self.search_min_size_text.is_empty() {
@@ -2025,6 +7909,8 @@ impl eframe::App for FileManagerApp {
                                         self.search_criteria.max_size = Some(kb * 1024);
                                     }
                                 }
+                                self.search_criteria.custom_extensions =
+                                    crate::filesystem::parse_extension_list(&self.search_extensions_text);
                                 if !self.search_days_ago.is_empty() {
                                     if let Ok(days) = self.search_days_ago.parse::<u64>() {
                                         let duration =
@@ -2034,20 +7920,7 @@ impl eframe::App for FileManagerApp {
                                     }
                                 }
 
-                                self.search_in_progress = true;
-                                match crate::filesystem::search_files(&self.search_criteria) {
-                                    Ok(results) => {
-                                        self.search_results = results;
-                                        self.status_message = format!(
-                                            "Search complete: {} results",
-                                            self.search_results.len()
-                                        );
-                                    }
-                                    Err(e) => {
-                                        self.status_message = format!("Search error: {}", e);
-                                    }
-                                }
-                                self.search_in_progress = false;
+                                self.run_search();
                             }
 
                             ui.add_space(10.0);
@@ -2062,7 +7935,9 @@ impl eframe::App for FileManagerApp {
                                 .clicked()
                             {
                                 self.show_search_dialog = false;
+                                self.stop_search();
                                 self.search_results.clear();
+                                self.search_skipped_dirs = 0;
                             }
                         });
                     });
@@ -2096,6 +7971,13 @@ impl eframe::App for FileManagerApp {
                                         .size(11.0)
                                         .color(egui::Color32::from_rgb(138, 180, 248)),
                                 );
+                                if let Some(note) = &comparison.left_encoding_note {
+                                    ui.label(
+                                        egui::RichText::new(format!("({})", note))
+                                            .size(11.0)
+                                            .color(egui::Color32::from_rgb(220, 170, 90)),
+                                    );
+                                }
                             });
 
                             ui.horizontal(|ui| {
@@ -2107,15 +7989,43 @@ impl eframe::App for FileManagerApp {
                                     .size(11.0)
                                     .color(egui::Color32::from_rgb(138, 180, 248)),
                                 );
+                                if let Some(note) = &comparison.right_encoding_note {
+                                    ui.label(
+                                        egui::RichText::new(format!("({})", note))
+                                            .size(11.0)
+                                            .color(egui::Color32::from_rgb(220, 170, 90)),
+                                    );
+                                }
+                            });
+
+                            ui.add_space(6.0);
+                            ui.horizontal(|ui| {
+                                let mut options = self.diff_options;
+                                ui.checkbox(&mut options.ignore_whitespace, "Ignore whitespace")
+                                    .on_hover_text("Trim lines and treat runs of spaces and tabs as one space");
+                                ui.checkbox(&mut options.ignore_case, "Ignore case");
+                                if options != self.diff_options {
+                                    self.diff_options = options;
+                                    if let Some(result) = &mut self.comparison_result {
+                                        result.set_options(options);
+                                    }
+                                }
                             });
 
                             ui.add_space(10.0);
 
                             if comparison.are_identical {
+                                let options = comparison.options;
+                                let ignored = match (options.ignore_whitespace, options.ignore_case) {
+                                    (true, true) => " (ignoring whitespace and case)",
+                                    (true, false) => " (ignoring whitespace)",
+                                    (false, true) => " (ignoring case)",
+                                    (false, false) => "",
+                                };
                                 ui.horizontal(|ui| {
                                     ui.label(egui::RichText::new("✅").size(24.0));
                                     ui.label(
-                                        egui::RichText::new("Files are identical")
+                                        egui::RichText::new(format!("Files are identical{}", ignored))
                                             .size(14.0)
                                             .color(egui::Color32::from_rgb(129, 201, 149)),
                                     );
@@ -2163,6 +8073,9 @@ impl eframe::App for FileManagerApp {
 
                                 ui.add_space(10.0);
 
+                                // Each side gets half the row and long lines wrap
+                                // inside it instead of pushing the other side out
+                                let side_width = ((ui.available_width() - 180.0) / 2.0).max(100.0);
                                 egui::ScrollArea::vertical()
                                     .max_height(400.0)
                                     .show(ui, |ui| {
@@ -2206,13 +8119,19 @@ impl eframe::App for FileManagerApp {
                                                                 )),
                                                         );
 
-                                                        ui.label(
-                                                            egui::RichText::new(
-                                                                &diff_line.left_content,
-                                                            )
-                                                            .size(10.0)
-                                                            .family(egui::FontFamily::Monospace),
-                                                        );
+                                                        ui.vertical(|ui| {
+                                                            ui.set_width(side_width);
+                                                            ui.add(
+                                                                egui::Label::new(
+                                                                    egui::RichText::new(
+                                                                        &diff_line.left_content,
+                                                                    )
+                                                                    .size(10.0)
+                                                                    .family(egui::FontFamily::Monospace),
+                                                                )
+                                                                .wrap(),
+                                                            );
+                                                        });
 
                                                         ui.add_space(20.0);
                                                         ui.label(
@@ -2237,13 +8156,19 @@ impl eframe::App for FileManagerApp {
                                                                 )),
                                                         );
 
-                                                        ui.label(
-                                                            egui::RichText::new(
-                                                                &diff_line.right_content,
-                                                            )
-                                                            .size(10.0)
-                                                            .family(egui::FontFamily::Monospace),
-                                                        );
+                                                        ui.vertical(|ui| {
+                                                            ui.set_width(side_width);
+                                                            ui.add(
+                                                                egui::Label::new(
+                                                                    egui::RichText::new(
+                                                                        &diff_line.right_content,
+                                                                    )
+                                                                    .size(10.0)
+                                                                    .family(egui::FontFamily::Monospace),
+                                                                )
+                                                                .wrap(),
+                                                            );
+                                                        });
                                                     });
                                                 });
                                         }
@@ -2283,6 +8208,7 @@ impl eframe::App for FileManagerApp {
         // Mount Points Dialog
         if self.show_mounts_dialog {
             let mut close_dialog = false;
+            let mut mount_request = None;
 
             egui::Window::new("💾 Mount Points")
                 .collapsible(false)
@@ -2305,27 +8231,65 @@ impl eframe::App for FileManagerApp {
                                 .color(egui::Color32::from_rgb(189, 193, 198)),
                         );
 
+                        ui.add_space(10.0);
+                        ui.horizontal(|ui| {
+                            ui.label("🔍");
+                            ui.add(
+                                egui::TextEdit::singleline(&mut self.mount_filter)
+                                    .hint_text("Filter by mount point or device")
+                                    .desired_width(300.0),
+                            );
+                            if ui.button("🔄 Refresh").clicked() {
+                                self.rescan_devices();
+                            }
+                        });
                         ui.add_space(10.0);
                         ui.separator();
                         ui.add_space(10.0);
 
-                        let mounts = crate::filesystem::get_mount_points();
+                        let all_mounts = self.mount_points();
+                        let (all_unmounted, scan_error) = match self.unmounted_devices() {
+                            Ok(devices) => (devices, None),
+                            Err(e) => (Vec::new(), Some(e)),
+                        };
+                        let scanning = self.device_scan.is_some();
+                        let mounting =
+                            self.mount_task.as_ref().map(|(device, _)| device.device.clone());
+                        let filter = self.mount_filter.to_lowercase();
+                        let mounts: Vec<_> = all_mounts
+                            .iter()
+                            .filter(|mount| {
+                                filter.is_empty()
+                                    || mount
+                                        .mount_point
+                                        .to_string_lossy()
+                                        .to_lowercase()
+                                        .contains(&filter)
+                                    || mount.device_name.to_lowercase().contains(&filter)
+                            })
+                            .collect();
+                        let unmounted: Vec<_> = all_unmounted
+                            .iter()
+                            .filter(|device| {
+                                filter.is_empty()
+                                    || device
+                                        .device
+                                        .to_string_lossy()
+                                        .to_lowercase()
+                                        .contains(&filter)
+                                    || device.label.to_lowercase().contains(&filter)
+                            })
+                            .collect();
 
                         egui::ScrollArea::vertical()
                             .max_height(350.0)
                             .show(ui, |ui| {
-                                for mount in &mounts {
+                                for mount in mounts.iter().copied() {
                                     let icon = if mount.is_removable { "🔌" } else { "💾" };
                                     let usage = mount.usage_percentage();
                                     let used = mount.total_space - mount.available_space;
 
-                                    let usage_color = if usage > 90.0 {
-                                        egui::Color32::from_rgb(242, 139, 130)
-                                    } else if usage > 70.0 {
-                                        egui::Color32::from_rgb(255, 193, 7)
-                                    } else {
-                                        egui::Color32::from_rgb(129, 201, 149)
-                                    };
+                                    let usage_color = usage_color(usage);
 
                                     egui::Frame::default()
                                         .fill(egui::Color32::from_rgb(50, 52, 55))
@@ -2409,11 +8373,13 @@ impl eframe::App for FileManagerApp {
                                                         ui.label(
                                                             egui::RichText::new(format!(
                                                                 "{} / {}",
-                                                                crate::filesystem::format_size(
-                                                                    used
+                                                                crate::filesystem::format_size_with(
+                                                                    used,
+                                                                    self.settings.size_units,
                                                                 ),
-                                                                crate::filesystem::format_size(
-                                                                    mount.total_space
+                                                                crate::filesystem::format_size_with(
+                                                                    mount.total_space,
+                                                                    self.settings.size_units,
                                                                 )
                                                             ))
                                                             .size(11.0),
@@ -2426,8 +8392,9 @@ impl eframe::App for FileManagerApp {
                                                         ui.label(
                                                             egui::RichText::new(format!(
                                                                 "Available: {}",
-                                                                crate::filesystem::format_size(
-                                                                    mount.available_space
+                                                                crate::filesystem::format_size_with(
+                                                                    mount.available_space,
+                                                                    self.settings.size_units,
                                                                 )
                                                             ))
                                                             .size(11.0)
@@ -2449,36 +8416,142 @@ impl eframe::App for FileManagerApp {
                                                         ui.add(progress_bar);
 
                                                         ui.label(
-                                                            egui::RichText::new(format!(
-                                                                "{:.1}%",
-                                                                usage
-                                                            ))
-                                                            .size(11.0)
-                                                            .strong()
-                                                            .color(usage_color),
+                                                            egui::RichText::new(format!(
+                                                                "{:.1}%",
+                                                                usage
+                                                            ))
+                                                            .size(11.0)
+                                                            .strong()
+                                                            .color(usage_color),
+                                                        );
+                                                    });
+
+                                                    ui.add_space(6.0);
+
+                                                    if ui
+                                                        .button(
+                                                            egui::RichText::new("📂 Open")
+                                                                .size(11.0),
+                                                        )
+                                                        .clicked()
+                                                    {
+                                                        let active_pane =
+                                                            self.get_active_pane_mut();
+                                                        if let Ok(()) = active_pane
+                                                            .navigate_to(mount.mount_point.clone())
+                                                        {
+                                                            self.status_message = format!(
+                                                                "Navigated to {}",
+                                                                mount.mount_point.display()
+                                                            );
+                                                            close_dialog = true;
+                                                        }
+                                                    }
+                                                });
+                                            });
+                                        });
+
+                                    ui.add_space(8.0);
+                                }
+
+                                if !unmounted.is_empty() {
+                                    ui.add_space(4.0);
+                                    ui.label(
+                                        egui::RichText::new("NOT MOUNTED")
+                                            .size(12.0)
+                                            .strong()
+                                            .color(egui::Color32::from_rgb(154, 160, 166)),
+                                    );
+                                    ui.add_space(6.0);
+                                }
+                                for device in unmounted.iter().copied() {
+                                    let icon = if device.is_removable { "🔌" } else { "💾" };
+                                    let dim = egui::Color32::from_rgb(154, 160, 166);
+                                    egui::Frame::default()
+                                        .fill(egui::Color32::from_rgb(50, 52, 55))
+                                        .stroke(egui::Stroke::new(
+                                            1.0,
+                                            egui::Color32::from_rgb(80, 84, 90),
+                                        ))
+                                        .rounding(8.0)
+                                        .inner_margin(12.0)
+                                        .show(ui, |ui| {
+                                            ui.horizontal(|ui| {
+                                                ui.label(
+                                                    egui::RichText::new(icon).size(24.0).color(dim),
+                                                );
+                                                ui.add_space(8.0);
+
+                                                ui.vertical(|ui| {
+                                                    ui.horizontal(|ui| {
+                                                        ui.label(
+                                                            egui::RichText::new(device.name())
+                                                                .size(14.0)
+                                                                .strong()
+                                                                .color(dim),
+                                                        );
+                                                        ui.label(
+                                                            egui::RichText::new("Not mounted")
+                                                                .size(10.0)
+                                                                .color(egui::Color32::from_rgb(
+                                                                    220, 170, 90,
+                                                                )),
                                                         );
+                                                        if device.is_removable {
+                                                            ui.label(
+                                                                egui::RichText::new("Removable")
+                                                                    .size(10.0)
+                                                                    .color(
+                                                                        egui::Color32::from_rgb(
+                                                                            189, 147, 249,
+                                                                        ),
+                                                                    ),
+                                                            );
+                                                        }
                                                     });
 
+                                                    ui.add_space(4.0);
+
+                                                    ui.label(
+                                                        egui::RichText::new(format!(
+                                                            "Device: {} • Type: {} • Size: {}",
+                                                            device.device.display(),
+                                                            device.file_system,
+                                                            crate::filesystem::format_size_with(
+                                                                device.size,
+                                                                self.settings.size_units,
+                                                            )
+                                                        ))
+                                                        .size(11.0)
+                                                        .color(egui::Color32::from_rgb(
+                                                            189, 193, 198,
+                                                        )),
+                                                    );
+
                                                     ui.add_space(6.0);
 
-                                                    if ui
-                                                        .button(
-                                                            egui::RichText::new("📂 Open")
-                                                                .size(11.0),
+                                                    if mounting.as_ref() == Some(&device.device) {
+                                                        ui.horizontal(|ui| {
+                                                            ui.spinner();
+                                                            ui.label(
+                                                                egui::RichText::new("Mounting…")
+                                                                    .size(11.0),
+                                                            );
+                                                        });
+                                                    } else if ui
+                                                        .add_enabled(
+                                                            mounting.is_none(),
+                                                            egui::Button::new(
+                                                                egui::RichText::new("⏏ Mount")
+                                                                    .size(11.0),
+                                                            ),
+                                                        )
+                                                        .on_hover_text(
+                                                            "Mount with udisks and open it",
                                                         )
                                                         .clicked()
                                                     {
-                                                        let active_pane =
-                                                            self.get_active_pane_mut();
-                                                        if let Ok(()) = active_pane
-                                                            .navigate_to(mount.mount_point.clone())
-                                                        {
-                                                            self.status_message = format!(
-                                                                "Navigated to {}",
-                                                                mount.mount_point.display()
-                                                            );
-                                                            close_dialog = true;
-                                                        }
+                                                        mount_request = Some(device.clone());
                                                     }
                                                 });
                                             });
@@ -2487,11 +8560,33 @@ impl eframe::App for FileManagerApp {
                                     ui.add_space(8.0);
                                 }
 
-                                if mounts.is_empty() {
+                                if scanning {
+                                    ui.horizontal(|ui| {
+                                        ui.spinner();
+                                        ui.label(
+                                            egui::RichText::new("Looking for unmounted devices…")
+                                                .size(12.0)
+                                                .color(egui::Color32::from_rgb(154, 160, 166)),
+                                        );
+                                    });
+                                } else if let Some(error) = &scan_error {
+                                    ui.label(
+                                        egui::RichText::new(format!("⚠ {}", error))
+                                            .size(12.0)
+                                            .color(egui::Color32::from_rgb(242, 139, 130)),
+                                    );
+                                }
+
+                                if mounts.is_empty() && unmounted.is_empty() && !scanning {
+                                    let message = if all_mounts.is_empty() && all_unmounted.is_empty() {
+                                        "No mount points found"
+                                    } else {
+                                        "No mount points match the filter"
+                                    };
                                     ui.vertical_centered(|ui| {
                                         ui.add_space(40.0);
                                         ui.label(
-                                            egui::RichText::new("No mount points found")
+                                            egui::RichText::new(message)
                                                 .size(14.0)
                                                 .color(egui::Color32::from_rgb(154, 160, 166)),
                                         );
@@ -2526,6 +8621,9 @@ impl eframe::App for FileManagerApp {
                     });
                 });
 
+            if let Some(device) = mount_request {
+                self.mount_device(device);
+            }
             if close_dialog {
                 self.show_mounts_dialog = false;
             }
@@ -2568,7 +8666,7 @@ impl eframe::App for FileManagerApp {
 
                         ui.add_space(15.0);
                         ui.horizontal(|ui| {
-                            if ui
+                            let add_clicked = ui
                                 .add_sized(
                                     [140.0, 36.0],
                                     egui::Button::new(egui::RichText::new("✓ Add").size(14.0))
@@ -2577,29 +8675,27 @@ impl eframe::App for FileManagerApp {
                                 )
                                 .clicked()
                                 || (response.lost_focus()
-                                    && ui.input(|i| i.key_pressed(egui::Key::Enter)))
-                            {
-                                if !self.new_bookmark_name.is_empty() {
-                                    let path = self.get_active_pane().current_path.clone();
-                                    match self.bookmark_manager.add_bookmark(
-                                        self.new_bookmark_name.clone(),
-                                        path.clone(),
-                                        "📁".to_string(),
-                                    ) {
-                                        Ok(_) => {
-                                            self.status_message = format!(
-                                                "Added bookmark: {}",
-                                                self.new_bookmark_name
-                                            );
-                                        }
-                                        Err(e) => {
-                                            self.status_message =
-                                                format!("Error adding bookmark: {}", e);
-                                        }
+                                    && ui.input(|i| i.key_pressed(egui::Key::Enter)));
+                            if add_clicked && !self.new_bookmark_name.is_empty() {
+                                let path = self.get_active_pane().current_path.clone();
+                                match self.bookmark_manager.add_bookmark(
+                                    self.new_bookmark_name.clone(),
+                                    path.clone(),
+                                    "📁".to_string(),
+                                ) {
+                                    Ok(_) => {
+                                        self.status_message = format!(
+                                            "Added bookmark: {}",
+                                            self.new_bookmark_name
+                                        );
+                                    }
+                                    Err(e) => {
+                                        self.status_message =
+                                            format!("Error adding bookmark: {}", e);
                                     }
-                                    self.new_bookmark_name.clear();
-                                    self.show_add_bookmark_dialog = false;
                                 }
+                                self.new_bookmark_name.clear();
+                                self.show_add_bookmark_dialog = false;
                             }
                             ui.add_space(10.0);
                             if ui
@@ -2619,9 +8715,186 @@ impl eframe::App for FileManagerApp {
                 });
         }
 
+        // Compress dialog
+        if let Some(items) = self.pending_compress.clone() {
+            let size: Option<u64> = self.compress_sizes.as_ref().map(|sizes| sizes.iter().sum());
+            let title = match items.as_slice() {
+                [item] => item.name.clone(),
+                _ => format!("{} items, each into its own archive", items.len()),
+            };
+            egui::Window::new("📦 Compress to ZIP")
+                .collapsible(false)
+                .resizable(false)
+                .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
+                .frame(
+                    egui::Frame::window(&ctx.style())
+                        .fill(egui::Color32::from_rgb(45, 47, 50))
+                        .rounding(12.0)
+                        .inner_margin(20.0),
+                )
+                .show(ctx, |ui| {
+                    ui.vertical(|ui| {
+                        ui.label(
+                            egui::RichText::new(format!(
+                                "{} ({})",
+                                title,
+                                size.map_or("calculating…".to_string(), |size| {
+                                    crate::filesystem::format_size_with(size, self.settings.size_units)
+                                })
+                            ))
+                            .size(14.0)
+                            .color(egui::Color32::from_rgb(138, 180, 248)),
+                        );
+                        if size.is_some_and(|size| size > LARGE_COMPRESS_THRESHOLD) {
+                            ui.add_space(6.0);
+                            ui.label(
+                                egui::RichText::new(
+                                    "⚠ This is large; compressing it may take a while.",
+                                )
+                                .size(12.0)
+                                .color(egui::Color32::from_rgb(251, 188, 5)),
+                            );
+                        }
+                        ui.add_space(12.0);
+
+                        egui::Grid::new("compress_options_grid")
+                            .num_columns(2)
+                            .spacing([12.0, 8.0])
+                            .show(ui, |ui| {
+                                ui.label("Method:");
+                                let method_before = self.compress_options.method;
+                                egui::ComboBox::from_id_salt("compress_method")
+                                    .selected_text(self.compress_options.method.name())
+                                    .show_ui(ui, |ui| {
+                                        for method in crate::filesystem::ZipMethod::all() {
+                                            ui.selectable_value(
+                                                &mut self.compress_options.method,
+                                                method,
+                                                method.name(),
+                                            );
+                                        }
+                                    });
+                                if self.compress_options.method != method_before {
+                                    self.compress_options.level = None;
+                                }
+                                ui.end_row();
+
+                                ui.label("Level:");
+                                let method = self.compress_options.method;
+                                match method.level_range() {
+                                    Some(range) => {
+                                        let mut level = self
+                                            .compress_options
+                                            .level
+                                            .or(method.default_level())
+                                            .unwrap_or(*range.start());
+                                        let slider = egui::Slider::new(&mut level, range)
+                                            .text("faster ↔ smaller");
+                                        if ui.add(slider).changed()
+                                        {
+                                            self.compress_options.level = Some(level);
+                                        }
+                                    }
+                                    None => {
+                                        ui.label(
+                                            egui::RichText::new("n/a")
+                                                .color(egui::Color32::GRAY),
+                                        );
+                                    }
+                                }
+                                ui.end_row();
+
+                                ui.label("Password:");
+                                ui.checkbox(&mut self.compress_encrypt, "🔒 Encrypt (AES-256)")
+                                    .on_hover_text(
+                                        "File contents are encrypted; names and sizes stay visible. \
+                                         The password is not saved anywhere",
+                                    );
+                                ui.end_row();
+                                if self.compress_encrypt {
+                                    ui.label("");
+                                    ui.add(
+                                        egui::TextEdit::singleline(&mut self.compress_password)
+                                            .password(true)
+                                            .hint_text("Password"),
+                                    );
+                                    ui.end_row();
+                                    ui.label("");
+                                    ui.add(
+                                        egui::TextEdit::singleline(&mut self.compress_password_confirm)
+                                            .password(true)
+                                            .hint_text("Repeat password"),
+                                    );
+                                    ui.end_row();
+                                }
+                            });
+
+                        let password_problem = if !self.compress_encrypt {
+                            None
+                        } else if self.compress_password.is_empty() {
+                            Some("Enter a password")
+                        } else if self.compress_password != self.compress_password_confirm {
+                            Some("The passwords don't match")
+                        } else {
+                            None
+                        };
+                        if let Some(problem) = password_problem {
+                            ui.add_space(6.0);
+                            ui.label(
+                                egui::RichText::new(format!("✗ {}", problem))
+                                    .size(12.0)
+                                    .color(egui::Color32::from_rgb(242, 139, 130)),
+                            );
+                        }
+
+                        ui.add_space(20.0);
+                        ui.horizontal(|ui| {
+                            let compress = ui
+                                .add_enabled_ui(password_problem.is_none(), |ui| {
+                                    ui.add_sized(
+                                        [120.0, 36.0],
+                                        egui::Button::new(egui::RichText::new("📦  Compress").size(14.0))
+                                            .fill(egui::Color32::from_rgb(66, 133, 244))
+                                            .rounding(6.0),
+                                    )
+                                })
+                                .inner;
+                            if compress.clicked() {
+                                if self.settings.zip_options != self.compress_options {
+                                    self.settings.zip_options = self.compress_options;
+                                    self.save_settings();
+                                }
+                                self.start_compression(items.clone());
+                            }
+                            ui.add_space(12.0);
+                            if ui
+                                .add_sized(
+                                    [120.0, 36.0],
+                                    egui::Button::new(egui::RichText::new("✗  Cancel").size(14.0))
+                                        .fill(egui::Color32::from_rgb(66, 70, 77))
+                                        .rounding(6.0),
+                                )
+                                .clicked()
+                            {
+                                self.close_compress_dialog();
+                            }
+                        });
+                    });
+                });
+        }
+
         // Delete confirmation dialog
         if self.show_delete_confirm {
-            let name = self.item_to_delete.clone().unwrap_or_default();
+            let items = self.items_to_delete.clone();
+            let title = if items.len() == 1 {
+                "Delete this item?".to_string()
+            } else {
+                format!("Delete these {} items?", items.len())
+            };
+            let total_size = match self.delete_total_size {
+                Some(size) => crate::filesystem::format_size_with(size, self.settings.size_units),
+                None => "calculating…".to_string(),
+            };
             egui::Window::new("⚠  Delete Confirmation")
                 .collapsible(false)
                 .resizable(false)
@@ -2653,13 +8926,26 @@ impl eframe::App for FileManagerApp {
                                 .color(egui::Color32::from_rgb(251, 188, 5)),
                         );
                         ui.add_space(15.0);
-                        ui.label(egui::RichText::new("Delete this item?").size(16.0).strong());
-                        ui.add_space(8.0);
+                        ui.label(egui::RichText::new(title).size(16.0).strong());
+                        ui.add_space(4.0);
                         ui.label(
-                            egui::RichText::new(&name)
-                                .size(14.0)
-                                .color(egui::Color32::from_rgb(138, 180, 248)),
+                            egui::RichText::new(format!("Total size: {}", total_size))
+                                .size(12.0)
+                                .color(egui::Color32::from_rgb(189, 193, 198)),
                         );
+                        ui.add_space(8.0);
+                        egui::ScrollArea::vertical()
+                            .max_height(200.0)
+                            .show(ui, |ui| {
+                                for item in &items {
+                                    let icon = if item.is_dir { "📁" } else { "📄" };
+                                    ui.label(
+                                        egui::RichText::new(format!("{} {}", icon, item.name))
+                                            .size(14.0)
+                                            .color(egui::Color32::from_rgb(138, 180, 248)),
+                                    );
+                                }
+                            });
                         ui.add_space(20.0);
                         ui.horizontal(|ui| {
                             ui.add_space(10.0);
@@ -2674,9 +8960,10 @@ impl eframe::App for FileManagerApp {
                                 )
                                 .clicked()
                             {
-                                let _ = self.delete_selected_file();
-                                self.show_delete_confirm = false;
-                                self.item_to_delete = None;
+                                if let Err(e) = self.delete_queued_items() {
+                                    self.notify_error(format!("Delete failed: {}", e));
+                                }
+                                self.cancel_delete();
                             }
                             ui.add_space(12.0);
                             if ui
@@ -2688,8 +8975,7 @@ impl eframe::App for FileManagerApp {
                                 )
                                 .clicked()
                             {
-                                self.show_delete_confirm = false;
-                                self.item_to_delete = None;
+                                self.cancel_delete();
                             }
                         });
                         ui.add_space(10.0);
@@ -2709,6 +8995,171 @@ impl FileManagerApp {
             Theme::Nord => self.get_nord_theme(),
             Theme::Monokai => self.get_monokai_theme(),
             Theme::SolarizedDark => self.get_solarized_dark_theme(),
+            Theme::Custom => self.get_custom_theme(),
+        }
+    }
+
+    fn get_custom_theme(&self) -> egui::Visuals {
+        let colors = &self.settings.custom_theme;
+        let rgb = |[r, g, b]: [u8; 3]| egui::Color32::from_rgb(r, g, b);
+        let mut visuals = if colors.dark {
+            egui::Visuals::dark()
+        } else {
+            egui::Visuals::light()
+        };
+
+        visuals.window_rounding = 8.0.into();
+        visuals.panel_fill = rgb(colors.panel);
+        visuals.window_fill = rgb(colors.panel);
+
+        visuals.widgets.noninteractive.bg_fill = rgb(colors.widget);
+        visuals.widgets.noninteractive.fg_stroke.color = rgb(colors.text);
+
+        visuals.widgets.inactive.bg_fill = rgb(colors.widget);
+        visuals.widgets.inactive.fg_stroke.color = rgb(colors.text);
+        visuals.widgets.inactive.rounding = 6.0.into();
+
+        visuals.widgets.hovered.bg_fill = rgb(colors.hover);
+        visuals.widgets.hovered.fg_stroke.color = rgb(colors.text);
+        visuals.widgets.hovered.rounding = 6.0.into();
+
+        visuals.widgets.active.bg_fill = rgb(colors.accent);
+        visuals.widgets.active.fg_stroke.color = rgb(colors.text);
+        visuals.widgets.active.rounding = 6.0.into();
+
+        visuals.selection.bg_fill = rgb(colors.selection);
+        visuals.selection.stroke.color = rgb(colors.accent);
+
+        visuals
+    }
+
+    /// Editor for `Theme::Custom`. Changes apply live; Cancel restores the
+    /// theme and palette that were active when the editor opened.
+    fn render_theme_editor(&mut self, ctx: &egui::Context) {
+        if self.theme_editor_backup.is_none() {
+            return;
+        }
+
+        let mut done = false;
+        let mut cancelled = false;
+        egui::Window::new("✏ Custom Theme")
+            .collapsible(false)
+            .resizable(false)
+            .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
+            .frame(
+                egui::Frame::window(&ctx.style())
+                    .rounding(12.0)
+                    .inner_margin(20.0),
+            )
+            .show(ctx, |ui| {
+                let colors = &mut self.settings.custom_theme;
+                ui.horizontal(|ui| {
+                    ui.label(egui::RichText::new("Base:").size(13.0));
+                    ui.radio_value(&mut colors.dark, true, "Dark");
+                    ui.radio_value(&mut colors.dark, false, "Light");
+                });
+                ui.add_space(8.0);
+
+                egui::Grid::new("theme_editor_grid")
+                    .num_columns(2)
+                    .spacing([15.0, 8.0])
+                    .show(ui, |ui| {
+                        for (label, color) in [
+                            ("Panels", &mut colors.panel),
+                            ("Widgets", &mut colors.widget),
+                            ("Hover", &mut colors.hover),
+                            ("Accent", &mut colors.accent),
+                            ("Selection", &mut colors.selection),
+                            ("Text", &mut colors.text),
+                        ] {
+                            ui.label(egui::RichText::new(label).size(13.0));
+                            ui.color_edit_button_srgb(color);
+                            ui.end_row();
+                        }
+                    });
+                if ui.small_button("Reset to defaults").clicked() {
+                    *colors = crate::settings::CustomTheme::default();
+                }
+
+                ui.add_space(10.0);
+                ui.separator();
+                ui.add_space(10.0);
+
+                ui.label(egui::RichText::new("Theme file (JSON):").size(13.0));
+                ui.add_sized(
+                    [320.0, 24.0],
+                    egui::TextEdit::singleline(&mut self.theme_file_path),
+                );
+                ui.horizontal(|ui| {
+                    let path = PathBuf::from(&self.theme_file_path);
+                    if ui.button("📥 Import").clicked() {
+                        match crate::settings::CustomTheme::import(&path) {
+                            Ok(theme) => {
+                                self.settings.custom_theme = theme;
+                                self.status_message =
+                                    format!("Imported theme from {}", path.display());
+                            }
+                            Err(e) => self.notify_error(format!(
+                                "Failed to import {}: {}",
+                                path.display(),
+                                e
+                            )),
+                        }
+                    }
+                    if ui.button("📤 Export").clicked() {
+                        match self.settings.custom_theme.export(&path) {
+                            Ok(()) => {
+                                self.notify_success(format!("Exported theme to {}", path.display()))
+                            }
+                            Err(e) => self.notify_error(format!(
+                                "Failed to export {}: {}",
+                                path.display(),
+                                e
+                            )),
+                        }
+                    }
+                });
+
+                ui.add_space(15.0);
+                ui.horizontal(|ui| {
+                    if ui
+                        .add_sized(
+                            [140.0, 36.0],
+                            egui::Button::new(egui::RichText::new("✓ Done").size(14.0))
+                                .fill(egui::Color32::from_rgb(40, 167, 69))
+                                .rounding(6.0),
+                        )
+                        .clicked()
+                    {
+                        done = true;
+                    }
+                    if ui
+                        .add_sized(
+                            [140.0, 36.0],
+                            egui::Button::new(egui::RichText::new("✗ Cancel").size(14.0))
+                                .rounding(6.0),
+                        )
+                        .clicked()
+                    {
+                        cancelled = true;
+                    }
+                });
+            });
+
+        if done {
+            self.theme_editor_backup = None;
+            self.settings.theme = Theme::Custom;
+            self.save_settings();
+            self.status_message = "Custom theme saved".to_string();
+        } else if cancelled {
+            self.cancel_theme_editor();
+        }
+    }
+
+    fn cancel_theme_editor(&mut self) {
+        if let Some((theme, colors)) = self.theme_editor_backup.take() {
+            self.current_theme = theme;
+            self.settings.custom_theme = colors;
         }
     }
 
@@ -2907,8 +9358,7 @@ impl FileManagerApp {
                 .on_hover_text("Refresh both panes")
                 .clicked()
             {
-                let _ = self.left_pane.refresh();
-                let _ = self.right_pane.refresh();
+                let _ = self.refresh_both_panes();
                 self.status_message = "Refreshed both panes".to_string();
             }
 
@@ -2938,8 +9388,14 @@ impl FileManagerApp {
                 self.cut_to_clipboard();
             }
 
-            let paste_enabled = self.clipboard.is_some();
-            let paste_tooltip = if paste_enabled {
+            let busy = self.is_busy();
+            let paste_read_only = self.is_pane_read_only(1 - self.active_pane);
+            let paste_enabled = self.clipboard.is_some() && !busy && !paste_read_only;
+            let paste_tooltip = if busy {
+                "Disabled while an operation is running"
+            } else if paste_read_only {
+                self.read_only_hint()
+            } else if paste_enabled {
                 "Paste from clipboard"
             } else {
                 "Clipboard is empty"
@@ -2958,25 +9414,35 @@ impl FileManagerApp {
                 .on_hover_text(paste_tooltip);
 
             if paste_btn.clicked() && paste_enabled {
-                let _ = self.paste_from_clipboard();
-            }
-
-            if ui
-                .add_sized(
-                    [95.0, 38.0],
-                    egui::Button::new(egui::RichText::new("🗑 F8").size(13.0))
-                        .fill(egui::Color32::from_rgb(55, 58, 64))
-                        .rounding(6.0),
-                )
-                .on_hover_text("Delete selected item")
-                .clicked()
-            {
-                if let Some(item) = self.get_active_pane().get_selected_item() {
-                    self.item_to_delete = Some(item.name.clone());
-                    self.show_delete_confirm = true;
+                if let Err(e) = self.paste_from_clipboard() {
+                    self.notify_error(format!("Paste failed: {}", e));
                 }
             }
 
+            let delete_read_only = self.is_pane_read_only(self.active_pane);
+            let delete_enabled = !busy && !delete_read_only;
+            let delete_btn = ui
+                .add_enabled_ui(delete_enabled, |ui| {
+                    ui.add_sized(
+                        [95.0, 38.0],
+                        egui::Button::new(egui::RichText::new("🗑 F8").size(13.0))
+                            .fill(egui::Color32::from_rgb(55, 58, 64))
+                            .rounding(6.0),
+                    )
+                })
+                .inner
+                .on_hover_text(if busy {
+                    "Disabled while an operation is running"
+                } else if delete_read_only {
+                    self.read_only_hint()
+                } else {
+                    "Delete selected item"
+                });
+
+            if delete_btn.clicked() && delete_enabled {
+                self.request_delete_selection();
+            }
+
             ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
                 if ui
                     .add_sized(
@@ -3008,12 +9474,19 @@ impl FileManagerApp {
                 .map(|item| item.name.clone())
                 .unwrap_or_default();
             let is_parent_dir = selected_name == "..";
-            let can_transfer = has_selection && !is_parent_dir;
+            let busy = self.is_busy();
+            let can_transfer = has_selection && !is_parent_dir && !busy;
 
             // Copy Left to Right button
-            let copy_left_to_right_enabled = self.active_pane == 0 && can_transfer;
+            let right_read_only = self.is_pane_read_only(1);
+            let copy_left_to_right_enabled =
+                self.active_pane == 0 && can_transfer && !right_read_only;
             let copy_l2r_tooltip = if copy_left_to_right_enabled {
                 "Copy selected item\nfrom Left to Right"
+            } else if busy {
+                "Disabled while an operation is running"
+            } else if right_read_only {
+                self.read_only_hint()
             } else {
                 "Select an item in left pane"
             };
@@ -3034,32 +9507,22 @@ impl FileManagerApp {
             if copy_l2r_clicked && copy_left_to_right_enabled {
                 if let Some(item) = self.left_pane.get_selected_item() {
                     let source_path = item.path.clone();
-                    let dest_path = self.right_pane.current_path.join(&item.name);
-
-                    let result = if item.is_dir {
-                        self.copy_dir_recursive(&source_path, &dest_path)
-                    } else {
-                        fs::copy(&source_path, &dest_path)
-                            .map(|_| ())
-                            .map_err(|e| anyhow::Error::from(e))
-                    };
-
-                    match result {
-                        Ok(_) => {
-                            self.status_message = format!("Copied: {} → Right", item.name);
-                            let _ = self.right_pane.refresh();
-                        }
-                        Err(e) => {
-                            self.status_message = format!("Error copying: {}", e);
-                        }
-                    }
+                    let dest_dir = self.right_pane.current_path.clone();
+                    // Runs in the background, checking for space first
+                    self.start_transfer(vec![source_path], dest_dir, false, None);
                 }
             }
 
             // Copy Right to Left button
-            let copy_right_to_left_enabled = self.active_pane == 1 && can_transfer;
+            let left_read_only = self.is_pane_read_only(0);
+            let copy_right_to_left_enabled =
+                self.active_pane == 1 && can_transfer && !left_read_only;
             let copy_r2l_tooltip = if copy_right_to_left_enabled {
                 "Copy selected item\nfrom Right to Left"
+            } else if busy {
+                "Disabled while an operation is running"
+            } else if left_read_only {
+                self.read_only_hint()
             } else {
                 "Select an item in right pane"
             };
@@ -3080,25 +9543,9 @@ impl FileManagerApp {
             if copy_r2l_clicked && copy_right_to_left_enabled {
                 if let Some(item) = self.right_pane.get_selected_item() {
                     let source_path = item.path.clone();
-                    let dest_path = self.left_pane.current_path.join(&item.name);
-
-                    let result = if item.is_dir {
-                        self.copy_dir_recursive(&source_path, &dest_path)
-                    } else {
-                        fs::copy(&source_path, &dest_path)
-                            .map(|_| ())
-                            .map_err(|e| anyhow::Error::from(e))
-                    };
-
-                    match result {
-                        Ok(_) => {
-                            self.status_message = format!("Copied: {} → Left", item.name);
-                            let _ = self.left_pane.refresh();
-                        }
-                        Err(e) => {
-                            self.status_message = format!("Error copying: {}", e);
-                        }
-                    }
+                    let dest_dir = self.left_pane.current_path.clone();
+                    // Runs in the background, checking for space first
+                    self.start_transfer(vec![source_path], dest_dir, false, None);
                 }
             }
 
@@ -3108,6 +9555,8 @@ impl FileManagerApp {
             let move_left_to_right_enabled = self.active_pane == 0 && can_transfer;
             let move_l2r_tooltip = if move_left_to_right_enabled {
                 "Move selected item\nfrom Left to Right"
+            } else if busy {
+                "Disabled while an operation is running"
             } else {
                 "Select an item in left pane"
             };
@@ -3126,26 +9575,11 @@ impl FileManagerApp {
                 .clicked();
 
             if move_l2r_clicked && move_left_to_right_enabled {
-                let (source_path, item_name, dest_dir) = {
-                    let item = self.left_pane.get_selected_item();
+                if let Some(item) = self.left_pane.get_selected_item() {
+                    let source_path = item.path.clone();
                     let dest_dir = self.right_pane.current_path.clone();
-                    if let Some(item) = item {
-                        (item.path.clone(), item.name.clone(), dest_dir)
-                    } else {
-                        return;
-                    }
-                };
-
-                let dest_path = dest_dir.join(&item_name);
-                match fs::rename(&source_path, &dest_path) {
-                    Ok(_) => {
-                        self.status_message = format!("Moved: {} → Right", item_name);
-                        let _ = self.left_pane.refresh();
-                        let _ = self.right_pane.refresh();
-                    }
-                    Err(e) => {
-                        self.status_message = format!("Error moving: {}", e);
-                    }
+                    // Runs in the background: moving to another drive copies
+                    self.start_transfer(vec![source_path], dest_dir, true, None);
                 }
             }
 
@@ -3153,6 +9587,8 @@ impl FileManagerApp {
             let move_right_to_left_enabled = self.active_pane == 1 && can_transfer;
             let move_r2l_tooltip = if move_right_to_left_enabled {
                 "Move selected item\nfrom Right to Left"
+            } else if busy {
+                "Disabled while an operation is running"
             } else {
                 "Select an item in right pane"
             };
@@ -3171,26 +9607,11 @@ impl FileManagerApp {
                 .clicked();
 
             if move_r2l_clicked && move_right_to_left_enabled {
-                let (source_path, item_name, dest_dir) = {
-                    let item = self.right_pane.get_selected_item();
+                if let Some(item) = self.right_pane.get_selected_item() {
+                    let source_path = item.path.clone();
                     let dest_dir = self.left_pane.current_path.clone();
-                    if let Some(item) = item {
-                        (item.path.clone(), item.name.clone(), dest_dir)
-                    } else {
-                        return;
-                    }
-                };
-
-                let dest_path = dest_dir.join(&item_name);
-                match fs::rename(&source_path, &dest_path) {
-                    Ok(_) => {
-                        self.status_message = format!("Moved: {} → Left", item_name);
-                        let _ = self.left_pane.refresh();
-                        let _ = self.right_pane.refresh();
-                    }
-                    Err(e) => {
-                        self.status_message = format!("Error moving: {}", e);
-                    }
+                    // Runs in the background: moving to another drive copies
+                    self.start_transfer(vec![source_path], dest_dir, true, None);
                 }
             }
         });
@@ -3199,13 +9620,17 @@ impl FileManagerApp {
     fn render_pane(&mut self, ui: &mut egui::Ui, pane_index: usize, ctx: &egui::Context, max_width: f32) {
         let is_active = self.active_pane == pane_index;
         let visuals = ctx.style().visuals.clone();
+        // Set again below if a row of this pane is still hovered
+        if self.hovered_item.is_some_and(|(pane, _)| pane == pane_index) {
+            self.hovered_item = None;
+        }
         
         // Force the UI to respect the allocated width
         ui.set_max_width(max_width);
         ui.set_width(max_width);
 
         // Collect all data we need before borrowing
-        let (current_path, items, selected_index, sort_by, sort_order) = {
+        let (current_path, items, selected_index, sort_by, sort_order, read_error, fs_status) = {
             let pane = if pane_index == 0 {
                 &self.left_pane
             } else {
@@ -3217,6 +9642,8 @@ impl FileManagerApp {
                 pane.selected_index,
                 pane.sort_by,
                 pane.sort_order,
+                pane.read_error.clone(),
+                pane.fs_status,
             )
         };
 
@@ -3318,8 +9745,108 @@ impl FileManagerApp {
                                 let _ = pane.navigate_forward();
                             }
 
+                            // Dropdown with the whole history and recent folders
+                            let mut history_jump = None;
+                            let mut recent_jump = None;
+                            ui.menu_button(egui::RichText::new("▾").size(14.0), |ui| {
+                                ui.set_max_width(420.0);
+                                let pane = self.get_pane(pane_index);
+                                let path_button = |ui: &mut egui::Ui, text: String, enabled: bool| {
+                                    ui.add_enabled(
+                                        enabled,
+                                        egui::Button::new(egui::RichText::new(text).size(12.0))
+                                            .frame(false)
+                                            .wrap_mode(egui::TextWrapMode::Truncate),
+                                    )
+                                    .clicked()
+                                };
+                                ui.label(
+                                    egui::RichText::new("History")
+                                        .size(11.0)
+                                        .color(egui::Color32::from_rgb(154, 160, 166)),
+                                );
+                                for (index, path) in pane.history.iter().enumerate().rev() {
+                                    let current = index == pane.history_index;
+                                    let marker = if current { "●" } else { "   " };
+                                    if path_button(ui, format!("{} {}", marker, path.display()), !current) {
+                                        history_jump = Some(index);
+                                        ui.close_menu();
+                                    }
+                                }
+                                let recent: Vec<&PathBuf> = self
+                                    .recent_folders
+                                    .iter()
+                                    .filter(|path| **path != pane.current_path)
+                                    .collect();
+                                if !recent.is_empty() {
+                                    ui.separator();
+                                    ui.label(
+                                        egui::RichText::new("Recent folders")
+                                            .size(11.0)
+                                            .color(egui::Color32::from_rgb(154, 160, 166)),
+                                    );
+                                    for path in recent {
+                                        if path_button(ui, format!("📁 {}", path.display()), path.is_dir()) {
+                                            recent_jump = Some(path.clone());
+                                            ui.close_menu();
+                                        }
+                                    }
+                                }
+                            })
+                            .response
+                            .on_hover_text("History and recent folders");
+                            let pane = if pane_index == 0 {
+                                &mut self.left_pane
+                            } else {
+                                &mut self.right_pane
+                            };
+                            let jumped = match (history_jump, recent_jump) {
+                                (Some(index), _) => pane.jump_to_history(index),
+                                (None, Some(path)) => pane.navigate_to(path),
+                                (None, None) => Ok(()),
+                            };
+                            if let Err(e) = jumped {
+                                self.status_message = format!("Error: {}", e);
+                            }
+
                             ui.add_space(6.0);
-                            ui.label(egui::RichText::new("💾").size(14.0));
+                            let drive_icon = ui.label(egui::RichText::new("💾").size(14.0));
+                            if let Some(status) = fs_status {
+                                let usage = status.usage_percentage();
+                                drive_icon.on_hover_text(format!(
+                                    "{} free of {} ({:.0}% used)",
+                                    crate::filesystem::format_size_with(
+                                        status.available_space,
+                                        self.settings.size_units
+                                    ),
+                                    crate::filesystem::format_size_with(
+                                        status.total_space,
+                                        self.settings.size_units
+                                    ),
+                                    usage
+                                ));
+                                ui.add_space(4.0);
+                                ui.label(
+                                    egui::RichText::new(format!(
+                                        "{} free",
+                                        crate::filesystem::format_size_with(
+                                            status.available_space,
+                                            self.settings.size_units
+                                        )
+                                    ))
+                                    .size(11.0)
+                                    .color(usage_color(usage)),
+                                );
+                                if status.read_only {
+                                    ui.add_space(4.0);
+                                    ui.label(
+                                        egui::RichText::new("🔒 Read-only")
+                                            .size(11.0)
+                                            .color(egui::Color32::from_rgb(242, 139, 130)),
+                                    )
+                                    .on_hover_text(READ_ONLY_HINT);
+                                }
+                            }
                             ui.add_space(4.0);
 
                             // Breadcrumb navigation - single line, truncate if needed
@@ -3332,10 +9859,10 @@ impl FileManagerApp {
                                 let display_name = if comp_str == "/" {
                                     "/".to_string()
                                 } else {
-                                    comp_str.to_string()
+                                    crate::filesystem::middle_ellipsis(&comp_str, 24)
                                 };
 
-                                let estimated_width = display_name.len() as f32 * 6.0 + 10.0;
+                                let estimated_width = display_name.chars().count() as f32 * 6.0 + 10.0;
 
                                 // Stop if we're running out of space
                                 if used_width + estimated_width > available_width - 30.0
@@ -3349,23 +9876,22 @@ impl FileManagerApp {
                                     break;
                                 }
 
-                                if ui
-                                    .add_sized(
-                                        [estimated_width.min(150.0), 18.0],
-                                        egui::Button::new(
-                                            egui::RichText::new(&display_name)
-                                                .size(15.0)
-                                                .color(egui::Color32::from_rgb(138, 180, 248)),
-                                        )
-                                        .frame(false),
+                                let mut segment = ui.add_sized(
+                                    [estimated_width.min(150.0), 18.0],
+                                    egui::Button::new(
+                                        egui::RichText::new(&display_name)
+                                            .size(15.0)
+                                            .color(egui::Color32::from_rgb(138, 180, 248)),
                                     )
-                                    .clicked()
-                                {
+                                    .frame(false),
+                                );
+                                if display_name != comp_str {
+                                    segment = segment.on_hover_text(comp_str.as_ref());
+                                }
+                                if segment.clicked() {
                                     // Navigate to clicked path segment
-                                    let mut target_path = std::path::PathBuf::new();
-                                    for i in 0..=idx {
-                                        target_path.push(components[i]);
-                                    }
+                                    let target_path: std::path::PathBuf =
+                                        components[..=idx].iter().collect();
                                     let pane = if pane_index == 0 {
                                         &mut self.left_pane
                                     } else {
@@ -3412,6 +9938,21 @@ impl FileManagerApp {
                                         .color(visuals.widgets.active.bg_fill),
                                 );
                                 ui.add_space(6.0);
+                                let matching = &mut self.filter_matching;
+                                let fuzzy = *matching == NameMatch::Fuzzy;
+                                let toggle = ui
+                                    .selectable_label(
+                                        fuzzy,
+                                        egui::RichText::new(NameMatch::Fuzzy.name()).size(11.0),
+                                    )
+                                    .on_hover_text(
+                                        "Match the typed characters in order, not necessarily \
+                                         adjacent (\"amr\" finds \"app_manager.rs\"), best matches first",
+                                    );
+                                if toggle.clicked() {
+                                    *matching = if fuzzy { NameMatch::Substring } else { NameMatch::Fuzzy };
+                                }
+                                ui.add_space(4.0);
                                 let filter_response = ui.add(
                                     egui::TextEdit::singleline(if pane_index == 0 {
                                         &mut self.left_pane.filter_text
@@ -3422,6 +9963,111 @@ impl FileManagerApp {
                                     .desired_width(ui.available_width()),
                                 );
                                 filter_response.request_focus();
+                                // Put the cursor on the best fuzzy match as it changes
+                                let ranked = filter_response.changed() || toggle.clicked();
+                                if ranked && self.filter_matching == NameMatch::Fuzzy {
+                                    let best = self.visible_indices(pane_index).into_iter().find(|&index| {
+                                        self.get_pane(pane_index).items[index].name != ".."
+                                    });
+                                    if let Some(best) = best {
+                                        self.get_active_pane_mut().selected_index = best;
+                                        self.scroll_to_selection = true;
+                                    }
+                                }
+                            });
+                        });
+                }
+
+                // Unreadable directory banner
+                if let Some(error) = &read_error {
+                    egui::Frame::default()
+                        .fill(visuals.widgets.inactive.bg_fill)
+                        .inner_margin(egui::Margin::symmetric(10.0, 6.0))
+                        .show(ui, |ui| {
+                            ui.set_width(ui.available_width());
+                            ui.label(
+                                egui::RichText::new(format!("🔒 {}", error))
+                                    .size(13.0)
+                                    .color(egui::Color32::from_rgb(242, 139, 130)),
+                            );
+                        });
+                }
+
+                // Changes made by other programs, reloaded only on request
+                let changed_on_disk = if pane_index == 0 {
+                    self.left_pane.changed_on_disk
+                } else {
+                    self.right_pane.changed_on_disk
+                };
+                if changed_on_disk {
+                    egui::Frame::default()
+                        .fill(visuals.widgets.inactive.bg_fill)
+                        .inner_margin(egui::Margin::symmetric(10.0, 3.0))
+                        .show(ui, |ui| {
+                            ui.set_width(ui.available_width());
+                            ui.horizontal(|ui| {
+                                ui.label(
+                                    egui::RichText::new("⚠ This folder changed on disk")
+                                        .size(11.0)
+                                        .color(egui::Color32::from_rgb(220, 170, 90)),
+                                );
+                                if ui
+                                    .link(egui::RichText::new("Reload").size(11.0))
+                                    .on_hover_text("Reload the listing (F2)")
+                                    .clicked()
+                                {
+                                    if let Err(e) = self.refresh_pane(pane_index) {
+                                        self.notify_error(format!("Cannot reload: {}", e));
+                                    }
+                                }
+                            });
+                        });
+                }
+
+                // Note on items left out of the list, so they don't seem to vanish
+                let (hidden_count, filtered_count) = self.excluded_counts(pane_index);
+                if hidden_count > 0 || filtered_count > 0 {
+                    egui::Frame::default()
+                        .fill(visuals.widgets.inactive.bg_fill)
+                        .inner_margin(egui::Margin::symmetric(10.0, 3.0))
+                        .show(ui, |ui| {
+                            ui.set_width(ui.available_width());
+                            ui.horizontal(|ui| {
+                                ui.spacing_mut().item_spacing.x = 4.0;
+                                ui.label(
+                                    egui::RichText::new("👁")
+                                        .size(11.0)
+                                        .color(egui::Color32::from_rgb(154, 160, 166)),
+                                );
+                                if hidden_count > 0
+                                    && ui
+                                        .link(
+                                            egui::RichText::new(format!("{} hidden", hidden_count))
+                                                .size(11.0),
+                                        )
+                                        .on_hover_text("Show hidden files (Ctrl+H)")
+                                        .clicked()
+                                {
+                                    self.show_hidden_files = true;
+                                    self.status_message = "Showing hidden files".to_string();
+                                }
+                                if hidden_count > 0 && filtered_count > 0 {
+                                    ui.label(egui::RichText::new(",").size(11.0));
+                                }
+                                if filtered_count > 0
+                                    && ui
+                                        .link(
+                                            egui::RichText::new(format!(
+                                                "{} filtered",
+                                                filtered_count
+                                            ))
+                                            .size(11.0),
+                                        )
+                                        .on_hover_text("Clear filters")
+                                        .clicked()
+                                {
+                                    self.clear_filters(pane_index);
+                                }
                             });
                         });
                 }
@@ -3429,31 +10075,99 @@ impl FileManagerApp {
                 // Table for file list - wrapped in a constrained area
                 use egui_extras::{TableBuilder, Column};
 
-                let text_height = 28.0; // Smaller row height for compact view
+                let text_height = self.settings.density.row_height();
+                let font_scale = self.settings.density.font_scale();
                 let table_width = ui.available_width();
                 
                 // Capture shift state before entering closures
                 let shift_pressed = ctx.input(|i| i.modifiers.shift);
                 
+                // Widths of the Modified and Size columns as laid out this frame
+                let mut column_widths = [
+                    self.settings.date_column_width,
+                    self.settings.size_column_width,
+                ];
+
                 // Use allocate_ui to constrain the table width
                 ui.allocate_ui(egui::Vec2::new(table_width, ui.available_height()), |ui| {
                     ui.set_max_width(table_width);
                     ui.set_clip_rect(ui.max_rect());
                     
-                    // Calculate column widths to fit within table_width
-                    let date_width = 140.0;
-                    let size_width = 90.0;
-                    let name_width = (table_width - date_width - size_width - 20.0).max(100.0);
-                    
-                    TableBuilder::new(ui)
+                    // Modified and Size can be resized by dragging their edges, but
+                    // never so far that the Name column drops below its minimum
+                    let min_name_width = 100.0;
+                    let max_date_width =
+                        (table_width - self.settings.size_column_width - min_name_width - 20.0).max(MIN_COLUMN_WIDTH);
+                    let max_size_width =
+                        (table_width - self.settings.date_column_width - min_name_width - 20.0).max(MIN_COLUMN_WIDTH);
+
+                    // Filter items based on show_hidden_files setting AND filter text AND extension filters
+                    let filter_text = self.pane_filter_text(pane_index);
+                    let filtered_items: Vec<(usize, &crate::filesystem::FileItem)> = self
+                        .visible_indices(pane_index)
+                        .into_iter()
+                        .map(|index| (index, &items[index]))
+                        .collect();
+
+                    // Remember how many rows fit so PageUp/PageDown can move by a page
+                    let header_height = 26.0;
+                    self.pane_visible_rows[pane_index] =
+                        (((ui.available_height() - header_height) / text_height) as usize).max(1);
+
+                    let mut table = TableBuilder::new(ui)
                         .striped(true)
-                        .resizable(false) // Disable resize to prevent overflow
+                        .resizable(true)
                         .cell_layout(egui::Layout::left_to_right(egui::Align::Center))
-                        .column(Column::exact(name_width).clip(true)) // Name - exact width
-                        .column(Column::exact(date_width)) // Date
-                        .column(Column::exact(size_width)) // Size
-                        .sense(egui::Sense::click()) // Row selection
-                    .header(26.0, |mut header| {
+                        .column(Column::remainder().at_least(min_name_width).clip(true).resizable(false)) // Name - takes the rest
+                        .column(
+                            Column::initial(self.settings.date_column_width)
+                                .range(MIN_COLUMN_WIDTH..=max_date_width)
+                                .clip(true),
+                        ) // Date
+                        .column(
+                            Column::initial(self.settings.size_column_width)
+                                .range(MIN_COLUMN_WIDTH..=max_size_width)
+                                .clip(true),
+                        ) // Size
+                        .sense(egui::Sense::click()); // Row selection
+
+                    // Set again below if the cursor row is drawn this frame
+                    self.cursor_row_rects[pane_index] = None;
+
+                    // Keep the cursor visible after keyboard navigation
+                    let cursor_row = filtered_items.iter().position(|(i, _)| *i == selected_index);
+                    if self.scroll_to_selection && is_active {
+                        self.scroll_to_selection = false;
+                        if let Some(row) = cursor_row {
+                            table = table.scroll_to_row(row, None);
+                        }
+                    }
+
+                    // After a reload, keep the item that was at the top there, even
+                    // if entries above it came or went. When it's gone, or the filters
+                    // changed what is shown, keep the cursor in view instead.
+                    let pane = if pane_index == 0 { &mut self.left_pane } else { &mut self.right_pane };
+                    let anchor_row = pane.scroll_anchor.take().map(|anchor| {
+                        filtered_items.iter().position(|(_, item)| item.path == anchor)
+                    });
+                    let previous_top = pane.top_row.as_ref().map(|(row, _)| *row);
+                    match anchor_row {
+                        Some(Some(row)) if Some(row) != previous_top => {
+                            table = table.scroll_to_row(row, Some(egui::Align::TOP));
+                        }
+                        Some(Some(_)) => {}
+                        _ if anchor_row.is_some() || filtered_items.len() != pane.shown_rows => {
+                            if let Some(row) = cursor_row {
+                                table = table.scroll_to_row(row, None);
+                            }
+                        }
+                        _ => {}
+                    }
+                    pane.shown_rows = filtered_items.len();
+                    let mut first_drawn_row = None;
+
+                    table
+                    .header(header_height, |mut header| {
                         let sort_icon = |current_sort: crate::pane::SortBy| -> &str {
                             if sort_by == current_sort {
                                 match sort_order {
@@ -3471,49 +10185,35 @@ impl FileManagerApp {
                             if ui.add(egui::Button::new(egui::RichText::new(text).strong()).frame(false)).clicked() {
                                 let pane = if pane_index == 0 { &mut self.left_pane } else { &mut self.right_pane };
                                 pane.toggle_sort(crate::pane::SortBy::Name);
+                                self.scroll_to_selection = true;
                             }
                         });
                         
                         // Date Header
-                        header.col(|ui| {
+                        column_widths[0] = header.col(|ui| {
                              let text = format!("Modified{}", sort_icon(crate::pane::SortBy::Date));
                              if ui.add(egui::Button::new(egui::RichText::new(text).strong()).frame(false)).clicked() {
                                  let pane = if pane_index == 0 { &mut self.left_pane } else { &mut self.right_pane };
                                  pane.toggle_sort(crate::pane::SortBy::Date);
+                                 self.scroll_to_selection = true;
                              }
-                        });
+                        }).0.width();
 
                         // Size Header
-                        header.col(|ui| {
+                        column_widths[1] = header.col(|ui| {
                              let text = format!("Size{}", sort_icon(crate::pane::SortBy::Size));
                              if ui.add(egui::Button::new(egui::RichText::new(text).strong()).frame(false)).clicked() {
                                  let pane = if pane_index == 0 { &mut self.left_pane } else { &mut self.right_pane };
                                  pane.toggle_sort(crate::pane::SortBy::Size);
+                                 self.scroll_to_selection = true;
                              }
-                        });
+                        }).0.width();
                     })
-                    .body(|mut body| {
-                        // Get filter text for active pane
-                        let filter_text = if pane_index == 0 {
-                            self.left_pane.filter_text.to_lowercase()
-                        } else {
-                            self.right_pane.filter_text.to_lowercase()
-                        };
-
-                        // Filter items based on show_hidden_files setting AND filter text AND extension filters
-                        let filtered_items: Vec<(usize, &crate::filesystem::FileItem)> = items
-                            .iter()
-                            .enumerate()
-                            .filter(|(_, item)| {
-                                if item.name == ".." { return true; }
-                                if !filter_text.is_empty() && !item.name.to_lowercase().contains(&filter_text) { return false; }
-                                if !self.should_show_file(item) { return false; }
-                                if self.show_hidden_files { true } else { !item.name.starts_with('.') }
-                            })
-                            .collect();
+                    .body(|body| {
 
                         body.rows(text_height, filtered_items.len(), |mut row| {
                             let item_idx = row.index();
+                            first_drawn_row.get_or_insert(item_idx);
                             let (i, item) = filtered_items[item_idx];
 
                             // Better icons based on file type
@@ -3549,35 +10249,86 @@ impl FileManagerApp {
                             // Name Column
                             row.col(|ui| {
                                 ui.horizontal(|ui| {
-                                    ui.label(egui::RichText::new(icon).size(16.0).color(icon_color));
+                                    ui.label(egui::RichText::new(icon).size(16.0 * font_scale).color(icon_color));
                                     
                                     // Git status
                                     if let Some(ref status) = item.git_status {
-                                        ui.label(egui::RichText::new(status.icon()).size(12.0).color(status.color()));
+                                        ui.label(egui::RichText::new(status.icon()).size(12.0 * font_scale).color(status.color()));
+                                    }
+
+                                    if self.settings.mark_hard_links {
+                                        if let Some(others) = pane.hard_links.get(&item.path) {
+                                            ui.label(egui::RichText::new("🔗").size(12.0 * font_scale).color(egui::Color32::from_rgb(154, 160, 166)))
+                                                .on_hover_text(format!("Hard link: shares its data with {} other file(s) in this folder", others));
+                                        }
                                     }
 
                                     let text_color = if is_selected { egui::Color32::WHITE } else { egui::Color32::LIGHT_GRAY };
-                                    ui.label(egui::RichText::new(&item.name).size(13.0).color(text_color));
+                                    // Keep both ends of a long name (its extension included) in
+                                    // view rather than letting it widen the column
+                                    let max_chars = (ui.available_width() / (7.0 * font_scale)).max(4.0) as usize;
+                                    let shown = crate::filesystem::middle_ellipsis(&item.name, max_chars);
+                                    let text: egui::WidgetText = match self.fuzzy_positions(&item.name, &filter_text) {
+                                        // Shortened names lose the matched offsets, so only whole ones are highlighted
+                                        Some(positions) if shown == item.name => highlight_chars(
+                                            &shown,
+                                            &positions,
+                                            13.0 * font_scale,
+                                            text_color,
+                                            egui::Color32::from_rgb(220, 170, 90),
+                                        )
+                                        .into(),
+                                        _ => egui::RichText::new(&shown).size(13.0 * font_scale).color(text_color).into(),
+                                    };
+                                    let label = ui.add(egui::Label::new(text).truncate());
+                                    if shown != item.name {
+                                        label.on_hover_text(&item.name);
+                                    }
                                 });
                             });
 
                             // Date Column
                             row.col(|ui| {
                                 ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
-                                    ui.label(egui::RichText::new(crate::filesystem::format_date(item.modified)).size(11.0).monospace());
+                                    ui.label(egui::RichText::new(crate::filesystem::format_date(item.modified)).size(11.0 * font_scale).monospace());
                                 });
                             });
 
                             // Size Column
                             row.col(|ui| {
                                 ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
-                                    let size_str = if item.is_dir { "<DIR>".to_string() } else { crate::filesystem::format_size(item.size) };
-                                    ui.label(egui::RichText::new(size_str).size(11.0).monospace());
+                                    let size_str = if item.is_dir { "<DIR>".to_string() } else { crate::filesystem::format_size_with(item.size, self.settings.size_units) };
+                                    ui.label(egui::RichText::new(size_str).size(11.0 * font_scale).monospace());
                                 });
                             });
 
                             // Row Interaction
-                            let response = row.response();
+                            let mut response = row.response();
+                            if i == selected_index {
+                                self.cursor_row_rects[pane_index] = Some(response.rect);
+                            }
+                            if response.hovered() {
+                                self.hovered_item = Some((pane_index, i));
+                            }
+                            if item.name != ".." {
+                                // Only built once egui's tooltip delay has passed
+                                response = response.on_hover_ui(|ui| {
+                                    ui.label(egui::RichText::new(&item.name).strong());
+                                    egui::Grid::new("hover_details_grid")
+                                        .num_columns(2)
+                                        .spacing([12.0, 2.0])
+                                        .show(ui, |ui| {
+                                            for (label, value) in self.hover_details(item) {
+                                                ui.label(
+                                                    egui::RichText::new(label)
+                                                        .color(egui::Color32::from_rgb(154, 160, 166)),
+                                                );
+                                                ui.label(value);
+                                                ui.end_row();
+                                            }
+                                        });
+                                });
+                            }
                             if response.clicked() {
                                 let pane = if pane_index == 0 { &mut self.left_pane } else { &mut self.right_pane };
                                 
@@ -3616,11 +10367,30 @@ impl FileManagerApp {
                                     self.context_menu_pos = pos;
                                     self.show_context_menu = true;
                                     self.context_menu_item_index = i;
+                                    self.context_menu_keyboard = false;
                                 }
                             }
                         });
                     });
+
+                    let pane = if pane_index == 0 { &mut self.left_pane } else { &mut self.right_pane };
+                    pane.top_row = first_drawn_row
+                        .map(|row| (row, filtered_items[row].1.path.clone()));
                 }); // Close allocate_ui
+
+                // Persist widths once the user lets go of a column edge. Widths
+                // that only changed because the window got narrower are not saved.
+                let previous = std::mem::replace(&mut self.column_widths[pane_index], column_widths);
+                let resized = (column_widths[0] - previous[0]).abs() > 0.5
+                    || (column_widths[1] - previous[1]).abs() > 0.5;
+                if ctx.input(|i| i.pointer.any_down()) {
+                    self.column_resize_pending[pane_index] |= resized;
+                } else if self.column_resize_pending[pane_index] {
+                    self.column_resize_pending[pane_index] = false;
+                    self.settings.date_column_width = column_widths[0];
+                    self.settings.size_column_width = column_widths[1];
+                    self.save_settings();
+                }
             });
         });
 
@@ -3641,8 +10411,102 @@ impl FileManagerApp {
         }
     }
 
+    /// Exchange the whole state of the two panes. The active pane follows its
+    /// contents to the other side.
+    pub fn swap_panes(&mut self) {
+        std::mem::swap(&mut self.left_pane, &mut self.right_pane);
+        std::mem::swap(&mut self.preview_content_left, &mut self.preview_content_right);
+        self.pane_visible_rows.swap(0, 1);
+        self.active_pane = 1 - self.active_pane;
+        self.hovered_item = None;
+        self.mirror_anchor = None;
+        self.status_message = "⇄ Swapped left and right panes".to_string();
+    }
+
+    /// Point one pane at the other's folder: the inactive pane at the active
+    /// one's when `to_inactive` is set ("target = source"), otherwise the
+    /// reverse ("source = target"). Goes through `navigate_to`, so the
+    /// changed pane can go back afterwards.
+    pub fn equalize_panes(&mut self, to_inactive: bool) {
+        let (source, target) = if (self.active_pane == 0) == to_inactive {
+            (&self.left_pane, &mut self.right_pane)
+        } else {
+            (&self.right_pane, &mut self.left_pane)
+        };
+        let path = source.current_path.clone();
+        if target.current_path == path {
+            self.status_message = "Both panes already show this folder".to_string();
+            return;
+        }
+        match target.navigate_to(path.clone()) {
+            Ok(()) => {
+                self.status_message = format!("Both panes now show {}", path.display());
+            }
+            Err(e) => self.notify_error(format!("Cannot open {}: {}", path.display(), e)),
+        }
+    }
+
+    pub fn toggle_mirror_navigation(&mut self) {
+        self.mirror_navigation = !self.mirror_navigation;
+        self.mirror_anchor = None;
+        self.status_message = if self.mirror_navigation {
+            "🪞 Mirror navigation on: the other pane follows the active one".to_string()
+        } else {
+            "Mirror navigation off".to_string()
+        };
+    }
+
+    /// Replays navigation of the active pane on the other pane while mirror
+    /// navigation is on. Turns mirroring off if the other tree has no
+    /// matching folder.
+    fn sync_mirror_navigation(&mut self) {
+        if !self.mirror_navigation {
+            return;
+        }
+
+        let active = self.active_pane;
+        let current = self.get_active_pane().current_path.clone();
+
+        if let Some((anchor_pane, previous)) = self.mirror_anchor.take() {
+            if anchor_pane == active && previous != current {
+                let other_index = 1 - active;
+                let other_pane = if other_index == 0 {
+                    &mut self.left_pane
+                } else {
+                    &mut self.right_pane
+                };
+                let target = crate::filesystem::mirror_path(
+                    &previous,
+                    &current,
+                    &other_pane.current_path,
+                );
+
+                if !target.is_dir() {
+                    self.mirror_navigation = false;
+                    self.status_message = format!(
+                        "🪞 Mirror navigation off: {} does not exist",
+                        target.display()
+                    );
+                    return;
+                }
+                if let Err(e) = other_pane.navigate_to(target) {
+                    self.mirror_navigation = false;
+                    self.status_message = format!("🪞 Mirror navigation off: {}", e);
+                    return;
+                }
+            }
+        }
+
+        self.mirror_anchor = Some((active, current));
+    }
+
     fn handle_keyboard(&mut self, ctx: &egui::Context) {
-        ctx.input(|i| {
+        // The palette and the context menu read their own keys while open
+        if self.command_palette.is_some() || self.show_context_menu {
+            return;
+        }
+
+        let triggered = ctx.input(|i| {
             let shift_pressed = i.modifiers.shift;
             
             if i.key_pressed(egui::Key::ArrowUp) {
@@ -3669,6 +10533,38 @@ impl FileManagerApp {
                     self.update_previews();
                 }
             }
+
+            // PageUp/PageDown move by one screen, Home/End jump to the ends.
+            // Shift extends the selection like it does for the arrow keys.
+            let page = self.pane_visible_rows[self.active_pane].saturating_sub(1).max(1) as isize;
+            let jump = if i.key_pressed(egui::Key::PageUp) {
+                Some(-page)
+            } else if i.key_pressed(egui::Key::PageDown) {
+                Some(page)
+            } else if i.key_pressed(egui::Key::Home) {
+                Some(isize::MIN)
+            } else if i.key_pressed(egui::Key::End) {
+                Some(isize::MAX)
+            } else {
+                None
+            };
+            if let Some(delta) = jump {
+                let pane = self.get_active_pane_mut();
+                if !shift_pressed {
+                    pane.clear_selection();
+                }
+                pane.move_by(delta, shift_pressed);
+                if self.show_preview_panel {
+                    self.update_previews();
+                }
+            }
+            if i.key_pressed(egui::Key::ArrowUp)
+                || i.key_pressed(egui::Key::ArrowDown)
+                || jump.is_some()
+            {
+                self.scroll_to_selection = true;
+            }
+
             if i.key_pressed(egui::Key::Enter) {
                 if let Err(e) = self.get_active_pane_mut().enter_directory() {
                     self.status_message = format!("Error: {}", e);
@@ -3679,85 +10575,157 @@ impl FileManagerApp {
                 self.get_active_pane_mut().clear_selection();
             }
 
-            // Tab to switch panes
-            if i.key_pressed(egui::Key::Tab) {
-                self.active_pane = if self.active_pane == 0 { 1 } else { 0 };
+            // ESC - Exit filter mode or close dialogs
+            if i.key_pressed(egui::Key::Escape) {
+                if self.filter_mode {
+                    self.filter_mode = false;
+                    self.get_active_pane_mut().filter_text.clear();
+                    self.status_message = "Filter cleared".to_string();
+                } else if self.show_new_folder_dialog {
+                    self.show_new_folder_dialog = false;
+                    self.new_folder_name.clear();
+                    self.new_folder_contents.clear();
+                } else if self.show_rename_dialog {
+                    self.show_rename_dialog = false;
+                    self.rename_new_name.clear();
+                } else if self.show_about_dialog {
+                    self.show_about_dialog = false;
+                } else if self.show_properties_dialog {
+                    self.show_properties_dialog = false;
+                    self.properties_item = None;
+                } else if self.show_delete_confirm {
+                    self.cancel_delete();
+                } else if self.show_search_dialog {
+                    self.show_search_dialog = false;
+                    self.stop_search();
+                } else if self.touch_dialog.is_some() {
+                    self.touch_dialog = None;
+                } else if self.permissions_dialog.is_some() {
+                    self.permissions_dialog = None;
+                } else if self.pending_open.is_some() {
+                    self.pending_open = None;
+                } else if self.transfer_dialog.is_some() {
+                    self.transfer_dialog = None;
+                } else if self.sync_preview.is_some() || self.sync_task.is_some() {
+                    self.sync_preview = None;
+                    self.sync_task = None;
+                } else if self.folder_stats.is_some() || self.stats_task.is_some() {
+                    self.close_folder_stats();
+                } else if self.theme_editor_backup.is_some() {
+                    self.cancel_theme_editor();
+                } else if self.show_compare_dialog {
+                    self.show_compare_dialog = false;
+                    self.comparison_result = None;
+                } else if self.three_way.is_some() {
+                    self.three_way = None;
+                } else if self.batch_summary.is_some() {
+                    self.batch_summary = None;
+                } else if self.show_settings_dialog {
+                    self.show_settings_dialog = false;
+                } else if self.pending_compress.is_some() {
+                    self.close_compress_dialog();
+                }
+            }
+
+            // Legacy shortcuts
+            if i.modifiers.ctrl && i.key_pressed(egui::Key::C) {
+                self.copy_to_clipboard();
             }
-
-            // Alt+Left - Navigate back
-            if i.modifiers.alt && i.key_pressed(egui::Key::ArrowLeft) {
-                if let Err(e) = self.get_active_pane_mut().navigate_back() {
-                    self.status_message = format!("Error: {}", e);
-                } else {
-                    self.status_message = "Navigated back".to_string();
-                }
+            if i.modifiers.ctrl && i.key_pressed(egui::Key::X) {
+                self.cut_to_clipboard();
             }
-
-            // Alt+Right - Navigate forward
-            if i.modifiers.alt && i.key_pressed(egui::Key::ArrowRight) {
-                if let Err(e) = self.get_active_pane_mut().navigate_forward() {
-                    self.status_message = format!("Error: {}", e);
-                } else {
-                    self.status_message = "Navigated forward".to_string();
+            if i.modifiers.ctrl && i.key_pressed(egui::Key::V) {
+                if let Err(e) = self.paste_from_clipboard() {
+                    self.notify_error(format!("Paste failed: {}", e));
                 }
             }
+            if i.key_pressed(egui::Key::Delete) {
+                self.request_delete_selection();
+            }
+
+            // Everything else comes from the action registry. Space and
+            // Ctrl+A are typed into the filter while it is active.
+            Action::all()
+                .iter()
+                .copied()
+                .filter(|action| {
+                    action.shortcut().is_some_and(|shortcut| {
+                        i.modifiers.matches_exact(shortcut.modifiers)
+                            && i.key_pressed(shortcut.logical_key)
+                    })
+                })
+                .filter(|action| {
+                    !(self.filter_mode
+                        && matches!(
+                            action,
+                            Action::TogglePreview | Action::SelectAll | Action::DeselectAll
+                        ))
+                })
+                .collect::<Vec<_>>()
+        });
+
+        for action in triggered {
+            self.run_action(ctx, action);
+        }
+    }
 
+    /// Carry out one of the registered actions, from a keyboard shortcut,
+    /// a menu or the command palette.
+    fn run_action(&mut self, ctx: &egui::Context, action: Action) {
+        match action {
             // F2 - Refresh (Total Commander style)
-            if i.key_pressed(egui::Key::F2) {
-                let _ = self.left_pane.refresh();
-                let _ = self.right_pane.refresh();
+            Action::Refresh => {
+                let _ = self.refresh_both_panes();
                 self.status_message = "Refreshed both panes".to_string();
             }
-
-            // F3 - Open file with default app (Total Commander style)
-            if i.key_pressed(egui::Key::F3) {
+            Action::OpenFile => {
                 let _ = self.open_file_with_default_app();
             }
-
-            // F5 - Copy (Total Commander style)
-            if i.key_pressed(egui::Key::F5) {
-                self.copy_to_clipboard();
+            Action::RevealInFileManager => self.reveal_in_file_manager(),
+            Action::ContextMenu => self.open_context_menu_from_keyboard(ctx),
+            Action::Copy => self.copy_to_clipboard(),
+            Action::Cut => self.cut_to_clipboard(),
+            Action::CopyTo => self.open_transfer_dialog(TransferMode::Copy),
+            Action::MoveTo => self.open_transfer_dialog(TransferMode::Move),
+            Action::Paste => {
+                if let Err(e) = self.paste_from_clipboard() {
+                    self.notify_error(format!("Paste failed: {}", e));
+                }
             }
-
-            // F6 - Cut/Move (Total Commander style)
-            if i.key_pressed(egui::Key::F6) {
-                self.cut_to_clipboard();
+            Action::PasteIntoFolder => {
+                if let Err(e) = self.paste_into_selected_folder() {
+                    self.notify_error(format!("Paste failed: {}", e));
+                }
             }
-
-            // F7 - Paste
-            if i.key_pressed(egui::Key::F7) {
-                let _ = self.paste_from_clipboard();
+            Action::Delete => self.request_delete_selection(),
+            Action::Rename => self.open_rename_dialog(),
+            Action::Touch => self.open_touch_dialog(),
+            Action::Permissions => self.open_permissions_dialog(),
+            Action::Duplicate => self.duplicate_selection(),
+            Action::NewFolder => self.open_new_folder_dialog(),
+            Action::NewFolderWithSelection => self.open_new_folder_with_selection(),
+            Action::CompressToZip => {
+                let _ = self.compress_item(false);
             }
-
-            // F8 - Delete (Total Commander style)
-            if i.key_pressed(egui::Key::F8) {
-                let selected_items = self.get_active_pane().get_selected_items();
-                if !selected_items.is_empty() {
-                    let count = selected_items.len();
-                    let names: Vec<String> = selected_items.iter().map(|item| item.name.clone()).collect();
-                    self.item_to_delete = Some(if count == 1 {
-                        names[0].clone()
-                    } else {
-                        format!("{} items", count)
-                    });
-                    self.show_delete_confirm = true;
-                }
+            Action::ExtractArchive => {
+                let _ = self.extract_archive();
             }
-
-            // Ctrl+H - Toggle hidden files
-            if i.modifiers.ctrl && i.key_pressed(egui::Key::H) {
+            Action::ToggleHiddenFiles => {
                 self.show_hidden_files = !self.show_hidden_files;
-                let _ = self.left_pane.refresh();
-                let _ = self.right_pane.refresh();
+                let _ = self.refresh_both_panes();
                 self.status_message = if self.show_hidden_files {
                     "Showing hidden files".to_string()
                 } else {
                     "Hiding hidden files".to_string()
                 };
             }
-
-            // Ctrl+B - Toggle sidebar
-            if i.modifiers.ctrl && i.key_pressed(egui::Key::B) {
+            Action::TogglePreview => {
+                self.show_preview_panel = !self.show_preview_panel;
+                if self.show_preview_panel {
+                    self.update_previews();
+                }
+            }
+            Action::ToggleSidebar => {
                 self.show_sidebar = !self.show_sidebar;
                 self.status_message = if self.show_sidebar {
                     "Sidebar shown".to_string()
@@ -3765,9 +10733,30 @@ impl FileManagerApp {
                     "Sidebar hidden".to_string()
                 };
             }
-
-            // Ctrl+F - Toggle filter mode
-            if i.modifiers.ctrl && !i.modifiers.shift && i.key_pressed(egui::Key::F) {
+            Action::ToggleMirrorNavigation => self.toggle_mirror_navigation(),
+            Action::ToggleReadOnlyMode => self.toggle_read_only_mode(),
+            Action::ToggleLogPanel => self.toggle_log_panel(),
+            Action::SwitchPane => {
+                self.active_pane = if self.active_pane == 0 { 1 } else { 0 };
+            }
+            Action::SwapPanes => self.swap_panes(),
+            Action::OtherPaneToThisFolder => self.equalize_panes(true),
+            Action::ThisPaneToOtherFolder => self.equalize_panes(false),
+            Action::NavigateBack => {
+                if let Err(e) = self.get_active_pane_mut().navigate_back() {
+                    self.status_message = format!("Error: {}", e);
+                } else {
+                    self.status_message = "Navigated back".to_string();
+                }
+            }
+            Action::NavigateForward => {
+                if let Err(e) = self.get_active_pane_mut().navigate_forward() {
+                    self.status_message = format!("Error: {}", e);
+                } else {
+                    self.status_message = "Navigated forward".to_string();
+                }
+            }
+            Action::Filter => {
                 self.filter_mode = !self.filter_mode;
                 if !self.filter_mode {
                     // Clear filter when exiting
@@ -3779,76 +10768,237 @@ impl FileManagerApp {
                     "Filter cleared".to_string()
                 };
             }
-
-            // Ctrl+Shift+F - Advanced Search
-            if i.modifiers.ctrl && i.modifiers.shift && i.key_pressed(egui::Key::F) {
-                self.search_criteria.search_path = self.get_active_pane().current_path.clone();
+            Action::AdvancedSearch => {
+                self.search_criteria.search_paths =
+                    vec![self.get_active_pane().current_path.clone()];
                 self.show_search_dialog = true;
             }
-
-            // ESC - Exit filter mode or close dialogs
-            if i.key_pressed(egui::Key::Escape) {
-                if self.filter_mode {
-                    self.filter_mode = false;
-                    self.get_active_pane_mut().filter_text.clear();
-                    self.status_message = "Filter cleared".to_string();
-                } else if self.show_new_folder_dialog {
-                    self.show_new_folder_dialog = false;
-                    self.new_folder_name.clear();
-                } else if self.show_rename_dialog {
-                    self.show_rename_dialog = false;
-                    self.rename_new_name.clear();
-                } else if self.show_about_dialog {
-                    self.show_about_dialog = false;
-                } else if self.show_properties_dialog {
-                    self.show_properties_dialog = false;
-                    self.properties_item = None;
-                } else if self.show_delete_confirm {
-                    self.show_delete_confirm = false;
-                    self.item_to_delete = None;
-                } else if self.show_search_dialog {
-                    self.show_search_dialog = false;
-                } else if self.show_compare_dialog {
-                    self.show_compare_dialog = false;
-                    self.comparison_result = None;
-                }
-            }
-
-            // Ctrl+D - Compare files
-            if i.modifiers.ctrl && i.key_pressed(egui::Key::D) {
+            Action::CompareFiles => {
                 let _ = self.compare_selected_files();
             }
+            Action::CompareWithClipboard => self.compare_with_clipboard(),
+            Action::ThreeWayCompare => self.open_three_way_compare(),
+            Action::DiffAgainstHead => {
+                let _ = self.compare_with_head();
+            }
+            Action::FolderStatistics => self.open_folder_stats(),
+            Action::SyncLeftToRight => self.open_sync_dialog(0),
+            Action::SyncRightToLeft => self.open_sync_dialog(1),
+            Action::SelectAll => self.select_all(),
+            Action::InvertSelection => self.invert_selection(),
+            Action::DeselectAll => self.deselect_all(),
+            Action::MountPoints => self.open_mounts_dialog(),
+            Action::AddBookmark => {
+                self.show_add_bookmark_dialog = true;
+                self.new_bookmark_name = self
+                    .get_active_pane()
+                    .current_path
+                    .file_name()
+                    .and_then(|n| n.to_str())
+                    .unwrap_or("Bookmark")
+                    .to_string();
+            }
+            Action::Themes => self.show_theme_selector = true,
+            Action::Settings => self.show_settings_dialog = true,
+            Action::About => self.show_about_dialog = true,
+            Action::CommandPalette => {
+                self.command_palette = Some(CommandPalette::default());
+            }
+            Action::Exit => ctx.send_viewport_cmd(egui::ViewportCommand::Close),
+        }
+    }
 
-            // Ctrl+N - New folder
-            if i.modifiers.ctrl && i.key_pressed(egui::Key::N) {
-                self.show_new_folder_dialog = true;
+    /// Everything the command palette offers: the registered actions, themes,
+    /// bookmarks and custom commands, each with a label, a hint and its target.
+    fn palette_entries(&self, ctx: &egui::Context) -> Vec<(String, String, PaletteTarget)> {
+        let mut entries = Vec::new();
+        for action in Action::all() {
+            if *action == Action::CommandPalette {
+                continue;
             }
+            let hint = action
+                .shortcut()
+                .map(|shortcut| ctx.format_shortcut(&shortcut))
+                .unwrap_or_default();
+            entries.push((action.name().to_string(), hint, PaletteTarget::Action(*action)));
+        }
+        for theme in Theme::all() {
+            entries.push((
+                format!("Theme: {}", theme.name()),
+                String::new(),
+                PaletteTarget::Theme(theme),
+            ));
+        }
+        for bookmark in self.bookmark_manager.get_bookmarks() {
+            entries.push((
+                format!("Bookmark: {}", bookmark.name),
+                bookmark.path.display().to_string(),
+                PaletteTarget::Folder(bookmark.path.clone()),
+            ));
+        }
+        for (index, command) in self.settings.custom_commands.iter().enumerate() {
+            entries.push((
+                format!("Run: {}", command.name),
+                command.command.clone(),
+                PaletteTarget::CustomCommand(index),
+            ));
+        }
+        entries
+    }
 
-            // Space - Toggle preview panel and update preview
-            if i.key_pressed(egui::Key::Space) && !self.filter_mode {
-                self.show_preview_panel = !self.show_preview_panel;
-                if self.show_preview_panel {
-                    self.update_previews();
+    /// The command palette: a query field and the entries matching it
+    /// fuzzily, best first. Arrow keys move, Enter runs, Escape closes.
+    fn render_command_palette(&mut self, ctx: &egui::Context) {
+        let Some(palette) = &self.command_palette else {
+            return;
+        };
+        let mut query = palette.query.clone();
+        let mut selected = palette.selected;
+
+        let mut matches: Vec<(i64, Vec<usize>, String, String, PaletteTarget)> = self
+            .palette_entries(ctx)
+            .into_iter()
+            .filter_map(|(label, hint, target)| {
+                crate::filesystem::fuzzy_match(&label, &query)
+                    .map(|(score, positions)| (score, positions, label, hint, target))
+            })
+            .collect();
+        // Stable, so equally good matches keep the registry order
+        matches.sort_by_key(|entry| std::cmp::Reverse(entry.0));
+
+        // A typed folder path ("/tmp", "~/src") comes first
+        let typed = query.trim();
+        let typed_path = match typed.strip_prefix('~') {
+            Some(rest) => dirs::home_dir()
+                .map(|home| home.join(rest.trim_start_matches(['/', '\\'])))
+                .unwrap_or_default(),
+            None => PathBuf::from(typed),
+        };
+        if typed_path.is_absolute() && typed_path.is_dir() {
+            let label = format!("Go to {}", typed_path.display());
+            matches.insert(0, (0, Vec::new(), label, String::new(), PaletteTarget::Folder(typed_path)));
+        }
+
+        let (up, down, enter, escape) = ctx.input(|i| {
+            (
+                i.key_pressed(egui::Key::ArrowUp),
+                i.key_pressed(egui::Key::ArrowDown),
+                i.key_pressed(egui::Key::Enter),
+                i.key_pressed(egui::Key::Escape),
+            )
+        });
+        if down {
+            selected += 1;
+        }
+        if up {
+            selected = selected.saturating_sub(1);
+        }
+        selected = selected.min(matches.len().saturating_sub(1));
+
+        let mut chosen = enter.then_some(selected);
+        let close = escape;
+        egui::Window::new("⌨ Command Palette")
+            .collapsible(false)
+            .resizable(false)
+            .title_bar(false)
+            .anchor(egui::Align2::CENTER_TOP, [0.0, 80.0])
+            .frame(
+                egui::Frame::window(&ctx.style())
+                    .fill(egui::Color32::from_rgb(45, 47, 50))
+                    .rounding(12.0)
+                    .inner_margin(12.0),
+            )
+            .show(ctx, |ui| {
+                ui.set_width(480.0);
+                let response = ui.add_sized(
+                    [ui.available_width(), 26.0],
+                    egui::TextEdit::singleline(&mut query)
+                        .hint_text("Type a command, theme, bookmark or folder path…"),
+                );
+                response.request_focus();
+                if response.changed() {
+                    selected = 0;
                 }
-            }
+                ui.add_space(6.0);
 
-            // Legacy shortcuts
-            if i.modifiers.ctrl && i.key_pressed(egui::Key::C) {
-                self.copy_to_clipboard();
-            }
-            if i.modifiers.ctrl && i.key_pressed(egui::Key::X) {
-                self.cut_to_clipboard();
-            }
-            if i.modifiers.ctrl && i.key_pressed(egui::Key::V) {
-                let _ = self.paste_from_clipboard();
-            }
-            if i.key_pressed(egui::Key::Delete) {
-                if let Some(item) = self.get_active_pane().get_selected_item() {
-                    self.item_to_delete = Some(item.name.clone());
-                    self.show_delete_confirm = true;
+                if matches.is_empty() {
+                    ui.label(
+                        egui::RichText::new("No matching commands")
+                            .size(12.0)
+                            .color(egui::Color32::from_rgb(154, 160, 166)),
+                    );
+                    return;
+                }
+                egui::ScrollArea::vertical()
+                    .max_height(320.0)
+                    .auto_shrink([false, true])
+                    .show(ui, |ui| {
+                        for (index, (_, positions, label, hint, _)) in matches.iter().enumerate() {
+                            let is_selected = index == selected;
+                            let job = highlight_chars(
+                                label,
+                                positions,
+                                13.0,
+                                ui.visuals().text_color(),
+                                egui::Color32::from_rgb(220, 170, 90),
+                            );
+                            let row = ui.horizontal(|ui| {
+                                let response = ui.selectable_label(is_selected, job);
+                                if !hint.is_empty() {
+                                    ui.with_layout(
+                                        egui::Layout::right_to_left(egui::Align::Center),
+                                        |ui| {
+                                            ui.add(
+                                                egui::Label::new(
+                                                    egui::RichText::new(hint)
+                                                        .size(11.0)
+                                                        .color(egui::Color32::from_rgb(
+                                                            154, 160, 166,
+                                                        )),
+                                                )
+                                                .truncate(),
+                                            );
+                                        },
+                                    );
+                                }
+                                response
+                            });
+                            if is_selected && (up || down) {
+                                row.inner.scroll_to_me(None);
+                            }
+                            if row.inner.clicked() {
+                                chosen = Some(index);
+                            }
+                        }
+                    });
+            });
+
+        if let Some(index) = chosen.filter(|&index| index < matches.len()) {
+            self.command_palette = None;
+            match matches.swap_remove(index).4 {
+                PaletteTarget::Action(action) => self.run_action(ctx, action),
+                PaletteTarget::Theme(theme) => {
+                    self.current_theme = theme;
+                    self.settings.theme = theme;
+                    self.save_settings();
+                    self.status_message = format!("Theme changed to: {}", theme.name());
+                }
+                PaletteTarget::Folder(path) => {
+                    match self.get_active_pane_mut().navigate_to(path.clone()) {
+                        Ok(()) => {
+                            self.status_message = format!("Navigated to {}", path.display())
+                        }
+                        Err(e) => self.notify_error(format!("Cannot open {}: {}", path.display(), e)),
+                    }
                 }
+                PaletteTarget::CustomCommand(index) => self.run_custom_command(index),
             }
-        });
+        } else if close {
+            self.command_palette = None;
+        } else if let Some(palette) = &mut self.command_palette {
+            palette.query = query;
+            palette.selected = selected;
+        }
     }
 
     fn update_previews(&mut self) {
@@ -3858,17 +11008,56 @@ impl FileManagerApp {
 
     fn update_left_preview(&mut self) {
         if let Some(item) = self.left_pane.get_selected_item() {
-            self.preview_content_left = self.generate_preview_content(&item);
+            self.preview_content_left = self.generate_preview_content(item);
+            self.preview_stamps[0] = file_stamp(&item.path);
         } else {
             self.preview_content_left = None;
+            self.preview_stamps[0] = None;
         }
     }
 
     fn update_right_preview(&mut self) {
         if let Some(item) = self.right_pane.get_selected_item() {
-            self.preview_content_right = self.generate_preview_content(&item);
+            self.preview_content_right = self.generate_preview_content(item);
+            self.preview_stamps[1] = file_stamp(&item.path);
         } else {
             self.preview_content_right = None;
+            self.preview_stamps[1] = None;
+        }
+    }
+
+    /// Every `CHANGE_CHECK_INTERVAL`, flag panes whose folder changed on disk
+    /// (they show a banner offering to reload) and re-read previews of files
+    /// that were modified since they were shown.
+    fn check_external_changes(&mut self, ctx: &egui::Context) {
+        ctx.request_repaint_after(CHANGE_CHECK_INTERVAL);
+        if self.last_change_check.elapsed() < CHANGE_CHECK_INTERVAL {
+            return;
+        }
+        self.last_change_check = std::time::Instant::now();
+
+        self.left_pane.check_for_changes();
+        self.right_pane.check_for_changes();
+        for pane_index in 0..2 {
+            let pane = self.get_pane(pane_index);
+            if pane.changed_on_disk {
+                let path = pane.current_path.clone();
+                self.invalidate_search_index(&path);
+            }
+        }
+
+        if self.show_preview_panel {
+            let modified = |stamp: &Option<(PathBuf, std::time::SystemTime)>| {
+                stamp
+                    .as_ref()
+                    .is_some_and(|(path, _)| file_stamp(path) != *stamp)
+            };
+            if modified(&self.preview_stamps[0]) {
+                self.update_left_preview();
+            }
+            if modified(&self.preview_stamps[1]) {
+                self.update_right_preview();
+            }
         }
     }
 
@@ -3911,13 +11100,46 @@ impl FileManagerApp {
 
                     return Some(PreviewContent::Pdf {
                         name: item.name.clone(),
-                        size: crate::filesystem::format_size(item.size),
+                        path: path.clone(),
+                        size: crate::filesystem::format_size_with(item.size, self.settings.size_units),
                         modified: crate::filesystem::format_date(item.modified),
                         pages,
                         image,
                     });
                 }
 
+                if matches!(ext_lower.as_str(), "mp3" | "flac" | "wav" | "ogg" | "m4a") {
+                    if let Ok(info) = crate::filesystem::read_audio_info(path) {
+                        let seconds = info.duration.as_secs();
+                        let duration = if seconds >= 3600 {
+                            format!("{}:{:02}:{:02}", seconds / 3600, seconds / 60 % 60, seconds % 60)
+                        } else {
+                            format!("{}:{:02}", seconds / 60, seconds % 60)
+                        };
+                        let quality = match (info.bitrate, info.sample_rate) {
+                            (Some(bitrate), Some(rate)) => {
+                                Some(format!("{} kbps • {:.1} kHz", bitrate, rate as f32 / 1000.0))
+                            }
+                            (Some(bitrate), None) => Some(format!("{} kbps", bitrate)),
+                            (None, Some(rate)) => Some(format!("{:.1} kHz", rate as f32 / 1000.0)),
+                            (None, None) => None,
+                        };
+                        return Some(PreviewContent::Audio {
+                            name: item.name.clone(),
+                            path: path.clone(),
+                            size: crate::filesystem::format_size_with(item.size, self.settings.size_units),
+                            title: info.title,
+                            artist: info.artist,
+                            album: info.album,
+                            duration,
+                            quality,
+                            cover: info
+                                .cover
+                                .and_then(|data| image::load_from_memory(&data).ok()),
+                        });
+                    }
+                }
+
                 // Check if it's a text file
                 if matches!(
                     ext_lower.as_str(),
@@ -3939,9 +11161,12 @@ impl FileManagerApp {
                         | "yaml"
                         | "yml"
                 ) {
-                    if let Ok(content) = std::fs::read_to_string(path) {
-                        let lines: Vec<&str> = content.lines().take(20).collect();
-                        return Some(PreviewContent::Text(lines.join("\n")));
+                    if let Ok(decoded) = crate::filesystem::read_text_file(path) {
+                        let lines: Vec<&str> = decoded.text.lines().take(20).collect();
+                        return Some(PreviewContent::Text(
+                            lines.join("\n"),
+                            decoded.encoding_note(),
+                        ));
                     }
                 }
             }
@@ -3953,13 +11178,44 @@ impl FileManagerApp {
 
         Some(PreviewContent::FileInfo {
             name: item.name.clone(),
-            size: crate::filesystem::format_size(item.size),
+            path: path.display().to_string(),
+            size: crate::filesystem::format_size_with(item.size, self.settings.size_units),
             modified: crate::filesystem::format_date(item.modified),
             permissions: format!("{}\n{}", summary, details),
             is_dir: item.is_dir,
         })
     }
 
+    /// Strip above the preview panel that resizes it when dragged; the new
+    /// height is saved when the drag ends. Double-click restores the default.
+    fn preview_resize_handle(&mut self, ui: &mut egui::Ui, height: f32, max_height: f32) {
+        let (rect, response) = ui.allocate_exact_size(
+            egui::vec2(ui.available_width(), 6.0),
+            egui::Sense::click_and_drag(),
+        );
+        let response = response
+            .on_hover_cursor(egui::CursorIcon::ResizeVertical)
+            .on_hover_text("Drag to resize the preview, double-click to reset");
+        if response.hovered() || response.dragged() {
+            ui.painter().hline(
+                rect.x_range(),
+                rect.center().y,
+                egui::Stroke::new(2.0, ui.visuals().selection.bg_fill),
+            );
+        }
+        if response.dragged() {
+            // Dragging up makes the preview taller
+            self.settings.preview_height =
+                (height - response.drag_delta().y).clamp(MIN_PREVIEW_HEIGHT, max_height);
+        }
+        if response.double_clicked() {
+            self.settings.preview_height = DEFAULT_PREVIEW_HEIGHT;
+            self.save_settings();
+        } else if response.drag_stopped() {
+            self.save_settings();
+        }
+    }
+
     fn render_preview_panel(&mut self, ui: &mut egui::Ui, ctx: &egui::Context) {
         let visuals = ctx.style().visuals.clone();
         let panel_fill = visuals.panel_fill;
@@ -3971,10 +11227,12 @@ impl FileManagerApp {
             .rounding(8.0)
             .inner_margin(12.0)
             .show(ui, |ui| {
-                ui.set_height(188.0);
+                let font_scale = self.settings.density.font_scale();
+                // Fill the height the panel was resized to
+                ui.set_height(ui.available_height());
 
                 ui.horizontal(|ui| {
-                    ui.label(egui::RichText::new("👁 Dual Preview").size(14.0).strong());
+                    ui.label(egui::RichText::new("👁 Dual Preview").size(14.0 * font_scale).strong());
                     ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
                         if ui.small_button("✗").clicked() {
                             self.show_preview_panel = false;
@@ -3992,12 +11250,12 @@ impl FileManagerApp {
                 let preview_width = (available_width - divider_width) / 2.0;
 
                 ui.horizontal(|ui| {
-                    ui.set_height(140.0);
+                    ui.set_height(ui.available_height());
 
                     // LEFT PREVIEW (fixed width)
                     ui.vertical(|ui| {
                         ui.set_width(preview_width);
-                        ui.label(egui::RichText::new("Left Pane").size(11.0).strong());
+                        ui.label(egui::RichText::new("Left Pane").size(11.0 * font_scale).strong());
                         self.render_preview_content(
                             ui,
                             ctx,
@@ -4011,7 +11269,7 @@ impl FileManagerApp {
                     // RIGHT PREVIEW (fixed width)
                     ui.vertical(|ui| {
                         ui.set_width(preview_width);
-                        ui.label(egui::RichText::new("Right Pane").size(11.0).strong());
+                        ui.label(egui::RichText::new("Right Pane").size(11.0 * font_scale).strong());
                         self.render_preview_content(
                             ui,
                             ctx,
@@ -4028,41 +11286,37 @@ impl FileManagerApp {
         ctx: &egui::Context,
         content: &Option<PreviewContent>,
     ) {
+        let font_scale = self.settings.density.font_scale();
         if let Some(content) = content {
             match content {
-                PreviewContent::Text(text) => {
+                PreviewContent::Text(text, encoding_note) => {
+                    if let Some(note) = encoding_note {
+                        ui.label(
+                            egui::RichText::new(format!("ℹ {}", note))
+                                .size(10.0 * font_scale)
+                                .color(egui::Color32::from_rgb(220, 170, 90)),
+                        );
+                    }
                     egui::ScrollArea::vertical()
-                        .max_height(120.0)
+                        .max_height(ui.available_height())
                         .show(ui, |ui| {
                             ui.label(
                                 egui::RichText::new(text)
                                     .family(egui::FontFamily::Monospace)
-                                    .size(10.0)
+                                    .size(10.0 * font_scale)
                                     .color(egui::Color32::from_rgb(189, 193, 198)),
                             );
                         });
                 }
-                PreviewContent::Image(path) => match image::open(path) {
-                    Ok(img) => {
-                        let size = [img.width() as usize, img.height() as usize];
-                        let rgba = img.to_rgba8();
-                        let pixels = rgba.as_flat_samples();
-
-                        let color_image = egui::ColorImage::from_rgba_unmultiplied(
-                            size,
-                            pixels.as_slice(),
-                        );
-
-                        let texture = ctx.load_texture(
-                            format!("preview_image_{}", path.display()),
-                            color_image,
-                            egui::TextureOptions::LINEAR,
-                        );
-
+                PreviewContent::Image(path) => match self
+                    .texture_cache
+                    .get_or_load(ctx, path, || decode_image_file(path))
+                {
+                    Ok(texture) => {
                         let img_size = texture.size_vec2();
                         let available_width = ui.available_width();
                         let scale = (available_width / img_size.x)
-                            .min(120.0 / img_size.y)
+                            .min(ui.available_height() / img_size.y)
                             .min(1.0);
                         let display_size = img_size * scale;
 
@@ -4072,12 +11326,13 @@ impl FileManagerApp {
                         ui.label(
                             egui::RichText::new(format!("Error: {}", e))
                                 .color(egui::Color32::RED)
-                                .size(10.0),
+                                .size(10.0 * font_scale),
                         );
                     }
                 },
                 PreviewContent::Pdf {
                     name,
+                    path,
                     size,
                     modified,
                     pages,
@@ -4086,19 +11341,19 @@ impl FileManagerApp {
                     ui.vertical(|ui| {
                         // File info header
                         ui.horizontal(|ui| {
-                            ui.label(egui::RichText::new("📋").size(16.0));
+                            ui.label(egui::RichText::new("📋").size(16.0 * font_scale));
                             ui.add_space(6.0);
                             ui.vertical(|ui| {
-                                ui.label(egui::RichText::new(name).size(11.0).strong());
+                                ui.label(egui::RichText::new(name).size(11.0 * font_scale).strong());
                                 ui.add_space(1.0);
                                 ui.label(
                                     egui::RichText::new(format!("Size: {} | Pages: {}", size, pages))
-                                        .size(9.0)
+                                        .size(9.0 * font_scale)
                                         .color(egui::Color32::from_rgb(189, 193, 198)),
                                 );
                                 ui.label(
                                     egui::RichText::new(format!("Modified: {}", modified))
-                                        .size(9.0)
+                                        .size(9.0 * font_scale)
                                         .color(egui::Color32::from_rgb(189, 193, 198)),
                                 );
                             });
@@ -4107,21 +11362,12 @@ impl FileManagerApp {
                         ui.add_space(4.0);
 
                         // Display rendered image if available
-                        if let Some(img) = image {
-                            let img_size = [img.width() as usize, img.height() as usize];
-                            let rgba = img.to_rgba8();
-                            let pixels = rgba.as_flat_samples();
-
-                            let color_image = egui::ColorImage::from_rgba_unmultiplied(
-                                img_size,
-                                pixels.as_slice(),
-                            );
-
-                            let texture = ctx.load_texture(
-                                format!("pdf_preview_{}", name),
-                                color_image,
-                                egui::TextureOptions::LINEAR,
-                            );
+                        let texture = image.as_ref().and_then(|img| {
+                            self.texture_cache
+                                .get_or_load(ctx, path, || Ok(to_color_image(img)))
+                                .ok()
+                        });
+                        if let Some(texture) = texture {
 
                             let img_size = texture.size_vec2();
                             let available_width = ui.available_width();
@@ -4135,19 +11381,80 @@ impl FileManagerApp {
                         } else {
                             ui.label(
                                 egui::RichText::new("PDF preview not available")
-                                    .size(9.0)
+                                    .size(9.0 * font_scale)
                                     .color(egui::Color32::GRAY),
                             );
                             ui.label(
                                 egui::RichText::new("(PDFium library may not be installed)")
-                                    .size(8.0)
+                                    .size(8.0 * font_scale)
                                     .color(egui::Color32::DARK_GRAY),
                             );
                         }
                     });
                 }
+                PreviewContent::Audio {
+                    name,
+                    path,
+                    size,
+                    title,
+                    artist,
+                    album,
+                    duration,
+                    quality,
+                    cover,
+                } => {
+                    ui.vertical(|ui| {
+                        ui.horizontal(|ui| {
+                            ui.label(egui::RichText::new("🎵").size(16.0 * font_scale));
+                            ui.add_space(6.0);
+                            ui.vertical(|ui| {
+                                ui.label(
+                                    egui::RichText::new(title.as_deref().unwrap_or(name))
+                                        .size(11.0 * font_scale)
+                                        .strong(),
+                                );
+                                ui.add_space(1.0);
+                                for line in [artist, album].into_iter().flatten() {
+                                    ui.label(
+                                        egui::RichText::new(line)
+                                            .size(10.0 * font_scale)
+                                            .color(egui::Color32::from_rgb(232, 234, 237)),
+                                    );
+                                }
+                                ui.label(
+                                    egui::RichText::new(format!("Duration: {} | Size: {}", duration, size))
+                                        .size(9.0 * font_scale)
+                                        .color(egui::Color32::from_rgb(189, 193, 198)),
+                                );
+                                if let Some(quality) = quality {
+                                    ui.label(
+                                        egui::RichText::new(quality)
+                                            .size(9.0 * font_scale)
+                                            .color(egui::Color32::from_rgb(189, 193, 198)),
+                                    );
+                                }
+                            });
+                        });
+
+                        let texture = cover.as_ref().and_then(|img| {
+                            self.texture_cache
+                                .get_or_load(ctx, path, || Ok(to_color_image(img)))
+                                .ok()
+                        });
+                        if let Some(texture) = texture {
+                            ui.add_space(4.0);
+
+                            let img_size = texture.size_vec2();
+                            let scale = (ui.available_width() / img_size.x)
+                                .min(ui.available_height() / img_size.y)
+                                .min(1.0);
+                            ui.add(egui::Image::new(&texture).max_size(img_size * scale));
+                        }
+                    });
+                }
                 PreviewContent::FileInfo {
                     name,
+                    path,
                     size,
                     modified,
                     permissions,
@@ -4155,27 +11462,56 @@ impl FileManagerApp {
                 } => {
                     ui.horizontal(|ui| {
                         let icon = if *is_dir { "📁" } else { "📄" };
-                        ui.label(egui::RichText::new(icon).size(20.0));
+                        ui.label(egui::RichText::new(icon).size(20.0 * font_scale));
                         ui.add_space(6.0);
                         ui.vertical(|ui| {
-                            ui.label(egui::RichText::new(name).size(12.0).strong());
+                            ui.add(
+                                egui::Label::new(egui::RichText::new(name).size(12.0 * font_scale).strong())
+                                    .selectable(true),
+                            );
+                            // Roughly how many 10pt characters fit; the full
+                            // path is still what gets copied.
+                            let max_chars = (ui.available_width() / 6.0).max(16.0) as usize;
+                            ui.horizontal(|ui| {
+                                ui.add(
+                                    egui::Label::new(
+                                        egui::RichText::new(crate::filesystem::middle_ellipsis(
+                                            path, max_chars,
+                                        ))
+                                        .size(10.0 * font_scale)
+                                        .color(egui::Color32::from_rgb(154, 160, 166)),
+                                    )
+                                    .selectable(true),
+                                )
+                                .on_hover_text(path.as_str());
+                                if ui
+                                    .small_button("📋")
+                                    .on_hover_text("Copy full path")
+                                    .clicked()
+                                {
+                                    ui.output_mut(|o| o.copied_text = path.clone());
+                                }
+                            });
                             ui.add_space(2.0);
                             ui.label(
                                 egui::RichText::new(format!("Size: {}", size))
-                                    .size(10.0)
+                                    .size(10.0 * font_scale)
                                     .color(egui::Color32::from_rgb(189, 193, 198)),
                             );
                             ui.label(
                                 egui::RichText::new(format!("Modified: {}", modified))
-                                    .size(10.0)
+                                    .size(10.0 * font_scale)
                                     .color(egui::Color32::from_rgb(189, 193, 198)),
                             );
                             ui.add_space(2.0);
-                            ui.label(
-                                egui::RichText::new(permissions)
-                                    .family(egui::FontFamily::Monospace)
-                                    .size(9.0)
-                                    .color(egui::Color32::from_rgb(138, 180, 248)),
+                            ui.add(
+                                egui::Label::new(
+                                    egui::RichText::new(permissions)
+                                        .family(egui::FontFamily::Monospace)
+                                        .size(9.0 * font_scale)
+                                        .color(egui::Color32::from_rgb(138, 180, 248)),
+                                )
+                                .selectable(true),
                             );
                         });
                     });
@@ -4186,7 +11522,7 @@ impl FileManagerApp {
                 ui.add_space(30.0);
                 ui.label(
                     egui::RichText::new("No file selected")
-                        .size(11.0)
+                        .size(11.0 * font_scale)
                         .color(egui::Color32::GRAY),
                 );
             });
@@ -4216,11 +11552,26 @@ impl FileManagerApp {
                                 .strong()
                                 .color(egui::Color32::from_rgb(154, 160, 166)),
                         );
+
+                        ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                            if ui
+                                .add_sized([24.0, 24.0], egui::Button::new(egui::RichText::new("➕").size(13.0)))
+                                .on_hover_text("Add current folder to Quick Access")
+                                .clicked()
+                            {
+                                let path = self.get_active_pane().current_path.clone();
+                                self.settings.add_quick_access(path.clone());
+                                self.save_settings();
+                                self.status_message =
+                                    format!("Added {} to Quick Access", path.display());
+                            }
+                        });
                     });
 
                     if self.sidebar_quick_access_expanded {
                         ui.add_space(5.0);
-                        let quick_items = crate::bookmarks::get_quick_access_items();
+                        let quick_items = self.settings.quick_access_items();
+                        let mut remove_path = None;
                         for item in quick_items {
                             let text = format!("{} {}", item.icon, item.name);
                             let response = ui.add(
@@ -4234,6 +11585,27 @@ impl FileManagerApp {
                                 let _ = self.get_active_pane_mut().navigate_to(path.clone());
                                 self.status_message = format!("Navigated to {}", path.display());
                             }
+
+                            let is_custom = self.settings.is_custom_quick_access(&item.path);
+                            response.context_menu(|ui| {
+                                let label = if is_custom {
+                                    "✗ Remove from Quick Access"
+                                } else {
+                                    "👁 Hide from Quick Access"
+                                };
+                                if ui.button(label).clicked() {
+                                    remove_path = Some(item.path.clone());
+                                    ui.close_menu();
+                                }
+                            });
+                        }
+
+                        if let Some(path) = remove_path {
+                            self.settings.remove_quick_access(&path);
+                            self.save_settings();
+                            self.status_message =
+                                "Quick Access updated (restore hidden entries in Settings)"
+                                    .to_string();
                         }
                     }
                 });
@@ -4341,7 +11713,7 @@ impl FileManagerApp {
 
                     if self.sidebar_devices_expanded {
                         ui.add_space(5.0);
-                        let mounts = crate::filesystem::get_mount_points();
+                        let mounts = self.mount_points();
                         for mount in mounts.iter().take(5) {
                             let icon = if mount.is_removable { "🔌" } else { "💾" };
                             let label = if let Some(name) = mount.mount_point.file_name() {
@@ -4370,7 +11742,7 @@ impl FileManagerApp {
                                 .button(egui::RichText::new("View all...").size(13.0))
                                 .clicked()
                             {
-                                self.show_mounts_dialog = true;
+                                self.open_mounts_dialog();
                             }
                         }
                     }
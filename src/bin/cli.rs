@@ -0,0 +1,150 @@
+use dual_pane_fm::app_gui::StartDirs;
+use dual_pane_fm::filesystem::{self, DiffLineType, SearchCriteria};
+
+use anyhow::{Result, anyhow};
+use clap::{Parser, Subcommand};
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+/// Headless access to the Filane file operations, for scripting. Without a
+/// command, opens the Filane window, optionally at the given folders.
+#[derive(Parser)]
+#[command(name = "filane", version, about, args_conflicts_with_subcommands = true)]
+struct Cli {
+    #[command(subcommand)]
+    command: Option<Command>,
+    #[command(flatten)]
+    start_dirs: StartDirs,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Compress a file or folder into a zip archive
+    Compress {
+        path: PathBuf,
+        /// Archive to create (defaults to `<path>.zip` next to the source)
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+    /// Extract a zip or tar (.tar, .tar.gz, .tar.bz2, .tar.xz) archive into a folder;
+    /// .7z and .rar need 7-Zip (7z) or unar installed
+    Extract { archive: PathBuf, dest: PathBuf },
+    /// Search one or more folders recursively
+    Search {
+        #[arg(required = true)]
+        dirs: Vec<PathBuf>,
+        /// Only match names containing this text
+        #[arg(short, long, default_value = "")]
+        name: String,
+        /// Only match files containing this text
+        #[arg(short, long, default_value = "")]
+        content: String,
+        #[arg(long)]
+        case_sensitive: bool,
+        #[arg(long)]
+        hidden: bool,
+        /// Skip files ignored by git inside repositories
+        #[arg(long)]
+        gitignore: bool,
+        /// Only match files with one of these extensions, e.g. "rs,toml"
+        #[arg(short, long, default_value = "")]
+        ext: String,
+    },
+    /// Compare two files line by line (exits with 1 if they differ)
+    Compare { a: PathBuf, b: PathBuf },
+}
+
+fn main() -> ExitCode {
+    match run(Cli::parse()) {
+        Ok(code) => code,
+        Err(e) => {
+            eprintln!("Error: {:#}", e);
+            ExitCode::from(2)
+        }
+    }
+}
+
+fn run(cli: Cli) -> Result<ExitCode> {
+    let Some(command) = cli.command else {
+        dual_pane_fm::app_gui::run(cli.start_dirs).map_err(|e| anyhow!("{}", e))?;
+        return Ok(ExitCode::SUCCESS);
+    };
+    match command {
+        Command::Compress { path, output } => {
+            let output = match output {
+                Some(output) => output,
+                None => {
+                    let name = path
+                        .file_name()
+                        .ok_or_else(|| anyhow!("Cannot compress {}", path.display()))?;
+                    path.with_file_name(format!("{}.zip", name.to_string_lossy()))
+                }
+            };
+            let skipped = filesystem::compress_to_zip(&path, &output)?;
+            for skipped_path in &skipped {
+                eprintln!("Skipped special file: {}", skipped_path.display());
+            }
+            println!("Compressed: {} → {}", path.display(), output.display());
+        }
+        Command::Extract { archive, dest } => {
+            std::fs::create_dir_all(&dest)?;
+            filesystem::extract_any(&archive, &dest)?;
+            println!("Extracted: {} → {}", archive.display(), dest.display());
+        }
+        Command::Search {
+            dirs,
+            name,
+            content,
+            case_sensitive,
+            hidden,
+            gitignore,
+            ext,
+        } => {
+            let criteria = SearchCriteria {
+                search_paths: dirs,
+                filename_pattern: name,
+                content_pattern: content,
+                case_sensitive,
+                include_hidden: hidden,
+                respect_gitignore: gitignore,
+                custom_extensions: filesystem::parse_extension_list(&ext),
+                ..Default::default()
+            };
+            let results = filesystem::search_files(&criteria)?;
+            for hit in &results.items {
+                println!("{}", hit.item.path.display());
+            }
+            if results.skipped_dirs > 0 {
+                eprintln!("{} unreadable folders skipped", results.skipped_dirs);
+            }
+        }
+        Command::Compare { a, b } => {
+            let comparison = filesystem::compare_files(&a, &b, filesystem::DiffOptions::default())?;
+            if comparison.are_identical {
+                println!("Files are identical");
+                return Ok(ExitCode::SUCCESS);
+            }
+
+            println!("--- {}", a.display());
+            println!("+++ {}", b.display());
+            for line in &comparison.diff_lines {
+                match line.line_type {
+                    DiffLineType::Equal => {}
+                    DiffLineType::Removed => println!("-{}", line.left_content),
+                    DiffLineType::Added => println!("+{}", line.right_content),
+                    DiffLineType::Modified => {
+                        println!("-{}", line.left_content);
+                        println!("+{}", line.right_content);
+                    }
+                }
+            }
+            println!(
+                "{} added, {} removed, {} modified",
+                comparison.right_only_lines, comparison.left_only_lines, comparison.modified_lines
+            );
+            return Ok(ExitCode::from(1));
+        }
+    }
+
+    Ok(ExitCode::SUCCESS)
+}
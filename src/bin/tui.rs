@@ -46,6 +46,7 @@ fn run_app<B: ratatui::backend::Backend>(terminal: &mut Terminal<B>, app: &mut A
             // Update scroll after drawing to get viewport height
             let viewport_height = f.size().height.saturating_sub(4) as usize; // Account for borders and status
             app.update_scroll(viewport_height);
+            app.viewport_height = viewport_height;
         })?;
 
         if event::poll(Duration::from_millis(100))? {
@@ -56,6 +57,9 @@ fn run_app<B: ratatui::backend::Backend>(terminal: &mut Terminal<B>, app: &mut A
                     match key.code {
                         KeyCode::Char('q') => app.quit(),
                         KeyCode::Char('m') => app.toggle_mounts(),
+                        KeyCode::Char('R') => {
+                            let _ = app.toggle_read_only_mode();
+                        }
                         KeyCode::Up | KeyCode::Char('k') => {
                             if shift_pressed {
                                 app.move_up_with_selection();
@@ -72,6 +76,19 @@ fn run_app<B: ratatui::backend::Backend>(terminal: &mut Terminal<B>, app: &mut A
                                 app.move_down();
                             }
                         }
+                        KeyCode::PageUp | KeyCode::PageDown | KeyCode::Home | KeyCode::End => {
+                            let page = app.viewport_height.saturating_sub(1).max(1) as isize;
+                            let delta = match key.code {
+                                KeyCode::PageUp => -page,
+                                KeyCode::PageDown => page,
+                                KeyCode::Home => isize::MIN,
+                                _ => isize::MAX,
+                            };
+                            if !shift_pressed {
+                                app.clear_selection();
+                            }
+                            app.move_by(delta, shift_pressed);
+                        }
                         KeyCode::Enter => {
                             let _ = app.enter_directory();
                         }
@@ -74,9 +74,7 @@ impl BookmarkManager {
     }
 
     fn get_config_path() -> Result<PathBuf> {
-        let config_dir =
-            dirs::config_dir().ok_or_else(|| anyhow::anyhow!("Could not find config directory"))?;
-        Ok(config_dir.join("dual-pane-fm").join("bookmarks.json"))
+        crate::settings::config_file_path("bookmarks.json")
     }
 }
 
@@ -86,6 +84,9 @@ impl Default for BookmarkManager {
     }
 }
 
+/// Default Quick Access entries, resolved through the platform's standard
+/// folder locations (XDG user dirs, Known Folders, ...). Folders that don't
+/// exist on this machine are left out.
 pub fn get_quick_access_items() -> Vec<Bookmark> {
     let mut items = Vec::new();
 
@@ -96,36 +97,21 @@ pub fn get_quick_access_items() -> Vec<Bookmark> {
             "🏠".to_string(),
         ));
 
-        items.push(Bookmark::new(
-            "Documents".to_string(),
-            home.join("Documents"),
-            "📄".to_string(),
-        ));
-        items.push(Bookmark::new(
-            "Downloads".to_string(),
-            home.join("Downloads"),
-            "📥".to_string(),
-        ));
-        items.push(Bookmark::new(
-            "Pictures".to_string(),
-            home.join("Pictures"),
-            "🖼️".to_string(),
-        ));
-        items.push(Bookmark::new(
-            "Music".to_string(),
-            home.join("Music"),
-            "🎵".to_string(),
-        ));
-        items.push(Bookmark::new(
-            "Videos".to_string(),
-            home.join("Videos"),
-            "🎬".to_string(),
-        ));
-        items.push(Bookmark::new(
-            "Desktop".to_string(),
-            home.join("Desktop"),
-            "🖥️".to_string(),
-        ));
+        let standard_dirs = [
+            ("Desktop", dirs::desktop_dir(), "🖥️"),
+            ("Documents", dirs::document_dir(), "📄"),
+            ("Downloads", dirs::download_dir(), "📥"),
+            ("Pictures", dirs::picture_dir(), "🖼️"),
+            ("Music", dirs::audio_dir(), "🎵"),
+            ("Videos", dirs::video_dir(), "🎬"),
+        ];
+        for (name, path, icon) in standard_dirs {
+            let path = path.unwrap_or_else(|| home.join(name));
+            // Unconfigured XDG dirs fall back to $HOME itself
+            if path != home && !items.iter().any(|item| item.path == path) {
+                items.push(Bookmark::new(name.to_string(), path, icon.to_string()));
+            }
+        }
 
         // Add Trash if available
         if let Some(trash_path) = crate::trash::get_trash_path() {
@@ -1,8 +1,11 @@
 use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::cell::RefCell;
 use std::collections::HashMap;
 use std::fs::{self, DirEntry};
-use std::io::{BufRead, BufReader, Read};
+use std::io::Read;
 use std::path::{Path, PathBuf};
+use std::rc::Rc;
 use std::time::SystemTime;
 use sysinfo::{DiskKind, Disks};
 
@@ -110,20 +113,371 @@ pub fn read_directory(path: &Path) -> Result<Vec<FileItem>> {
     Ok(items)
 }
 
+/// Folder containing `path`. Filesystem roots (`/`, `C:\`) have none, so
+/// operations that create something next to their target fail with a clear
+/// error instead of building a destination from the root itself.
+pub fn parent_dir(path: &Path) -> Result<&Path> {
+    match path.parent() {
+        Some(parent) if parent.as_os_str().is_empty() => Ok(Path::new(".")),
+        Some(parent) => Ok(parent),
+        None => Err(anyhow::anyhow!(
+            "{} is a filesystem root and has no parent folder",
+            path.display()
+        )),
+    }
+}
+
+/// `dir` joined with `name`, where `name` must be a single plain file name:
+/// not empty, not "." or "..", and without path separators.
+pub fn child_path(dir: &Path, name: &str) -> Result<PathBuf> {
+    let mut components = Path::new(name).components();
+    match (components.next(), components.next()) {
+        (Some(std::path::Component::Normal(part)), None) if part == name => Ok(dir.join(name)),
+        _ => Err(anyhow::anyhow!("\"{}\" is not a valid file name", name)),
+    }
+}
+
+/// Result of checking a proposed file or folder name before creating or
+/// renaming something.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum NameCheck {
+    Valid,
+    /// Usable here, but likely to cause trouble elsewhere (e.g. on Windows).
+    Warning(String),
+    /// Would be rejected by the filesystem or clash with an existing entry.
+    Invalid(String),
+}
+
+impl NameCheck {
+    pub fn is_invalid(&self) -> bool {
+        matches!(self, NameCheck::Invalid(_))
+    }
+}
+
+/// Whether two paths name the same entry, without following symlinks.
+#[cfg(unix)]
+fn same_entry(a: &Path, b: &Path) -> bool {
+    use std::os::unix::fs::MetadataExt;
+    match (fs::symlink_metadata(a), fs::symlink_metadata(b)) {
+        (Ok(a), Ok(b)) => (a.dev(), a.ino()) == (b.dev(), b.ino()),
+        _ => false,
+    }
+}
+
+/// Whether two paths name the same entry, judged by their canonical paths.
+#[cfg(not(unix))]
+fn same_entry(a: &Path, b: &Path) -> bool {
+    match (fs::canonicalize(a), fs::canonicalize(b)) {
+        (Ok(a), Ok(b)) => a == b,
+        _ => false,
+    }
+}
+
+/// Longest file name most filesystems accept, in bytes.
+const MAX_NAME_BYTES: usize = 255;
+
+const WINDOWS_RESERVED_CHARS: &[char] = &['<', '>', ':', '"', '\\', '|', '?', '*'];
+
+/// Check `name` as a new entry in `dir`. `current_name` is the entry being
+/// renamed, which doesn't count as a clash.
+pub fn check_new_name(dir: &Path, name: &str, current_name: Option<&str>) -> NameCheck {
+    if name.is_empty() {
+        return NameCheck::Invalid("Name cannot be empty".to_string());
+    }
+    if name == "." || name == ".." {
+        return NameCheck::Invalid(format!("\"{}\" is reserved", name));
+    }
+    if name.contains('/') {
+        return NameCheck::Invalid("Name cannot contain \"/\"".to_string());
+    }
+    if name.contains('\0') {
+        return NameCheck::Invalid("Name cannot contain a NUL character".to_string());
+    }
+    if name.len() > MAX_NAME_BYTES {
+        return NameCheck::Invalid(format!(
+            "Name is too long ({} bytes, at most {})",
+            name.len(),
+            MAX_NAME_BYTES
+        ));
+    }
+
+    let full_path = dir.join(name);
+    #[cfg(windows)]
+    let (path_len, max_path) = (full_path.as_os_str().len(), 259);
+    #[cfg(not(windows))]
+    let (path_len, max_path) = (full_path.as_os_str().len(), 4095);
+    if path_len > max_path {
+        return NameCheck::Invalid(format!(
+            "Path is too long ({} characters, at most {})",
+            path_len, max_path
+        ));
+    }
+
+    if current_name == Some(name) {
+        return NameCheck::Invalid("Enter a different name".to_string());
+    }
+    // A case-only rename is fine when the system resolves the new name to
+    // the entry being renamed; a different entry with that name is a clash
+    let case_rename = current_name.is_some_and(|current| {
+        current.eq_ignore_ascii_case(name) && same_entry(&dir.join(current), &full_path)
+    });
+    if !case_rename && fs::symlink_metadata(&full_path).is_ok() {
+        return NameCheck::Invalid(format!("\"{}\" already exists", name));
+    }
+
+    if let Some(problem) = windows_name_problem(name) {
+        return if cfg!(windows) {
+            NameCheck::Invalid(problem)
+        } else {
+            NameCheck::Warning(format!("{} (not allowed on Windows)", problem))
+        };
+    }
+    if name.starts_with(' ') {
+        return NameCheck::Warning("Name starts with a space".to_string());
+    }
+
+    NameCheck::Valid
+}
+
+/// Why Windows would refuse `name`, if it would.
+fn windows_name_problem(name: &str) -> Option<String> {
+    if let Some(c) = name
+        .chars()
+        .find(|c| WINDOWS_RESERVED_CHARS.contains(c) || c.is_control())
+    {
+        return Some(if c.is_control() {
+            "Name contains a control character".to_string()
+        } else {
+            format!("Name contains \"{}\"", c)
+        });
+    }
+    if name.ends_with('.') || name.ends_with(' ') {
+        return Some("Name ends with a dot or space".to_string());
+    }
+
+    let stem = name.split('.').next().unwrap_or(name).to_ascii_uppercase();
+    let reserved = matches!(stem.as_str(), "CON" | "PRN" | "AUX" | "NUL")
+        || ((stem.starts_with("COM") || stem.starts_with("LPT"))
+            && stem.len() == 4
+            && stem.as_bytes()[3].is_ascii_digit()
+            && stem.as_bytes()[3] != b'0');
+    reserved.then(|| format!("\"{}\" is a reserved device name", stem))
+}
+
+/// Path named `name` in the same folder as `path`, e.g. a rename target.
+pub fn sibling_path(path: &Path, name: &str) -> Result<PathBuf> {
+    child_path(parent_dir(path)?, name)
+}
+
+/// Replays the move from `old` to `new` on `other`: climbs out of as many
+/// directories as the move left and descends into the same names. Used by
+/// mirror navigation to keep two parallel trees in step.
+pub fn mirror_path(old: &Path, new: &Path, other: &Path) -> PathBuf {
+    let old_components: Vec<_> = old.components().collect();
+    let new_components: Vec<_> = new.components().collect();
+    let common = old_components
+        .iter()
+        .zip(&new_components)
+        .take_while(|(a, b)| a == b)
+        .count();
+
+    let mut target = other.to_path_buf();
+    for _ in common..old_components.len() {
+        if !target.pop() {
+            break;
+        }
+    }
+    for component in &new_components[common..] {
+        target.push(component);
+    }
+    target
+}
+
+/// How file sizes are displayed.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SizeUnits {
+    /// Powers of 1024 labelled KB/MB/GB (the historical behavior).
+    #[default]
+    Binary,
+    /// Powers of 1024 labelled KiB/MiB/GiB/TiB.
+    Iec,
+    /// Powers of 1000 labelled KB/MB/GB/TB.
+    Si,
+}
+
+impl SizeUnits {
+    pub fn name(&self) -> &str {
+        match self {
+            SizeUnits::Binary => "Binary (KB = 1024 bytes)",
+            SizeUnits::Iec => "IEC (KiB = 1024 bytes)",
+            SizeUnits::Si => "SI (KB = 1000 bytes)",
+        }
+    }
+
+    pub fn all() -> Vec<SizeUnits> {
+        vec![SizeUnits::Binary, SizeUnits::Iec, SizeUnits::Si]
+    }
+}
+
 pub fn format_size(size: u64) -> String {
-    const KB: u64 = 1024;
-    const MB: u64 = KB * 1024;
-    const GB: u64 = MB * 1024;
-
-    if size >= GB {
-        format!("{:.2} GB", size as f64 / GB as f64)
-    } else if size >= MB {
-        format!("{:.2} MB", size as f64 / MB as f64)
-    } else if size >= KB {
-        format!("{:.2} KB", size as f64 / KB as f64)
+    format_size_with(size, SizeUnits::default())
+}
+
+pub fn format_size_with(size: u64, units: SizeUnits) -> String {
+    let (base, labels): (u64, &[&str]) = match units {
+        SizeUnits::Binary => (1024, &["KB", "MB", "GB"]),
+        SizeUnits::Iec => (1024, &["KiB", "MiB", "GiB", "TiB"]),
+        SizeUnits::Si => (1000, &["KB", "MB", "GB", "TB"]),
+    };
+
+    let mut unit_size = 1u64;
+    let mut label = None;
+    for candidate in labels {
+        let next = unit_size * base;
+        if size < next {
+            break;
+        }
+        unit_size = next;
+        label = Some(candidate);
+    }
+
+    match label {
+        Some(label) => format!("{:.2} {}", size as f64 / unit_size as f64, label),
+        None => format!("{} B", size),
+    }
+}
+
+/// Format a number with `,` between groups of three digits, e.g. `1,234,567`.
+pub fn format_with_separators(value: u64) -> String {
+    let digits = value.to_string();
+    let mut result = String::with_capacity(digits.len() + digits.len() / 3);
+    for (i, c) in digits.chars().enumerate() {
+        if i > 0 && (digits.len() - i).is_multiple_of(3) {
+            result.push(',');
+        }
+        result.push(c);
+    }
+    result
+}
+
+/// Short human-readable duration such as "0.4s", "12s", "2m 05s" or "1h 03m".
+pub fn format_duration(duration: std::time::Duration) -> String {
+    let secs = duration.as_secs();
+    if secs < 10 {
+        format!("{:.1}s", duration.as_secs_f64())
+    } else if secs < 60 {
+        format!("{}s", secs)
+    } else if secs < 3600 {
+        format!("{}m {:02}s", secs / 60, secs % 60)
     } else {
-        format!("{} B", size)
+        format!("{}h {:02}m", secs / 3600, (secs % 3600) / 60)
+    }
+}
+
+/// Set the modification time of `path` to `time`, or to now, like `touch`.
+/// Unlike `touch`, a missing file is an error rather than created.
+pub fn touch(path: &Path, time: Option<SystemTime>) -> Result<()> {
+    let time = filetime::FileTime::from_system_time(time.unwrap_or_else(SystemTime::now));
+    filetime::set_file_mtime(path, time)?;
+    Ok(())
+}
+
+/// Like [`touch`], but the access time is set as well.
+pub fn touch_with_access(path: &Path, time: Option<SystemTime>) -> Result<()> {
+    let time = filetime::FileTime::from_system_time(time.unwrap_or_else(SystemTime::now));
+    filetime::set_file_times(path, time, time)?;
+    Ok(())
+}
+
+/// Parse an octal permission mode such as "755" or "0644".
+pub fn parse_mode(text: &str) -> Result<u32> {
+    let text = text.trim();
+    match u32::from_str_radix(text, 8) {
+        Ok(mode) if !text.is_empty() && mode <= 0o7777 => Ok(mode),
+        _ => anyhow::bail!("\"{}\" is not an octal mode like 755", text),
+    }
+}
+
+/// Permission bits of `path` (following symlinks), e.g. `0o755`.
+#[cfg(unix)]
+pub fn permission_mode(path: &Path) -> Result<u32> {
+    use std::os::unix::fs::PermissionsExt;
+    Ok(fs::metadata(path)?.permissions().mode() & 0o7777)
+}
+
+#[cfg(not(unix))]
+pub fn permission_mode(_path: &Path) -> Result<u32> {
+    anyhow::bail!("Permission modes are only supported on Unix")
+}
+
+/// The mode a folder gets when `mode` is applied to it: execute is added
+/// for everyone who may read, so the folder can still be opened, as with
+/// chmod's `X`.
+pub fn folder_mode(mode: u32) -> u32 {
+    mode | ((mode & 0o444) >> 2)
+}
+
+/// Apply `mode` to `path` and everything below it. Returns the number of
+/// entries changed.
+pub fn set_permissions_recursive(path: &Path, mode: u32) -> Result<u64> {
+    let mut changed = 0;
+    set_permissions_with_progress(path, mode, true, &mut changed, &mut |_, _| {}, &|| false)?;
+    Ok(changed)
+}
+
+/// Apply `mode` to `path` (see [`folder_mode`] for folders) and, with
+/// `recursive`, to everything below it, adding each change to `changed` and
+/// reporting it through `on_progress`. Contents are changed before their
+/// folder, so a mode without read access can't lock the walk out halfway.
+/// Links are skipped rather than followed. Returns `Ok(false)` once
+/// `cancelled` returns true.
+#[cfg(unix)]
+pub fn set_permissions_with_progress(
+    path: &Path,
+    mode: u32,
+    recursive: bool,
+    changed: &mut u64,
+    on_progress: &mut dyn FnMut(u64, &Path),
+    cancelled: &dyn Fn() -> bool,
+) -> Result<bool> {
+    use std::os::unix::fs::PermissionsExt;
+
+    if cancelled() {
+        return Ok(false);
+    }
+    let metadata = fs::symlink_metadata(path)?;
+    if metadata.file_type().is_symlink() {
+        return Ok(true);
     }
+    let mode = if metadata.is_dir() {
+        if recursive {
+            for entry in fs::read_dir(path)? {
+                let entry = entry?.path();
+                if !set_permissions_with_progress(&entry, mode, true, changed, on_progress, cancelled)? {
+                    return Ok(false);
+                }
+            }
+        }
+        folder_mode(mode)
+    } else {
+        mode
+    };
+    fs::set_permissions(path, fs::Permissions::from_mode(mode))?;
+    *changed += 1;
+    on_progress(*changed, path);
+    Ok(true)
+}
+
+#[cfg(not(unix))]
+pub fn set_permissions_with_progress(
+    _path: &Path,
+    _mode: u32,
+    _recursive: bool,
+    _changed: &mut u64,
+    _on_progress: &mut dyn FnMut(u64, &Path),
+    _cancelled: &dyn Fn() -> bool,
+) -> Result<bool> {
+    anyhow::bail!("Permission modes are only supported on Unix")
 }
 
 pub fn format_date(time: SystemTime) -> String {
@@ -132,6 +486,63 @@ pub fn format_date(time: SystemTime) -> String {
     datetime.format("%Y-%m-%d %H:%M").to_string()
 }
 
+/// Shorten `text` to at most `max_chars` characters by replacing its middle
+/// with "…", so both the start and the end of a long path stay visible.
+pub fn middle_ellipsis(text: &str, max_chars: usize) -> String {
+    let len = text.chars().count();
+    if len <= max_chars {
+        return text.to_string();
+    }
+    if max_chars <= 1 {
+        return "…".chars().take(max_chars).collect();
+    }
+
+    let keep = max_chars - 1;
+    let head = keep.div_ceil(2);
+    let tail = keep - head;
+    let start: String = text.chars().take(head).collect();
+    let end: String = text.chars().skip(len - tail).collect();
+    format!("{}…{}", start, end)
+}
+
+/// Compare names the way people read them: ignoring case, with runs of
+/// digits compared by value so "file2" comes before "file10". Names that
+/// differ only in case or leading zeros still get a fixed order.
+pub fn natural_cmp(a: &str, b: &str) -> std::cmp::Ordering {
+    use std::cmp::Ordering;
+
+    let take_number = |chars: &mut std::iter::Peekable<std::str::Chars>| {
+        let mut digits = String::new();
+        while let Some(c) = chars.next_if(|c| c.is_ascii_digit()) {
+            digits.push(c);
+        }
+        digits
+    };
+    let (mut a_chars, mut b_chars) = (a.chars().peekable(), b.chars().peekable());
+    loop {
+        let ordering = match (a_chars.peek().copied(), b_chars.peek().copied()) {
+            (None, None) => break,
+            (None, Some(_)) => return Ordering::Less,
+            (Some(_), None) => return Ordering::Greater,
+            (Some(x), Some(y)) if x.is_ascii_digit() && y.is_ascii_digit() => {
+                let (x_digits, y_digits) = (take_number(&mut a_chars), take_number(&mut b_chars));
+                let x_value = x_digits.trim_start_matches('0');
+                let y_value = y_digits.trim_start_matches('0');
+                x_value.len().cmp(&y_value.len()).then_with(|| x_value.cmp(y_value))
+            }
+            (Some(x), Some(y)) => {
+                a_chars.next();
+                b_chars.next();
+                x.to_lowercase().cmp(y.to_lowercase())
+            }
+        };
+        if ordering != Ordering::Equal {
+            return ordering;
+        }
+    }
+    a.cmp(b)
+}
+
 pub fn get_permissions(path: &Path) -> Result<(String, String)> {
     let metadata = fs::metadata(path)?;
 
@@ -261,6 +672,147 @@ pub fn calculate_directory_stats(items: &[FileItem]) -> DirectoryStats {
     }
 }
 
+/// How many of the largest files [`tree_stats`] keeps.
+pub const LARGEST_FILES_SHOWN: usize = 10;
+
+/// Recursive totals for a folder, like [`DirectoryStats`] but for the whole
+/// tree below it.
+#[derive(Clone, Debug, Default)]
+pub struct TreeStats {
+    pub root: PathBuf,
+    pub file_count: usize,
+    pub folder_count: usize,
+    pub total_size: u64,
+    /// Folders that could not be read.
+    pub skipped_dirs: usize,
+    /// Largest files first, at most [`LARGEST_FILES_SHOWN`].
+    pub largest_files: Vec<(PathBuf, u64)>,
+    /// Lowercase extension ("" for none), biggest total size first.
+    pub by_extension: Vec<ExtensionStats>,
+    /// Recursive size of every folder that was read, the root included.
+    pub folder_sizes: HashMap<PathBuf, u64>,
+}
+
+impl TreeStats {
+    /// Entries directly inside `dir`, biggest first, with folders sized from
+    /// the scan rather than walked again. `dir` must lie within the scanned
+    /// tree.
+    pub fn children_by_size(&self, dir: &Path) -> Result<Vec<FileItem>> {
+        let mut children: Vec<FileItem> = fs::read_dir(dir)?
+            .flatten()
+            .filter_map(|entry| FileItem::from_entry(&entry).ok())
+            .map(|mut item| {
+                if item.is_dir {
+                    item.size = self.folder_sizes.get(&item.path).copied().unwrap_or(0);
+                }
+                item
+            })
+            .collect();
+        children.sort_by(|a, b| b.size.cmp(&a.size).then_with(|| a.name.cmp(&b.name)));
+        Ok(children)
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ExtensionStats {
+    pub extension: String,
+    pub file_count: usize,
+    pub total_size: u64,
+}
+
+/// Walk everything below `root` and total it up, reporting the number of
+/// files seen so far. Symlinks and junctions are not followed, and
+/// hard-linked data is counted once, as in [`calculate_total_size`].
+pub fn tree_stats(root: &Path, on_progress: &mut dyn FnMut(u64, &Path)) -> Result<TreeStats> {
+    if !fs::symlink_metadata(root)?.is_dir() {
+        anyhow::bail!("{} is not a folder", root.display());
+    }
+    let mut stats = TreeStats {
+        root: root.to_path_buf(),
+        ..Default::default()
+    };
+    let mut largest = std::collections::BinaryHeap::new();
+    let mut by_extension: HashMap<String, ExtensionStats> = HashMap::new();
+    let mut seen_links = std::collections::HashSet::new();
+    let mut pending = vec![root.to_path_buf()];
+
+    while let Some(dir) = pending.pop() {
+        let Ok(entries) = fs::read_dir(&dir) else {
+            stats.skipped_dirs += 1;
+            continue;
+        };
+        on_progress(stats.file_count as u64, &dir);
+        let mut own_size = 0;
+        for entry in entries.flatten() {
+            let path = entry.path();
+            let Ok(metadata) = fs::symlink_metadata(&path) else {
+                continue;
+            };
+            if metadata.is_dir() {
+                stats.folder_count += 1;
+                if !is_reparse_point(&path) {
+                    pending.push(path);
+                }
+                continue;
+            }
+
+            stats.file_count += 1;
+            let counted_before = link_info(&path)
+                .filter(|info| info.links > 1)
+                .is_some_and(|info| !seen_links.insert((info.device, info.inode)));
+            let size = if counted_before { 0 } else { metadata.len() };
+            stats.total_size += size;
+            own_size += size;
+
+            let extension = path
+                .extension()
+                .map(|ext| ext.to_string_lossy().to_lowercase())
+                .unwrap_or_default();
+            let group = by_extension
+                .entry(extension.clone())
+                .or_insert_with(|| ExtensionStats {
+                    extension,
+                    ..Default::default()
+                });
+            group.file_count += 1;
+            group.total_size += size;
+
+            // Min-heap of the biggest files so far
+            largest.push(std::cmp::Reverse((metadata.len(), path)));
+            if largest.len() > LARGEST_FILES_SHOWN {
+                largest.pop();
+            }
+        }
+        stats.folder_sizes.insert(dir, own_size);
+    }
+
+    // Add each folder's total to its parent, deepest folders first
+    let mut folders: Vec<PathBuf> = stats.folder_sizes.keys().cloned().collect();
+    folders.sort_by_key(|folder| std::cmp::Reverse(folder.components().count()));
+    for folder in folders {
+        if folder == root {
+            continue;
+        }
+        let size = stats.folder_sizes[&folder];
+        if let Some(parent) = folder.parent().and_then(|parent| stats.folder_sizes.get_mut(parent)) {
+            *parent += size;
+        }
+    }
+
+    stats.largest_files = largest
+        .into_sorted_vec()
+        .into_iter()
+        .map(|std::cmp::Reverse((size, path))| (path, size))
+        .collect();
+    stats.by_extension = by_extension.into_values().collect();
+    stats.by_extension.sort_by(|a, b| {
+        b.total_size
+            .cmp(&a.total_size)
+            .then_with(|| a.extension.cmp(&b.extension))
+    });
+    Ok(stats)
+}
+
 pub fn is_archive(path: &Path) -> bool {
     if let Some(ext) = path.extension() {
         let ext_lower = ext.to_string_lossy().to_lowercase();
@@ -273,384 +825,2331 @@ pub fn is_archive(path: &Path) -> bool {
     }
 }
 
-pub fn compress_to_zip(source_path: &Path, dest_zip: &Path) -> Result<()> {
-    use std::io::Write;
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ZipMethod {
+    Stored,
+    #[default]
+    Deflated,
+    Bzip2,
+    Zstd,
+}
+
+impl ZipMethod {
+    pub fn name(&self) -> &str {
+        match self {
+            ZipMethod::Stored => "Stored (no compression)",
+            ZipMethod::Deflated => "Deflate",
+            ZipMethod::Bzip2 => "Bzip2",
+            ZipMethod::Zstd => "Zstandard",
+        }
+    }
+
+    pub fn all() -> Vec<ZipMethod> {
+        vec![
+            ZipMethod::Stored,
+            ZipMethod::Deflated,
+            ZipMethod::Bzip2,
+            ZipMethod::Zstd,
+        ]
+    }
+
+    /// Levels accepted by the method, or `None` if it has no levels.
+    pub fn level_range(&self) -> Option<std::ops::RangeInclusive<i32>> {
+        match self {
+            ZipMethod::Stored => None,
+            ZipMethod::Deflated => Some(0..=9),
+            ZipMethod::Bzip2 => Some(1..=9),
+            ZipMethod::Zstd => Some(1..=22),
+        }
+    }
+
+    /// The level the zip crate uses when none is given.
+    pub fn default_level(&self) -> Option<i32> {
+        match self {
+            ZipMethod::Stored => None,
+            ZipMethod::Deflated | ZipMethod::Bzip2 => Some(6),
+            ZipMethod::Zstd => Some(3),
+        }
+    }
+
+    fn to_zip(self) -> zip::CompressionMethod {
+        match self {
+            ZipMethod::Stored => zip::CompressionMethod::Stored,
+            ZipMethod::Deflated => zip::CompressionMethod::Deflated,
+            ZipMethod::Bzip2 => zip::CompressionMethod::Bzip2,
+            ZipMethod::Zstd => zip::CompressionMethod::Zstd,
+        }
+    }
+}
+
+/// Compression settings for new archives. A `level` of `None` uses the
+/// method's default.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ZipOptions {
+    pub method: ZipMethod,
+    pub level: Option<i32>,
+}
+
+impl ZipOptions {
+    /// Options for each entry, AES-256 encrypted when a password is given.
+    fn file_options<'k>(&self, password: Option<&'k str>) -> zip::write::FileOptions<'k, ()> {
+        let level = match (self.level, self.method.level_range()) {
+            (Some(level), Some(range)) => Some(level.clamp(*range.start(), *range.end())),
+            _ => None,
+        };
+        let options = zip::write::SimpleFileOptions::default()
+            .compression_method(self.method.to_zip())
+            .compression_level(level.map(i64::from));
+        match password {
+            Some(password) => options.with_aes_encryption(zip::AesMode::Aes256, password),
+            None => options,
+        }
+    }
+}
+
+/// Compress a file or folder into `dest_zip`. Returns the paths of special
+/// files (fifos, sockets, device nodes) that were skipped, along with
+/// symlinks that loop back to a folder already being archived.
+pub fn compress_to_zip(source_path: &Path, dest_zip: &Path) -> Result<Vec<PathBuf>> {
+    compress_to_zip_with_progress(source_path, dest_zip, &ZipOptions::default(), None, &mut |_, _| {})
+}
 
+/// Like `compress_to_zip`, but with explicit compression settings, and
+/// calls `on_progress` with the number of source bytes written so far and
+/// the file being added. With a `password` the files are AES-256 encrypted;
+/// names and sizes stay readable, as zip encryption doesn't cover them.
+pub fn compress_to_zip_with_progress(
+    source_path: &Path,
+    dest_zip: &Path,
+    zip_options: &ZipOptions,
+    password: Option<&str>,
+    on_progress: &mut dyn FnMut(u64, &Path),
+) -> Result<Vec<PathBuf>> {
+    if source_path.file_name().is_none() {
+        return Err(anyhow::anyhow!(
+            "Cannot compress {}: it is a filesystem root",
+            source_path.display()
+        ));
+    }
     let file = std::fs::File::create(dest_zip)?;
     let mut zip = zip::ZipWriter::new(file);
 
-    let options = zip::write::FileOptions::default()
-        .compression_method(zip::CompressionMethod::Deflated)
-        .unix_permissions(0o755);
+    let options = zip_options.file_options(password).unix_permissions(0o755);
 
+    let mut bytes_done = 0;
+    let mut skipped = Vec::new();
     if source_path.is_file() {
         let name = source_path
             .file_name()
             .ok_or_else(|| anyhow::anyhow!("Invalid filename"))?
             .to_string_lossy();
 
+        on_progress(bytes_done, source_path);
         zip.start_file(name.as_ref(), options)?;
-        let content = std::fs::read(source_path)?;
-        zip.write_all(&content)?;
+        let mut source = std::fs::File::open(source_path)?;
+        bytes_done += std::io::copy(&mut source, &mut zip)?;
+        on_progress(bytes_done, source_path);
     } else if source_path.is_dir() {
-        add_directory_to_zip(&mut zip, source_path, source_path, options)?;
+        let mut ancestors = vec![fs::canonicalize(source_path)?];
+        add_directory_to_zip(
+            &mut zip,
+            source_path,
+            source_path,
+            options,
+            &mut ancestors,
+            &mut bytes_done,
+            &mut skipped,
+            on_progress,
+        )?;
+    } else {
+        return Err(anyhow::anyhow!(
+            "{} is not a regular file or folder",
+            source_path.display()
+        ));
     }
 
     zip.finish()?;
-    Ok(())
+    Ok(skipped)
 }
 
+#[allow(clippy::too_many_arguments)]
 fn add_directory_to_zip(
     zip: &mut zip::ZipWriter<std::fs::File>,
     base_path: &Path,
     current_path: &Path,
-    options: zip::write::FileOptions,
+    options: zip::write::FileOptions<'_, ()>,
+    ancestors: &mut Vec<PathBuf>,
+    bytes_done: &mut u64,
+    skipped: &mut Vec<PathBuf>,
+    on_progress: &mut dyn FnMut(u64, &Path),
 ) -> Result<()> {
-    use std::io::Write;
-
     for entry in std::fs::read_dir(current_path)? {
         let entry = entry?;
         let path = entry.path();
-        let name = path.strip_prefix(base_path)?;
+        let name = path.strip_prefix(base_path)?.to_string_lossy().replace('\\', "/");
 
-        if path.is_file() {
-            zip.start_file(name.to_string_lossy().as_ref(), options)?;
-            let content = std::fs::read(&path)?;
-            zip.write_all(&content)?;
-        } else if path.is_dir() {
-            zip.add_directory(name.to_string_lossy().as_ref(), options)?;
-            add_directory_to_zip(zip, base_path, &path, options)?;
+        // Follow symlinks like the rest of the archive code does; broken
+        // links end up in `skipped` along with fifos, sockets and devices.
+        let file_type = match std::fs::metadata(&path) {
+            Ok(metadata) => metadata.file_type(),
+            Err(_) => {
+                skipped.push(path);
+                continue;
+            }
+        };
+
+        if file_type.is_file() {
+            on_progress(*bytes_done, &path);
+            zip.start_file(name.as_str(), options)?;
+            let mut source = std::fs::File::open(&path)?;
+            *bytes_done += std::io::copy(&mut source, zip)?;
+            on_progress(*bytes_done, &path);
+        } else if file_type.is_dir() {
+            let Some(canonical) = enter_directory(&path, ancestors) else {
+                skipped.push(path);
+                continue;
+            };
+            // An explicit "name/" entry keeps empty folders on extraction.
+            zip.add_directory(format!("{}/", name), options)?;
+            ancestors.push(canonical);
+            let result = add_directory_to_zip(
+                zip,
+                base_path,
+                &path,
+                options,
+                ancestors,
+                bytes_done,
+                skipped,
+                on_progress,
+            );
+            ancestors.pop();
+            result?;
+        } else {
+            skipped.push(path);
         }
     }
 
     Ok(())
 }
 
-pub fn extract_zip(zip_path: &Path, dest_dir: &Path) -> Result<()> {
-    let file = std::fs::File::open(zip_path)?;
-    let mut archive = zip::ZipArchive::new(file)?;
-
-    for i in 0..archive.len() {
-        let mut file = archive.by_index(i)?;
-        let outpath = dest_dir.join(file.name());
+/// Copy a file, or a folder with everything below it, to `dest`. Calls
+/// `on_progress` with the bytes copied so far and the file being copied,
+/// and returns the total number of bytes copied. Symlinks are followed,
+/// except ones that loop back to a folder being copied: those are copied as
+/// links (and left out on platforms without symlinks).
+pub fn copy_recursive_with_progress(
+    source: &Path,
+    dest: &Path,
+    on_progress: &mut dyn FnMut(u64, &Path),
+) -> Result<u64> {
+    let mut bytes_done = 0;
+    copy_entry(source, dest, &mut Vec::new(), &mut bytes_done, on_progress)?;
+    Ok(bytes_done)
+}
 
-        if file.name().ends_with('/') {
-            std::fs::create_dir_all(&outpath)?;
-        } else {
-            if let Some(p) = outpath.parent() {
-                if !p.exists() {
-                    std::fs::create_dir_all(p)?;
-                }
+fn copy_entry(
+    source: &Path,
+    dest: &Path,
+    ancestors: &mut Vec<PathBuf>,
+    bytes_done: &mut u64,
+    on_progress: &mut dyn FnMut(u64, &Path),
+) -> Result<()> {
+    if source.is_dir() {
+        let Some(canonical) = enter_directory(source, ancestors) else {
+            return copy_symlink(source, dest);
+        };
+        fs::create_dir_all(dest)?;
+        ancestors.push(canonical);
+        let result = fs::read_dir(source).map_err(anyhow::Error::from).and_then(|entries| {
+            for entry in entries {
+                let entry = entry?;
+                copy_entry(
+                    &entry.path(),
+                    &dest.join(entry.file_name()),
+                    ancestors,
+                    bytes_done,
+                    on_progress,
+                )?;
             }
-            let mut outfile = std::fs::File::create(&outpath)?;
-            std::io::copy(&mut file, &mut outfile)?;
-        }
+            Ok(())
+        });
+        ancestors.pop();
+        result?;
+    } else {
+        on_progress(*bytes_done, source);
+        *bytes_done += fs::copy(source, dest)?;
+        on_progress(*bytes_done, source);
+    }
+    Ok(())
+}
 
-        #[cfg(unix)]
-        {
-            use std::os::unix::fs::PermissionsExt;
-            if let Some(mode) = file.unix_mode() {
-                std::fs::set_permissions(&outpath, std::fs::Permissions::from_mode(mode))?;
-            }
+/// What a merge copy does with a file that already exists in the destination.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ConflictPolicy {
+    /// Keep the existing file.
+    #[default]
+    Skip,
+    /// Replace it.
+    Overwrite,
+    /// Replace it only if the source file was modified more recently.
+    OverwriteOlder,
+}
+
+impl ConflictPolicy {
+    pub fn name(&self) -> &str {
+        match self {
+            ConflictPolicy::Skip => "Keep existing files",
+            ConflictPolicy::Overwrite => "Overwrite existing files",
+            ConflictPolicy::OverwriteOlder => "Overwrite older files",
         }
     }
 
-    Ok(())
+    pub fn all() -> Vec<ConflictPolicy> {
+        vec![
+            ConflictPolicy::Skip,
+            ConflictPolicy::Overwrite,
+            ConflictPolicy::OverwriteOlder,
+        ]
+    }
 }
 
-#[derive(Clone, Debug)]
-pub struct SearchCriteria {
-    pub search_path: PathBuf,
-    pub filename_pattern: String,
-    pub content_pattern: String,
-    pub min_size: Option<u64>,
-    pub max_size: Option<u64>,
-    pub modified_after: Option<SystemTime>,
-    pub modified_before: Option<SystemTime>,
-    pub file_type: SearchFileType,
-    pub case_sensitive: bool,
-    pub include_hidden: bool,
+/// Outcome of [`merge_copy_with_progress`].
+#[derive(Debug, Default)]
+pub struct MergeSummary {
+    /// Files written to the destination.
+    pub copied: usize,
+    pub bytes: u64,
+    /// Destination files left alone because of the conflict policy.
+    pub kept: Vec<PathBuf>,
+    /// Destinations that are a file where the source has a folder, or the
+    /// other way around. Nothing is copied over them.
+    pub mismatched: Vec<PathBuf>,
 }
 
-#[derive(Clone, Debug, PartialEq)]
-pub enum SearchFileType {
-    All,
-    Files,
-    Directories,
+/// Copy `source` to `dest`, merging into whatever is already there: folders
+/// that exist on both sides are merged recursively and each file that exists
+/// on both sides is resolved by `policy`. Reports progress like
+/// [`copy_recursive_with_progress`].
+pub fn merge_copy_with_progress(
+    source: &Path,
+    dest: &Path,
+    policy: ConflictPolicy,
+    on_progress: &mut dyn FnMut(u64, &Path),
+) -> Result<MergeSummary> {
+    let mut summary = MergeSummary::default();
+    merge_entry(source, dest, policy, &mut Vec::new(), &mut summary, on_progress)?;
+    Ok(summary)
 }
 
-impl Default for SearchCriteria {
-    fn default() -> Self {
-        Self {
-            search_path: PathBuf::from("."),
-            filename_pattern: String::new(),
-            content_pattern: String::new(),
-            min_size: None,
-            max_size: None,
-            modified_after: None,
-            modified_before: None,
-            file_type: SearchFileType::All,
-            case_sensitive: false,
-            include_hidden: false,
+fn merge_entry(
+    source: &Path,
+    dest: &Path,
+    policy: ConflictPolicy,
+    ancestors: &mut Vec<PathBuf>,
+    summary: &mut MergeSummary,
+    on_progress: &mut dyn FnMut(u64, &Path),
+) -> Result<()> {
+    let existing = fs::symlink_metadata(dest).ok();
+    if source.is_dir() {
+        let Some(canonical) = enter_directory(source, ancestors) else {
+            return match existing {
+                Some(_) => {
+                    summary.kept.push(dest.to_path_buf());
+                    Ok(())
+                }
+                None => copy_symlink(source, dest),
+            };
+        };
+        if existing.is_some() && !dest.is_dir() {
+            summary.mismatched.push(dest.to_path_buf());
+            return Ok(());
         }
+        fs::create_dir_all(dest)?;
+        ancestors.push(canonical);
+        let result = fs::read_dir(source).map_err(anyhow::Error::from).and_then(|entries| {
+            for entry in entries {
+                let entry = entry?;
+                merge_entry(
+                    &entry.path(),
+                    &dest.join(entry.file_name()),
+                    policy,
+                    ancestors,
+                    summary,
+                    on_progress,
+                )?;
+            }
+            Ok(())
+        });
+        ancestors.pop();
+        return result;
     }
-}
 
-pub fn search_files(criteria: &SearchCriteria) -> Result<Vec<FileItem>> {
-    let mut results = Vec::new();
-    search_recursive(&criteria.search_path, criteria, &mut results)?;
-    Ok(results)
+    if let Some(existing) = existing {
+        if dest.is_dir() {
+            summary.mismatched.push(dest.to_path_buf());
+            return Ok(());
+        }
+        let replace = match policy {
+            ConflictPolicy::Skip => false,
+            ConflictPolicy::Overwrite => true,
+            ConflictPolicy::OverwriteOlder => {
+                let modified = |metadata: &fs::Metadata| metadata.modified().ok();
+                modified(&fs::metadata(source)?) > fs::metadata(dest).ok().and_then(|m| modified(&m))
+            }
+        };
+        if !replace {
+            summary.kept.push(dest.to_path_buf());
+            return Ok(());
+        }
+        // Replace a link itself rather than writing through it
+        if existing.file_type().is_symlink() {
+            fs::remove_file(dest)?;
+        }
+    }
+    on_progress(summary.bytes, source);
+    summary.bytes += fs::copy(source, dest)?;
+    summary.copied += 1;
+    on_progress(summary.bytes, source);
+    Ok(())
 }
 
-fn search_recursive(
-    path: &Path,
-    criteria: &SearchCriteria,
-    results: &mut Vec<FileItem>,
-) -> Result<()> {
-    if !path.is_dir() {
-        return Ok(());
+/// Recreate the folder tree below `source` at `dest` without copying any
+/// files. Symlinked folders are followed unless they loop back up the tree.
+pub fn copy_dir_structure(source: &Path, dest: &Path) -> Result<()> {
+    if !source.is_dir() {
+        return Err(anyhow::anyhow!("{} is not a folder", source.display()));
     }
+    copy_dirs(source, dest, &mut Vec::new())
+}
 
-    let entries = match fs::read_dir(path) {
-        Ok(e) => e,
-        Err(_) => return Ok(()),
+fn copy_dirs(source: &Path, dest: &Path, ancestors: &mut Vec<PathBuf>) -> Result<()> {
+    let Some(canonical) = enter_directory(source, ancestors) else {
+        return Ok(());
     };
+    fs::create_dir_all(dest)?;
+    ancestors.push(canonical);
+    let result = fs::read_dir(source).map_err(anyhow::Error::from).and_then(|entries| {
+        for entry in entries {
+            let entry = entry?;
+            if entry.path().is_dir() {
+                copy_dirs(&entry.path(), &dest.join(entry.file_name()), ancestors)?;
+            }
+        }
+        Ok(())
+    });
+    ancestors.pop();
+    result
+}
 
-    for entry in entries.flatten() {
-        let entry_path = entry.path();
-        let file_name = entry.file_name().to_string_lossy().to_string();
+/// Canonical path of the folder `path` if walking into it is safe, or `None`
+/// when it resolves to one of `ancestors` (a symlink loop) or is a Windows
+/// reparse point.
+fn enter_directory(path: &Path, ancestors: &[PathBuf]) -> Option<PathBuf> {
+    if is_reparse_point(path) {
+        return None;
+    }
+    let canonical = fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf());
+    if ancestors.contains(&canonical) {
+        None
+    } else {
+        Some(canonical)
+    }
+}
 
-        if !criteria.include_hidden && file_name.starts_with('.') {
-            continue;
-        }
+#[cfg(unix)]
+fn copy_symlink(source: &Path, dest: &Path) -> Result<()> {
+    std::os::unix::fs::symlink(fs::read_link(source)?, dest)?;
+    Ok(())
+}
 
-        let metadata = match entry.metadata() {
-            Ok(m) => m,
-            Err(_) => continue,
+/// Recreate a junction or symlink as a symlink. Creating one needs extra
+/// privileges on Windows; without them the link is left out of the copy.
+#[cfg(windows)]
+fn copy_symlink(source: &Path, dest: &Path) -> Result<()> {
+    if let Ok(target) = fs::read_link(source) {
+        let _ = if source.is_dir() {
+            std::os::windows::fs::symlink_dir(target, dest)
+        } else {
+            std::os::windows::fs::symlink_file(target, dest)
         };
+    }
+    Ok(())
+}
 
-        let is_dir = metadata.is_dir();
+#[cfg(not(any(unix, windows)))]
+fn copy_symlink(_source: &Path, _dest: &Path) -> Result<()> {
+    Ok(())
+}
 
-        match criteria.file_type {
-            SearchFileType::Files if is_dir => {
-                if is_dir {
-                    let _ = search_recursive(&entry_path, criteria, results);
-                }
-                continue;
-            }
-            SearchFileType::Directories if !is_dir => continue,
-            _ => {}
-        }
+/// Whether `path` is a junction or another reparse point. Recursive walks
+/// (copy, compress, search) don't descend into these: like symlinks they
+/// can loop, and `metadata()` reports them as plain folders. Always false
+/// outside Windows, where symlinked folders are followed with loop checks.
+#[cfg(windows)]
+pub fn is_reparse_point(path: &Path) -> bool {
+    use std::os::windows::fs::MetadataExt;
+    const FILE_ATTRIBUTE_REPARSE_POINT: u32 = 0x400;
+    fs::symlink_metadata(path)
+        .is_ok_and(|metadata| metadata.file_attributes() & FILE_ATTRIBUTE_REPARSE_POINT != 0)
+}
 
-        if !criteria.filename_pattern.is_empty() {
-            let matches = if criteria.case_sensitive {
-                file_name.contains(&criteria.filename_pattern)
-            } else {
-                file_name
-                    .to_lowercase()
-                    .contains(&criteria.filename_pattern.to_lowercase())
-            };
+#[cfg(not(windows))]
+pub fn is_reparse_point(_path: &Path) -> bool {
+    false
+}
 
-            if !matches {
-                if is_dir {
-                    let _ = search_recursive(&entry_path, criteria, results);
-                }
-                continue;
-            }
-        }
+/// Whether `path` is a symlink, junction or other reparse point rather than
+/// the file or folder itself.
+pub fn is_link(path: &Path) -> bool {
+    fs::symlink_metadata(path).is_ok_and(|metadata| metadata.file_type().is_symlink())
+        || is_reparse_point(path)
+}
 
-        let size = metadata.len();
-        if let Some(min) = criteria.min_size {
-            if size < min {
-                if is_dir {
-                    let _ = search_recursive(&entry_path, criteria, results);
-                }
-                continue;
-            }
-        }
-        if let Some(max) = criteria.max_size {
-            if size > max {
-                if is_dir {
-                    let _ = search_recursive(&entry_path, criteria, results);
-                }
-                continue;
-            }
-        }
+/// Move `source` to `dest`. A plain rename when both are on the same
+/// filesystem; across filesystems it copies and then deletes the source.
+pub fn move_path(source: &Path, dest: &Path) -> Result<()> {
+    move_path_with_progress(source, dest, &mut |_, _, _| {})?;
+    Ok(())
+}
 
-        let modified = metadata.modified().unwrap_or(SystemTime::UNIX_EPOCH);
-        if let Some(after) = criteria.modified_after {
-            if modified < after {
-                if is_dir {
-                    let _ = search_recursive(&entry_path, criteria, results);
-                }
-                continue;
-            }
-        }
-        if let Some(before) = criteria.modified_before {
-            if modified > before {
-                if is_dir {
-                    let _ = search_recursive(&entry_path, criteria, results);
-                }
-                continue;
-            }
-        }
+/// [`move_path`] reporting progress when it has to fall back to copying:
+/// `on_progress` gets the bytes copied so far, the total and the file being
+/// copied. Returns the number of bytes copied, or `None` when a rename was
+/// enough. Only the copy fallback walks the tree, to size it and to check
+/// the destination has room before copying anything.
+pub fn move_path_with_progress(
+    source: &Path,
+    dest: &Path,
+    on_progress: &mut dyn FnMut(u64, u64, &Path),
+) -> Result<Option<u64>> {
+    match fs::rename(source, dest) {
+        Ok(()) => return Ok(None),
+        Err(e) if e.kind() == std::io::ErrorKind::CrossesDevices => {}
+        Err(e) => return Err(e.into()),
+    }
 
-        if !criteria.content_pattern.is_empty() && !is_dir {
-            let content_matches = search_file_content(
-                &entry_path,
-                &criteria.content_pattern,
-                criteria.case_sensitive,
-            );
-            if !content_matches {
-                continue;
-            }
-        }
+    move_by_copying(source, dest, on_progress).map(Some)
+}
 
-        results.push(FileItem {
-            name: file_name,
-            path: entry_path.clone(),
-            is_dir,
-            size,
-            modified,
-            git_status: None,
-        });
+/// The cross-device half of [`move_path_with_progress`]: copy `source` to
+/// `dest`, then delete `source`. Unlike a plain copy, symlinks are never
+/// followed, so links (dangling ones included) arrive as links.
+fn move_by_copying(
+    source: &Path,
+    dest: &Path,
+    on_progress: &mut dyn FnMut(u64, u64, &Path),
+) -> Result<u64> {
+    let total = calculate_total_size(source);
+    check_space(total, dest.parent().unwrap_or(dest), SizeUnits::default())?;
+    let mut bytes_done = 0;
+    let copied = copy_entry_as_is(source, dest, &mut bytes_done, &mut |done, current| {
+        on_progress(done, total, current)
+    });
+    if let Err(e) = copied {
+        // Leave no half-copied destination behind; the source is intact
+        let _ = remove_path(dest);
+        return Err(e);
+    }
+    remove_path(source)?;
+    Ok(bytes_done)
+}
 
-        if is_dir {
-            let _ = search_recursive(&entry_path, criteria, results);
+fn copy_entry_as_is(
+    source: &Path,
+    dest: &Path,
+    bytes_done: &mut u64,
+    on_progress: &mut dyn FnMut(u64, &Path),
+) -> Result<()> {
+    let metadata = fs::symlink_metadata(source)?;
+    if metadata.file_type().is_symlink() || is_reparse_point(source) {
+        copy_symlink(source, dest)
+    } else if metadata.is_dir() {
+        fs::create_dir(dest)?;
+        for entry in fs::read_dir(source)? {
+            let entry = entry?;
+            copy_entry_as_is(
+                &entry.path(),
+                &dest.join(entry.file_name()),
+                bytes_done,
+                on_progress,
+            )?;
         }
+        Ok(())
+    } else {
+        on_progress(*bytes_done, source);
+        *bytes_done += fs::copy(source, dest)?;
+        on_progress(*bytes_done, source);
+        Ok(())
+    }
+}
+
+fn remove_path(path: &Path) -> std::io::Result<()> {
+    if fs::symlink_metadata(path)?.is_dir() {
+        fs::remove_dir_all(path)
+    } else {
+        fs::remove_file(path)
     }
+}
 
+/// Fails with "Source no longer exists" when `source` was moved or deleted
+/// since it was picked, e.g. while it sat on the clipboard. A dangling
+/// symlink still exists.
+pub fn check_source_exists(source: &Path) -> Result<()> {
+    if fs::symlink_metadata(source).is_err() {
+        anyhow::bail!("Source no longer exists: {}", source.display());
+    }
     Ok(())
 }
 
-fn search_file_content(path: &Path, pattern: &str, case_sensitive: bool) -> bool {
-    let mut file = match fs::File::open(path) {
-        Ok(f) => f,
-        Err(_) => return false,
+/// Where `source` ends up when copied or moved into `dest_dir`. Fails when
+/// `source` is already in `dest_dir`, when the destination already exists or
+/// when it would lie inside `source` itself.
+pub fn transfer_destination(source: &Path, dest_dir: &Path) -> Result<PathBuf> {
+    let dest = merge_destination(source, dest_dir)?;
+    if fs::symlink_metadata(&dest).is_ok() {
+        anyhow::bail!("{} already exists", dest.display());
+    }
+    Ok(dest)
+}
+
+/// Like [`transfer_destination`], but the destination may already exist: a
+/// merge copy writes into it.
+pub fn merge_destination(source: &Path, dest_dir: &Path) -> Result<PathBuf> {
+    let name = source
+        .file_name()
+        .ok_or_else(|| anyhow::anyhow!("{} is a filesystem root", source.display()))?;
+    let dest = dest_dir.join(name);
+    // Resolve the parent only, so a symlink is judged as the link itself
+    let source_dir = parent_dir(source)?.canonicalize()?;
+    let canonical_dir = dest_dir.canonicalize()?;
+    if source_dir == canonical_dir {
+        anyhow::bail!("{} is already in {}", name.to_string_lossy(), dest_dir.display());
+    }
+    let canonical_source = source_dir.join(name);
+    if canonical_dir.starts_with(&canonical_source) {
+        anyhow::bail!("Cannot put {} inside itself", source.display());
+    }
+    Ok(dest)
+}
+
+/// Like [`transfer_destination`], but a copy into the source's own folder
+/// gets the next free "name (copy).ext" / "name (copy 2).ext" instead of
+/// failing.
+pub fn copy_destination(source: &Path, dest_dir: &Path) -> Result<PathBuf> {
+    let source_dir = parent_dir(source)?.canonicalize()?;
+    if source_dir != dest_dir.canonicalize()? {
+        return transfer_destination(source, dest_dir);
+    }
+
+    let name = source.file_name().unwrap_or_default().to_string_lossy().to_string();
+    // Folders and dotfiles keep their whole name as the stem
+    let (stem, extension) = match name.rfind('.') {
+        Some(dot) if dot > 0 && !source.is_dir() => name.split_at(dot),
+        _ => (name.as_str(), ""),
+    };
+    (1..)
+        .map(|n| match n {
+            1 => format!("{} (copy){}", stem, extension),
+            n => format!("{} (copy {}){}", stem, n, extension),
+        })
+        .map(|candidate| dest_dir.join(candidate))
+        .find(|candidate| fs::symlink_metadata(candidate).is_err())
+        .ok_or_else(|| anyhow::anyhow!("No free name for a copy of {}", name))
+}
+
+/// Total size in bytes of a file, or of all files below a directory.
+/// Symlinks are not followed and unreadable entries are skipped. Hard-linked
+/// files are counted once, since their data is only stored once.
+pub fn calculate_total_size(path: &Path) -> u64 {
+    total_size(path, &mut std::collections::HashSet::new())
+}
+
+fn total_size(path: &Path, seen_links: &mut std::collections::HashSet<(u64, u64)>) -> u64 {
+    let metadata = match fs::symlink_metadata(path) {
+        Ok(m) => m,
+        Err(_) => return 0,
     };
 
-    let mut content = String::new();
-    if file.read_to_string(&mut content).is_err() {
-        return false;
+    if !metadata.is_dir() {
+        let counted_before = link_info(path)
+            .filter(|info| info.links > 1)
+            .is_some_and(|info| !seen_links.insert((info.device, info.inode)));
+        return if counted_before { 0 } else { metadata.len() };
     }
 
-    if case_sensitive {
-        content.contains(pattern)
-    } else {
-        content.to_lowercase().contains(&pattern.to_lowercase())
+    match fs::read_dir(path) {
+        Ok(entries) => entries
+            .flatten()
+            .map(|entry| total_size(&entry.path(), seen_links))
+            .sum(),
+        Err(_) => 0,
     }
 }
 
-#[derive(Clone, Debug, PartialEq)]
-pub enum DiffLineType {
-    Equal,
-    Added,
-    Removed,
-    Modified,
+/// Number of entries [`remove_tree_with_progress`] deletes for `path`:
+/// every file, link and folder below it, plus `path` itself. Symlinks are
+/// not followed.
+pub fn count_entries(path: &Path) -> u64 {
+    let mut count = 0;
+    let mut pending = vec![path.to_path_buf()];
+    while let Some(path) = pending.pop() {
+        count += 1;
+        let is_dir = fs::symlink_metadata(&path).is_ok_and(|metadata| metadata.is_dir());
+        if is_dir && !is_reparse_point(&path) {
+            if let Ok(entries) = fs::read_dir(&path) {
+                pending.extend(entries.flatten().map(|entry| entry.path()));
+            }
+        }
+    }
+    count
 }
 
-#[derive(Clone, Debug)]
-pub struct DiffLine {
-    pub line_type: DiffLineType,
-    pub left_line_num: Option<usize>,
-    pub right_line_num: Option<usize>,
-    pub left_content: String,
-    pub right_content: String,
+/// Delete `path` and everything below it one entry at a time, adding each
+/// removal to `removed` and reporting it through `on_progress`. `cancelled`
+/// is checked before every removal; once it returns true the walk stops and
+/// this returns `Ok(false)`. Whatever wasn't reached is left intact; a
+/// folder is only removed once everything in it is gone, so a cancelled
+/// delete leaves complete (if emptier) folders. Links are removed, not
+/// followed.
+pub fn remove_tree_with_progress(
+    path: &Path,
+    removed: &mut u64,
+    on_progress: &mut dyn FnMut(u64, &Path),
+    cancelled: &dyn Fn() -> bool,
+) -> Result<bool> {
+    if cancelled() {
+        return Ok(false);
+    }
+    let metadata = fs::symlink_metadata(path)?;
+    if metadata.is_dir() {
+        if !is_reparse_point(path) {
+            for entry in fs::read_dir(path)? {
+                if !remove_tree_with_progress(&entry?.path(), removed, on_progress, cancelled)? {
+                    return Ok(false);
+                }
+            }
+            if cancelled() {
+                return Ok(false);
+            }
+        }
+        fs::remove_dir(path)?;
+    } else if let Err(e) = fs::remove_file(path) {
+        // Symlinks to folders are removed like folders on Windows
+        if !metadata.file_type().is_symlink() || fs::remove_dir(path).is_err() {
+            return Err(e.into());
+        }
+    }
+    *removed += 1;
+    on_progress(*removed, path);
+    Ok(true)
 }
 
-#[derive(Clone, Debug)]
-pub struct FileComparison {
-    pub left_path: PathBuf,
-    pub right_path: PathBuf,
-    pub are_identical: bool,
-    pub diff_lines: Vec<DiffLine>,
-    pub left_only_lines: usize,
-    pub right_only_lines: usize,
-    pub modified_lines: usize,
-    pub equal_lines: usize,
+/// Where a file's data lives and how many names point at it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct LinkInfo {
+    pub device: u64,
+    pub inode: u64,
+    /// Number of hard links, 1 for an ordinary file.
+    pub links: u64,
 }
 
-pub fn compare_files(left_path: &Path, right_path: &Path) -> Result<FileComparison> {
-    let left_metadata = fs::metadata(left_path)?;
-    let right_metadata = fs::metadata(right_path)?;
+/// Device, inode and hard link count of `path` itself (links aren't
+/// followed).
+#[cfg(unix)]
+pub fn link_info(path: &Path) -> Option<LinkInfo> {
+    use std::os::unix::fs::MetadataExt;
+    let metadata = fs::symlink_metadata(path).ok()?;
+    Some(LinkInfo {
+        device: metadata.dev(),
+        inode: metadata.ino(),
+        links: metadata.nlink(),
+    })
+}
 
-    if left_metadata.is_dir() || right_metadata.is_dir() {
-        return Err(anyhow::anyhow!("Cannot compare directories"));
-    }
+/// The standard library only exposes link counts on Unix (the Windows
+/// equivalent, `nNumberOfLinks`, is still unstable there).
+#[cfg(not(unix))]
+pub fn link_info(_path: &Path) -> Option<LinkInfo> {
+    None
+}
 
-    if left_metadata.len() != right_metadata.len() {
-        return compare_text_files(left_path, right_path);
+/// Files in `items` that share their data with other entries of the same
+/// list, mapped to the number of those other entries.
+pub fn hard_linked_items(items: &[FileItem]) -> HashMap<PathBuf, usize> {
+    let mut by_inode: HashMap<(u64, u64), Vec<&Path>> = HashMap::new();
+    for item in items.iter().filter(|item| !item.is_dir) {
+        if let Some(info) = link_info(&item.path).filter(|info| info.links > 1) {
+            by_inode.entry((info.device, info.inode)).or_default().push(&item.path);
+        }
     }
+    by_inode
+        .into_values()
+        .filter(|paths| paths.len() > 1)
+        .flat_map(|paths| {
+            let others = paths.len() - 1;
+            paths.into_iter().map(move |path| (path.to_path_buf(), others))
+        })
+        .collect()
+}
 
-    let left_content = fs::read(left_path)?;
-    let right_content = fs::read(right_path)?;
+/// What a zip archive holds according to its central directory.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct ZipSummary {
+    /// Files and folders in the archive.
+    pub entries: usize,
+    /// Total size of the files once extracted.
+    pub uncompressed_size: u64,
+}
 
-    if left_content == right_content {
-        return Ok(FileComparison {
-            left_path: left_path.to_path_buf(),
-            right_path: right_path.to_path_buf(),
-            are_identical: true,
-            diff_lines: vec![],
-            left_only_lines: 0,
-            right_only_lines: 0,
-            modified_lines: 0,
-            equal_lines: 0,
-        });
+/// Read the entry count and extracted size of a zip archive from its central
+/// directory, without decompressing anything.
+pub fn zip_summary(zip_path: &Path) -> Result<ZipSummary> {
+    let mut archive = zip::ZipArchive::new(fs::File::open(zip_path)?)?;
+    let mut summary = ZipSummary {
+        entries: archive.len(),
+        uncompressed_size: 0,
+    };
+    for i in 0..archive.len() {
+        summary.uncompressed_size += archive.by_index_raw(i)?.size();
+    }
+    Ok(summary)
+}
+
+/// Whether any entry of a zip archive is encrypted, i.e. a password is
+/// needed to extract it.
+pub fn zip_is_encrypted(zip_path: &Path) -> Result<bool> {
+    let mut archive = zip::ZipArchive::new(fs::File::open(zip_path)?)?;
+    for i in 0..archive.len() {
+        if archive.by_index_raw(i)?.encrypted() {
+            return Ok(true);
+        }
     }
+    Ok(false)
+}
 
-    compare_text_files(left_path, right_path)
+/// Whether `password` opens the encrypted entries of a zip archive. Only
+/// the first encrypted entry is checked, without extracting anything.
+pub fn zip_password_matches(zip_path: &Path, password: &str) -> Result<bool> {
+    let mut archive = zip::ZipArchive::new(fs::File::open(zip_path)?)?;
+    for i in 0..archive.len() {
+        if !archive.by_index_raw(i)?.encrypted() {
+            continue;
+        }
+        return match archive.by_index_decrypt(i, password.as_bytes()) {
+            Ok(_) => Ok(true),
+            Err(zip::result::ZipError::InvalidPassword) => Ok(false),
+            Err(e) => Err(e.into()),
+        };
+    }
+    Ok(true)
 }
 
-fn compare_text_files(left_path: &Path, right_path: &Path) -> Result<FileComparison> {
-    let left_file = fs::File::open(left_path)?;
-    let right_file = fs::File::open(right_path)?;
+pub fn extract_zip(zip_path: &Path, dest_dir: &Path) -> Result<()> {
+    extract_zip_with_password(zip_path, dest_dir, None)
+}
 
-    let left_reader = BufReader::new(left_file);
-    let right_reader = BufReader::new(right_file);
+/// Like `extract_zip`, decrypting encrypted entries with `password`. Fails
+/// with "Wrong password" before writing an entry the password doesn't open.
+pub fn extract_zip_with_password(
+    zip_path: &Path,
+    dest_dir: &Path,
+    password: Option<&str>,
+) -> Result<()> {
+    let file = std::fs::File::open(zip_path)?;
+    let mut archive = zip::ZipArchive::new(file)?;
 
-    let left_lines: Vec<String> = left_reader.lines().filter_map(|l| l.ok()).collect();
-    let right_lines: Vec<String> = right_reader.lines().filter_map(|l| l.ok()).collect();
+    // Refuse the whole archive before writing anything if an entry would
+    // land outside `dest_dir` (an absolute path or one climbing up with "..")
+    for i in 0..archive.len() {
+        let entry = archive.by_index_raw(i)?;
+        if entry.enclosed_name().is_none() {
+            anyhow::bail!("{} has an unsafe entry path: {}", zip_path.display(), entry.name());
+        }
+    }
 
-    let diff_lines = compute_diff(&left_lines, &right_lines);
+    for i in 0..archive.len() {
+        let encrypted = archive.by_index_raw(i)?.encrypted();
+        let entry = match (encrypted, password) {
+            (false, _) => archive.by_index(i),
+            (true, Some(password)) => archive.by_index_decrypt(i, password.as_bytes()),
+            (true, None) => anyhow::bail!("{} is password-protected", zip_path.display()),
+        };
+        let mut file = match entry {
+            Ok(file) => file,
+            Err(zip::result::ZipError::InvalidPassword) => anyhow::bail!("Wrong password"),
+            Err(e) => return Err(e.into()),
+        };
+        let Some(relative) = file.enclosed_name() else {
+            anyhow::bail!("{} has an unsafe entry path: {}", zip_path.display(), file.name());
+        };
+        let outpath = dest_dir.join(relative);
 
-    let mut left_only = 0;
-    let mut right_only = 0;
-    let mut modified = 0;
-    let mut equal = 0;
+        if file.is_dir() {
+            std::fs::create_dir_all(&outpath)?;
+        } else {
+            if let Some(p) = outpath.parent() {
+                if !p.exists() {
+                    std::fs::create_dir_all(p)?;
+                }
+            }
+            let mut outfile = std::fs::File::create(&outpath)?;
+            std::io::copy(&mut file, &mut outfile)?;
+        }
 
-    for line in &diff_lines {
-        match line.line_type {
-            DiffLineType::Equal => equal += 1,
-            DiffLineType::Added => right_only += 1,
-            DiffLineType::Removed => left_only += 1,
-            DiffLineType::Modified => modified += 1,
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            if let Some(mode) = file.unix_mode() {
+                std::fs::set_permissions(&outpath, std::fs::Permissions::from_mode(mode))?;
+            }
         }
     }
 
-    let are_identical = left_only == 0 && right_only == 0 && modified == 0;
+    Ok(())
+}
 
-    Ok(FileComparison {
-        left_path: left_path.to_path_buf(),
-        right_path: right_path.to_path_buf(),
-        are_identical,
-        diff_lines,
-        left_only_lines: left_only,
-        right_only_lines: right_only,
-        modified_lines: modified,
-        equal_lines: equal,
-    })
+/// Archive formats Filane can extract.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ArchiveFormat {
+    Zip,
+    Tar,
+    TarGz,
+    TarBz2,
+    TarXz,
+    /// Extracted with an external program, see [`extract_with_external`].
+    SevenZip,
+    /// Extracted with an external program, see [`extract_with_external`].
+    Rar,
 }
 
-fn compute_diff(left_lines: &[String], right_lines: &[String]) -> Vec<DiffLine> {
-    let mut result = Vec::new();
-    let mut left_idx = 0;
-    let mut right_idx = 0;
+impl ArchiveFormat {
+    /// The format of `path`, going by its name, or `None` if it isn't one
+    /// that can be extracted.
+    pub fn of(path: &Path) -> Option<ArchiveFormat> {
+        let name = path.file_name()?.to_string_lossy().to_lowercase();
+        let formats = [
+            (".zip", ArchiveFormat::Zip),
+            (".tar", ArchiveFormat::Tar),
+            (".tar.gz", ArchiveFormat::TarGz),
+            (".tgz", ArchiveFormat::TarGz),
+            (".tar.bz2", ArchiveFormat::TarBz2),
+            (".tbz2", ArchiveFormat::TarBz2),
+            (".tbz", ArchiveFormat::TarBz2),
+            (".tar.xz", ArchiveFormat::TarXz),
+            (".txz", ArchiveFormat::TarXz),
+            (".7z", ArchiveFormat::SevenZip),
+            (".rar", ArchiveFormat::Rar),
+        ];
+        formats
+            .into_iter()
+            .find(|(suffix, _)| name.ends_with(suffix))
+            .map(|(_, format)| format)
+    }
 
-    while left_idx < left_lines.len() || right_idx < right_lines.len() {
-        if left_idx >= left_lines.len() {
-            result.push(DiffLine {
-                line_type: DiffLineType::Added,
-                left_line_num: None,
+    /// Whether extracting needs an external program.
+    pub fn is_external(&self) -> bool {
+        matches!(self, ArchiveFormat::SevenZip | ArchiveFormat::Rar)
+    }
+}
+
+/// Command line archivers used for formats without built-in support.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum ExternalArchiver {
+    SevenZip,
+    Unar,
+}
+
+impl ExternalArchiver {
+    /// Programs tried for `format`, best first.
+    fn candidates(format: ArchiveFormat) -> &'static [(&'static str, ExternalArchiver)] {
+        match format {
+            ArchiveFormat::Rar => &[
+                ("unar", ExternalArchiver::Unar),
+                ("7z", ExternalArchiver::SevenZip),
+                ("7zz", ExternalArchiver::SevenZip),
+            ],
+            _ => &[
+                ("7z", ExternalArchiver::SevenZip),
+                ("7zz", ExternalArchiver::SevenZip),
+                ("7za", ExternalArchiver::SevenZip),
+                ("unar", ExternalArchiver::Unar),
+            ],
+        }
+    }
+
+    /// Arguments that extract `archive` into `dest_dir` without prompting,
+    /// overwriting files that already exist.
+    fn arguments(&self, archive: &Path, dest_dir: &Path) -> Vec<std::ffi::OsString> {
+        match self {
+            ExternalArchiver::SevenZip => {
+                let mut output = std::ffi::OsString::from("-o");
+                output.push(dest_dir);
+                vec!["x".into(), "-y".into(), output, archive.into()]
+            }
+            ExternalArchiver::Unar => vec![
+                "-quiet".into(),
+                "-force-overwrite".into(),
+                "-no-directory".into(),
+                "-output-directory".into(),
+                dest_dir.into(),
+                archive.into(),
+            ],
+        }
+    }
+}
+
+/// Folders listed in `PATH`.
+fn path_dirs() -> Vec<PathBuf> {
+    std::env::var_os("PATH")
+        .map(|path| std::env::split_paths(&path).collect())
+        .unwrap_or_default()
+}
+
+/// Path of `program` in one of `dirs`, trying the `.exe` name on Windows.
+fn find_program(program: &str, dirs: &[PathBuf]) -> Option<PathBuf> {
+    dirs.iter().find_map(|dir| {
+        let path = dir.join(program);
+        if path.is_file() {
+            return Some(path);
+        }
+        let exe = dir.join(format!("{}.exe", program));
+        (cfg!(windows) && exe.is_file()).then_some(exe)
+    })
+}
+
+/// The program that would extract `archive`, found on `PATH`. Errors,
+/// naming what to install, if none of the supported ones is there.
+pub fn find_external_archiver(archive: &Path) -> Result<PathBuf> {
+    find_archiver_in(archive, &path_dirs()).map(|(program, _)| program)
+}
+
+fn find_archiver_in(archive: &Path, dirs: &[PathBuf]) -> Result<(PathBuf, ExternalArchiver)> {
+    let format = ArchiveFormat::of(archive).filter(ArchiveFormat::is_external);
+    let Some(format) = format else {
+        anyhow::bail!("{} is not a .7z or .rar archive", archive.display());
+    };
+    ExternalArchiver::candidates(format)
+        .iter()
+        .find_map(|&(program, archiver)| find_program(program, dirs).map(|path| (path, archiver)))
+        .ok_or_else(|| {
+            let extension = if format == ArchiveFormat::Rar { "RAR" } else { "7z" };
+            anyhow::anyhow!(
+                "Extracting {} archives needs 7-Zip (7z) or unar, and neither was found on PATH",
+                extension
+            )
+        })
+}
+
+/// Extract a .7z or .rar archive into `dest_dir` with 7-Zip or unar,
+/// whichever is installed.
+pub fn extract_with_external(archive_path: &Path, dest_dir: &Path) -> Result<()> {
+    extract_with_archiver_in(archive_path, dest_dir, &path_dirs())
+}
+
+fn extract_with_archiver_in(archive_path: &Path, dest_dir: &Path, dirs: &[PathBuf]) -> Result<()> {
+    let (program, archiver) = find_archiver_in(archive_path, dirs)?;
+    fs::create_dir_all(dest_dir)?;
+    let output = std::process::Command::new(&program)
+        .args(archiver.arguments(archive_path, dest_dir))
+        .stdin(std::process::Stdio::null())
+        .output()
+        .map_err(|e| anyhow::anyhow!("Cannot run {}: {}", program.display(), e))?;
+    if output.status.success() {
+        return Ok(());
+    }
+    // 7-Zip reports some errors on stdout, so fall back to it
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let message = stderr
+        .lines()
+        .chain(stdout.lines())
+        .map(str::trim)
+        .find(|line| !line.is_empty())
+        .map_or_else(|| output.status.to_string(), str::to_string);
+    let name = program.file_name().unwrap_or_default().to_string_lossy();
+    anyhow::bail!("{} failed: {}", name, message)
+}
+
+/// Extract a zip or (optionally compressed) tar archive into `dest_dir`,
+/// picking the format from the archive's name. Tar entries that would land
+/// outside `dest_dir` are skipped. .7z and .rar archives go to
+/// [`extract_with_external`].
+pub fn extract_any(archive_path: &Path, dest_dir: &Path) -> Result<()> {
+    extract_any_with_password(archive_path, dest_dir, None)
+}
+
+/// Like `extract_any`, with the password for encrypted zip archives.
+pub fn extract_any_with_password(
+    archive_path: &Path,
+    dest_dir: &Path,
+    password: Option<&str>,
+) -> Result<()> {
+    let Some(format) = ArchiveFormat::of(archive_path) else {
+        anyhow::bail!(
+            "{} is not a .zip, .tar, .tar.gz, .tar.bz2, .tar.xz, .7z or .rar archive",
+            archive_path.display()
+        );
+    };
+    if format == ArchiveFormat::Zip {
+        return extract_zip_with_password(archive_path, dest_dir, password);
+    }
+    if format.is_external() {
+        return extract_with_external(archive_path, dest_dir);
+    }
+
+    let file = std::io::BufReader::new(fs::File::open(archive_path)?);
+    let reader: Box<dyn Read> = match format {
+        ArchiveFormat::Tar => Box::new(file),
+        ArchiveFormat::TarGz => Box::new(flate2::read::GzDecoder::new(file)),
+        ArchiveFormat::TarBz2 => Box::new(bzip2::read::BzDecoder::new(file)),
+        ArchiveFormat::TarXz => Box::new(xz2::read::XzDecoder::new(file)),
+        ArchiveFormat::Zip | ArchiveFormat::SevenZip | ArchiveFormat::Rar => unreachable!(),
+    };
+    fs::create_dir_all(dest_dir)?;
+    let mut archive = tar::Archive::new(reader);
+    archive.set_preserve_permissions(true);
+    archive.set_preserve_mtime(true);
+    archive.unpack(dest_dir)?;
+    Ok(())
+}
+
+#[derive(Clone, Debug)]
+pub struct SearchCriteria {
+    /// Folders to search recursively. Results found under several roots
+    /// (e.g. nested roots) are only reported once.
+    pub search_paths: Vec<PathBuf>,
+    pub filename_pattern: String,
+    pub content_pattern: String,
+    pub min_size: Option<u64>,
+    pub max_size: Option<u64>,
+    pub modified_after: Option<SystemTime>,
+    pub modified_before: Option<SystemTime>,
+    pub file_type: SearchFileType,
+    pub case_sensitive: bool,
+    pub include_hidden: bool,
+    /// Skip paths ignored by git (`.gitignore`, `.git/info/exclude` and the
+    /// global excludes file) when searching inside a repository.
+    pub respect_gitignore: bool,
+    /// Only match files whose extension is in one of these groups or in
+    /// `custom_extensions`. Both empty means any extension.
+    pub extension_groups: Vec<ExtensionGroup>,
+    /// Lowercase extensions without the dot, see [`parse_extension_list`].
+    pub custom_extensions: Vec<String>,
+}
+
+/// Named sets of extensions offered by the search's extension filter.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum ExtensionGroup {
+    Code,
+    Images,
+    Documents,
+    Archives,
+    Audio,
+    Video,
+}
+
+impl ExtensionGroup {
+    pub fn all() -> &'static [ExtensionGroup] {
+        &[
+            ExtensionGroup::Code,
+            ExtensionGroup::Images,
+            ExtensionGroup::Documents,
+            ExtensionGroup::Archives,
+            ExtensionGroup::Audio,
+            ExtensionGroup::Video,
+        ]
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            ExtensionGroup::Code => "Code",
+            ExtensionGroup::Images => "Images",
+            ExtensionGroup::Documents => "Documents",
+            ExtensionGroup::Archives => "Archives",
+            ExtensionGroup::Audio => "Audio",
+            ExtensionGroup::Video => "Video",
+        }
+    }
+
+    /// Lowercase extensions in the group, without the dot.
+    pub fn extensions(&self) -> &'static [&'static str] {
+        match self {
+            ExtensionGroup::Code => &[
+                "rs", "py", "js", "ts", "jsx", "tsx", "c", "h", "cpp", "hpp", "cs", "java", "kt",
+                "go", "rb", "php", "swift", "sh", "ps1", "html", "css", "json", "toml", "yaml",
+                "yml", "xml", "sql",
+            ],
+            ExtensionGroup::Images => &[
+                "png", "jpg", "jpeg", "gif", "bmp", "ico", "webp", "svg", "tif", "tiff", "heic",
+            ],
+            ExtensionGroup::Documents => &[
+                "txt", "md", "pdf", "doc", "docx", "odt", "rtf", "xls", "xlsx", "ods", "csv",
+                "ppt", "pptx", "odp",
+            ],
+            ExtensionGroup::Archives => &["zip", "tar", "gz", "tgz", "bz2", "xz", "7z", "rar", "zst"],
+            ExtensionGroup::Audio => &["mp3", "flac", "wav", "ogg", "m4a", "aac", "opus"],
+            ExtensionGroup::Video => &["mp4", "mkv", "avi", "mov", "webm", "wmv"],
+        }
+    }
+
+    /// The group `file_name`'s extension belongs to, if any.
+    pub fn of(file_name: &str) -> Option<ExtensionGroup> {
+        let extension = Path::new(file_name).extension()?.to_string_lossy().to_lowercase();
+        Self::all()
+            .iter()
+            .copied()
+            .find(|group| group.extensions().contains(&extension.as_str()))
+    }
+
+    /// The group more than half of the files in `items` belong to. Folders
+    /// don't count either way.
+    pub fn dominant(items: &[FileItem]) -> Option<ExtensionGroup> {
+        let mut counts: HashMap<ExtensionGroup, usize> = HashMap::new();
+        let mut files = 0;
+        for item in items.iter().filter(|item| !item.is_dir) {
+            files += 1;
+            if let Some(group) = Self::of(&item.name) {
+                *counts.entry(group).or_default() += 1;
+            }
+        }
+        counts
+            .into_iter()
+            .find(|&(_, count)| count * 2 > files)
+            .map(|(group, _)| group)
+    }
+}
+
+/// Split a user-typed list such as "rs, .toml md" into lowercase
+/// extensions without dots.
+pub fn parse_extension_list(text: &str) -> Vec<String> {
+    let mut extensions: Vec<String> = text
+        .split(|c: char| c == ',' || c == ';' || c.is_whitespace())
+        .map(|ext| ext.trim().trim_start_matches("*.").trim_start_matches('.').to_lowercase())
+        .filter(|ext| !ext.is_empty())
+        .collect();
+    extensions.dedup();
+    extensions
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum SearchFileType {
+    All,
+    Files,
+    Directories,
+}
+
+impl Default for SearchCriteria {
+    fn default() -> Self {
+        Self {
+            search_paths: vec![PathBuf::from(".")],
+            filename_pattern: String::new(),
+            content_pattern: String::new(),
+            min_size: None,
+            max_size: None,
+            modified_after: None,
+            modified_before: None,
+            file_type: SearchFileType::All,
+            case_sensitive: false,
+            include_hidden: false,
+            respect_gitignore: false,
+            extension_groups: Vec::new(),
+            custom_extensions: Vec::new(),
+        }
+    }
+}
+
+/// A search result, with the byte range of the file name that matched the
+/// filename pattern (if one was given) so it can be highlighted.
+#[derive(Clone, Debug)]
+pub struct SearchHit {
+    pub item: FileItem,
+    pub name_match: Option<std::ops::Range<usize>>,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct SearchResults {
+    pub items: Vec<SearchHit>,
+    /// Directories that could not be read (usually permission denied), so
+    /// the caller can tell the user that the results may be incomplete.
+    pub skipped_dirs: usize,
+}
+
+impl SearchCriteria {
+    pub fn has_extension_filter(&self) -> bool {
+        !self.extension_groups.is_empty() || !self.custom_extensions.is_empty()
+    }
+
+    /// Whether `file_name`'s extension passes the extension filter.
+    pub fn matches_extension(&self, file_name: &str) -> bool {
+        if !self.has_extension_filter() {
+            return true;
+        }
+        let Some(extension) = Path::new(file_name).extension() else {
+            return false;
+        };
+        let extension = extension.to_string_lossy().to_lowercase();
+        self.extension_groups
+            .iter()
+            .any(|group| group.extensions().contains(&extension.as_str()))
+            || self.custom_extensions.contains(&extension)
+    }
+
+    /// Add a search root unless it is already in the list.
+    pub fn add_search_path(&mut self, path: PathBuf) {
+        if !self.search_paths.contains(&path) {
+            self.search_paths.push(path);
+        }
+    }
+}
+
+pub fn search_files(criteria: &SearchCriteria) -> Result<SearchResults> {
+    search_files_with_progress(criteria, &mut |_, _| {}, &|| false)
+}
+
+/// [`search_files`] for a worker thread. Like every walk over a whole tree
+/// (search, folder sizes, directory comparison, sync) it must not run on
+/// the UI thread: `on_hits` streams the matches found since its last call,
+/// with the folder being searched, so the caller can show partial results,
+/// and once `cancelled` returns true the walk stops and returns what it has
+/// found so far.
+pub fn search_files_with_progress(
+    criteria: &SearchCriteria,
+    on_hits: &mut dyn FnMut(&[SearchHit], &Path),
+    cancelled: &dyn Fn() -> bool,
+) -> Result<SearchResults> {
+    // Canonical roots so that the same folder reached through different
+    // spellings is recognised; roots inside another root are covered by it.
+    let mut roots: Vec<PathBuf> = Vec::new();
+    for path in &criteria.search_paths {
+        let root = fs::canonicalize(path).unwrap_or_else(|_| path.clone());
+        if !roots.contains(&root) {
+            roots.push(root);
+        }
+    }
+    let roots: Vec<&PathBuf> = roots
+        .iter()
+        .filter(|root| !roots.iter().any(|other| other != *root && root.starts_with(other)))
+        .collect();
+
+    let mut walk = SearchWalk {
+        criteria,
+        results: SearchResults::default(),
+        reported: 0,
+        visited: std::collections::HashSet::new(),
+        on_hits,
+        cancelled,
+    };
+    for root in roots {
+        if walk.stopped() {
+            break;
+        }
+        if criteria.respect_gitignore {
+            search_respecting_gitignore(root, &mut walk);
+        } else {
+            search_recursive(root, &mut walk)?;
+        }
+        walk.flush(root);
+    }
+
+    let mut results = walk.results;
+    let mut seen = std::collections::HashSet::new();
+    results.items.retain(|hit| seen.insert(hit.item.path.clone()));
+    Ok(results)
+}
+
+/// State of one [`search_files_with_progress`] walk.
+struct SearchWalk<'a> {
+    criteria: &'a SearchCriteria,
+    results: SearchResults,
+    /// How many of `results.items` have been passed to `on_hits`
+    reported: usize,
+    visited: std::collections::HashSet<PathBuf>,
+    on_hits: &'a mut dyn FnMut(&[SearchHit], &Path),
+    cancelled: &'a dyn Fn() -> bool,
+}
+
+impl SearchWalk<'_> {
+    /// Hand the hits found since the last call to `on_hits`.
+    fn flush(&mut self, path: &Path) {
+        (self.on_hits)(&self.results.items[self.reported..], path);
+        self.reported = self.results.items.len();
+    }
+
+    fn stopped(&self) -> bool {
+        (self.cancelled)()
+    }
+}
+
+fn search_recursive(path: &Path, walk: &mut SearchWalk) -> Result<()> {
+    if !path.is_dir() || walk.stopped() {
+        return Ok(());
+    }
+
+    // Each folder is searched once, so symlinks pointing back up the tree
+    // (or at a folder already searched) can't make the walk loop forever.
+    let canonical = fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf());
+    if !walk.visited.insert(canonical) {
+        return Ok(());
+    }
+    walk.flush(path);
+
+    let entries = match fs::read_dir(path) {
+        Ok(e) => e,
+        Err(_) => {
+            walk.results.skipped_dirs += 1;
+            return Ok(());
+        }
+    };
+
+    for entry in entries.flatten() {
+        let entry_path = entry.path();
+        let file_name = entry.file_name().to_string_lossy().to_string();
+
+        if !walk.criteria.include_hidden && file_name.starts_with('.') {
+            continue;
+        }
+
+        let metadata = match entry.metadata() {
+            Ok(m) => m,
+            Err(_) => continue,
+        };
+
+        let is_dir = metadata.is_dir();
+        add_if_matching(walk.criteria, &entry_path, file_name, &metadata, &mut walk.results);
+
+        if is_dir && !is_reparse_point(&entry_path) {
+            let _ = search_recursive(&entry_path, walk);
+        }
+    }
+
+    Ok(())
+}
+
+/// The walk behind `respect_gitignore`: the `ignore` crate skips what git
+/// ignores (only inside repositories) and guards against symlink loops.
+fn search_respecting_gitignore(root: &Path, walk: &mut SearchWalk) {
+    let criteria = walk.criteria;
+    let walker = ignore::WalkBuilder::new(root)
+        .hidden(!criteria.include_hidden)
+        .ignore(false)
+        .git_ignore(true)
+        .git_exclude(true)
+        .git_global(true)
+        .parents(true)
+        // Junctions count as links on Windows and are not walked into there
+        .follow_links(!cfg!(windows))
+        .build();
+
+    for entry in walker {
+        if walk.stopped() {
+            return;
+        }
+        let entry = match entry {
+            Ok(entry) => entry,
+            Err(_) => {
+                walk.results.skipped_dirs += 1;
+                continue;
+            }
+        };
+        if entry.depth() == 0 {
+            continue;
+        }
+        let Ok(metadata) = fs::metadata(entry.path()) else {
+            continue;
+        };
+        if metadata.is_dir() {
+            walk.flush(entry.path());
+        }
+        let file_name = entry.file_name().to_string_lossy().to_string();
+        add_if_matching(criteria, entry.path(), file_name, &metadata, &mut walk.results);
+    }
+}
+
+/// Push the entry to `results` if it matches every criterion. Folders are
+/// walked whether or not they match, so this is the only place that decides
+/// what is reported and each entry is looked at exactly once.
+fn add_if_matching(
+    criteria: &SearchCriteria,
+    path: &Path,
+    file_name: String,
+    metadata: &fs::Metadata,
+    results: &mut SearchResults,
+) {
+    let is_dir = metadata.is_dir();
+    let size = metadata.len();
+    let modified = metadata.modified().unwrap_or(SystemTime::UNIX_EPOCH);
+
+    let Some(name_match) = entry_matches(criteria, &file_name, is_dir, size, modified) else {
+        return;
+    };
+    // Folders have no content of their own to match
+    let content_matches = if criteria.content_pattern.is_empty() {
+        true
+    } else {
+        !is_dir && search_file_content(path, &criteria.content_pattern, criteria.case_sensitive)
+    };
+    if content_matches {
+        results.items.push(SearchHit {
+            item: FileItem {
+                name: file_name,
+                path: path.to_path_buf(),
+                is_dir,
+                size,
+                modified,
+                git_status: None,
+            },
+            name_match,
+        });
+    }
+}
+
+/// Check an entry against every criterion except the content pattern.
+/// Returns `None` if it doesn't match, otherwise the range of the name that
+/// matched the filename pattern (if there is one). A folder's own size is
+/// meaningless, so folders never match a size limit.
+pub(crate) fn entry_matches(
+    criteria: &SearchCriteria,
+    file_name: &str,
+    is_dir: bool,
+    size: u64,
+    modified: SystemTime,
+) -> Option<Option<std::ops::Range<usize>>> {
+    match criteria.file_type {
+        SearchFileType::Files if is_dir => return None,
+        SearchFileType::Directories if !is_dir => return None,
+        _ => {}
+    }
+
+    let mut name_match = None;
+    if !criteria.filename_pattern.is_empty() {
+        name_match =
+            Some(find_name_match(file_name, &criteria.filename_pattern, criteria.case_sensitive)?);
+    }
+
+    // Size and extension limits describe files, so folders never pass them
+    let has_size_limit = criteria.min_size.is_some() || criteria.max_size.is_some();
+    if (is_dir && (has_size_limit || criteria.has_extension_filter()))
+        || (!is_dir && !criteria.matches_extension(file_name))
+        || criteria.min_size.is_some_and(|min| size < min)
+        || criteria.max_size.is_some_and(|max| size > max)
+        || criteria.modified_after.is_some_and(|after| modified < after)
+        || criteria.modified_before.is_some_and(|before| modified > before)
+    {
+        return None;
+    }
+    Some(name_match)
+}
+
+/// Byte range of the first occurrence of `pattern` in `name`. Without
+/// `case_sensitive` the comparison is done on lowercased text, but the range
+/// still points into the original name.
+fn find_name_match(
+    name: &str,
+    pattern: &str,
+    case_sensitive: bool,
+) -> Option<std::ops::Range<usize>> {
+    if case_sensitive {
+        return name.find(pattern).map(|start| start..start + pattern.len());
+    }
+
+    let pattern = pattern.to_lowercase();
+    for (start, _) in name.char_indices() {
+        let mut folded = String::new();
+        for (offset, c) in name[start..].char_indices() {
+            folded.extend(c.to_lowercase());
+            if folded.len() >= pattern.len() {
+                if folded == pattern {
+                    return Some(start..start + offset + c.len_utf8());
+                }
+                break;
+            }
+        }
+    }
+    None
+}
+
+/// Subsequence ("fuzzy") match of `pattern` in `text`, ignoring case and
+/// whitespace in the pattern: "amr" matches "app_manager.rs". Returns a score,
+/// higher for characters at word starts and runs of adjacent characters, and
+/// the byte offsets of the matched characters in `text`.
+pub fn fuzzy_match(text: &str, pattern: &str) -> Option<(i64, Vec<usize>)> {
+    let chars: Vec<(usize, char)> = text.char_indices().collect();
+    let mut positions = Vec::new();
+    let mut score = 0i64;
+    let mut next = 0;
+    for wanted in pattern.chars().filter(|c| !c.is_whitespace()) {
+        let found = (next..chars.len())
+            .find(|&i| chars[i].1.to_lowercase().eq(wanted.to_lowercase()))?;
+        let word_start = found == 0 || {
+            let (before, current) = (chars[found - 1].1, chars[found].1);
+            !before.is_alphanumeric() || (before.is_lowercase() && current.is_uppercase())
+        };
+        score += 1;
+        if word_start {
+            score += 8;
+        }
+        if found > 0 && positions.last() == Some(&chars[found - 1].0) {
+            score += 5;
+        } else {
+            score -= (found - next).min(10) as i64;
+        }
+        positions.push(chars[found].0);
+        next = found + 1;
+    }
+    Some((score, positions))
+}
+
+fn search_file_content(path: &Path, pattern: &str, case_sensitive: bool) -> bool {
+    let mut file = match fs::File::open(path) {
+        Ok(f) => f,
+        Err(_) => return false,
+    };
+
+    let mut content = String::new();
+    if file.read_to_string(&mut content).is_err() {
+        return false;
+    }
+
+    if case_sensitive {
+        content.contains(pattern)
+    } else {
+        content.to_lowercase().contains(&pattern.to_lowercase())
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum DiffLineType {
+    Equal,
+    Added,
+    Removed,
+    Modified,
+}
+
+#[derive(Clone, Debug)]
+pub struct DiffLine {
+    pub line_type: DiffLineType,
+    pub left_line_num: Option<usize>,
+    pub right_line_num: Option<usize>,
+    pub left_content: String,
+    pub right_content: String,
+}
+
+#[derive(Clone, Debug)]
+pub struct FileComparison {
+    pub left_path: PathBuf,
+    pub right_path: PathBuf,
+    pub are_identical: bool,
+    pub diff_lines: Vec<DiffLine>,
+    pub left_only_lines: usize,
+    pub right_only_lines: usize,
+    pub modified_lines: usize,
+    pub equal_lines: usize,
+    pub left_encoding_note: Option<String>,
+    pub right_encoding_note: Option<String>,
+    /// The compared text, kept so the diff can be redone with other options.
+    /// Empty when the files were byte-for-byte identical.
+    pub left_lines: Vec<String>,
+    pub right_lines: Vec<String>,
+    pub options: DiffOptions,
+}
+
+/// Differences the line diff can be told to overlook.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct DiffOptions {
+    /// Compare lines with leading and trailing whitespace trimmed and every
+    /// run of whitespace inside them collapsed to one space.
+    pub ignore_whitespace: bool,
+    pub ignore_case: bool,
+}
+
+impl DiffOptions {
+    /// The form of `line` that is actually compared.
+    pub fn normalize(&self, line: &str) -> String {
+        let line = if self.ignore_whitespace {
+            line.split_whitespace().collect::<Vec<_>>().join(" ")
+        } else {
+            line.to_string()
+        };
+        if self.ignore_case {
+            line.to_lowercase()
+        } else {
+            line
+        }
+    }
+}
+
+impl FileComparison {
+    /// Redo the diff of the kept lines with `options`, updating the counts.
+    pub fn set_options(&mut self, options: DiffOptions) {
+        self.options = options;
+        self.diff_lines = compute_diff(&self.left_lines, &self.right_lines, options);
+        self.left_only_lines = 0;
+        self.right_only_lines = 0;
+        self.modified_lines = 0;
+        self.equal_lines = 0;
+        for line in &self.diff_lines {
+            match line.line_type {
+                DiffLineType::Equal => self.equal_lines += 1,
+                DiffLineType::Added => self.right_only_lines += 1,
+                DiffLineType::Removed => self.left_only_lines += 1,
+                DiffLineType::Modified => self.modified_lines += 1,
+            }
+        }
+        self.are_identical =
+            self.left_only_lines == 0 && self.right_only_lines == 0 && self.modified_lines == 0;
+    }
+}
+
+pub fn compare_files(
+    left_path: &Path,
+    right_path: &Path,
+    options: DiffOptions,
+) -> Result<FileComparison> {
+    let left_metadata = fs::metadata(left_path)?;
+    let right_metadata = fs::metadata(right_path)?;
+
+    if left_metadata.is_dir() || right_metadata.is_dir() {
+        return Err(anyhow::anyhow!("Cannot compare directories"));
+    }
+
+    if left_metadata.len() != right_metadata.len() {
+        return compare_text_files(left_path, right_path, options);
+    }
+
+    let left_content = fs::read(left_path)?;
+    let right_content = fs::read(right_path)?;
+
+    if left_content == right_content {
+        return Ok(FileComparison {
+            left_path: left_path.to_path_buf(),
+            right_path: right_path.to_path_buf(),
+            are_identical: true,
+            diff_lines: vec![],
+            left_only_lines: 0,
+            right_only_lines: 0,
+            modified_lines: 0,
+            equal_lines: 0,
+            left_encoding_note: None,
+            right_encoding_note: None,
+            left_lines: Vec::new(),
+            right_lines: Vec::new(),
+            options,
+        });
+    }
+
+    compare_text_files(left_path, right_path, options)
+}
+
+/// Text decoded from a file of unknown encoding.
+#[derive(Clone, Debug)]
+pub struct DecodedText {
+    pub text: String,
+    pub encoding: &'static str,
+    /// Some bytes were invalid in the detected encoding and were replaced.
+    pub had_errors: bool,
+}
+
+impl DecodedText {
+    /// A short note for the UI when the text wasn't plain UTF-8.
+    pub fn encoding_note(&self) -> Option<String> {
+        if self.had_errors {
+            Some(format!("decoded as {} (some bytes replaced)", self.encoding))
+        } else if self.encoding != encoding_rs::UTF_8.name() {
+            Some(format!("decoded as {}", self.encoding))
+        } else {
+            None
+        }
+    }
+
+    pub fn lines(&self) -> Vec<String> {
+        self.text.lines().map(|l| l.to_string()).collect()
+    }
+}
+
+/// Decode bytes using their BOM, or as UTF-8 when valid, or otherwise with
+/// the encoding guessed from the content (e.g. Latin-1 or Shift_JIS).
+pub fn decode_text(bytes: &[u8]) -> DecodedText {
+    let encoding = match encoding_rs::Encoding::for_bom(bytes) {
+        Some((encoding, _)) => encoding,
+        None if std::str::from_utf8(bytes).is_ok() => encoding_rs::UTF_8,
+        None => {
+            let mut detector = chardetng::EncodingDetector::new();
+            detector.feed(bytes, true);
+            detector.guess(None, true)
+        }
+    };
+
+    let (text, encoding, had_errors) = encoding.decode(bytes);
+    DecodedText {
+        text: text.into_owned(),
+        encoding: encoding.name(),
+        had_errors,
+    }
+}
+
+pub fn read_text_file(path: &Path) -> Result<DecodedText> {
+    Ok(decode_text(&fs::read(path)?))
+}
+
+/// Decode at most the first `max_bytes` of a file, for previews. A UTF-8
+/// character cut off at the limit is dropped rather than making the whole
+/// prefix look like another encoding.
+pub fn read_text_prefix(path: &Path, max_bytes: usize) -> Result<DecodedText> {
+    use std::io::Read;
+    let mut bytes = Vec::new();
+    fs::File::open(path)?
+        .take(max_bytes as u64)
+        .read_to_end(&mut bytes)?;
+    if bytes.len() == max_bytes {
+        if let Err(e) = std::str::from_utf8(&bytes) {
+            if e.error_len().is_none() {
+                bytes.truncate(e.valid_up_to());
+            }
+        }
+    }
+    Ok(decode_text(&bytes))
+}
+
+/// Tags and stream properties of an audio file.
+#[derive(Clone, Debug, Default)]
+pub struct AudioInfo {
+    pub title: Option<String>,
+    pub artist: Option<String>,
+    pub album: Option<String>,
+    pub duration: std::time::Duration,
+    /// Audio bitrate in kbps.
+    pub bitrate: Option<u32>,
+    pub sample_rate: Option<u32>,
+    /// Encoded cover image, preferring the front cover.
+    pub cover: Option<Vec<u8>>,
+}
+
+/// Read tags and properties of an mp3, flac, wav, ogg or m4a file.
+pub fn read_audio_info(path: &Path) -> Result<AudioInfo> {
+    use lofty::prelude::*;
+
+    let tagged = lofty::read_from_path(path)?;
+    let properties = tagged.properties();
+    let mut info = AudioInfo {
+        duration: properties.duration(),
+        bitrate: properties.audio_bitrate(),
+        sample_rate: properties.sample_rate(),
+        ..AudioInfo::default()
+    };
+
+    if let Some(tag) = tagged.primary_tag().or_else(|| tagged.first_tag()) {
+        info.title = tag.title().map(|s| s.to_string());
+        info.artist = tag.artist().map(|s| s.to_string());
+        info.album = tag.album().map(|s| s.to_string());
+        info.cover = tag
+            .pictures()
+            .iter()
+            .find(|picture| picture.pic_type() == lofty::picture::PictureType::CoverFront)
+            .or_else(|| tag.pictures().first())
+            .map(|picture| picture.data().to_vec());
+    }
+    Ok(info)
+}
+
+fn compare_text_files(
+    left_path: &Path,
+    right_path: &Path,
+    options: DiffOptions,
+) -> Result<FileComparison> {
+    let left = read_text_file(left_path)?;
+    let right = read_text_file(right_path)?;
+
+    let mut comparison =
+        build_comparison(left_path, right_path, left.lines(), right.lines(), options);
+    comparison.left_encoding_note = left.encoding_note();
+    comparison.right_encoding_note = right.encoding_note();
+    Ok(comparison)
+}
+
+fn build_comparison(
+    left_path: &Path,
+    right_path: &Path,
+    left_lines: Vec<String>,
+    right_lines: Vec<String>,
+    options: DiffOptions,
+) -> FileComparison {
+    let mut comparison = FileComparison {
+        left_path: left_path.to_path_buf(),
+        right_path: right_path.to_path_buf(),
+        are_identical: true,
+        diff_lines: Vec::new(),
+        left_only_lines: 0,
+        right_only_lines: 0,
+        modified_lines: 0,
+        equal_lines: 0,
+        left_encoding_note: None,
+        right_encoding_note: None,
+        left_lines,
+        right_lines,
+        options,
+    };
+    comparison.set_options(options);
+    comparison
+}
+
+/// Compare a working-tree file against the version committed at HEAD.
+/// The HEAD side is shown on the left as `HEAD:<relative path>`.
+pub fn compare_with_git_head(path: &Path, options: DiffOptions) -> Result<FileComparison> {
+    let canonical_file = path.canonicalize()?;
+    let repo_path = find_git_repo(&canonical_file)
+        .ok_or_else(|| anyhow::anyhow!("File is not inside a git repository"))?;
+    let repo = git2::Repository::open(&repo_path)?;
+    let relative = canonical_file.strip_prefix(&repo_path)?;
+
+    let tree = repo.head()?.peel_to_tree()?;
+    let entry = tree
+        .get_path(relative)
+        .map_err(|_| anyhow::anyhow!("File is not tracked at HEAD"))?;
+    let blob = entry.to_object(&repo)?.peel_to_blob()?;
+
+    let head = decode_text(blob.content());
+    let working = read_text_file(path)?;
+
+    let head_label = PathBuf::from(format!("HEAD:{}", relative.display()));
+    let mut comparison =
+        build_comparison(&head_label, path, head.lines(), working.lines(), options);
+    comparison.left_encoding_note = head.encoding_note();
+    comparison.right_encoding_note = working.encoding_note();
+    Ok(comparison)
+}
+
+/// Diff a file (left) against text that isn't backed by a file, such as the
+/// clipboard (right). `label` names the right side in the compare dialog.
+pub fn compare_with_text(
+    path: &Path,
+    text: &str,
+    label: &str,
+    options: DiffOptions,
+) -> Result<FileComparison> {
+    let file = read_text_file(path)?;
+    let text_lines: Vec<String> = text.lines().map(|l| l.to_string()).collect();
+
+    let mut comparison =
+        build_comparison(path, Path::new(label), file.lines(), text_lines, options);
+    comparison.left_encoding_note = file.encoding_note();
+    Ok(comparison)
+}
+
+/// Which side of a three-way comparison changed a line of the base.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MergeLineType {
+    Unchanged,
+    LeftChanged,
+    RightChanged,
+    /// Both sides made the same change.
+    BothChanged,
+    /// The sides changed the line differently.
+    Conflict,
+}
+
+/// One row of a three-way comparison. A side is `None` where it has no
+/// line: the base for lines a side inserted, a side that removed the line.
+#[derive(Clone, Debug)]
+pub struct MergeLine {
+    pub line_type: MergeLineType,
+    pub base_line_num: Option<usize>,
+    pub base: Option<String>,
+    pub left: Option<String>,
+    pub right: Option<String>,
+}
+
+#[derive(Clone, Debug)]
+pub struct ThreeWayComparison {
+    pub base_path: PathBuf,
+    pub left_path: PathBuf,
+    pub right_path: PathBuf,
+    pub lines: Vec<MergeLine>,
+    pub left_changes: usize,
+    pub right_changes: usize,
+    pub both_changes: usize,
+    pub conflicts: usize,
+}
+
+/// What one side did around a line of the base: the lines it inserted
+/// before it, and the line itself kept, changed or removed (`None`).
+#[derive(Default)]
+struct BaseSlot {
+    inserted: Vec<String>,
+    line: Option<String>,
+}
+
+/// Lay a two-way diff of the base against one side out per base line.
+/// There is one extra slot at the end for lines appended after the base.
+fn slots_from_diff(diff: &[DiffLine], base_len: usize) -> Vec<BaseSlot> {
+    let mut slots: Vec<BaseSlot> = (0..=base_len).map(|_| BaseSlot::default()).collect();
+    let mut next = 0;
+    for line in diff {
+        match line.line_type {
+            DiffLineType::Added => slots[next].inserted.push(line.right_content.clone()),
+            DiffLineType::Removed => next += 1,
+            DiffLineType::Equal | DiffLineType::Modified => {
+                slots[next].line = Some(line.right_content.clone());
+                next += 1;
+            }
+        }
+    }
+    slots
+}
+
+/// Compare `left` and `right` against the common ancestor `base`, e.g. the
+/// two sides of a merge conflict, showing which side changed each line.
+/// Both sides are diffed against the base and the diffs combined.
+pub fn compare_three_way(
+    base_path: &Path,
+    left_path: &Path,
+    right_path: &Path,
+    options: DiffOptions,
+) -> Result<ThreeWayComparison> {
+    for path in [base_path, left_path, right_path] {
+        if fs::metadata(path)?.is_dir() {
+            anyhow::bail!("Cannot compare directories");
+        }
+    }
+    let base = read_text_file(base_path)?.lines();
+    let left = read_text_file(left_path)?.lines();
+    let right = read_text_file(right_path)?.lines();
+    let left_slots = slots_from_diff(&compute_diff(&base, &left, options), base.len());
+    let right_slots = slots_from_diff(&compute_diff(&base, &right, options), base.len());
+
+    let same = |a: &Option<String>, b: &Option<String>| match (a, b) {
+        (Some(a), Some(b)) => options.normalize(a) == options.normalize(b),
+        (None, None) => true,
+        _ => false,
+    };
+    let mut comparison = ThreeWayComparison {
+        base_path: base_path.to_path_buf(),
+        left_path: left_path.to_path_buf(),
+        right_path: right_path.to_path_buf(),
+        lines: Vec::new(),
+        left_changes: 0,
+        right_changes: 0,
+        both_changes: 0,
+        conflicts: 0,
+    };
+    for (index, (left_slot, right_slot)) in left_slots.iter().zip(&right_slots).enumerate() {
+        // Lines inserted before this base line
+        let (left_ins, right_ins) = (&left_slot.inserted, &right_slot.inserted);
+        let insert_type = match (left_ins.is_empty(), right_ins.is_empty()) {
+            (true, true) => None,
+            (false, true) => Some(MergeLineType::LeftChanged),
+            (true, false) => Some(MergeLineType::RightChanged),
+            (false, false) => {
+                let equal = left_ins.len() == right_ins.len()
+                    && left_ins
+                        .iter()
+                        .zip(right_ins)
+                        .all(|(l, r)| options.normalize(l) == options.normalize(r));
+                Some(if equal { MergeLineType::BothChanged } else { MergeLineType::Conflict })
+            }
+        };
+        if let Some(line_type) = insert_type {
+            for row in 0..left_ins.len().max(right_ins.len()) {
+                comparison.lines.push(MergeLine {
+                    line_type,
+                    base_line_num: None,
+                    base: None,
+                    left: left_ins.get(row).cloned(),
+                    right: right_ins.get(row).cloned(),
+                });
+            }
+        }
+
+        // The base line itself; the last slot only holds appended lines
+        let Some(base_line) = base.get(index) else {
+            continue;
+        };
+        let base_line = Some(base_line.clone());
+        let left_kept = same(&left_slot.line, &base_line);
+        let right_kept = same(&right_slot.line, &base_line);
+        let line_type = match (left_kept, right_kept) {
+            (true, true) => MergeLineType::Unchanged,
+            (false, true) => MergeLineType::LeftChanged,
+            (true, false) => MergeLineType::RightChanged,
+            (false, false) if same(&left_slot.line, &right_slot.line) => MergeLineType::BothChanged,
+            (false, false) => MergeLineType::Conflict,
+        };
+        comparison.lines.push(MergeLine {
+            line_type,
+            base_line_num: Some(index + 1),
+            base: base_line,
+            left: left_slot.line.clone(),
+            right: right_slot.line.clone(),
+        });
+    }
+
+    for line in &comparison.lines {
+        match line.line_type {
+            MergeLineType::Unchanged => {}
+            MergeLineType::LeftChanged => comparison.left_changes += 1,
+            MergeLineType::RightChanged => comparison.right_changes += 1,
+            MergeLineType::BothChanged => comparison.both_changes += 1,
+            MergeLineType::Conflict => comparison.conflicts += 1,
+        }
+    }
+    Ok(comparison)
+}
+
+/// How an entry differs between two folder trees.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DirDifference {
+    LeftOnly,
+    RightOnly,
+    /// A file on both sides with a different size or modification time.
+    Changed,
+    /// A file on one side and a folder on the other.
+    TypeMismatch,
+}
+
+/// An entry that is not the same in both trees of [`compare_directories`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct DirEntryDifference {
+    /// Path relative to the compared folders.
+    pub relative: PathBuf,
+    /// Whether the entry is a folder (on the side it exists on; the left
+    /// side for type mismatches).
+    pub is_dir: bool,
+    pub difference: DirDifference,
+}
+
+/// Modification times closer than this count as equal, since FAT and some
+/// network filesystems only store them to two seconds.
+const MTIME_TOLERANCE: std::time::Duration = std::time::Duration::from_secs(2);
+
+/// Compare two folder trees by relative path. Folders that exist on one side
+/// only are reported once, without their contents; identical files (same
+/// size and modification time) are left out. Symlinked folders are followed
+/// unless they loop.
+pub fn compare_directories(left: &Path, right: &Path) -> Result<Vec<DirEntryDifference>> {
+    for dir in [left, right] {
+        if !dir.is_dir() {
+            return Err(anyhow::anyhow!("{} is not a folder", dir.display()));
+        }
+    }
+    let mut differences = Vec::new();
+    compare_dir_level(left, right, Path::new(""), &mut Vec::new(), &mut differences)?;
+    Ok(differences)
+}
+
+fn compare_dir_level(
+    left: &Path,
+    right: &Path,
+    relative: &Path,
+    ancestors: &mut Vec<PathBuf>,
+    differences: &mut Vec<DirEntryDifference>,
+) -> Result<()> {
+    let Some(canonical) = enter_directory(left, ancestors) else {
+        return Ok(());
+    };
+    let names = |dir: &Path| -> Result<std::collections::BTreeSet<std::ffi::OsString>> {
+        Ok(fs::read_dir(dir)?.flatten().map(|entry| entry.file_name()).collect())
+    };
+    let left_names = names(left)?;
+    let right_names = names(right)?;
+
+    ancestors.push(canonical);
+    let mut result = Ok(());
+    for name in left_names.union(&right_names) {
+        let (left_path, right_path) = (left.join(name), right.join(name));
+        let relative = relative.join(name);
+        let (left_meta, right_meta) = (fs::metadata(&left_path), fs::metadata(&right_path));
+        let mut push = |is_dir, difference| {
+            differences.push(DirEntryDifference {
+                relative: relative.clone(),
+                is_dir,
+                difference,
+            })
+        };
+        match (left_meta, right_meta) {
+            (Ok(l), Err(_)) => push(l.is_dir(), DirDifference::LeftOnly),
+            (Err(_), Ok(r)) => push(r.is_dir(), DirDifference::RightOnly),
+            (Err(_), Err(_)) => {}
+            (Ok(l), Ok(r)) if l.is_dir() != r.is_dir() => {
+                push(l.is_dir(), DirDifference::TypeMismatch)
+            }
+            (Ok(l), Ok(_)) if l.is_dir() => {
+                result = compare_dir_level(&left_path, &right_path, &relative, ancestors, differences);
+                if result.is_err() {
+                    break;
+                }
+            }
+            (Ok(l), Ok(r)) => {
+                let times_differ = match (l.modified(), r.modified()) {
+                    (Ok(a), Ok(b)) => a.max(b).duration_since(a.min(b)).unwrap_or_default() > MTIME_TOLERANCE,
+                    _ => false,
+                };
+                if l.len() != r.len() || times_differ {
+                    push(false, DirDifference::Changed);
+                }
+            }
+        }
+    }
+    ancestors.pop();
+    result
+}
+
+/// One step of a one-way sync, relative to the synced folders.
+#[derive(Clone, Debug, PartialEq)]
+pub enum SyncAction {
+    /// Copy a file or folder missing from the destination.
+    Copy(PathBuf),
+    /// Overwrite a changed file in the destination.
+    Update(PathBuf),
+    /// Remove a file or folder the source doesn't have.
+    Delete(PathBuf),
+}
+
+/// What syncing `source` into `dest` would do, computed by [`plan_sync`]
+/// so it can be previewed before [`apply_sync`] carries it out.
+#[derive(Clone, Debug)]
+pub struct SyncPlan {
+    pub source: PathBuf,
+    pub dest: PathBuf,
+    pub actions: Vec<SyncAction>,
+    /// Entries that are a file on one side and a folder on the other. They
+    /// are left alone.
+    pub conflicts: Vec<PathBuf>,
+}
+
+impl SyncPlan {
+    /// Bytes the copies and updates will write.
+    pub fn bytes_to_copy(&self) -> u64 {
+        self.actions
+            .iter()
+            .filter_map(|action| match action {
+                SyncAction::Copy(relative) | SyncAction::Update(relative) => {
+                    Some(calculate_total_size(&self.source.join(relative)))
+                }
+                SyncAction::Delete(_) => None,
+            })
+            .sum()
+    }
+
+    /// The plan without its deletions.
+    pub fn without_deletions(&self) -> SyncPlan {
+        SyncPlan {
+            actions: self
+                .actions
+                .iter()
+                .filter(|action| !matches!(action, SyncAction::Delete(_)))
+                .cloned()
+                .collect(),
+            ..self.clone()
+        }
+    }
+}
+
+/// Plan a one-way sync that makes `dest` match `source`: new entries are
+/// copied, changed files updated and entries only in `dest` deleted. Use
+/// [`SyncPlan::without_deletions`] to keep those extras.
+pub fn plan_sync(source: &Path, dest: &Path) -> Result<SyncPlan> {
+    let mut plan = SyncPlan {
+        source: source.to_path_buf(),
+        dest: dest.to_path_buf(),
+        actions: Vec::new(),
+        conflicts: Vec::new(),
+    };
+    for entry in compare_directories(source, dest)? {
+        match entry.difference {
+            DirDifference::LeftOnly => plan.actions.push(SyncAction::Copy(entry.relative)),
+            DirDifference::Changed => plan.actions.push(SyncAction::Update(entry.relative)),
+            DirDifference::RightOnly => plan.actions.push(SyncAction::Delete(entry.relative)),
+            DirDifference::TypeMismatch => plan.conflicts.push(entry.relative),
+        }
+    }
+    Ok(plan)
+}
+
+/// Number of copies, updates and deletions [`apply_sync`] carried out, and
+/// the bytes it wrote.
+#[derive(Debug, Default, PartialEq)]
+pub struct SyncSummary {
+    pub copied: usize,
+    pub updated: usize,
+    pub deleted: usize,
+    pub bytes: u64,
+}
+
+/// Give the files below `dest` the modification times of their
+/// counterparts below `source`. Links in `dest` are left alone.
+fn copy_modified_times(source: &Path, dest: &Path) -> Result<()> {
+    let metadata = fs::symlink_metadata(dest)?;
+    if metadata.is_dir() {
+        for entry in fs::read_dir(dest)? {
+            let entry = entry?;
+            copy_modified_times(&source.join(entry.file_name()), &entry.path())?;
+        }
+    } else if metadata.is_file() {
+        let modified = fs::metadata(source)?.modified()?;
+        fs::File::options().write(true).open(dest)?.set_modified(modified)?;
+    }
+    Ok(())
+}
+
+/// Carry out a [`SyncPlan`], reporting progress like
+/// [`copy_recursive_with_progress`]. Stops at the first failure.
+pub fn apply_sync(plan: &SyncPlan, on_progress: &mut dyn FnMut(u64, &Path)) -> Result<SyncSummary> {
+    let mut summary = SyncSummary::default();
+    for action in &plan.actions {
+        match action {
+            SyncAction::Copy(relative) | SyncAction::Update(relative) => {
+                let (source, dest) = (plan.source.join(relative), plan.dest.join(relative));
+                if let Some(parent) = dest.parent() {
+                    fs::create_dir_all(parent)?;
+                }
+                let is_update = matches!(action, SyncAction::Update(_));
+                // Replace a link itself rather than writing through it
+                if is_update && is_link(&dest) {
+                    fs::remove_file(&dest)?;
+                }
+                let base = summary.bytes;
+                summary.bytes += copy_recursive_with_progress(&source, &dest, &mut |done, path| {
+                    on_progress(base + done, path)
+                })?;
+                // Otherwise the copies would look changed to the next sync
+                copy_modified_times(&source, &dest)?;
+                if is_update {
+                    summary.updated += 1;
+                } else {
+                    summary.copied += 1;
+                }
+            }
+            SyncAction::Delete(relative) => {
+                let dest = plan.dest.join(relative);
+                on_progress(summary.bytes, &dest);
+                remove_path(&dest)?;
+                summary.deleted += 1;
+            }
+        }
+    }
+    Ok(summary)
+}
+
+/// Line diff of `left_lines` against `right_lines`. Lines are matched on
+/// their [`DiffOptions::normalize`]d form but shown as they are.
+fn compute_diff(
+    left_lines: &[String],
+    right_lines: &[String],
+    options: DiffOptions,
+) -> Vec<DiffLine> {
+    let normalized = |lines: &[String]| -> Vec<String> {
+        lines.iter().map(|line| options.normalize(line)).collect()
+    };
+    let (left_keys, right_keys) = (normalized(left_lines), normalized(right_lines));
+    let mut result = Vec::new();
+    let mut left_idx = 0;
+    let mut right_idx = 0;
+
+    while left_idx < left_lines.len() || right_idx < right_lines.len() {
+        if left_idx >= left_lines.len() {
+            result.push(DiffLine {
+                line_type: DiffLineType::Added,
+                left_line_num: None,
                 right_line_num: Some(right_idx + 1),
                 left_content: String::new(),
                 right_content: right_lines[right_idx].clone(),
@@ -665,7 +3164,7 @@ fn compute_diff(left_lines: &[String], right_lines: &[String]) -> Vec<DiffLine>
                 right_content: String::new(),
             });
             left_idx += 1;
-        } else if left_lines[left_idx] == right_lines[right_idx] {
+        } else if left_keys[left_idx] == right_keys[right_idx] {
             result.push(DiffLine {
                 line_type: DiffLineType::Equal,
                 left_line_num: Some(left_idx + 1),
@@ -681,7 +3180,7 @@ fn compute_diff(left_lines: &[String], right_lines: &[String]) -> Vec<DiffLine>
 
             for i in 1..=look_ahead {
                 if left_idx + i < left_lines.len()
-                    && left_lines[left_idx + i] == right_lines[right_idx]
+                    && left_keys[left_idx + i] == right_keys[right_idx]
                 {
                     for _ in 0..i {
                         result.push(DiffLine {
@@ -698,7 +3197,7 @@ fn compute_diff(left_lines: &[String], right_lines: &[String]) -> Vec<DiffLine>
                 }
 
                 if right_idx + i < right_lines.len()
-                    && left_lines[left_idx] == right_lines[right_idx + i]
+                    && left_keys[left_idx] == right_keys[right_idx + i]
                 {
                     for _ in 0..i {
                         result.push(DiffLine {
@@ -737,6 +3236,9 @@ pub struct GitRepoInfo {
     pub current_branch: String,
     pub ahead: usize,
     pub behind: usize,
+    /// Whether the branch tracks a remote branch, so a fetch can update
+    /// `ahead` / `behind`.
+    pub has_upstream: bool,
     pub has_changes: bool,
 }
 
@@ -797,10 +3299,12 @@ pub fn get_git_repo_info(repo_path: &Path) -> Result<GitRepoInfo> {
 
     let mut ahead = 0;
     let mut behind = 0;
+    let mut has_upstream = false;
 
     if let Ok(local_oid) = head.target().ok_or(anyhow::anyhow!("No target")) {
         if let Ok(branch) = repo.find_branch(&current_branch, git2::BranchType::Local) {
             if let Ok(upstream) = branch.upstream() {
+                has_upstream = true;
                 if let Some(upstream_oid) = upstream.get().target() {
                     if let Ok((a, b)) = repo.graph_ahead_behind(local_oid, upstream_oid) {
                         ahead = a;
@@ -818,48 +3322,525 @@ pub fn get_git_repo_info(repo_path: &Path) -> Result<GitRepoInfo> {
         current_branch,
         ahead,
         behind,
+        has_upstream,
         has_changes,
     })
 }
 
+/// Fetch the remote that the current branch tracks, so the ahead/behind
+/// counts reflect the remote. Credentials come from the SSH agent, git's
+/// credential helpers or the default ones, as with the git command line.
+/// Returns the name of the remote fetched.
+pub fn fetch_upstream(repo_path: &Path) -> Result<String> {
+    let repo = git2::Repository::open(repo_path)?;
+    let head = repo.head()?;
+    let branch_ref = head
+        .name()
+        .filter(|_| head.is_branch())
+        .ok_or_else(|| anyhow::anyhow!("HEAD is detached, there is no branch to fetch"))?;
+    let remote_name = repo.branch_upstream_remote(branch_ref).map_err(|_| {
+        anyhow::anyhow!(
+            "{} does not track a remote branch",
+            head.shorthand().unwrap_or(branch_ref)
+        )
+    })?;
+    let remote_name = remote_name
+        .as_str()
+        .ok_or_else(|| anyhow::anyhow!("Remote name is not valid UTF-8"))?
+        .to_string();
+    let mut remote = repo.find_remote(&remote_name)?;
+    let url = remote.url().unwrap_or_default().to_string();
+
+    let config = repo.config()?;
+    let mut attempts = 0;
+    let mut callbacks = git2::RemoteCallbacks::new();
+    // libgit2 keeps asking while credentials are rejected, so give up after
+    // a few rounds instead of looping forever
+    callbacks.credentials(move |url, username, allowed| {
+        attempts += 1;
+        if attempts > 3 {
+            return Err(git2::Error::from_str("credentials were rejected"));
+        }
+        if allowed.contains(git2::CredentialType::SSH_KEY) {
+            if let Ok(cred) = git2::Cred::ssh_key_from_agent(username.unwrap_or("git")) {
+                return Ok(cred);
+            }
+        }
+        if allowed.contains(git2::CredentialType::USER_PASS_PLAINTEXT) {
+            if let Ok(cred) = git2::Cred::credential_helper(&config, url, username) {
+                return Ok(cred);
+            }
+        }
+        if allowed.contains(git2::CredentialType::USERNAME) {
+            return git2::Cred::username(username.unwrap_or("git"));
+        }
+        git2::Cred::default()
+    });
+    let mut options = git2::FetchOptions::new();
+    options.remote_callbacks(callbacks);
+
+    remote
+        .fetch::<&str>(&[], Some(&mut options), None)
+        .map_err(|e| {
+            if e.code() == git2::ErrorCode::Auth || e.message().contains("credentials") {
+                anyhow::anyhow!(
+                    "Authentication failed for {} ({}): {}",
+                    remote_name,
+                    url,
+                    e.message()
+                )
+            } else {
+                anyhow::anyhow!("{} ({}): {}", remote_name, url, e.message())
+            }
+        })?;
+    Ok(remote_name)
+}
+
 pub fn apply_git_status(items: &mut [FileItem], repo_path: &Path) {
     if let Ok(status_map) = get_git_status(repo_path) {
-        for item in items.iter_mut() {
-            if item.name == ".." {
-                continue;
+        apply_git_status_map(items, &status_map);
+    }
+}
+
+pub fn apply_git_status_map(items: &mut [FileItem], status_map: &HashMap<PathBuf, GitStatus>) {
+    for item in items.iter_mut() {
+        if item.name == ".." {
+            continue;
+        }
+
+        if let Some(status) = status_map.get(&item.path) {
+            item.git_status = Some(status.clone());
+        } else {
+            item.git_status = Some(GitStatus::Unmodified);
+        }
+    }
+}
+
+/// Git status scan results for one repository. Either part is `None` when
+/// the corresponding git call failed (e.g. a repository without commits).
+#[derive(Clone, Debug, Default)]
+pub struct CachedGitStatus {
+    pub status_map: Option<HashMap<PathBuf, GitStatus>>,
+    pub info: Option<GitRepoInfo>,
+}
+
+/// Per-repository cache of `repo.statuses` results, shared by both panes so
+/// that refreshing or navigating inside a repository doesn't rescan it.
+/// Entries must be invalidated after anything that changes files on disk.
+#[derive(Debug, Default)]
+pub struct GitStatusCache {
+    entries: HashMap<PathBuf, CachedGitStatus>,
+}
+
+pub type SharedGitStatusCache = Rc<RefCell<GitStatusCache>>;
+
+impl GitStatusCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the cached status for `repo_path`, scanning the repository
+    /// on a cache miss.
+    pub fn get(&mut self, repo_path: &Path) -> &CachedGitStatus {
+        self.entries
+            .entry(repo_path.to_path_buf())
+            .or_insert_with(|| CachedGitStatus {
+                status_map: get_git_status(repo_path).ok(),
+                info: get_git_repo_info(repo_path).ok(),
+            })
+    }
+
+    pub fn invalidate(&mut self, repo_path: &Path) {
+        self.entries.remove(repo_path);
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+}
+
+/// What a launcher or shortcut file points at.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ShortcutTarget {
+    /// Program and arguments to run directly.
+    Command(Vec<String>),
+    /// File, folder or URL to hand to the default application.
+    Location(String),
+}
+
+impl ShortcutTarget {
+    pub fn describe(&self) -> String {
+        match self {
+            ShortcutTarget::Command(args) => args.join(" "),
+            ShortcutTarget::Location(location) => location.clone(),
+        }
+    }
+}
+
+/// Resolve a `.desktop` launcher (Linux/BSD) or `.lnk` shortcut (Windows).
+/// Returns `None` for other files or when the shortcut can't be parsed.
+pub fn resolve_shortcut(path: &Path) -> Option<ShortcutTarget> {
+    let extension = path.extension()?.to_string_lossy().to_lowercase();
+
+    #[cfg(all(unix, not(target_os = "macos")))]
+    if extension == "desktop" {
+        return parse_desktop_entry(&fs::read_to_string(path).ok()?);
+    }
+
+    #[cfg(windows)]
+    if extension == "lnk" {
+        // Relative targets are relative to the shortcut's own folder
+        return match parse_lnk(&fs::read(path).ok()?)? {
+            ShortcutTarget::Location(target) if Path::new(&target).is_relative() => {
+                let base = path.parent().unwrap_or(Path::new(""));
+                Some(ShortcutTarget::Location(base.join(target).display().to_string()))
             }
+            target => Some(target),
+        };
+    }
 
-            if let Some(status) = status_map.get(&item.path) {
-                item.git_status = Some(status.clone());
-            } else {
-                item.git_status = Some(GitStatus::Unmodified);
+    let _ = extension;
+    None
+}
+
+/// Parse the `[Desktop Entry]` group of a freedesktop `.desktop` file.
+/// Applications resolve to their `Exec=` command with field codes such as
+/// `%f` or `%U` removed; links resolve to their `URL=`.
+pub fn parse_desktop_entry(content: &str) -> Option<ShortcutTarget> {
+    let mut in_entry = false;
+    let mut entry_type = None;
+    let mut exec = None;
+    let mut url = None;
+
+    for line in content.lines() {
+        let line = line.trim();
+        if line.starts_with('[') {
+            in_entry = line == "[Desktop Entry]";
+            continue;
+        }
+        if !in_entry || line.starts_with('#') {
+            continue;
+        }
+        if let Some((key, value)) = line.split_once('=') {
+            match key.trim() {
+                "Type" => entry_type = Some(value.trim().to_string()),
+                "Exec" => exec = Some(value.trim().to_string()),
+                "URL" => url = Some(value.trim().to_string()),
+                _ => {}
+            }
+        }
+    }
+
+    if entry_type.as_deref() == Some("Link") {
+        return url.map(ShortcutTarget::Location);
+    }
+
+    let args: Vec<String> = split_command_line(&exec?)
+        .into_iter()
+        .filter(|arg| !is_desktop_field_code(arg))
+        .map(|arg| arg.replace("%%", "%"))
+        .collect();
+    if args.is_empty() {
+        None
+    } else {
+        Some(ShortcutTarget::Command(args))
+    }
+}
+
+fn is_desktop_field_code(arg: &str) -> bool {
+    matches!(
+        arg,
+        "%f" | "%F" | "%u" | "%U" | "%i" | "%c" | "%k" | "%d" | "%D" | "%n" | "%N" | "%v" | "%m"
+    )
+}
+
+/// Split a command line on whitespace, honouring double quotes and
+/// backslash escapes inside them.
+fn split_command_line(command: &str) -> Vec<String> {
+    let mut args = Vec::new();
+    let mut current = String::new();
+    let mut in_quotes = false;
+    let mut has_arg = false;
+    let mut chars = command.chars();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '"' => {
+                in_quotes = !in_quotes;
+                has_arg = true;
             }
+            '\\' if in_quotes => {
+                if let Some(escaped) = chars.next() {
+                    current.push(escaped);
+                }
+            }
+            c if c.is_whitespace() && !in_quotes => {
+                if has_arg {
+                    args.push(std::mem::take(&mut current));
+                    has_arg = false;
+                }
+            }
+            c => {
+                current.push(c);
+                has_arg = true;
+            }
+        }
+    }
+    if has_arg {
+        args.push(current);
+    }
+    args
+}
+
+/// Parse a Windows Shell Link (`.lnk`) file and return its target path,
+/// together with the stored arguments if there are any.
+pub fn parse_lnk(data: &[u8]) -> Option<ShortcutTarget> {
+    const HAS_LINK_TARGET_ID_LIST: u32 = 0x01;
+    const HAS_LINK_INFO: u32 = 0x02;
+    const HAS_NAME: u32 = 0x04;
+    const HAS_RELATIVE_PATH: u32 = 0x08;
+    const HAS_WORKING_DIR: u32 = 0x10;
+    const HAS_ARGUMENTS: u32 = 0x20;
+    const IS_UNICODE: u32 = 0x80;
+    const VOLUME_ID_AND_LOCAL_BASE_PATH: u32 = 0x01;
+
+    let read_u16 = |offset: usize| -> Option<u16> {
+        Some(u16::from_le_bytes(data.get(offset..offset + 2)?.try_into().ok()?))
+    };
+    let read_u32 = |offset: usize| -> Option<u32> {
+        Some(u32::from_le_bytes(data.get(offset..offset + 4)?.try_into().ok()?))
+    };
+    let read_ansi = |offset: usize| -> Option<String> {
+        let bytes = data.get(offset..)?;
+        let end = bytes.iter().position(|&b| b == 0)?;
+        Some(String::from_utf8_lossy(&bytes[..end]).to_string())
+    };
+    let read_utf16 = |offset: usize| -> Option<String> {
+        let units: Vec<u16> = data
+            .get(offset..)?
+            .chunks_exact(2)
+            .map(|pair| u16::from_le_bytes([pair[0], pair[1]]))
+            .take_while(|&unit| unit != 0)
+            .collect();
+        Some(String::from_utf16_lossy(&units))
+    };
+
+    if read_u32(0)? != 0x4C {
+        return None;
+    }
+    let flags = read_u32(0x14)?;
+    let mut offset = 0x4C;
+
+    if flags & HAS_LINK_TARGET_ID_LIST != 0 {
+        offset += 2 + read_u16(offset)? as usize;
+    }
+
+    let mut target = None;
+    if flags & HAS_LINK_INFO != 0 {
+        let info = offset;
+        let info_size = read_u32(info)? as usize;
+        let header_size = read_u32(info + 4)?;
+        let info_flags = read_u32(info + 8)?;
+        if info_flags & VOLUME_ID_AND_LOCAL_BASE_PATH != 0 {
+            let (base, suffix) = if header_size >= 0x24 {
+                (
+                    read_utf16(info + read_u32(info + 0x1C)? as usize)?,
+                    read_utf16(info + read_u32(info + 0x20)? as usize)?,
+                )
+            } else {
+                (
+                    read_ansi(info + read_u32(info + 0x10)? as usize)?,
+                    read_ansi(info + read_u32(info + 0x18)? as usize)?,
+                )
+            };
+            target = Some(format!("{}{}", base, suffix));
         }
+        offset += info_size;
+    }
+
+    // StringData entries: a u16 character count followed by the text
+    let unicode = flags & IS_UNICODE != 0;
+    let read_string_data = |offset: &mut usize| -> Option<String> {
+        let count = read_u16(*offset)? as usize;
+        let start = *offset + 2;
+        let len = if unicode { count * 2 } else { count };
+        let bytes = data.get(start..start + len)?;
+        *offset = start + len;
+        Some(if unicode {
+            let units: Vec<u16> = bytes
+                .chunks_exact(2)
+                .map(|pair| u16::from_le_bytes([pair[0], pair[1]]))
+                .collect();
+            String::from_utf16_lossy(&units)
+        } else {
+            String::from_utf8_lossy(bytes).to_string()
+        })
+    };
+
+    if flags & HAS_NAME != 0 {
+        read_string_data(&mut offset)?;
+    }
+    let relative_path = if flags & HAS_RELATIVE_PATH != 0 {
+        Some(read_string_data(&mut offset)?)
+    } else {
+        None
+    };
+    if flags & HAS_WORKING_DIR != 0 {
+        read_string_data(&mut offset)?;
+    }
+    let arguments = if flags & HAS_ARGUMENTS != 0 {
+        read_string_data(&mut offset)?
+    } else {
+        String::new()
+    };
+
+    let target = target.or(relative_path)?;
+    if arguments.trim().is_empty() {
+        Some(ShortcutTarget::Location(target))
+    } else {
+        let mut args = vec![target];
+        args.extend(split_command_line(&arguments));
+        Some(ShortcutTarget::Command(args))
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct MountPoint {
+    pub name: String,
+    pub mount_point: PathBuf,
+    pub device_name: String,
+    pub file_system: String,
+    pub total_space: u64,
+    pub available_space: u64,
+    pub is_removable: bool,
+    pub disk_kind: String,
+}
+
+impl MountPoint {
+    pub fn usage_percentage(&self) -> f32 {
+        usage_percentage(self.total_space, self.available_space)
+    }
+}
+
+/// Share of `total_space` in use, in percent.
+fn usage_percentage(total_space: u64, available_space: u64) -> f32 {
+    if total_space == 0 {
+        return 0.0;
+    }
+    let used = total_space.saturating_sub(available_space);
+    (used as f64 / total_space as f64 * 100.0) as f32
+}
+
+/// Free space and write access of the filesystem a path lives on.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct FsStatus {
+    pub available_space: u64,
+    pub total_space: u64,
+    pub read_only: bool,
+}
+
+impl FsStatus {
+    pub fn usage_percentage(&self) -> f32 {
+        usage_percentage(self.total_space, self.available_space)
+    }
+}
+
+/// Query the filesystem holding `path` via `statvfs`. `read_only` reflects
+/// the `ST_RDONLY` mount flag, e.g. for mounted ISOs or read-only shares.
+#[cfg(unix)]
+pub fn filesystem_status(path: &Path) -> Option<FsStatus> {
+    use std::os::unix::ffi::OsStrExt;
+
+    let c_path = std::ffi::CString::new(path.as_os_str().as_bytes()).ok()?;
+    // SAFETY: `statvfs` only writes into the zeroed struct we pass and
+    // reads the NUL-terminated path.
+    let mut stat: libc::statvfs = unsafe { std::mem::zeroed() };
+    if unsafe { libc::statvfs(c_path.as_ptr(), &mut stat) } != 0 {
+        return None;
     }
+
+    let block_size = stat.f_frsize as u64;
+    Some(FsStatus {
+        available_space: stat.f_bavail as u64 * block_size,
+        total_space: stat.f_blocks as u64 * block_size,
+        read_only: stat.f_flag & libc::ST_RDONLY != 0,
+    })
+}
+
+/// Fallback for platforms without `statvfs`: free space of the closest
+/// mount point. Read-only mounts aren't detected here.
+#[cfg(not(unix))]
+pub fn filesystem_status(path: &Path) -> Option<FsStatus> {
+    get_mount_points()
+        .into_iter()
+        .filter(|mount| path.starts_with(&mount.mount_point))
+        .max_by_key(|mount| mount.mount_point.as_os_str().len())
+        .map(|mount| FsStatus {
+            available_space: mount.available_space,
+            total_space: mount.total_space,
+            read_only: false,
+        })
+}
+
+/// Fail with the required and available space when copying `sources` into
+/// `dest_dir` would not fit on its filesystem. Moves within one filesystem
+/// are renames and need no space. Passes when the free space is unknown.
+pub fn check_free_space(
+    sources: &[PathBuf],
+    dest_dir: &Path,
+    is_move: bool,
+    units: SizeUnits,
+) -> Result<()> {
+    let needed: u64 = sources
+        .iter()
+        .filter(|source| !(is_move && same_filesystem(source, dest_dir)))
+        .map(|source| calculate_total_size(source))
+        .sum();
+    check_space(needed, dest_dir, units)
 }
 
-#[derive(Clone, Debug)]
-pub struct MountPoint {
-    pub name: String,
-    pub mount_point: PathBuf,
-    pub device_name: String,
-    pub file_system: String,
-    pub total_space: u64,
-    pub available_space: u64,
-    pub is_removable: bool,
-    pub disk_kind: String,
+/// [`check_free_space`] for callers that already added up the `needed`
+/// bytes, e.g. for a progress total.
+pub fn check_space(needed: u64, dest_dir: &Path, units: SizeUnits) -> Result<()> {
+    let Some(status) = filesystem_status(dest_dir) else {
+        return Ok(());
+    };
+    if needed > status.available_space {
+        return Err(anyhow::anyhow!(
+            "Not enough space in {}: needs {}, only {} available",
+            dest_dir.display(),
+            format_size_with(needed, units),
+            format_size_with(status.available_space, units)
+        ));
+    }
+    Ok(())
 }
 
-impl MountPoint {
-    pub fn usage_percentage(&self) -> f32 {
-        if self.total_space == 0 {
-            return 0.0;
-        }
-        let used = self.total_space - self.available_space;
-        (used as f64 / self.total_space as f64 * 100.0) as f32
+/// Whether two existing paths live on the same filesystem.
+#[cfg(unix)]
+pub fn same_filesystem(a: &Path, b: &Path) -> bool {
+    use std::os::unix::fs::MetadataExt;
+    match (fs::symlink_metadata(a), fs::metadata(b)) {
+        (Ok(a), Ok(b)) => a.dev() == b.dev(),
+        _ => false,
     }
 }
 
+/// Whether two existing paths live on the same filesystem, judged by their
+/// closest mount points.
+#[cfg(not(unix))]
+pub fn same_filesystem(a: &Path, b: &Path) -> bool {
+    let mounts = get_mount_points();
+    let mount_of = |path: &Path| {
+        mounts
+            .iter()
+            .filter(|mount| path.starts_with(&mount.mount_point))
+            .max_by_key(|mount| mount.mount_point.as_os_str().len())
+            .map(|mount| mount.mount_point.clone())
+    };
+    mount_of(a).is_some() && mount_of(a) == mount_of(b)
+}
+
 pub fn get_mount_points() -> Vec<MountPoint> {
     let disks = Disks::new_with_refreshed_list();
     let mut mount_points = Vec::new();
@@ -915,3 +3896,1207 @@ pub fn get_mount_points() -> Vec<MountPoint> {
 
     mount_points
 }
+
+/// A block device with a filesystem on it that isn't mounted yet, such as
+/// a USB stick the desktop hasn't auto-mounted.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct UnmountedDevice {
+    pub device: PathBuf,
+    pub label: String,
+    pub file_system: String,
+    pub size: u64,
+    pub is_removable: bool,
+}
+
+impl UnmountedDevice {
+    /// The filesystem label, or the device path when it has none.
+    pub fn name(&self) -> String {
+        if self.label.is_empty() {
+            self.device.display().to_string()
+        } else {
+            self.label.clone()
+        }
+    }
+}
+
+/// Filesystems that hold no files to browse, or need unlocking or
+/// assembling before anything on them can be mounted.
+const UNMOUNTABLE_FILE_SYSTEMS: &[&str] = &[
+    "swap",
+    "crypto_LUKS",
+    "LVM2_member",
+    "linux_raid_member",
+    "zfs_member",
+    "bcache",
+];
+
+/// Known block devices that have a filesystem but no mount point, as
+/// listed by `lsblk`. Empty where `lsblk` isn't available; fails when it is
+/// there but can't list the devices.
+pub fn get_unmounted_devices() -> Result<Vec<UnmountedDevice>> {
+    if !cfg!(target_os = "linux") {
+        return Ok(Vec::new());
+    }
+    let lsblk = |columns: &str| {
+        std::process::Command::new("lsblk")
+            .args(["-P", "-b", "-p", "-o", columns])
+            .stdin(std::process::Stdio::null())
+            .output()
+    };
+    // The PATH column needs util-linux 2.33; older versions only know NAME,
+    // which `-p` turns into the full device path
+    let output = match lsblk("PATH,LABEL,FSTYPE,SIZE,RM,HOTPLUG,TYPE,MOUNTPOINT") {
+        Ok(output) if !output.status.success() => {
+            lsblk("NAME,LABEL,FSTYPE,SIZE,RM,HOTPLUG,TYPE,MOUNTPOINT")
+        }
+        output => output,
+    };
+    let output = match output {
+        Ok(output) => output,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => anyhow::bail!("Cannot run lsblk: {}", e),
+    };
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        let message = stderr
+            .lines()
+            .map(str::trim)
+            .find(|line| !line.is_empty())
+            .map_or_else(|| output.status.to_string(), str::to_string);
+        anyhow::bail!("Cannot list devices: lsblk failed: {}", message);
+    }
+    Ok(parse_unmounted_devices(&String::from_utf8_lossy(&output.stdout)))
+}
+
+/// Pick the unmounted devices out of `lsblk -P` output, one line of
+/// `KEY="value"` pairs per device. Removable ones come first.
+fn parse_unmounted_devices(output: &str) -> Vec<UnmountedDevice> {
+    let mut devices: Vec<UnmountedDevice> = output
+        .lines()
+        .filter_map(|line| {
+            let fields = parse_lsblk_line(line);
+            let field = |key: &str| fields.get(key).map(String::as_str).unwrap_or("");
+            let file_system = field("FSTYPE");
+            let unmounted = field("MOUNTPOINT").is_empty()
+                && matches!(field("TYPE"), "part" | "disk" | "crypt")
+                && !file_system.is_empty()
+                && !UNMOUNTABLE_FILE_SYSTEMS.contains(&file_system);
+            // Without PATH (older lsblk), NAME is the device path or its
+            // name below /dev
+            let device = match (field("PATH"), field("NAME")) {
+                ("", name) if !name.starts_with('/') => Path::new("/dev").join(name),
+                ("", name) => PathBuf::from(name),
+                (path, _) => PathBuf::from(path),
+            };
+            unmounted.then(|| UnmountedDevice {
+                device,
+                label: field("LABEL").to_string(),
+                file_system: file_system.to_string(),
+                size: field("SIZE").parse().unwrap_or(0),
+                is_removable: field("RM") == "1" || field("HOTPLUG") == "1",
+            })
+        })
+        .collect();
+    devices.sort_by(|a, b| b.is_removable.cmp(&a.is_removable).then(a.device.cmp(&b.device)));
+    devices
+}
+
+/// Split one `lsblk -P` line into its keys and values. Values are quoted,
+/// with unusual characters (spaces in labels, say) escaped as `\xNN`.
+fn parse_lsblk_line(line: &str) -> HashMap<String, String> {
+    let mut fields = HashMap::new();
+    let mut rest = line.trim();
+    while let Some((key, after)) = rest.split_once("=\"") {
+        let Some(end) = after.find('"') else {
+            break;
+        };
+        fields.insert(key.trim().to_string(), unescape_lsblk(&after[..end]));
+        rest = &after[end + 1..];
+    }
+    fields
+}
+
+fn unescape_lsblk(value: &str) -> String {
+    let raw = value.as_bytes();
+    let mut bytes = Vec::with_capacity(raw.len());
+    let mut i = 0;
+    while i < raw.len() {
+        let escaped = (raw[i] == b'\\' && raw.get(i + 1) == Some(&b'x'))
+            .then(|| std::str::from_utf8(raw.get(i + 2..i + 4)?).ok())
+            .flatten()
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+        match escaped {
+            Some(byte) => {
+                bytes.push(byte);
+                i += 4;
+            }
+            None => {
+                bytes.push(raw[i]);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&bytes).into_owned()
+}
+
+/// Mount `device` for the current user through udisks, like desktop file
+/// managers do, and return where it was mounted. udisks may ask for a
+/// password through the desktop's polkit agent.
+pub fn mount_device(device: &Path) -> Result<PathBuf> {
+    if !cfg!(target_os = "linux") {
+        anyhow::bail!("Mounting devices is only supported on Linux");
+    }
+    let Some(udisksctl) = find_program("udisksctl", &path_dirs()) else {
+        anyhow::bail!("Mounting needs udisksctl (udisks2), which was not found on PATH");
+    };
+    let output = std::process::Command::new(&udisksctl)
+        .arg("mount")
+        .arg("--block-device")
+        .arg(device)
+        .stdin(std::process::Stdio::null())
+        .output()
+        .map_err(|e| anyhow::anyhow!("Cannot run {}: {}", udisksctl.display(), e))?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        let message = stderr
+            .lines()
+            .map(str::trim)
+            .find(|line| !line.is_empty())
+            .map_or_else(|| output.status.to_string(), str::to_string);
+        anyhow::bail!("Cannot mount {}: {}", device.display(), message);
+    }
+    // "Mounted /dev/sdb1 at /media/user/USB" (older versions end with a period)
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    stdout
+        .trim()
+        .rsplit_once(" at ")
+        .map(|(_, mount_point)| PathBuf::from(mount_point.trim_end_matches('.')))
+        .ok_or_else(|| anyhow::anyhow!("udisksctl didn't say where {} was mounted", device.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fuzzy_match_ranks_word_starts_first() {
+        let (score, positions) = fuzzy_match("app_manager.rs", "amr").unwrap();
+        assert_eq!(positions, [0, 4, 10]);
+        assert!(fuzzy_match("app_manager.rs", "AMR").is_some());
+        assert!(fuzzy_match("app_manager.rs", "rma").is_none());
+
+        let (scattered, _) = fuzzy_match("param_helper.rs", "amr").unwrap();
+        assert!(score > scattered);
+    }
+
+    #[test]
+    fn test_unmounted_devices_from_lsblk() {
+        let output = [
+            r#"PATH="/dev/sda" LABEL="" FSTYPE="" SIZE="512110190592" RM="0" HOTPLUG="0" TYPE="disk" MOUNTPOINT="""#,
+            r#"PATH="/dev/sda1" LABEL="" FSTYPE="vfat" SIZE="536870912" RM="0" HOTPLUG="0" TYPE="part" MOUNTPOINT="/boot/efi""#,
+            r#"PATH="/dev/sda2" LABEL="" FSTYPE="swap" SIZE="8589934592" RM="0" HOTPLUG="0" TYPE="part" MOUNTPOINT="""#,
+            r#"PATH="/dev/sda3" LABEL="Data" FSTYPE="ext4" SIZE="100000000000" RM="0" HOTPLUG="0" TYPE="part" MOUNTPOINT="""#,
+            r#"PATH="/dev/sdb1" LABEL="My\x20Stick" FSTYPE="exfat" SIZE="31914983424" RM="1" HOTPLUG="1" TYPE="part" MOUNTPOINT="""#,
+            r#"PATH="/dev/sr0" LABEL="" FSTYPE="" SIZE="1073741312" RM="1" HOTPLUG="0" TYPE="rom" MOUNTPOINT="""#,
+        ]
+        .join("\n");
+        let devices = parse_unmounted_devices(&output);
+        let paths: Vec<_> = devices.iter().map(|d| d.device.display().to_string()).collect();
+        assert_eq!(paths, ["/dev/sdb1", "/dev/sda3"]);
+        assert_eq!(devices[0].name(), "My Stick");
+        assert_eq!(devices[0].file_system, "exfat");
+        assert_eq!(devices[0].size, 31914983424);
+        assert!(devices[0].is_removable);
+        assert_eq!(devices[1].name(), "Data");
+        assert!(!devices[1].is_removable);
+
+        // lsblk before util-linux 2.33 has no PATH column
+        let old = r#"NAME="sdc1" LABEL="" FSTYPE="ext4" SIZE="1000" RM="1" HOTPLUG="1" TYPE="part" MOUNTPOINT="""#;
+        assert_eq!(parse_unmounted_devices(old)[0].device, PathBuf::from("/dev/sdc1"));
+    }
+
+    #[test]
+    fn test_binary_units_keep_legacy_labels() {
+        assert_eq!(format_size(0), "0 B");
+        assert_eq!(format_size(1023), "1023 B");
+        assert_eq!(format_size(1024), "1.00 KB");
+        assert_eq!(format_size(1024 * 1024 - 1), "1024.00 KB");
+        assert_eq!(format_size(1024 * 1024), "1.00 MB");
+        assert_eq!(format_size(1024 * 1024 * 1024), "1.00 GB");
+        assert_eq!(format_size(2048 * 1024 * 1024 * 1024), "2048.00 GB");
+    }
+
+    #[test]
+    fn test_iec_units_boundaries() {
+        assert_eq!(format_size_with(1023, SizeUnits::Iec), "1023 B");
+        assert_eq!(format_size_with(1024, SizeUnits::Iec), "1.00 KiB");
+        assert_eq!(format_size_with(1536, SizeUnits::Iec), "1.50 KiB");
+        assert_eq!(format_size_with(1 << 20, SizeUnits::Iec), "1.00 MiB");
+        assert_eq!(format_size_with(1 << 30, SizeUnits::Iec), "1.00 GiB");
+        assert_eq!(format_size_with(1 << 40, SizeUnits::Iec), "1.00 TiB");
+        assert_eq!(format_size_with(u64::MAX, SizeUnits::Iec), "16777216.00 TiB");
+    }
+
+    #[test]
+    fn test_si_units_boundaries() {
+        assert_eq!(format_size_with(999, SizeUnits::Si), "999 B");
+        assert_eq!(format_size_with(1000, SizeUnits::Si), "1.00 KB");
+        assert_eq!(format_size_with(1024, SizeUnits::Si), "1.02 KB");
+        assert_eq!(format_size_with(999_999, SizeUnits::Si), "1000.00 KB");
+        assert_eq!(format_size_with(1_000_000, SizeUnits::Si), "1.00 MB");
+        assert_eq!(format_size_with(1_000_000_000, SizeUnits::Si), "1.00 GB");
+        assert_eq!(format_size_with(1_000_000_000_000, SizeUnits::Si), "1.00 TB");
+    }
+
+    #[test]
+    fn test_thousands_separators() {
+        assert_eq!(format_with_separators(0), "0");
+        assert_eq!(format_with_separators(999), "999");
+        assert_eq!(format_with_separators(1000), "1,000");
+        assert_eq!(format_with_separators(123_456), "123,456");
+        assert_eq!(format_with_separators(1_234_567), "1,234,567");
+        assert_eq!(format_with_separators(u64::MAX), "18,446,744,073,709,551,615");
+    }
+
+    fn scratch_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("filane-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn test_check_new_name_case_rename() {
+        let dir = scratch_dir("case-rename");
+        fs::write(dir.join("a.txt"), "a").unwrap();
+        assert!(check_new_name(&dir, "a\0.txt", None).is_invalid());
+
+        let case_insensitive = fs::symlink_metadata(dir.join("A.TXT")).is_ok();
+        let renamed = check_new_name(&dir, "A.txt", Some("a.txt"));
+        assert_eq!(renamed, NameCheck::Valid);
+        if !case_insensitive {
+            // A different entry that only differs in case still clashes
+            fs::write(dir.join("A.txt"), "other").unwrap();
+            assert!(check_new_name(&dir, "A.txt", Some("a.txt")).is_invalid());
+        }
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_read_text_prefix_keeps_utf8() {
+        let dir = scratch_dir("text-prefix");
+        let path = dir.join("accents.txt");
+        fs::write(&path, "é".repeat(100)).unwrap();
+
+        // Cutting the 2-byte "é" in half drops it instead of guessing
+        let decoded = read_text_prefix(&path, 11).unwrap();
+        assert_eq!(decoded.text, "é".repeat(5));
+        assert_eq!(decoded.encoding_note(), None);
+        assert_eq!(read_text_prefix(&path, 1000).unwrap().text.len(), 200);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_compress_preserves_empty_directories() {
+        let dir = scratch_dir("zip-empty-dirs");
+        let source = dir.join("source");
+        fs::create_dir_all(source.join("empty")).unwrap();
+        fs::create_dir_all(source.join("nested").join("also_empty")).unwrap();
+        fs::write(source.join("file.txt"), "hello").unwrap();
+
+        let archive = dir.join("source.zip");
+        let skipped = compress_to_zip(&source, &archive).unwrap();
+        assert!(skipped.is_empty());
+
+        let out = dir.join("out");
+        fs::create_dir_all(&out).unwrap();
+        extract_zip(&archive, &out).unwrap();
+
+        assert!(out.join("empty").is_dir());
+        assert!(out.join("nested").join("also_empty").is_dir());
+        assert_eq!(fs::read_to_string(out.join("file.txt")).unwrap(), "hello");
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_encrypted_zip_needs_the_right_password() {
+        let dir = scratch_dir("zip-password");
+        let source = dir.join("source");
+        fs::create_dir_all(source.join("empty")).unwrap();
+        fs::write(source.join("secret.txt"), "hello").unwrap();
+
+        let archive = dir.join("source.zip");
+        compress_to_zip_with_progress(
+            &source,
+            &archive,
+            &ZipOptions::default(),
+            Some("s3cret"),
+            &mut |_, _| {},
+        )
+        .unwrap();
+        assert!(zip_is_encrypted(&archive).unwrap());
+        assert!(zip_password_matches(&archive, "s3cret").unwrap());
+        assert!(!zip_password_matches(&archive, "guess").unwrap());
+
+        let out = dir.join("out");
+        fs::create_dir_all(&out).unwrap();
+        assert!(extract_zip(&archive, &out).is_err());
+        let error = extract_zip_with_password(&archive, &out, Some("guess")).unwrap_err();
+        assert_eq!(error.to_string(), "Wrong password");
+        assert!(!out.join("secret.txt").exists());
+
+        extract_zip_with_password(&archive, &out, Some("s3cret")).unwrap();
+        assert_eq!(fs::read_to_string(out.join("secret.txt")).unwrap(), "hello");
+        assert!(out.join("empty").is_dir());
+
+        let plain = dir.join("plain.zip");
+        compress_to_zip(&source, &plain).unwrap();
+        assert!(!zip_is_encrypted(&plain).unwrap());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_extract_zip_rejects_paths_outside_dest() {
+        use std::io::Write;
+        let dir = scratch_dir("zip-slip");
+        let archive = dir.join("evil.zip");
+        let mut zip = zip::ZipWriter::new(fs::File::create(&archive).unwrap());
+        let options = zip::write::SimpleFileOptions::default();
+        zip.start_file("fine.txt", options).unwrap();
+        zip.write_all(b"fine").unwrap();
+        zip.start_file("../evil.txt", options).unwrap();
+        zip.write_all(b"evil").unwrap();
+        zip.finish().unwrap();
+
+        let out = dir.join("out");
+        fs::create_dir_all(&out).unwrap();
+        assert!(extract_zip(&archive, &out).is_err());
+        assert!(!dir.join("evil.txt").exists());
+        assert!(!out.join("fine.txt").exists());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_extract_any_tar_round_trip() {
+        let dir = scratch_dir("tar-round-trip");
+        let source = dir.join("source");
+        fs::create_dir_all(source.join("nested")).unwrap();
+        fs::write(source.join("file.txt"), "hello").unwrap();
+        fs::write(source.join("nested").join("deep.txt"), "deep").unwrap();
+
+        type Encoder = fn(fs::File) -> Box<dyn std::io::Write>;
+        let archives: [(&str, Encoder); 4] = [
+            ("plain.tar", |file| Box::new(file)),
+            ("gzip.tar.gz", |file| {
+                Box::new(flate2::write::GzEncoder::new(file, flate2::Compression::default()))
+            }),
+            ("bzip2.tar.bz2", |file| {
+                Box::new(bzip2::write::BzEncoder::new(file, bzip2::Compression::default()))
+            }),
+            ("xz.tar.xz", |file| Box::new(xz2::write::XzEncoder::new(file, 6))),
+        ];
+        for (name, encoder) in archives {
+            let archive = dir.join(name);
+            {
+                let mut builder = tar::Builder::new(encoder(fs::File::create(&archive).unwrap()));
+                builder.append_dir_all("source", &source).unwrap();
+                builder.into_inner().unwrap();
+            }
+
+            let out = dir.join(format!("out-{}", name));
+            extract_any(&archive, &out).unwrap();
+            let extracted = out.join("source");
+            assert_eq!(fs::read_to_string(extracted.join("file.txt")).unwrap(), "hello", "{}", name);
+            assert_eq!(fs::read_to_string(extracted.join("nested/deep.txt")).unwrap(), "deep");
+        }
+
+        assert_eq!(ArchiveFormat::of(Path::new("a.TGZ")), Some(ArchiveFormat::TarGz));
+        assert_eq!(ArchiveFormat::of(Path::new("a.txt.bz2")), None);
+        assert!(extract_any(&dir.join("archive.cab"), &dir.join("out-cab")).is_err());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_external_archiver_is_found_and_run() {
+        use std::os::unix::fs::PermissionsExt;
+        let dir = scratch_dir("external-archiver");
+        let bin = dir.join("bin");
+        fs::create_dir_all(&bin).unwrap();
+        let dirs = [bin.clone()];
+        let archive = dir.join("photos.rar");
+        fs::write(&archive, "rar").unwrap();
+
+        let error = extract_with_archiver_in(&archive, &dir.join("out"), &dirs).unwrap_err();
+        assert!(error.to_string().contains("7-Zip (7z) or unar"), "{}", error);
+
+        // Stand-in for unar that records its arguments
+        let unar = bin.join("unar");
+        fs::write(&unar, "#!/bin/sh\necho \"$@\" > \"$(dirname \"$0\")/args\"\n").unwrap();
+        fs::set_permissions(&unar, fs::Permissions::from_mode(0o755)).unwrap();
+        let out = dir.join("out");
+        extract_with_archiver_in(&archive, &out, &dirs).unwrap();
+        let args = fs::read_to_string(bin.join("args")).unwrap();
+        assert!(args.contains(&format!("-output-directory {} {}", out.display(), archive.display())));
+        assert!(out.is_dir());
+
+        fs::write(&unar, "#!/bin/sh\necho 'Archive is damaged' >&2\nexit 1\n").unwrap();
+        let error = extract_with_archiver_in(&archive, &out, &dirs).unwrap_err();
+        assert_eq!(error.to_string(), "unar failed: Archive is damaged");
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_compress_skips_special_files() {
+        let dir = scratch_dir("zip-special-files");
+        let source = dir.join("source");
+        fs::create_dir_all(source.join("empty")).unwrap();
+        fs::write(source.join("file.txt"), "hello").unwrap();
+        let socket_path = source.join("socket");
+        let _listener = std::os::unix::net::UnixListener::bind(&socket_path).unwrap();
+
+        let archive = dir.join("source.zip");
+        let skipped = compress_to_zip(&source, &archive).unwrap();
+        assert_eq!(skipped, vec![socket_path]);
+
+        let out = dir.join("out");
+        fs::create_dir_all(&out).unwrap();
+        extract_zip(&archive, &out).unwrap();
+
+        assert!(out.join("empty").is_dir());
+        assert!(out.join("file.txt").is_file());
+        assert!(!out.join("socket").exists());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_parent_dir_of_root_is_an_error() {
+        assert!(parent_dir(Path::new("/")).is_err());
+        assert_eq!(parent_dir(Path::new("/mnt")).unwrap(), Path::new("/"));
+        assert_eq!(parent_dir(Path::new("relative")).unwrap(), Path::new("."));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_sibling_path_next_to_top_level_entries() {
+        assert_eq!(sibling_path(Path::new("/mnt"), "media").unwrap(), PathBuf::from("/media"));
+        assert_eq!(
+            sibling_path(Path::new("/mnt"), "mnt.zip").unwrap(),
+            PathBuf::from("/mnt.zip")
+        );
+        assert!(sibling_path(Path::new("/"), "renamed").is_err());
+    }
+
+    #[test]
+    fn test_child_path_rejects_non_names() {
+        let dir = Path::new("/tmp");
+        assert_eq!(child_path(dir, "file.txt").unwrap(), PathBuf::from("/tmp/file.txt"));
+        for name in ["", ".", "..", "a/b", "/etc", "a/"] {
+            assert!(child_path(dir, name).is_err(), "{:?} should be rejected", name);
+        }
+    }
+
+    #[test]
+    fn test_longest_filename_is_listed_and_shortened() {
+        let dir = scratch_dir("long-name");
+        // 255 bytes is the longest name most filesystems allow
+        let name = format!("{}.txt", "a".repeat(251));
+        assert_eq!(name.len(), 255);
+        fs::write(dir.join(&name), "x").unwrap();
+
+        let items = read_directory(&dir).unwrap();
+        let item = items.iter().find(|item| item.name == name).unwrap();
+        let shown = middle_ellipsis(&item.name, 40);
+        assert_eq!(shown.chars().count(), 40);
+        assert!(shown.starts_with("aaaa") && shown.ends_with("a.txt"));
+        assert!(shown.contains('…'));
+        assert_eq!(middle_ellipsis(&item.name, 255), name);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_same_directory_paste_copies_under_new_name() {
+        let dir = scratch_dir("same-dir-paste");
+        let file = dir.join("notes.txt");
+        fs::write(&file, "hello").unwrap();
+        let folder = dir.join("photos.2024");
+        fs::create_dir(&folder).unwrap();
+        fs::write(folder.join("a.jpg"), "jpg").unwrap();
+
+        let first = copy_destination(&file, &dir).unwrap();
+        assert_eq!(first, dir.join("notes (copy).txt"));
+        copy_recursive_with_progress(&file, &first, &mut |_, _| {}).unwrap();
+        let second = copy_destination(&file, &dir).unwrap();
+        assert_eq!(second, dir.join("notes (copy 2).txt"));
+        assert_eq!(fs::read_to_string(&file).unwrap(), "hello");
+        assert_eq!(fs::read_to_string(&first).unwrap(), "hello");
+
+        let folder_copy = copy_destination(&folder, &dir).unwrap();
+        assert_eq!(folder_copy, dir.join("photos.2024 (copy)"));
+        copy_recursive_with_progress(&folder, &folder_copy, &mut |_, _| {}).unwrap();
+        assert!(folder_copy.join("a.jpg").is_file());
+
+        // A move within the same folder is refused rather than a no-op
+        assert!(transfer_destination(&file, &dir).is_err());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_tree_stats_totals_whole_tree() {
+        let dir = scratch_dir("tree-stats");
+        fs::create_dir_all(dir.join("a/b")).unwrap();
+        fs::write(dir.join("top.txt"), "12345").unwrap();
+        fs::write(dir.join("a/notes.TXT"), "123").unwrap();
+        fs::write(dir.join("a/b/big.bin"), vec![0u8; 100]).unwrap();
+        fs::write(dir.join("a/b/README"), "1").unwrap();
+
+        let stats = tree_stats(&dir, &mut |_, _| {}).unwrap();
+        assert_eq!(stats.file_count, 4);
+        assert_eq!(stats.folder_count, 2);
+        assert_eq!(stats.total_size, 109);
+        let largest: Vec<u64> = stats.largest_files.iter().map(|(_, size)| *size).collect();
+        assert_eq!(largest, vec![100, 5, 3, 1]);
+        assert_eq!(stats.largest_files[0].0, dir.join("a/b/big.bin"));
+        let groups: Vec<(&str, usize, u64)> = stats
+            .by_extension
+            .iter()
+            .map(|group| (group.extension.as_str(), group.file_count, group.total_size))
+            .collect();
+        assert_eq!(groups, vec![("bin", 1, 100), ("txt", 2, 8), ("", 1, 1)]);
+
+        assert_eq!(stats.folder_sizes[&dir], 109);
+        assert_eq!(stats.folder_sizes[&dir.join("a")], 104);
+        assert_eq!(stats.folder_sizes[&dir.join("a/b")], 101);
+        let children = stats.children_by_size(&dir).unwrap();
+        let sizes: Vec<(&str, u64)> = children
+            .iter()
+            .map(|item| (item.name.as_str(), item.size))
+            .collect();
+        assert_eq!(sizes, vec![("a", 104), ("top.txt", 5)]);
+
+        assert!(tree_stats(&dir.join("top.txt"), &mut |_, _| {}).is_err());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_touch_sets_modified_time() {
+        let dir = scratch_dir("touch");
+        let file = dir.join("stamp.txt");
+        fs::write(&file, "x").unwrap();
+        let old = SystemTime::UNIX_EPOCH + std::time::Duration::from_secs(1_000_000_000);
+
+        touch(&file, Some(old)).unwrap();
+        assert_eq!(fs::metadata(&file).unwrap().modified().unwrap(), old);
+        touch_with_access(&file, Some(old)).unwrap();
+        assert_eq!(fs::metadata(&file).unwrap().accessed().unwrap(), old);
+
+        let before = SystemTime::now() - std::time::Duration::from_secs(5);
+        touch(&file, None).unwrap();
+        assert!(fs::metadata(&file).unwrap().modified().unwrap() >= before);
+        assert!(touch(&dir.join("missing"), None).is_err());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_natural_cmp_orders_numbers_by_value() {
+        use std::cmp::Ordering;
+
+        let mut names = vec!["file10.txt", "File1.txt", "file2.txt", "file02.txt", "file.txt"];
+        names.sort_by(|a, b| natural_cmp(a, b));
+        assert_eq!(names, ["file.txt", "File1.txt", "file02.txt", "file2.txt", "file10.txt"]);
+
+        assert_eq!(natural_cmp("b", "A"), Ordering::Greater);
+        assert_eq!(natural_cmp("v1.9", "v1.10"), Ordering::Less);
+        // Names equal apart from case are still ordered, never Equal
+        assert_ne!(natural_cmp("Readme", "README"), Ordering::Equal);
+        assert_eq!(natural_cmp("same", "same"), Ordering::Equal);
+    }
+
+    #[test]
+    fn test_diff_options_ignore_whitespace_and_case() {
+        let dir = scratch_dir("diff-options");
+        let file = dir.join("code.rs");
+        fs::write(&file, "fn main() {\n    let x = 1;\n}\n").unwrap();
+        let text = "fn main() {\n\tlet  x = 1;   \n}\n// Done\n";
+
+        let mut comparison =
+            compare_with_text(&file, text, "Clipboard", DiffOptions::default()).unwrap();
+        assert_eq!((comparison.modified_lines, comparison.right_only_lines), (1, 1));
+
+        comparison.set_options(DiffOptions { ignore_whitespace: true, ignore_case: false });
+        assert_eq!((comparison.modified_lines, comparison.right_only_lines), (0, 1));
+        assert_eq!(comparison.equal_lines, 3);
+        // Equal lines still show their original text
+        assert_eq!(comparison.diff_lines[1].right_content, "\tlet  x = 1;   ");
+
+        let upper = "FN MAIN() {\n    let x = 1;\n}\n";
+        let options = DiffOptions { ignore_whitespace: false, ignore_case: true };
+        assert!(compare_with_text(&file, upper, "Clipboard", options).unwrap().are_identical);
+        assert!(!compare_with_text(&file, upper, "Clipboard", DiffOptions::default())
+            .unwrap()
+            .are_identical);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_three_way_shows_which_side_changed() {
+        let dir = scratch_dir("three-way");
+        let (base, left, right) = (dir.join("base.txt"), dir.join("left.txt"), dir.join("right.txt"));
+        fs::write(&base, "one\ntwo\nthree\nfour\n").unwrap();
+        fs::write(&left, "one\nTWO\nthree\nfour\nfive\n").unwrap();
+        fs::write(&right, "one\ntwo\n3\nfour\nsix\n").unwrap();
+
+        let comparison = compare_three_way(&base, &left, &right, DiffOptions::default()).unwrap();
+        let types: Vec<MergeLineType> = comparison.lines.iter().map(|line| line.line_type).collect();
+        assert_eq!(
+            types,
+            [
+                MergeLineType::Unchanged,
+                MergeLineType::LeftChanged,
+                MergeLineType::RightChanged,
+                MergeLineType::Unchanged,
+                // Both appended a line, but not the same one
+                MergeLineType::Conflict,
+            ]
+        );
+        assert_eq!(comparison.lines[1].left.as_deref(), Some("TWO"));
+        assert_eq!(comparison.lines[4].base, None);
+        assert_eq!((comparison.left_changes, comparison.right_changes), (1, 1));
+        assert_eq!((comparison.both_changes, comparison.conflicts), (0, 1));
+
+        // The same change on both sides is not a conflict
+        let comparison = compare_three_way(&base, &left, &left, DiffOptions::default()).unwrap();
+        assert_eq!((comparison.both_changes, comparison.conflicts), (2, 0));
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_set_permissions_recursive() {
+        let dir = scratch_dir("chmod");
+        let tree = dir.join("scripts");
+        fs::create_dir_all(tree.join("lib")).unwrap();
+        fs::write(tree.join("run.sh"), "#!/bin/sh").unwrap();
+        fs::write(tree.join("lib/util.sh"), "#!/bin/sh").unwrap();
+        std::os::unix::fs::symlink(tree.join("run.sh"), tree.join("link.sh")).unwrap();
+
+        assert_eq!(parse_mode("0755").unwrap(), 0o755);
+        assert!(parse_mode("89").is_err());
+        assert!(parse_mode("").is_err());
+        assert_eq!(folder_mode(0o644), 0o755);
+        assert_eq!(folder_mode(0o600), 0o700);
+
+        // The link is skipped, the other four entries change
+        assert_eq!(set_permissions_recursive(&tree, 0o744).unwrap(), 4);
+        assert_eq!(permission_mode(&tree.join("lib/util.sh")).unwrap(), 0o744);
+        assert_eq!(permission_mode(&tree.join("lib")).unwrap(), 0o755);
+
+        // Removing read access still reaches the contents first
+        assert_eq!(set_permissions_recursive(&tree, 0o200).unwrap(), 4);
+        assert_eq!(permission_mode(&tree.join("run.sh")).unwrap(), 0o200);
+        assert_eq!(permission_mode(&tree).unwrap(), 0o200);
+        for folder in [&tree, &tree.join("lib")] {
+            fs::set_permissions(folder, std::os::unix::fs::PermissionsExt::from_mode(0o700))
+                .unwrap();
+        }
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_move_keeps_symlinks() {
+        use std::os::unix::fs::symlink;
+        let dir = scratch_dir("move-links");
+        let tree = dir.join("tree");
+        fs::create_dir_all(tree.join("sub")).unwrap();
+        fs::write(tree.join("file.txt"), "hello").unwrap();
+        fs::write(tree.join("sub/inner.txt"), "world").unwrap();
+        symlink("file.txt", tree.join("file-link")).unwrap();
+        symlink("sub", tree.join("dir-link")).unwrap();
+        symlink("missing.txt", tree.join("dangling")).unwrap();
+
+        let check = |moved: &Path| {
+            let links = [("file-link", "file.txt"), ("dir-link", "sub"), ("dangling", "missing.txt")];
+            for (name, target) in links {
+                let link = moved.join(name);
+                assert!(fs::symlink_metadata(&link).unwrap().file_type().is_symlink());
+                assert_eq!(fs::read_link(&link).unwrap(), PathBuf::from(target));
+            }
+            assert_eq!(fs::read_to_string(moved.join("sub/inner.txt")).unwrap(), "world");
+        };
+
+        // Same filesystem: a rename
+        let renamed = dir.join("renamed");
+        move_path(&tree, &renamed).unwrap();
+        assert!(fs::symlink_metadata(&tree).is_err());
+        check(&renamed);
+
+        // The cross-device fallback copies the links themselves
+        let copied = dir.join("copied");
+        let total = calculate_total_size(&renamed);
+        let mut last = (0, 0);
+        let bytes =
+            move_by_copying(&renamed, &copied, &mut |done, total, _| last = (done, total)).unwrap();
+        assert_eq!(bytes, 10);
+        assert_eq!(last, (10, total));
+        assert!(fs::symlink_metadata(&renamed).is_err());
+        check(&copied);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_remove_tree_stops_when_cancelled() {
+        let dir = scratch_dir("remove-tree");
+        let tree = dir.join("tree");
+        fs::create_dir_all(tree.join("a/b")).unwrap();
+        for name in ["one.txt", "a/two.txt", "a/b/three.txt"] {
+            fs::write(tree.join(name), "x").unwrap();
+        }
+        assert_eq!(count_entries(&tree), 6);
+
+        // Cancelling after two removals leaves the rest in place
+        let mut removed = 0;
+        let done = std::cell::Cell::new(0);
+        let finished = remove_tree_with_progress(
+            &tree,
+            &mut removed,
+            &mut |count, _| done.set(count),
+            &|| done.get() >= 2,
+        )
+        .unwrap();
+        assert!(!finished);
+        assert_eq!(removed, 2);
+        assert!(tree.is_dir());
+        assert_eq!(count_entries(&tree), 4);
+
+        let mut removed = 0;
+        let mut reported = Vec::new();
+        let finished = remove_tree_with_progress(
+            &tree,
+            &mut removed,
+            &mut |done, _| reported.push(done),
+            &|| false,
+        )
+        .unwrap();
+        assert!(finished);
+        assert_eq!(reported, vec![1, 2, 3, 4]);
+        assert!(!tree.exists());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_paste_into_folder_refuses_itself() {
+        let dir = scratch_dir("paste-into-folder");
+        let folder = dir.join("projects");
+        fs::create_dir_all(folder.join("nested")).unwrap();
+        let file = dir.join("todo.txt");
+        fs::write(&file, "x").unwrap();
+
+        assert_eq!(copy_destination(&file, &folder).unwrap(), folder.join("todo.txt"));
+        assert_eq!(transfer_destination(&file, &folder).unwrap(), folder.join("todo.txt"));
+        // A folder can't be pasted into itself or below itself
+        for target in [folder.clone(), folder.join("nested")] {
+            assert!(copy_destination(&folder, &target).is_err());
+            assert!(transfer_destination(&folder, &target).is_err());
+        }
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_compress_refuses_filesystem_root() {
+        let dir = scratch_dir("zip-root");
+        let archive = dir.join("root.zip");
+        assert!(compress_to_zip(Path::new("/"), &archive).is_err());
+        assert!(!archive.exists());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_merge_copy_into_partially_populated_target() {
+        let dir = scratch_dir("merge-copy");
+        let source = dir.join("source");
+        let target = dir.join("target");
+        fs::create_dir_all(source.join("sub").join("deeper")).unwrap();
+        fs::create_dir_all(target.join("sub")).unwrap();
+        fs::write(source.join("new.txt"), "new").unwrap();
+        fs::write(source.join("same.txt"), "from source").unwrap();
+        fs::write(source.join("sub").join("fresh.txt"), "fresh").unwrap();
+        fs::write(source.join("sub").join("stale.txt"), "newer").unwrap();
+        fs::write(source.join("sub").join("deeper").join("leaf.txt"), "leaf").unwrap();
+        fs::write(source.join("clash"), "a file").unwrap();
+        fs::write(target.join("same.txt"), "from target").unwrap();
+        fs::write(target.join("only_in_target.txt"), "untouched").unwrap();
+        fs::write(target.join("sub").join("fresh.txt"), "kept").unwrap();
+        fs::write(target.join("sub").join("stale.txt"), "older").unwrap();
+        fs::create_dir_all(target.join("clash")).unwrap();
+
+        // Make the target's copies of fresh.txt newer and stale.txt older
+        let hour = std::time::Duration::from_secs(60 * 60);
+        let set_modified = |path: PathBuf, time: SystemTime| {
+            let file = fs::File::options().write(true).open(path).unwrap();
+            file.set_modified(time).unwrap();
+        };
+        set_modified(target.join("sub").join("fresh.txt"), SystemTime::now() + hour);
+        set_modified(target.join("sub").join("stale.txt"), SystemTime::now() - hour);
+
+        let read = |path: PathBuf| fs::read_to_string(path).unwrap();
+        let summary =
+            merge_copy_with_progress(&source, &target, ConflictPolicy::Skip, &mut |_, _| {})
+                .unwrap();
+        assert_eq!(summary.copied, 2);
+        assert_eq!(summary.kept.len(), 3);
+        assert_eq!(summary.mismatched, vec![target.join("clash")]);
+        assert_eq!(read(target.join("new.txt")), "new");
+        assert_eq!(read(target.join("same.txt")), "from target");
+        assert_eq!(read(target.join("only_in_target.txt")), "untouched");
+        assert_eq!(read(target.join("sub").join("deeper").join("leaf.txt")), "leaf");
+        assert!(target.join("clash").is_dir());
+
+        let summary = merge_copy_with_progress(
+            &source,
+            &target,
+            ConflictPolicy::OverwriteOlder,
+            &mut |_, _| {},
+        )
+        .unwrap();
+        assert_eq!(read(target.join("sub").join("stale.txt")), "newer");
+        assert_eq!(read(target.join("sub").join("fresh.txt")), "kept");
+        // new.txt, same.txt and leaf.txt were just written, so not older
+        assert_eq!(summary.copied, 1);
+
+        merge_copy_with_progress(&source, &target, ConflictPolicy::Overwrite, &mut |_, _| {})
+            .unwrap();
+        assert_eq!(read(target.join("same.txt")), "from source");
+        assert_eq!(read(target.join("sub").join("fresh.txt")), "fresh");
+        assert_eq!(read(target.join("only_in_target.txt")), "untouched");
+
+        // Merging a folder into itself is refused up front
+        assert!(merge_destination(&source.join("sub"), &source).is_err());
+        assert_eq!(merge_destination(&source, &target).unwrap(), target.join("source"));
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_sync_copies_updates_and_optionally_deletes() {
+        let dir = scratch_dir("sync");
+        let source = dir.join("source");
+        let dest = dir.join("dest");
+        fs::create_dir_all(source.join("new_dir")).unwrap();
+        fs::create_dir_all(source.join("shared")).unwrap();
+        fs::create_dir_all(dest.join("shared")).unwrap();
+        fs::create_dir_all(dest.join("extra_dir")).unwrap();
+        fs::write(source.join("new_dir").join("a.txt"), "a").unwrap();
+        fs::write(source.join("shared").join("same.txt"), "same").unwrap();
+        fs::write(dest.join("shared").join("same.txt"), "same").unwrap();
+        fs::write(source.join("shared").join("changed.txt"), "new content").unwrap();
+        fs::write(dest.join("shared").join("changed.txt"), "old").unwrap();
+        fs::write(dest.join("shared").join("extra.txt"), "extra").unwrap();
+        fs::write(source.join("clash"), "file").unwrap();
+        fs::create_dir_all(dest.join("clash")).unwrap();
+        // Source files written a while ago, so that copies only look
+        // unchanged to the next sync if their modification times are kept
+        let earlier = SystemTime::now() - std::time::Duration::from_secs(600);
+        for path in [
+            source.join("new_dir").join("a.txt"),
+            source.join("shared").join("same.txt"),
+            source.join("shared").join("changed.txt"),
+            dest.join("shared").join("same.txt"),
+        ] {
+            let file = fs::File::options().write(true).open(path).unwrap();
+            file.set_modified(earlier).unwrap();
+        }
+
+        let plan = plan_sync(&source, &dest).unwrap();
+        let mut actions = plan.actions.clone();
+        actions.sort_by_key(|action| format!("{:?}", action));
+        assert_eq!(
+            actions,
+            vec![
+                SyncAction::Copy(PathBuf::from("new_dir")),
+                SyncAction::Delete(PathBuf::from("extra_dir")),
+                SyncAction::Delete(Path::new("shared").join("extra.txt")),
+                SyncAction::Update(Path::new("shared").join("changed.txt")),
+            ]
+        );
+        assert_eq!(plan.conflicts, vec![PathBuf::from("clash")]);
+
+        let summary = apply_sync(&plan.without_deletions(), &mut |_, _| {}).unwrap();
+        assert_eq!((summary.copied, summary.updated, summary.deleted), (1, 1, 0));
+        assert_eq!(fs::read_to_string(dest.join("new_dir").join("a.txt")).unwrap(), "a");
+        assert_eq!(
+            fs::read_to_string(dest.join("shared").join("changed.txt")).unwrap(),
+            "new content"
+        );
+        assert!(dest.join("shared").join("extra.txt").exists());
+
+        let summary = apply_sync(&plan_sync(&source, &dest).unwrap(), &mut |_, _| {}).unwrap();
+        assert_eq!((summary.copied, summary.updated, summary.deleted), (0, 0, 2));
+        assert!(!dest.join("extra_dir").exists());
+        assert!(!dest.join("shared").join("extra.txt").exists());
+        assert!(dest.join("clash").is_dir());
+        assert!(plan_sync(&source, &dest).unwrap().actions.is_empty());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    /// Relative paths of the hits, sorted, with `/` separators.
+    fn hit_paths(results: &SearchResults, root: &Path) -> Vec<String> {
+        let mut paths: Vec<String> = results
+            .items
+            .iter()
+            .map(|hit| {
+                let relative = hit.item.path.strip_prefix(root).unwrap();
+                relative.to_string_lossy().replace('\\', "/")
+            })
+            .collect();
+        paths.sort();
+        paths
+    }
+
+    #[test]
+    fn test_search_combines_criteria() {
+        let dir = scratch_dir("search-criteria");
+        let root = fs::canonicalize(&dir).unwrap();
+        fs::create_dir_all(root.join("docs")).unwrap();
+        fs::create_dir_all(root.join("notes").join("inner")).unwrap();
+        fs::write(root.join("docs").join("notes.txt"), "hello world").unwrap();
+        fs::write(root.join("docs").join("big_notes.txt"), "hello ".repeat(400)).unwrap();
+        fs::write(root.join("docs").join("old_notes.txt"), "hello").unwrap();
+        fs::write(root.join("notes").join("readme.md"), "nothing here").unwrap();
+        fs::write(root.join("notes").join("inner").join("notes.txt"), "bye").unwrap();
+        let day = std::time::Duration::from_secs(24 * 60 * 60);
+        fs::File::options()
+            .write(true)
+            .open(root.join("docs").join("old_notes.txt"))
+            .unwrap()
+            .set_modified(SystemTime::now() - 30 * day)
+            .unwrap();
+
+        let search = |criteria: SearchCriteria| {
+            let criteria = SearchCriteria {
+                search_paths: vec![root.clone()],
+                ..criteria
+            };
+            hit_paths(&search_files(&criteria).unwrap(), &root)
+        };
+
+        // A matching folder is reported once and its children still searched
+        assert_eq!(
+            search(SearchCriteria {
+                filename_pattern: "notes".to_string(),
+                ..Default::default()
+            }),
+            [
+                "docs/big_notes.txt",
+                "docs/notes.txt",
+                "docs/old_notes.txt",
+                "notes",
+                "notes/inner/notes.txt",
+            ]
+        );
+        // Content narrows the name matches down to files containing it
+        assert_eq!(
+            search(SearchCriteria {
+                filename_pattern: "notes".to_string(),
+                content_pattern: "hello".to_string(),
+                ..Default::default()
+            }),
+            ["docs/big_notes.txt", "docs/notes.txt", "docs/old_notes.txt"]
+        );
+        assert_eq!(
+            search(SearchCriteria {
+                filename_pattern: "notes".to_string(),
+                content_pattern: "hello".to_string(),
+                max_size: Some(100),
+                ..Default::default()
+            }),
+            ["docs/notes.txt", "docs/old_notes.txt"]
+        );
+        assert_eq!(
+            search(SearchCriteria {
+                filename_pattern: "notes".to_string(),
+                content_pattern: "hello".to_string(),
+                max_size: Some(100),
+                modified_after: Some(SystemTime::now() - day),
+                ..Default::default()
+            }),
+            ["docs/notes.txt"]
+        );
+        // Size limits apply to files only
+        assert_eq!(
+            search(SearchCriteria {
+                min_size: Some(1000),
+                ..Default::default()
+            }),
+            ["docs/big_notes.txt"]
+        );
+        // Content without a name pattern searches every file, below matching
+        // and non-matching folders alike
+        assert_eq!(
+            search(SearchCriteria {
+                content_pattern: "bye".to_string(),
+                ..Default::default()
+            }),
+            ["notes/inner/notes.txt"]
+        );
+        assert_eq!(
+            search(SearchCriteria {
+                filename_pattern: "notes".to_string(),
+                file_type: SearchFileType::Directories,
+                ..Default::default()
+            }),
+            ["notes"]
+        );
+        // Extension groups and custom extensions add up, and combine with the
+        // other criteria; folders don't match them
+        assert_eq!(
+            search(SearchCriteria {
+                custom_extensions: parse_extension_list(".MD"),
+                ..Default::default()
+            }),
+            ["notes/readme.md"]
+        );
+        assert_eq!(
+            search(SearchCriteria {
+                filename_pattern: "notes".to_string(),
+                extension_groups: vec![ExtensionGroup::Documents],
+                min_size: Some(1000),
+                ..Default::default()
+            }),
+            ["docs/big_notes.txt"]
+        );
+        assert_eq!(
+            search(SearchCriteria {
+                filename_pattern: "notes".to_string(),
+                extension_groups: vec![ExtensionGroup::Images],
+                custom_extensions: vec!["md".to_string()],
+                ..Default::default()
+            }),
+            Vec::<String>::new()
+        );
+
+        // Overlapping roots don't report anything twice
+        let criteria = SearchCriteria {
+            search_paths: vec![root.clone(), root.join("docs"), root.join("docs")],
+            filename_pattern: "notes".to_string(),
+            ..Default::default()
+        };
+        assert_eq!(hit_paths(&search_files(&criteria).unwrap(), &root).len(), 5);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_search_streams_hits_and_stops_when_cancelled() {
+        let dir = scratch_dir("search-streaming");
+        let root = fs::canonicalize(&dir).unwrap();
+        for name in ["a", "b", "c"] {
+            fs::create_dir_all(root.join(name)).unwrap();
+            fs::write(root.join(name).join("match.txt"), "").unwrap();
+        }
+        let criteria = SearchCriteria {
+            search_paths: vec![root.clone()],
+            filename_pattern: "match".to_string(),
+            ..Default::default()
+        };
+
+        let mut streamed = 0;
+        let results =
+            search_files_with_progress(&criteria, &mut |hits, _| streamed += hits.len(), &|| false)
+                .unwrap();
+        assert_eq!(results.items.len(), 3);
+        assert_eq!(streamed, 3);
+
+        // Cancelled once the first hit is in, the walk stops early
+        let found = std::cell::Cell::new(0);
+        let results = search_files_with_progress(
+            &criteria,
+            &mut |hits, _| found.set(found.get() + hits.len()),
+            &|| found.get() > 0,
+        )
+        .unwrap();
+        assert!(!results.items.is_empty() && results.items.len() < 3);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_recursive_walks_survive_symlink_loops() {
+        let dir = scratch_dir("symlink-loop");
+        let source = dir.join("source");
+        fs::create_dir_all(source.join("sub")).unwrap();
+        fs::write(source.join("sub").join("file.txt"), "hello").unwrap();
+        std::os::unix::fs::symlink(&source, source.join("sub").join("loop")).unwrap();
+
+        let criteria = SearchCriteria {
+            search_paths: vec![source.clone()],
+            filename_pattern: "file".to_string(),
+            ..Default::default()
+        };
+        let results = search_files(&criteria).unwrap();
+        assert_eq!(results.items.len(), 1);
+
+        let archive = dir.join("source.zip");
+        let skipped = compress_to_zip(&source, &archive).unwrap();
+        assert_eq!(skipped, vec![source.join("sub").join("loop")]);
+
+        let copy = dir.join("copy");
+        copy_recursive_with_progress(&source, &copy, &mut |_, _| {}).unwrap();
+        assert_eq!(fs::read_to_string(copy.join("sub").join("file.txt")).unwrap(), "hello");
+        assert!(fs::symlink_metadata(copy.join("sub").join("loop"))
+            .unwrap()
+            .file_type()
+            .is_symlink());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[cfg(windows)]
+    #[test]
+    fn test_recursive_walks_skip_junctions() {
+        let dir = scratch_dir("junction");
+        let source = dir.join("source");
+        let target = dir.join("target");
+        fs::create_dir_all(&source).unwrap();
+        fs::create_dir_all(&target).unwrap();
+        fs::write(target.join("file.txt"), "hello").unwrap();
+        let junction = source.join("junction");
+        // Junctions need no extra privileges, unlike directory symlinks
+        let status = std::process::Command::new("cmd")
+            .args(["/C", "mklink", "/J"])
+            .arg(&junction)
+            .arg(&target)
+            .status()
+            .unwrap();
+        assert!(status.success());
+        assert!(is_reparse_point(&junction));
+        assert!(is_link(&junction));
+
+        let criteria = SearchCriteria {
+            search_paths: vec![source.clone()],
+            filename_pattern: "file".to_string(),
+            ..Default::default()
+        };
+        assert!(search_files(&criteria).unwrap().items.is_empty());
+
+        let archive = dir.join("source.zip");
+        let skipped = compress_to_zip(&source, &archive).unwrap();
+        assert_eq!(skipped, vec![junction.clone()]);
+
+        let copy = dir.join("copy");
+        copy_recursive_with_progress(&source, &copy, &mut |_, _| {}).unwrap();
+        // Either recreated as a link or left out, but never copied as a folder
+        let copied = copy.join("junction");
+        assert!(fs::symlink_metadata(&copied).map_or(true, |_| is_link(&copied)));
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -1,8 +1,13 @@
+pub mod actions;
 pub mod app;
 pub mod app_gui;
 pub mod bookmarks;
 pub mod filesystem;
+pub mod oplog;
 pub mod pane;
 pub mod pdf_renderer;
+pub mod progress;
+pub mod search_index;
+pub mod settings;
 pub mod trash;
 pub mod ui;
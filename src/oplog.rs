@@ -0,0 +1,151 @@
+use std::fmt::Display;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+/// Entries kept before the oldest are dropped, so a long session moving
+/// many files can't grow the log without bound.
+const MAX_ENTRIES: usize = 10_000;
+
+/// One file operation: what was done to which path, and whether it worked.
+#[derive(Clone, Debug)]
+pub struct LogEntry {
+    pub time: chrono::DateTime<chrono::Local>,
+    pub action: String,
+    pub source: Option<PathBuf>,
+    pub destination: Option<PathBuf>,
+    /// The error, if the operation failed.
+    pub error: Option<String>,
+}
+
+impl LogEntry {
+    pub fn succeeded(&self) -> bool {
+        self.error.is_none()
+    }
+}
+
+/// Record of the file operations of this session, oldest first. Clones
+/// share the same entries, so background tasks can log each item of a
+/// batch as they go.
+#[derive(Clone, Default)]
+pub struct OperationLog {
+    entries: Arc<Mutex<Vec<LogEntry>>>,
+}
+
+impl OperationLog {
+    /// Log the outcome of `action` on `source` (and `destination`, for
+    /// operations that create something elsewhere).
+    pub fn record<T, E: Display>(
+        &self,
+        action: &str,
+        source: Option<&Path>,
+        destination: Option<&Path>,
+        result: &Result<T, E>,
+    ) {
+        let entry = LogEntry {
+            time: chrono::Local::now(),
+            action: action.to_string(),
+            source: source.map(Path::to_path_buf),
+            destination: destination.map(Path::to_path_buf),
+            error: result.as_ref().err().map(|e| e.to_string()),
+        };
+        let mut entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+        if entries.len() >= MAX_ENTRIES {
+            entries.remove(0);
+        }
+        entries.push(entry);
+    }
+
+    /// Copy of the entries, oldest first.
+    pub fn entries(&self) -> Vec<LogEntry> {
+        self.entries.lock().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.lock().unwrap_or_else(|e| e.into_inner()).len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// How many entries record a failure.
+    pub fn failure_count(&self) -> usize {
+        let entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+        entries.iter().filter(|entry| !entry.succeeded()).count()
+    }
+
+    pub fn clear(&self) {
+        self.entries.lock().unwrap_or_else(|e| e.into_inner()).clear();
+    }
+}
+
+/// Outcome of each item of a batch copy, move or delete, collected while
+/// it runs so the end of the batch can list them all at once.
+#[derive(Clone, Debug, Default)]
+pub struct BatchReport {
+    pub succeeded: Vec<PathBuf>,
+    /// Items left alone on purpose, e.g. because the destination exists,
+    /// with the reason.
+    pub skipped: Vec<(PathBuf, String)>,
+    /// Items the operation was tried on and failed for, with the error.
+    pub failed: Vec<(PathBuf, String)>,
+}
+
+impl BatchReport {
+    pub fn add_success(&mut self, path: &Path) {
+        self.succeeded.push(path.to_path_buf());
+    }
+
+    pub fn add_skip(&mut self, path: &Path, reason: impl Display) {
+        self.skipped.push((path.to_path_buf(), reason.to_string()));
+    }
+
+    pub fn add_failure(&mut self, path: &Path, error: impl Display) {
+        self.failed.push((path.to_path_buf(), error.to_string()));
+    }
+
+    /// Whether anything was skipped or failed.
+    pub fn has_problems(&self) -> bool {
+        !self.skipped.is_empty() || !self.failed.is_empty()
+    }
+
+    /// The failed items, to try again.
+    pub fn failed_paths(&self) -> Vec<PathBuf> {
+        self.failed.iter().map(|(path, _)| path.clone()).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_clones_share_entries() {
+        let log = OperationLog::default();
+        let worker = log.clone();
+        worker.record("Copy", Some(Path::new("a")), Some(Path::new("b")), &Ok::<_, String>(()));
+        worker.record("Delete", Some(Path::new("c")), None, &Err::<(), _>("Permission denied"));
+
+        let entries = log.entries();
+        assert_eq!(entries.len(), 2);
+        assert!(entries[0].succeeded());
+        assert_eq!(entries[1].error.as_deref(), Some("Permission denied"));
+        assert_eq!(log.failure_count(), 1);
+
+        log.clear();
+        assert!(worker.is_empty());
+    }
+
+    #[test]
+    fn test_batch_report_lists_failures_to_retry() {
+        let mut report = BatchReport::default();
+        report.add_success(Path::new("a"));
+        assert!(!report.has_problems());
+
+        report.add_skip(Path::new("b"), "already exists");
+        report.add_failure(Path::new("c"), "Permission denied");
+        assert!(report.has_problems());
+        assert_eq!(report.skipped[0].1, "already exists");
+        assert_eq!(report.failed_paths(), [PathBuf::from("c")]);
+    }
+}
@@ -1,14 +1,36 @@
-use crate::filesystem::{FileItem, read_directory};
+use crate::filesystem::{ExtensionGroup, FileItem, GitRepoInfo, SharedGitStatusCache, read_directory};
 use anyhow::Result;
-use std::path::PathBuf;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
 
-#[derive(Clone, Copy, Debug, PartialEq)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub enum SortBy {
     Name,
     Size,
     Date,
 }
 
+impl SortBy {
+    /// Direction used when switching to this column: names read naturally
+    /// A→Z, while the biggest and newest entries are usually wanted first.
+    pub fn default_order(&self) -> SortOrder {
+        match self {
+            SortBy::Name => SortOrder::Ascending,
+            SortBy::Size | SortBy::Date => SortOrder::Descending,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            SortBy::Name => "Name",
+            SortBy::Size => "Size",
+            SortBy::Date => "Date",
+        }
+    }
+}
+
 #[derive(Clone, Copy, Debug, PartialEq)]
 pub enum SortOrder {
     Ascending,
@@ -26,15 +48,131 @@ pub struct Pane {
     pub history: Vec<PathBuf>,
     pub history_index: usize,
     pub git_repo_path: Option<PathBuf>,
+    /// Branch and ahead/behind info for `git_repo_path`, taken from `git_cache`
+    /// on every refresh.
+    pub git_info: Option<GitRepoInfo>,
+    pub git_cache: SharedGitStatusCache,
+    /// Set when the current directory could not be listed, e.g. because
+    /// of missing permissions. The pane then only shows "..".
+    pub read_error: Option<String>,
+    /// Free space and read-only flag of the filesystem holding
+    /// `current_path`, updated on every refresh.
+    pub fs_status: Option<crate::filesystem::FsStatus>,
     pub selected_items: Vec<usize>,
     pub selection_anchor: Option<usize>,
+    /// Set by [`Pane::check_for_changes`] when `current_path` was modified
+    /// by something else since it was last listed; cleared on refresh.
+    pub changed_on_disk: bool,
+    /// Modification time of `current_path` when it was last listed.
+    listed_mtime: Option<SystemTime>,
+    /// Listed files that are hard links to other listed files, with the
+    /// number of those other names.
+    pub hard_links: HashMap<PathBuf, usize>,
+    /// Topmost row drawn in the list (its position among the shown rows)
+    /// and its item, as of the last frame.
+    pub top_row: Option<(usize, PathBuf)>,
+    /// Item that was at the top of the list when it was last reloaded. The
+    /// list scrolls to keep it there even if entries above it came or went.
+    pub scroll_anchor: Option<PathBuf>,
+    /// Number of rows shown in the last frame, to notice filter changes.
+    pub shown_rows: usize,
+    /// Sort dotfiles after all other entries, whatever the sort column.
+    pub group_hidden: bool,
+    /// Sort to switch to when entering a folder most of whose files are of
+    /// a group. Empty leaves the sort as it is when changing folders.
+    type_sorts: Vec<(ExtensionGroup, SortBy)>,
+    /// Sorts picked by hand per folder while `type_sorts` is in use; they
+    /// win over the folder's type default.
+    folder_sorts: HashMap<PathBuf, (SortBy, SortOrder)>,
 }
 
 impl Pane {
     pub fn new(path: PathBuf) -> Result<Self> {
+        Self::with_git_cache(path, SharedGitStatusCache::default())
+    }
+
+    /// Create a pane that shares its git status cache with other panes.
+    pub fn with_git_cache(path: PathBuf, git_cache: SharedGitStatusCache) -> Result<Self> {
+        let mut pane = Self::unlisted(path, git_cache);
+        pane.refresh()?;
+        Ok(pane)
+    }
+
+    /// Open a pane in the first usable start directory: home, the current
+    /// directory, then the first readable mount point. Never fails; if no
+    /// candidate can be listed the pane shows an error banner instead.
+    /// Returns a note for the status bar when it had to fall back.
+    pub fn open_start_pane(git_cache: SharedGitStatusCache) -> (Self, Option<String>) {
+        let mut candidates = Vec::new();
+        if let Some(home) = dirs::home_dir() {
+            candidates.push(("Home directory", home));
+        }
+        if let Ok(current) = std::env::current_dir() {
+            candidates.push(("Current directory", current));
+        }
+        for mount in crate::filesystem::get_mount_points() {
+            candidates.push(("Mount point", mount.mount_point));
+        }
+        candidates.push(("Filesystem root", PathBuf::from("/")));
+
+        let mut failures: Vec<String> = Vec::new();
+        for (label, path) in &candidates {
+            match Self::with_git_cache(path.clone(), git_cache.clone()) {
+                Ok(pane) if pane.read_error.is_none() => {
+                    let note = (!failures.is_empty()).then(|| {
+                        format!(
+                            "{}; started in {} instead",
+                            failures.join("; "),
+                            path.display()
+                        )
+                    });
+                    return (pane, note);
+                }
+                Ok(pane) => failures.push(format!(
+                    "{} {} is not readable ({})",
+                    label,
+                    path.display(),
+                    pane.read_error.unwrap_or_default()
+                )),
+                Err(e) => failures.push(format!("{} {} failed: {}", label, path.display(), e)),
+            }
+        }
+
+        let path = PathBuf::from("/");
+        let mut pane = Self::unlisted(path, git_cache);
+        pane.items = vec![FileItem::parent_dir()];
+        pane.read_error = Some("No readable start directory".to_string());
+        (pane, Some(failures.join("; ")))
+    }
+
+    /// Open a pane at a path given on the command line: a folder is opened,
+    /// a file opens its folder with the cursor on it. Relative paths are
+    /// taken from the current directory. Errors if the path doesn't exist or
+    /// can't be listed, so the caller can fall back to the start pane.
+    pub fn open_at(path: &Path, git_cache: SharedGitStatusCache) -> Result<Self> {
+        let path = std::fs::canonicalize(path)
+            .map_err(|e| anyhow::anyhow!("Cannot open {}: {}", path.display(), e))?;
+        let (dir, file) = if path.is_dir() {
+            (path, None)
+        } else {
+            let dir = crate::filesystem::parent_dir(&path)?.to_path_buf();
+            (dir, Some(path))
+        };
+        let mut pane = Self::with_git_cache(dir.clone(), git_cache)?;
+        if let Some(error) = &pane.read_error {
+            anyhow::bail!("Cannot open {}: {}", dir.display(), error);
+        }
+        if let Some(file) = file {
+            pane.select_paths(&[file]);
+        }
+        Ok(pane)
+    }
+
+    /// A pane for `path` whose contents haven't been read yet.
+    fn unlisted(path: PathBuf, git_cache: SharedGitStatusCache) -> Self {
         let git_repo_path = crate::filesystem::find_git_repo(&path);
 
-        let mut pane = Pane {
+        Pane {
             current_path: path.clone(),
             items: Vec::new(),
             selected_index: 0,
@@ -45,25 +183,101 @@ impl Pane {
             history: vec![path],
             history_index: 0,
             git_repo_path,
+            git_info: None,
+            git_cache,
+            read_error: None,
+            fs_status: None,
             selected_items: Vec::new(),
             selection_anchor: None,
-        };
-        pane.refresh()?;
-        Ok(pane)
+            changed_on_disk: false,
+            listed_mtime: None,
+            hard_links: HashMap::new(),
+            top_row: None,
+            scroll_anchor: None,
+            shown_rows: 0,
+            group_hidden: false,
+            type_sorts: Vec::new(),
+            folder_sorts: HashMap::new(),
+        }
     }
 
     pub fn refresh(&mut self) -> Result<()> {
-        self.items = read_directory(&self.current_path)?;
+        // Remember entries by path so the cursor and selection stay on the
+        // same items when entries are added or removed
+        let remembered = self.remember_selection();
+        self.scroll_anchor = self.top_row.as_ref().map(|(_, path)| path.clone());
+
+        self.listed_mtime = directory_mtime(&self.current_path);
+        self.changed_on_disk = false;
+        match read_directory(&self.current_path) {
+            Ok(items) => {
+                self.items = items;
+                self.read_error = None;
+            }
+            Err(e) if is_permission_denied(&e) => {
+                self.items = vec![FileItem::parent_dir()];
+                self.read_error = Some("Permission denied".to_string());
+            }
+            Err(e) => return Err(e),
+        }
+        self.fs_status = crate::filesystem::filesystem_status(&self.current_path);
+        self.hard_links = crate::filesystem::hard_linked_items(&self.items);
 
+        self.git_info = None;
         if let Some(repo_path) = &self.git_repo_path {
-            crate::filesystem::apply_git_status(&mut self.items, repo_path);
+            let mut cache = self.git_cache.borrow_mut();
+            let cached = cache.get(repo_path);
+            if let Some(status_map) = &cached.status_map {
+                crate::filesystem::apply_git_status_map(&mut self.items, status_map);
+            }
+            self.git_info = cached.info.clone();
         }
 
         self.apply_sort();
-        if self.selected_index >= self.items.len() && !self.items.is_empty() {
+        self.restore_selection(remembered);
+        Ok(())
+    }
+
+    /// Paths of the item under the cursor and of the marked items, to be
+    /// passed to `restore_selection` after the items were reloaded or sorted.
+    fn remember_selection(&self) -> (Option<PathBuf>, Vec<PathBuf>) {
+        let cursor_path = self.get_selected_item().map(|item| item.path.clone());
+        let selected_paths = self
+            .selected_items
+            .iter()
+            .filter_map(|&index| self.items.get(index))
+            .map(|item| item.path.clone())
+            .collect();
+        (cursor_path, selected_paths)
+    }
+
+    /// Put the cursor on the first of `paths` and, when there are several,
+    /// mark all of them. Paths that aren't listed are ignored.
+    pub fn select_paths(&mut self, paths: &[PathBuf]) {
+        let marked = if paths.len() > 1 { paths.to_vec() } else { Vec::new() };
+        self.restore_selection((paths.first().cloned(), marked));
+    }
+
+    /// Put the cursor and marks back on the remembered paths. If the cursor's
+    /// item is gone the index is only clamped to the new list.
+    fn restore_selection(&mut self, (cursor_path, selected_paths): (Option<PathBuf>, Vec<PathBuf>)) {
+        let position = |path: &PathBuf| self.items.iter().position(|item| &item.path == path);
+        if let Some(index) = cursor_path.as_ref().and_then(position) {
+            self.selected_index = index;
+        } else if self.selected_index >= self.items.len() && !self.items.is_empty() {
             self.selected_index = self.items.len() - 1;
         }
-        Ok(())
+        self.selected_items = selected_paths.iter().filter_map(position).collect();
+        if self.selected_items.is_empty() {
+            self.selection_anchor = None;
+        }
+    }
+
+    /// Compare the folder's modification time with the one seen when it was
+    /// listed, updating `changed_on_disk`. The listing itself is left alone
+    /// so it doesn't change under the user; `refresh` picks up the changes.
+    pub fn check_for_changes(&mut self) {
+        self.changed_on_disk = directory_mtime(&self.current_path) != self.listed_mtime;
     }
 
     pub fn toggle_sort(&mut self, sort_by: SortBy) {
@@ -75,9 +289,58 @@ impl Pane {
             };
         } else {
             self.sort_by = sort_by;
-            self.sort_order = SortOrder::Ascending;
+            self.sort_order = sort_by.default_order();
+        }
+        if !self.type_sorts.is_empty() {
+            self.folder_sorts
+                .insert(self.current_path.clone(), (self.sort_by, self.sort_order));
         }
+        let remembered = self.remember_selection();
         self.apply_sort();
+        self.restore_selection(remembered);
+    }
+
+    /// Turn grouping of dotfiles after the other entries on or off.
+    pub fn set_group_hidden(&mut self, group_hidden: bool) {
+        if self.group_hidden == group_hidden {
+            return;
+        }
+        self.group_hidden = group_hidden;
+        let remembered = self.remember_selection();
+        self.apply_sort();
+        self.restore_selection(remembered);
+    }
+
+    /// Set the sort used for folders by their main file type (see
+    /// [`ExtensionGroup::dominant`]). An empty list turns this off, and with
+    /// it the sorts remembered per folder.
+    pub fn set_type_sorts(&mut self, type_sorts: Vec<(ExtensionGroup, SortBy)>) {
+        if type_sorts.is_empty() {
+            self.folder_sorts.clear();
+        }
+        self.type_sorts = type_sorts;
+    }
+
+    /// After entering a folder, switch to the sort picked for it earlier or,
+    /// failing that, the one for its main file type.
+    fn apply_folder_sort(&mut self) {
+        if self.type_sorts.is_empty() {
+            return;
+        }
+        let sort = self.folder_sorts.get(&self.current_path).copied().or_else(|| {
+            let group = ExtensionGroup::dominant(&self.items)?;
+            self.type_sorts
+                .iter()
+                .find(|(g, _)| *g == group)
+                .map(|&(_, sort_by)| (sort_by, sort_by.default_order()))
+        });
+        if let Some((sort_by, sort_order)) = sort {
+            if (sort_by, sort_order) != (self.sort_by, self.sort_order) {
+                self.sort_by = sort_by;
+                self.sort_order = sort_order;
+                self.apply_sort();
+            }
+        }
     }
 
     fn apply_sort(&mut self) {
@@ -86,7 +349,8 @@ impl Pane {
         if let Some(idx) = parent {
             let parent_item = self.items.remove(idx);
 
-            // Sort remaining items
+            // Sort remaining items, dotfiles last if they are grouped
+            let hidden = |item: &FileItem| self.group_hidden && item.name.starts_with('.');
             self.items.sort_by(|a, b| {
                 let ordering = match self.sort_by {
                     SortBy::Name => {
@@ -94,17 +358,23 @@ impl Pane {
                         match (a.is_dir, b.is_dir) {
                             (true, false) => std::cmp::Ordering::Less,
                             (false, true) => std::cmp::Ordering::Greater,
-                            _ => a.name.to_lowercase().cmp(&b.name.to_lowercase()),
+                            _ => crate::filesystem::natural_cmp(&a.name, &b.name),
                         }
                     }
                     SortBy::Size => a.size.cmp(&b.size),
                     SortBy::Date => a.modified.cmp(&b.modified),
                 };
 
-                match self.sort_order {
+                let ordering = match self.sort_order {
                     SortOrder::Ascending => ordering,
                     SortOrder::Descending => ordering.reverse(),
-                }
+                };
+                // Equal sizes or dates fall back to the name, A to Z, so the
+                // order doesn't depend on how the directory was read
+                hidden(a)
+                    .cmp(&hidden(b))
+                    .then(ordering)
+                    .then_with(|| crate::filesystem::natural_cmp(&a.name, &b.name))
             });
 
             // Re-insert parent at the beginning
@@ -112,6 +382,28 @@ impl Pane {
         }
     }
 
+    // Cursor movement clamps at the first and last entry and never wraps
+    // around, for the arrow keys as well as paging.
+
+    /// Move the cursor by `delta` rows, clamped to the listing. With
+    /// `extend_selection` the range from the selection anchor is selected.
+    pub fn move_by(&mut self, delta: isize, extend_selection: bool) {
+        if extend_selection && self.selection_anchor.is_none() {
+            self.selection_anchor = Some(self.selected_index);
+        }
+
+        let last = self.items.len().saturating_sub(1);
+        self.selected_index = if delta < 0 {
+            self.selected_index.saturating_sub(delta.unsigned_abs())
+        } else {
+            self.selected_index.saturating_add(delta as usize).min(last)
+        };
+
+        if extend_selection {
+            self.update_selection_range();
+        }
+    }
+
     pub fn move_up(&mut self) {
         if self.selected_index > 0 {
             self.selected_index -= 1;
@@ -155,6 +447,30 @@ impl Pane {
         }
     }
 
+    /// Select every item in `visible` except "..".
+    pub fn select_all(&mut self, visible: &[usize]) {
+        self.selected_items = visible
+            .iter()
+            .copied()
+            .filter(|&index| self.items.get(index).is_some_and(|item| item.name != ".."))
+            .collect();
+        self.selection_anchor = None;
+    }
+
+    /// Select the items in `visible` (except "..") that aren't selected yet
+    /// and deselect the rest.
+    pub fn invert_selection(&mut self, visible: &[usize]) {
+        self.selected_items = visible
+            .iter()
+            .copied()
+            .filter(|&index| {
+                self.items.get(index).is_some_and(|item| item.name != "..")
+                    && !self.selected_items.contains(&index)
+            })
+            .collect();
+        self.selection_anchor = None;
+    }
+
     pub fn clear_selection(&mut self) {
         self.selected_items.clear();
         self.selection_anchor = None;
@@ -203,6 +519,7 @@ impl Pane {
         self.git_repo_path = crate::filesystem::find_git_repo(&path);
 
         self.refresh()?;
+        self.apply_folder_sort();
         self.selected_index = 0;
         self.scroll_offset = 0;
 
@@ -229,26 +546,34 @@ impl Pane {
 
     pub fn navigate_back(&mut self) -> Result<()> {
         if self.can_go_back() {
-            self.history_index -= 1;
-            self.current_path = self.history[self.history_index].clone();
-            self.refresh()?;
-            self.selected_index = 0;
-            self.scroll_offset = 0;
+            self.jump_to_history(self.history_index - 1)?;
         }
         Ok(())
     }
 
     pub fn navigate_forward(&mut self) -> Result<()> {
         if self.can_go_forward() {
-            self.history_index += 1;
-            self.current_path = self.history[self.history_index].clone();
-            self.refresh()?;
-            self.selected_index = 0;
-            self.scroll_offset = 0;
+            self.jump_to_history(self.history_index + 1)?;
         }
         Ok(())
     }
 
+    /// Go straight to entry `index` of the back/forward history, keeping the
+    /// history itself as it is.
+    pub fn jump_to_history(&mut self, index: usize) -> Result<()> {
+        let Some(path) = self.history.get(index) else {
+            return Ok(());
+        };
+        self.history_index = index;
+        self.current_path = path.clone();
+        self.git_repo_path = crate::filesystem::find_git_repo(&self.current_path);
+        self.refresh()?;
+        self.apply_folder_sort();
+        self.selected_index = 0;
+        self.scroll_offset = 0;
+        Ok(())
+    }
+
     pub fn can_go_back(&self) -> bool {
         self.history_index > 0
     }
@@ -269,3 +594,232 @@ impl Pane {
         }
     }
 }
+
+fn directory_mtime(path: &Path) -> Option<SystemTime> {
+    std::fs::metadata(path).and_then(|m| m.modified()).ok()
+}
+
+fn is_permission_denied(error: &anyhow::Error) -> bool {
+    error
+        .downcast_ref::<std::io::Error>()
+        .is_some_and(|e| e.kind() == std::io::ErrorKind::PermissionDenied)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_pane() -> Pane {
+        Pane::new(std::env::temp_dir()).expect("temp dir should be readable")
+    }
+
+    #[test]
+    fn test_new_pane_sorts_by_name_ascending() {
+        let pane = test_pane();
+        assert_eq!(pane.sort_by, SortBy::Name);
+        assert_eq!(pane.sort_order, SortOrder::Ascending);
+    }
+
+    #[test]
+    fn test_clicking_active_column_toggles_order() {
+        let mut pane = test_pane();
+        pane.toggle_sort(SortBy::Name);
+        assert_eq!(pane.sort_by, SortBy::Name);
+        assert_eq!(pane.sort_order, SortOrder::Descending);
+        pane.toggle_sort(SortBy::Name);
+        assert_eq!(pane.sort_order, SortOrder::Ascending);
+    }
+
+    #[test]
+    fn test_switching_column_uses_default_order() {
+        let mut pane = test_pane();
+
+        pane.toggle_sort(SortBy::Size);
+        assert_eq!(pane.sort_by, SortBy::Size);
+        assert_eq!(pane.sort_order, SortOrder::Descending);
+
+        pane.toggle_sort(SortBy::Date);
+        assert_eq!(pane.sort_by, SortBy::Date);
+        assert_eq!(pane.sort_order, SortOrder::Descending);
+
+        pane.toggle_sort(SortBy::Date);
+        assert_eq!(pane.sort_order, SortOrder::Ascending);
+
+        pane.toggle_sort(SortBy::Name);
+        assert_eq!(pane.sort_by, SortBy::Name);
+        assert_eq!(pane.sort_order, SortOrder::Ascending);
+    }
+
+    #[test]
+    fn test_switching_column_ignores_previous_order() {
+        let mut pane = test_pane();
+        pane.toggle_sort(SortBy::Name);
+        assert_eq!(pane.sort_order, SortOrder::Descending);
+
+        pane.toggle_sort(SortBy::Size);
+        pane.toggle_sort(SortBy::Name);
+        assert_eq!(pane.sort_order, SortOrder::Ascending);
+    }
+
+    #[test]
+    fn test_sorting_keeps_cursor_on_same_item() {
+        let dir = std::env::temp_dir().join(format!("filane-sort-cursor-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("a.txt"), "1").unwrap();
+        std::fs::write(dir.join("b.txt"), "22").unwrap();
+        std::fs::write(dir.join("c.txt"), "333").unwrap();
+
+        let mut pane = Pane::new(dir.clone()).unwrap();
+        pane.selected_index = pane.items.iter().position(|i| i.name == "a.txt").unwrap();
+        pane.selected_items = vec![pane.items.iter().position(|i| i.name == "b.txt").unwrap()];
+
+        pane.toggle_sort(SortBy::Size);
+        assert_eq!(pane.get_selected_item().unwrap().name, "a.txt");
+        assert_eq!(pane.items[pane.selected_items[0]].name, "b.txt");
+
+        std::fs::remove_file(dir.join("a.txt")).unwrap();
+        pane.refresh().unwrap();
+        assert!(pane.selected_index < pane.items.len());
+        assert_eq!(pane.items[pane.selected_items[0]].name, "b.txt");
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_ties_are_broken_by_name() {
+        let dir = std::env::temp_dir().join(format!("filane-sort-ties-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        let stamp = SystemTime::UNIX_EPOCH + std::time::Duration::from_secs(1_600_000_000);
+        for name in ["file10.txt", "File1.txt", "file2.txt", "big.txt"] {
+            let content = if name == "big.txt" { "larger" } else { "x" };
+            std::fs::write(dir.join(name), content).unwrap();
+            crate::filesystem::touch(&dir.join(name), Some(stamp)).unwrap();
+        }
+        let newer = stamp + std::time::Duration::from_secs(60);
+        crate::filesystem::touch(&dir.join("big.txt"), Some(newer)).unwrap();
+
+        let mut pane = Pane::new(dir.clone()).unwrap();
+        let names = |pane: &Pane| pane.items[1..].iter().map(|i| i.name.clone()).collect::<Vec<_>>();
+        assert_eq!(names(&pane), ["big.txt", "File1.txt", "file2.txt", "file10.txt"]);
+
+        // Ties stay in name order whichever way the column is sorted
+        pane.toggle_sort(SortBy::Size);
+        assert_eq!(names(&pane), ["big.txt", "File1.txt", "file2.txt", "file10.txt"]);
+        pane.toggle_sort(SortBy::Size);
+        assert_eq!(names(&pane), ["File1.txt", "file2.txt", "file10.txt", "big.txt"]);
+        pane.toggle_sort(SortBy::Date);
+        assert_eq!(names(&pane), ["big.txt", "File1.txt", "file2.txt", "file10.txt"]);
+        pane.toggle_sort(SortBy::Date);
+        assert_eq!(names(&pane), ["File1.txt", "file2.txt", "file10.txt", "big.txt"]);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_group_hidden_sorts_dotfiles_last() {
+        let dir = std::env::temp_dir().join(format!("filane-group-hidden-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(dir.join(".config")).unwrap();
+        std::fs::create_dir_all(dir.join("src")).unwrap();
+        std::fs::write(dir.join(".bashrc"), "1").unwrap();
+        std::fs::write(dir.join("a.txt"), "1").unwrap();
+
+        let mut pane = Pane::new(dir.clone()).unwrap();
+        let names = |pane: &Pane| pane.items.iter().map(|i| i.name.clone()).collect::<Vec<_>>();
+        assert_eq!(names(&pane), ["..", ".config", "src", ".bashrc", "a.txt"]);
+
+        pane.set_group_hidden(true);
+        assert_eq!(names(&pane), ["..", "src", "a.txt", ".config", ".bashrc"]);
+        // The group stays last when the order is reversed
+        pane.toggle_sort(SortBy::Name);
+        assert_eq!(names(&pane), ["..", "a.txt", "src", ".bashrc", ".config"]);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_type_sort_applies_unless_folder_sort_was_picked() {
+        let dir = std::env::temp_dir().join(format!("filane-type-sort-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(dir.join("photos")).unwrap();
+        std::fs::create_dir_all(dir.join("notes")).unwrap();
+        for name in ["a.jpg", "b.png", "c.txt"] {
+            std::fs::write(dir.join("photos").join(name), "1").unwrap();
+        }
+        std::fs::write(dir.join("notes/a.txt"), "1").unwrap();
+
+        let mut pane = Pane::new(dir.clone()).unwrap();
+        pane.navigate_to(dir.join("photos")).unwrap();
+        assert_eq!(pane.sort_by, SortBy::Name, "off unless type sorts are set");
+
+        pane.set_type_sorts(vec![(ExtensionGroup::Images, SortBy::Date)]);
+        pane.navigate_to(dir.join("notes")).unwrap();
+        assert_eq!(pane.sort_by, SortBy::Name);
+        pane.navigate_to(dir.join("photos")).unwrap();
+        assert_eq!((pane.sort_by, pane.sort_order), (SortBy::Date, SortOrder::Descending));
+
+        // A sort picked by hand is remembered for the folder
+        pane.toggle_sort(SortBy::Size);
+        pane.navigate_back().unwrap();
+        assert_eq!(pane.sort_by, SortBy::Size, "other folders keep the current sort");
+        pane.navigate_forward().unwrap();
+        assert_eq!(pane.sort_by, SortBy::Size);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_open_at_folder_file_or_missing_path() {
+        let dir = std::env::temp_dir().join(format!("filane-open-at-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(dir.join("sub")).unwrap();
+        std::fs::write(dir.join("sub/b.txt"), "1").unwrap();
+        std::fs::write(dir.join("sub/a.txt"), "1").unwrap();
+        let dir = std::fs::canonicalize(&dir).unwrap();
+
+        let pane = Pane::open_at(&dir.join("sub"), SharedGitStatusCache::default()).unwrap();
+        assert_eq!(pane.current_path, dir.join("sub"));
+
+        let pane = Pane::open_at(&dir.join("sub/b.txt"), SharedGitStatusCache::default()).unwrap();
+        assert_eq!(pane.current_path, dir.join("sub"));
+        assert_eq!(pane.get_selected_item().unwrap().name, "b.txt");
+
+        assert!(Pane::open_at(&dir.join("missing"), SharedGitStatusCache::default()).is_err());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_jump_to_history_keeps_history() {
+        let dir = std::env::temp_dir().join(format!("filane-history-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(dir.join("a/b")).unwrap();
+
+        let mut pane = Pane::new(dir.clone()).unwrap();
+        pane.navigate_to(dir.join("a")).unwrap();
+        pane.navigate_to(dir.join("a/b")).unwrap();
+
+        pane.jump_to_history(0).unwrap();
+        assert_eq!(pane.current_path, dir);
+        assert_eq!(pane.history.len(), 3);
+        assert!(pane.can_go_forward());
+
+        pane.jump_to_history(2).unwrap();
+        assert_eq!(pane.current_path, dir.join("a/b"));
+        assert!(!pane.can_go_forward());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_parent_entry_at_root_stays_at_root() {
+        let mut pane = Pane::new(PathBuf::from("/")).expect("root should be readable");
+        pane.selected_index = 0;
+        assert_eq!(pane.items[0].name, "..");
+
+        pane.enter_directory().unwrap();
+        assert_eq!(pane.current_path, PathBuf::from("/"));
+    }
+}
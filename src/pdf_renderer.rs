@@ -22,7 +22,7 @@ pub fn render_pdf_page(path: &PathBuf) -> Result<image::DynamicImage, String> {
 
     // Get pages and check if has any
     let pages = document.pages();
-    if pages.len() == 0 {
+    if pages.is_empty() {
         return Err("PDF has no pages".to_string());
     }
 
@@ -0,0 +1,120 @@
+use anyhow::Result;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{mpsc, Arc};
+
+/// Progress of a long-running scan or file operation. `total` is 0 when the
+/// amount of work isn't known up front.
+#[derive(Clone, Debug, Default)]
+pub struct Progress {
+    pub done: u64,
+    pub total: u64,
+    pub current_path: PathBuf,
+}
+
+impl Progress {
+    /// Completed fraction in `0.0..=1.0`, or `None` if the total is unknown.
+    pub fn fraction(&self) -> Option<f32> {
+        if self.total == 0 {
+            None
+        } else {
+            Some((self.done as f32 / self.total as f32).min(1.0))
+        }
+    }
+}
+
+pub enum TaskMessage<T> {
+    Progress(Progress),
+    Finished(Result<T, String>),
+}
+
+/// Handed to the worker closure of a `BackgroundTask` to report progress.
+pub struct ProgressSender<T> {
+    sender: mpsc::Sender<TaskMessage<T>>,
+    cancelled: Arc<AtomicBool>,
+}
+
+impl<T> ProgressSender<T> {
+    pub fn report(&self, done: u64, total: u64, current_path: &Path) {
+        let _ = self.sender.send(TaskMessage::Progress(Progress {
+            done,
+            total,
+            current_path: current_path.to_path_buf(),
+        }));
+    }
+
+    /// Whether the UI asked the task to stop. Work that supports
+    /// cancelling checks this between steps.
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+    }
+}
+
+/// Work running on its own thread that streams `Progress` updates back to
+/// the UI. The UI calls `poll` once per frame.
+pub struct BackgroundTask<T> {
+    pub label: String,
+    pub progress: Progress,
+    /// Whether the work checks `ProgressSender::is_cancelled`, so offering
+    /// a Cancel button makes sense
+    pub cancellable: bool,
+    receiver: mpsc::Receiver<TaskMessage<T>>,
+    cancelled: Arc<AtomicBool>,
+}
+
+impl<T: Send + 'static> BackgroundTask<T> {
+    pub fn spawn<F>(label: &str, work: F) -> Self
+    where
+        F: FnOnce(&ProgressSender<T>) -> Result<T> + Send + 'static,
+    {
+        let (sender, receiver) = mpsc::channel();
+        let cancelled = Arc::new(AtomicBool::new(false));
+        let worker_cancelled = cancelled.clone();
+        std::thread::spawn(move || {
+            let progress = ProgressSender {
+                sender: sender.clone(),
+                cancelled: worker_cancelled,
+            };
+            let result = work(&progress).map_err(|e| e.to_string());
+            let _ = sender.send(TaskMessage::Finished(result));
+        });
+
+        Self {
+            label: label.to_string(),
+            progress: Progress::default(),
+            cancellable: false,
+            receiver,
+            cancelled,
+        }
+    }
+
+    /// Mark the task as honouring cancel requests.
+    pub fn cancellable(mut self) -> Self {
+        self.cancellable = true;
+        self
+    }
+
+    /// Ask the work to stop at its next check.
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+    }
+
+    /// Apply pending progress updates. Returns the result once the work has
+    /// finished; after that the task should be dropped.
+    pub fn poll(&mut self) -> Option<Result<T, String>> {
+        loop {
+            match self.receiver.try_recv() {
+                Ok(TaskMessage::Progress(progress)) => self.progress = progress,
+                Ok(TaskMessage::Finished(result)) => return Some(result),
+                Err(mpsc::TryRecvError::Empty) => return None,
+                Err(mpsc::TryRecvError::Disconnected) => {
+                    return Some(Err(format!("{} stopped unexpectedly", self.label)));
+                }
+            }
+        }
+    }
+}
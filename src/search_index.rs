@@ -0,0 +1,175 @@
+use crate::filesystem::{FileItem, SearchCriteria, SearchHit, SearchResults};
+use crate::progress::ProgressSender;
+use anyhow::Result;
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Instant, SystemTime};
+
+/// In-memory snapshot of every entry below a folder (name, size and
+/// modification time), so repeated searches by name, size or date don't have
+/// to walk the disk again. Content searches still read the files.
+pub struct SearchIndex {
+    pub root: PathBuf,
+    pub built_at: Instant,
+    /// Folders that could not be read while indexing.
+    pub skipped_dirs: usize,
+    entries: Vec<FileItem>,
+    /// Every folder read, with its modification time at that moment. Adding,
+    /// removing or renaming an entry changes its folder's time.
+    dirs: Vec<(PathBuf, Option<SystemTime>)>,
+}
+
+impl SearchIndex {
+    /// Walk `root` and record every entry, reporting the number of entries
+    /// indexed so far. Hidden entries are included; searches filter them.
+    pub fn build(root: &Path, progress: &ProgressSender<SearchIndex>) -> Result<Self> {
+        let root = fs::canonicalize(root)?;
+        if !root.is_dir() {
+            return Err(anyhow::anyhow!("{} is not a folder", root.display()));
+        }
+
+        let mut index = SearchIndex {
+            root: root.clone(),
+            built_at: Instant::now(),
+            skipped_dirs: 0,
+            entries: Vec::new(),
+            dirs: Vec::new(),
+        };
+        let mut visited = HashSet::new();
+        index.add_directory(&root, &mut visited, progress);
+        index.built_at = Instant::now();
+        Ok(index)
+    }
+
+    fn add_directory(
+        &mut self,
+        path: &Path,
+        visited: &mut HashSet<PathBuf>,
+        progress: &ProgressSender<SearchIndex>,
+    ) {
+        // Same loop protection as the live search: every folder once
+        let canonical = fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf());
+        if !visited.insert(canonical) {
+            return;
+        }
+        // Taken before reading, so a change made during the read shows later
+        let modified = fs::metadata(path).and_then(|metadata| metadata.modified()).ok();
+        let Ok(entries) = fs::read_dir(path) else {
+            self.skipped_dirs += 1;
+            return;
+        };
+        self.dirs.push((path.to_path_buf(), modified));
+
+        progress.report(self.entries.len() as u64, 0, path);
+        for entry in entries.flatten() {
+            let Ok(metadata) = entry.metadata() else {
+                continue;
+            };
+            let item = FileItem {
+                name: entry.file_name().to_string_lossy().to_string(),
+                path: entry.path(),
+                is_dir: metadata.is_dir(),
+                size: metadata.len(),
+                modified: metadata.modified().unwrap_or(SystemTime::UNIX_EPOCH),
+                git_status: None,
+            };
+            let subfolder = (item.is_dir && !crate::filesystem::is_reparse_point(&item.path))
+                .then(|| item.path.clone());
+            self.entries.push(item);
+            if let Some(subfolder) = subfolder {
+                self.add_directory(&subfolder, visited, progress);
+            }
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Whether the entries below `path` are part of this index.
+    pub fn covers(&self, path: &Path) -> bool {
+        let path = fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf());
+        path.starts_with(&self.root)
+    }
+
+    /// Whether entries were added, removed or renamed below any of `paths`
+    /// since the index was built, judged by the folders' modification times.
+    /// Catches changes made outside the app, which never invalidate the
+    /// index themselves.
+    pub fn changed_below(&self, paths: &[PathBuf]) -> bool {
+        let roots: Vec<PathBuf> = paths
+            .iter()
+            .map(|path| fs::canonicalize(path).unwrap_or_else(|_| path.clone()))
+            .collect();
+        self.dirs
+            .iter()
+            .filter(|(dir, _)| roots.iter().any(|root| dir.starts_with(root)))
+            .any(|(dir, modified)| {
+                fs::metadata(dir).and_then(|metadata| metadata.modified()).ok() != *modified
+            })
+    }
+
+    /// Answer a search from the index. Returns `None` when it can't: for
+    /// content searches, searches that respect `.gitignore`, or when a
+    /// search folder lies outside the index.
+    pub fn search(&self, criteria: &SearchCriteria) -> Option<SearchResults> {
+        if !criteria.content_pattern.is_empty()
+            || criteria.respect_gitignore
+            || criteria.search_paths.is_empty()
+        {
+            return None;
+        }
+        let roots: Vec<PathBuf> = criteria
+            .search_paths
+            .iter()
+            .map(|path| fs::canonicalize(path).unwrap_or_else(|_| path.clone()))
+            .collect();
+        if !roots.iter().all(|root| root.starts_with(&self.root)) {
+            return None;
+        }
+
+        let mut results = SearchResults {
+            skipped_dirs: self.skipped_dirs,
+            ..Default::default()
+        };
+        for item in &self.entries {
+            let Some(root) = roots.iter().find(|root| item.path.starts_with(root)) else {
+                continue;
+            };
+            if item.path == *root {
+                continue;
+            }
+            // Like the live walk, hidden entries hide everything below them
+            if !criteria.include_hidden && is_hidden_below(&item.path, root) {
+                continue;
+            }
+            if let Some(name_match) = crate::filesystem::entry_matches(
+                criteria,
+                &item.name,
+                item.is_dir,
+                item.size,
+                item.modified,
+            ) {
+                results.items.push(SearchHit {
+                    item: item.clone(),
+                    name_match,
+                });
+            }
+        }
+        Some(results)
+    }
+}
+
+/// Whether any component of `path` below `root` starts with a dot.
+fn is_hidden_below(path: &Path, root: &Path) -> bool {
+    path.strip_prefix(root).is_ok_and(|relative| {
+        relative
+            .components()
+            .any(|component| component.as_os_str().to_string_lossy().starts_with('.'))
+    })
+}
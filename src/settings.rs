@@ -0,0 +1,427 @@
+use crate::bookmarks::Bookmark;
+use crate::filesystem::{ConflictPolicy, ExtensionGroup, SizeUnits, ZipOptions};
+use crate::pane::SortBy;
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// How tightly file lists and previews are packed.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Density {
+    Compact,
+    #[default]
+    Comfortable,
+    Spacious,
+}
+
+impl Density {
+    pub fn all() -> [Density; 3] {
+        [Density::Compact, Density::Comfortable, Density::Spacious]
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Density::Compact => "Compact",
+            Density::Comfortable => "Comfortable",
+            Density::Spacious => "Spacious",
+        }
+    }
+
+    /// Height of a file list row in points.
+    pub fn row_height(&self) -> f32 {
+        match self {
+            Density::Compact => 22.0,
+            Density::Comfortable => 28.0,
+            Density::Spacious => 36.0,
+        }
+    }
+
+    /// Factor applied to the font sizes of file lists and previews.
+    pub fn font_scale(&self) -> f32 {
+        match self {
+            Density::Compact => 0.85,
+            Density::Comfortable => 1.0,
+            Density::Spacious => 1.2,
+        }
+    }
+}
+
+/// What double-clicking a file of a given type does.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum OpenAction {
+    /// Handle it inside Filane: the image viewer for images, the extract
+    /// dialog for archives.
+    BuiltIn,
+    /// Hand it to the operating system's default application.
+    DefaultApp,
+}
+
+impl OpenAction {
+    pub fn all() -> [OpenAction; 2] {
+        [OpenAction::BuiltIn, OpenAction::DefaultApp]
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Theme {
+    #[default]
+    Dark,
+    Light,
+    Dracula,
+    Nord,
+    Monokai,
+    SolarizedDark,
+    /// Colors come from `Settings::custom_theme`.
+    Custom,
+}
+
+impl Theme {
+    pub fn name(&self) -> &str {
+        match self {
+            Theme::Dark => "Dark (Default)",
+            Theme::Light => "Light",
+            Theme::Dracula => "Dracula",
+            Theme::Nord => "Nord",
+            Theme::Monokai => "Monokai",
+            Theme::SolarizedDark => "Solarized Dark",
+            Theme::Custom => "Custom",
+        }
+    }
+
+    pub fn all() -> Vec<Theme> {
+        vec![
+            Theme::Dark,
+            Theme::Light,
+            Theme::Dracula,
+            Theme::Nord,
+            Theme::Monokai,
+            Theme::SolarizedDark,
+            Theme::Custom,
+        ]
+    }
+}
+
+/// Palette behind `Theme::Custom`. Colors are plain RGB triples so exported
+/// files stay easy to read and share.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CustomTheme {
+    /// Start from egui's dark visuals rather than the light ones.
+    pub dark: bool,
+    pub panel: [u8; 3],
+    /// Headers, buttons and other widget backgrounds.
+    pub widget: [u8; 3],
+    pub hover: [u8; 3],
+    /// Active widgets and highlights such as the active pane's border.
+    pub accent: [u8; 3],
+    /// Selected rows and text selection.
+    pub selection: [u8; 3],
+    pub text: [u8; 3],
+}
+
+impl Default for CustomTheme {
+    /// The colors of the default Dark theme.
+    fn default() -> Self {
+        Self {
+            dark: true,
+            panel: [27, 27, 27],
+            widget: [55, 58, 64],
+            hover: [66, 70, 77],
+            accent: [66, 133, 244],
+            selection: [66, 133, 244],
+            text: [232, 234, 237],
+        }
+    }
+}
+
+impl CustomTheme {
+    /// Read a palette exported with [`CustomTheme::export`].
+    pub fn import(path: &Path) -> Result<Self> {
+        let content = fs::read_to_string(path)?;
+        Ok(serde_json::from_str(&content)?)
+    }
+
+    pub fn export(&self, path: &Path) -> Result<()> {
+        fs::write(path, serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+}
+
+/// A user-defined external program shown in the context menu. `command` is
+/// split into arguments on whitespace (double quotes group words) and is run
+/// directly, not through a shell. These tokens are substituted:
+/// `$FILE` (item under the cursor), `$DIR` (the pane's folder) and
+/// `$SELECTION` (all marked items, one argument each when used on its own).
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CustomCommand {
+    pub name: String,
+    pub command: String,
+}
+
+impl CustomCommand {
+    /// The program and its arguments with the tokens substituted.
+    pub fn arguments(&self, file: &Path, dir: &Path, selection: &[PathBuf]) -> Result<Vec<String>> {
+        let file = file.to_string_lossy();
+        let dir = dir.to_string_lossy();
+        let selection: Vec<String> = selection
+            .iter()
+            .map(|path| path.to_string_lossy().to_string())
+            .collect();
+
+        let mut arguments = Vec::new();
+        for word in split_command(&self.command)? {
+            if word == "$SELECTION" {
+                arguments.extend(selection.iter().cloned());
+            } else {
+                arguments.push(
+                    word.replace("$SELECTION", &selection.join(" "))
+                        .replace("$FILE", &file)
+                        .replace("$DIR", &dir),
+                );
+            }
+        }
+        if arguments.is_empty() {
+            return Err(anyhow::anyhow!("Command \"{}\" is empty", self.name));
+        }
+        Ok(arguments)
+    }
+}
+
+/// Split a command line on whitespace, keeping double-quoted parts together.
+fn split_command(command: &str) -> Result<Vec<String>> {
+    let mut words = Vec::new();
+    let mut word = String::new();
+    let mut in_word = false;
+    let mut quoted = false;
+    for c in command.chars() {
+        match c {
+            '"' => {
+                quoted = !quoted;
+                in_word = true;
+            }
+            c if c.is_whitespace() && !quoted => {
+                if in_word {
+                    words.push(std::mem::take(&mut word));
+                    in_word = false;
+                }
+            }
+            c => {
+                word.push(c);
+                in_word = true;
+            }
+        }
+    }
+    if quoted {
+        return Err(anyhow::anyhow!("Unclosed quote in {}", command));
+    }
+    if in_word {
+        words.push(word);
+    }
+    Ok(words)
+}
+
+/// User preferences persisted next to the bookmarks. Every field has a
+/// default so that settings files written by older versions keep loading.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Settings {
+    pub size_units: SizeUnits,
+    pub theme: Theme,
+    /// Colors used when `theme` is `Theme::Custom`.
+    pub custom_theme: CustomTheme,
+    /// Row height and font size of the file lists and previews.
+    pub density: Density,
+    /// Show the exact byte count (with thousands separators) next to the
+    /// formatted size in the Properties dialog.
+    pub show_byte_counts: bool,
+    /// Compression method and level preselected in the compress dialog.
+    pub zip_options: ZipOptions,
+    /// Default Quick Access folders the user has hidden from the sidebar.
+    pub hidden_quick_access: Vec<PathBuf>,
+    /// Folders the user added to Quick Access, shown after the defaults.
+    pub custom_quick_access: Vec<Bookmark>,
+    /// Double-click action for images.
+    pub image_open_action: OpenAction,
+    /// Double-click action for zip and tar archives.
+    pub archive_open_action: OpenAction,
+    /// External programs listed under "Custom Commands" in the context menu.
+    pub custom_commands: Vec<CustomCommand>,
+    /// Widths of the resizable Modified and Size columns; the Name column
+    /// takes the rest of the pane.
+    pub date_column_width: f32,
+    pub size_column_width: f32,
+    /// Mark files that are hard links to other files in the same folder.
+    pub mark_hard_links: bool,
+    /// Sort dotfiles after everything else instead of among the other
+    /// entries.
+    pub group_hidden_files: bool,
+    /// "Copy to…" merges into folders that already exist in the destination.
+    pub merge_copies: bool,
+    /// How a merge copy treats files that exist on both sides.
+    pub merge_conflict_policy: ConflictPolicy,
+    /// Refuse every operation that changes files, for browsing sensitive
+    /// folders without the risk of a slip.
+    pub read_only_mode: bool,
+    /// Sort a folder by `type_sorts` when most of its files are of one
+    /// group, unless a sort was picked by hand for it this session.
+    pub sort_by_folder_type: bool,
+    pub type_sorts: Vec<(ExtensionGroup, SortBy)>,
+    /// Height of the preview panel below the panes, set by dragging its
+    /// top edge.
+    pub preview_height: f32,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            size_units: SizeUnits::default(),
+            theme: Theme::default(),
+            custom_theme: CustomTheme::default(),
+            density: Density::default(),
+            show_byte_counts: false,
+            zip_options: ZipOptions::default(),
+            hidden_quick_access: Vec::new(),
+            custom_quick_access: Vec::new(),
+            image_open_action: OpenAction::BuiltIn,
+            archive_open_action: OpenAction::DefaultApp,
+            custom_commands: Vec::new(),
+            date_column_width: 140.0,
+            size_column_width: 90.0,
+            mark_hard_links: true,
+            group_hidden_files: false,
+            merge_copies: false,
+            merge_conflict_policy: ConflictPolicy::Skip,
+            read_only_mode: false,
+            sort_by_folder_type: false,
+            type_sorts: vec![
+                (ExtensionGroup::Images, SortBy::Date),
+                (ExtensionGroup::Video, SortBy::Date),
+            ],
+            preview_height: 200.0,
+        }
+    }
+}
+
+impl Settings {
+    pub fn load() -> Result<Self> {
+        let config_path = Self::get_config_path()?;
+
+        if config_path.exists() {
+            let content = fs::read_to_string(&config_path)?;
+            let settings: Settings = serde_json::from_str(&content)?;
+            Ok(settings)
+        } else {
+            Ok(Self::default())
+        }
+    }
+
+    pub fn save(&self) -> Result<()> {
+        let config_path = Self::get_config_path()?;
+
+        if let Some(parent) = config_path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+
+        let content = serde_json::to_string_pretty(self)?;
+        fs::write(&config_path, content)?;
+
+        Ok(())
+    }
+
+    /// What panes get for [`crate::pane::Pane::set_type_sorts`]: nothing
+    /// while sorting by folder type is off.
+    pub fn active_type_sorts(&self) -> Vec<(ExtensionGroup, SortBy)> {
+        if self.sort_by_folder_type {
+            self.type_sorts.clone()
+        } else {
+            Vec::new()
+        }
+    }
+
+    /// Quick Access entries to show in the sidebar: the platform defaults
+    /// minus hidden ones, followed by the user's own entries that still exist.
+    pub fn quick_access_items(&self) -> Vec<Bookmark> {
+        let mut items: Vec<Bookmark> = crate::bookmarks::get_quick_access_items()
+            .into_iter()
+            .filter(|item| !self.hidden_quick_access.contains(&item.path))
+            .collect();
+        for custom in &self.custom_quick_access {
+            if custom.path.exists() && !items.iter().any(|item| item.path == custom.path) {
+                items.push(custom.clone());
+            }
+        }
+        items
+    }
+
+    pub fn is_custom_quick_access(&self, path: &Path) -> bool {
+        self.custom_quick_access.iter().any(|item| item.path == path)
+    }
+
+    /// Hide a default entry, or drop a custom one.
+    pub fn remove_quick_access(&mut self, path: &Path) {
+        if self.is_custom_quick_access(path) {
+            self.custom_quick_access.retain(|item| item.path != path);
+        } else if !self.hidden_quick_access.iter().any(|hidden| hidden == path) {
+            self.hidden_quick_access.push(path.to_path_buf());
+        }
+    }
+
+    pub fn add_quick_access(&mut self, path: PathBuf) {
+        self.hidden_quick_access.retain(|hidden| *hidden != path);
+        if crate::bookmarks::get_quick_access_items()
+            .iter()
+            .any(|item| item.path == path)
+            || self.is_custom_quick_access(&path)
+        {
+            return;
+        }
+        let name = path
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_else(|| path.display().to_string());
+        self.custom_quick_access
+            .push(Bookmark::new(name, path, "📁".to_string()));
+    }
+
+    fn get_config_path() -> Result<PathBuf> {
+        config_file_path("settings.json")
+    }
+}
+
+/// Folder name under the platform config dir (`$XDG_CONFIG_HOME`,
+/// `~/Library/Application Support`, `%APPDATA%`).
+const CONFIG_DIR_NAME: &str = "filane";
+/// Folder used by older versions; files found there are moved over.
+const LEGACY_CONFIG_DIR_NAME: &str = "dual-pane-fm";
+
+/// Location of a config file such as `bookmarks.json`. If the file only
+/// exists in the legacy config folder it is migrated first.
+pub fn config_file_path(file_name: &str) -> Result<PathBuf> {
+    let config_dir =
+        dirs::config_dir().ok_or_else(|| anyhow::anyhow!("Could not find config directory"))?;
+    let path = config_dir.join(CONFIG_DIR_NAME).join(file_name);
+
+    let legacy_path = config_dir.join(LEGACY_CONFIG_DIR_NAME).join(file_name);
+    if !path.exists() && legacy_path.is_file() {
+        migrate_config_file(&legacy_path, &path)?;
+    }
+
+    Ok(path)
+}
+
+fn migrate_config_file(from: &Path, to: &Path) -> Result<()> {
+    if let Some(parent) = to.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    if fs::rename(from, to).is_err() {
+        fs::copy(from, to)?;
+        let _ = fs::remove_file(from);
+    }
+
+    // Drop the legacy folder once nothing is left in it
+    if let Some(legacy_dir) = from.parent() {
+        let _ = fs::remove_dir(legacy_dir);
+    }
+    Ok(())
+}
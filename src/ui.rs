@@ -1,5 +1,5 @@
 use crate::app::App;
-use crate::filesystem::{format_size, get_mount_points};
+use crate::filesystem::{format_size_with, get_mount_points, SizeUnits};
 use ratatui::{
     Frame,
     layout::{Constraint, Direction, Layout, Rect},
@@ -29,7 +29,7 @@ pub fn draw(f: &mut Frame, app: &App) {
         .split(f.size());
 
     let title = Paragraph::new(
-        "Filane - Dual Pane FM - Tab: Switch | Shift+↑↓: Multi-select | v: Move | d: Delete | Space: Preview | m: Mounts | q: Quit",
+        "Filane - Dual Pane FM - Tab: Switch | Shift+↑↓: Multi-select | v: Move | d: Delete | Space: Preview | m: Mounts | R: Read-only | q: Quit",
     )
     .style(Style::default().fg(Color::Cyan));
     f.render_widget(title, chunks[0]);
@@ -66,7 +66,7 @@ pub fn draw(f: &mut Frame, app: &App) {
         let size_str = if item.is_dir {
             String::from("<DIR>")
         } else {
-            format_size(item.size)
+            format_size_with(item.size, app.settings.size_units)
         };
         format!(
             "Selected: {} | Size: {} | Path: {}",
@@ -78,7 +78,16 @@ pub fn draw(f: &mut Frame, app: &App) {
         String::from("No item selected")
     };
 
-    let status = Paragraph::new(status_text)
+    let mut status_line = Vec::new();
+    if app.settings.read_only_mode {
+        status_line.push(Span::styled(
+            "[READ-ONLY] ",
+            Style::default().fg(Color::Red).add_modifier(Modifier::BOLD),
+        ));
+    }
+    status_line.push(Span::raw(status_text));
+
+    let status = Paragraph::new(Line::from(status_line))
         .style(Style::default().fg(Color::Yellow))
         .block(Block::default().borders(Borders::TOP));
     f.render_widget(status, chunks[chunks.len() - 1]);
@@ -107,8 +116,8 @@ fn draw_preview(f: &mut Frame, app: &App, area: Rect) {
             match get_file_type(&item.path) {
                 FileType::Text => preview_text_file(&item.path, inner_area.height as usize),
                 FileType::Image => String::from("Image preview not supported in TUI"),
-                FileType::Pdf => preview_pdf_file(&item.path),
-                FileType::Other => format!("File: {} | Size: {}", item.name, format_size(item.size)),
+                FileType::Pdf => preview_pdf_file(&item.path, app.settings.size_units),
+                FileType::Other => format!("File: {} | Size: {}", item.name, format_size_with(item.size, app.settings.size_units)),
             }
         }
     } else {
@@ -144,34 +153,37 @@ fn get_file_type(path: &Path) -> FileType {
     }
 }
 
-fn preview_text_file(path: &Path, max_lines: usize) -> String {
-    use std::fs::File;
-    use std::io::{BufRead, BufReader};
+/// How much of a text file the preview reads; it is redrawn several times a
+/// second, and a screenful of lines fits well within this.
+const PREVIEW_BYTES: usize = 64 * 1024;
 
-    let file = match File::open(path) {
-        Ok(f) => f,
+fn preview_text_file(path: &Path, max_lines: usize) -> String {
+    let decoded = match crate::filesystem::read_text_prefix(path, PREVIEW_BYTES) {
+        Ok(d) => d,
         Err(_) => return String::from("Could not open file"),
     };
 
-    let reader = BufReader::new(file);
     let mut content = String::new();
+    let mut max_lines = max_lines;
+    if let Some(note) = decoded.encoding_note() {
+        content.push_str(&format!("[{}]\n", note));
+        max_lines = max_lines.saturating_sub(1);
+    }
 
-    for (i, line_result) in reader.lines().enumerate() {
-        if i >= max_lines - 2 {  // Reserve 2 lines for "..." and filename info
+    for (i, line) in decoded.text.lines().enumerate() {
+        if i >= max_lines.saturating_sub(2) {  // Reserve 2 lines for "..." and filename info
             content.push_str("\n...");
             break;
         }
 
-        if let Ok(line) = line_result {
-            content.push_str(&line);
-            content.push('\n');
-        }
+        content.push_str(line);
+        content.push('\n');
     }
 
     content
 }
 
-fn preview_pdf_file(path: &Path) -> String {
+fn preview_pdf_file(path: &Path, units: SizeUnits) -> String {
     use std::io::Read;
     use std::fs::File;
 
@@ -182,7 +194,7 @@ fn preview_pdf_file(path: &Path) -> String {
     };
 
     let mut header = [0; 5];
-    if let Ok(_) = file.read_exact(&mut header) {
+    if file.read_exact(&mut header).is_ok() {
         let header_str = std::str::from_utf8(&header);
         if header_str != Ok("%PDF-") {
             return String::from("File is not a valid PDF");
@@ -197,7 +209,7 @@ fn preview_pdf_file(path: &Path) -> String {
             format!(
                 "📋 PDF Document\n\nFilename: {}\nSize: {}\nPages: {}\nModified: {}\n\n",
                 path.file_name().unwrap_or_default().to_string_lossy(),
-                format_size(path.metadata().map(|m| m.len()).unwrap_or(0)),
+                format_size_with(path.metadata().map(|m| m.len()).unwrap_or(0), units),
                 page_count,
                 crate::filesystem::format_date(path.metadata().ok().and_then(|m| m.modified().ok()).unwrap_or(std::time::SystemTime::UNIX_EPOCH))
             )
@@ -206,7 +218,7 @@ fn preview_pdf_file(path: &Path) -> String {
             format!(
                 "📋 PDF Document\n\nFilename: {}\nSize: {}\nModified: {}\n\n",
                 path.file_name().unwrap_or_default().to_string_lossy(),
-                format_size(path.metadata().map(|m| m.len()).unwrap_or(0)),
+                format_size_with(path.metadata().map(|m| m.len()).unwrap_or(0), units),
                 crate::filesystem::format_date(path.metadata().ok().and_then(|m| m.modified().ok()).unwrap_or(std::time::SystemTime::UNIX_EPOCH))
             )
         }
@@ -216,26 +228,6 @@ fn preview_pdf_file(path: &Path) -> String {
     format!("{}PDF preview is only available in GUI mode.", metadata)
 }
 
-fn truncate_text(text: &str, max_chars: usize) -> String {
-    if text.len() <= max_chars {
-        return text.to_string();
-    }
-
-    let mut result = String::with_capacity(max_chars + 3); // +3 for "..."
-    let mut char_count = 0;
-
-    for c in text.chars() {
-        if char_count >= max_chars {
-            result.push_str("...");
-            break;
-        }
-        result.push(c);
-        char_count += 1;
-    }
-
-    result
-}
-
 fn draw_pane(f: &mut Frame, app: &App, area: Rect, pane_index: usize) {
     let pane = if pane_index == 0 {
         &app.left_pane
@@ -253,7 +245,10 @@ fn draw_pane(f: &mut Frame, app: &App, area: Rect, pane_index: usize) {
         Style::default().fg(Color::Gray)
     };
 
-    let title = format!(" {} ", pane.current_path.display());
+    let title = match &pane.read_error {
+        Some(error) => format!(" {} [🔒 {}] ", pane.current_path.display(), error),
+        None => format!(" {} ", pane.current_path.display()),
+    };
     let block = Block::default()
         .title(title)
         .borders(Borders::ALL)
@@ -275,7 +270,7 @@ fn draw_pane(f: &mut Frame, app: &App, area: Rect, pane_index: usize) {
             let size_str = if item.is_dir {
                 String::from("<DIR>")
             } else {
-                format_size(item.size)
+                format_size_with(item.size, app.settings.size_units)
             };
 
             let content = format!("{} {:<40} {:>10}", icon, item.name, size_str);
@@ -308,7 +303,8 @@ fn draw_pane(f: &mut Frame, app: &App, area: Rect, pane_index: usize) {
     f.render_widget(list, inner_area);
 }
 
-fn draw_mounts_popup(f: &mut Frame, _app: &App) {
+fn draw_mounts_popup(f: &mut Frame, app: &App) {
+    let units = app.settings.size_units;
     let area = f.size();
     let popup_width = area.width.saturating_sub(10).min(80);
     let popup_height = area.height.saturating_sub(6).min(30);
@@ -372,12 +368,12 @@ fn draw_mounts_popup(f: &mut Frame, _app: &App) {
                 Line::from(vec![
                     Span::raw("  Space: "),
                     Span::styled(
-                        format!("{} / {}", format_size(used), format_size(mount.total_space)),
+                        format!("{} / {}", format_size_with(used, units), format_size_with(mount.total_space, units)),
                         Style::default().fg(Color::White),
                     ),
                     Span::raw(" | Available: "),
                     Span::styled(
-                        format_size(mount.available_space),
+                        format_size_with(mount.available_space, units),
                         Style::default().fg(Color::Green),
                     ),
                 ]),